@@ -4,15 +4,22 @@
  * HTTP-based protocol for third-party content sources
  * Inspired by Stremio's addon protocol
  */
+use crate::version::Version;
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
 use std::time::Duration;
-use url::Url;
+use url::{Host, Url};
 
 // Security constants
 const MAX_MANIFEST_SIZE: u64 = 102400; // 100KB
-const MAX_RESPONSE_SIZE: u64 = 10485760; // 10MB
+const DEFAULT_MAX_RESPONSE_SIZE: u64 = 10485760; // 10MB
 const REQUEST_TIMEOUT_SECS: u64 = 5;
-const MAX_CATALOG_ITEMS: usize = 1000;
+const DEFAULT_MAX_CATALOG_ITEMS: usize = 1000;
+
+// Bounds for user-configured response limits
+const CATALOG_ITEMS_BOUNDS: RangeInclusive<usize> = 10..=10_000;
+const RESPONSE_SIZE_BOUNDS: RangeInclusive<u64> = 1_048_576..=104_857_600; // 1MB to 100MB
 
 // Retry configuration
 const MAX_RETRIES: u32 = 3;
@@ -21,6 +28,9 @@ const INITIAL_RETRY_DELAY_MS: u64 = 100;
 // Compatibility limits (relaxed for wide addon support)
 const MAX_EXTRA_OPTIONS: usize = 1000; // previously 100; relaxed to support large lists like genres
 
+// Keys some addons wrap their result list in instead of `metas`/`streams`
+const LENIENT_WRAPPER_KEYS: &[&str] = &["data", "meta", "result", "results", "items"];
+
 /// Addon manifest - describes capabilities and metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddonManifest {
@@ -268,6 +278,80 @@ where
     deserializer.deserialize_option(FloatVisitor)
 }
 
+/// Parse a list response that should look like `{ "<key>": [...] }`.
+///
+/// Also accepts a bare array and the list nested under a common wrapper key,
+/// either directly (`{ "data": [...] }`) or one level down
+/// (`{ "data": { "<key>": [...] } }`), logging when a non-standard shape is used.
+fn parse_lenient_list<T: serde::de::DeserializeOwned>(
+    body: &[u8],
+    key: &str,
+) -> Result<Vec<T>, AddonError> {
+    use serde_json::Value;
+
+    let value: Value =
+        serde_json::from_slice(body).map_err(|e| AddonError::ParseError(e.to_string()))?;
+
+    let (list, shape) = match value {
+        Value::Object(mut obj) => {
+            if let Some(list) = obj.remove(key) {
+                (list, None)
+            } else if let Some((wrapper, inner)) = LENIENT_WRAPPER_KEYS
+                .iter()
+                .find_map(|k| obj.remove(*k).map(|v| (*k, v)))
+            {
+                match inner {
+                    Value::Object(mut inner) if inner.contains_key(key) => (
+                        inner.remove(key).unwrap_or_default(),
+                        Some(format!("{}.{}", wrapper, key)),
+                    ),
+                    inner => (inner, Some(wrapper.to_string())),
+                }
+            } else {
+                return Err(AddonError::ParseError(format!("missing field `{}`", key)));
+            }
+        }
+        array @ Value::Array(_) => (array, Some("bare array".to_string())),
+        _ => {
+            return Err(AddonError::ParseError(format!(
+                "expected an object with `{}` or an array",
+                key
+            )))
+        }
+    };
+
+    if let Some(shape) = shape {
+        tracing::warn!(expected = %key, shape = %shape, "Accepted non-standard addon response shape");
+    }
+
+    serde_json::from_value(list).map_err(|e| AddonError::ParseError(e.to_string()))
+}
+
+/// Treat `null` like a missing field for lists and other defaultable values
+fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Accept either a string or a bare number (e.g. `"runtime": 142`)
+fn deserialize_optional_string_or_number<'de, D>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(s)) => Some(s),
+            Some(serde_json::Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        },
+    )
+}
+
 /// Catalog response - list of metadata items
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CatalogResponse {
@@ -284,7 +368,7 @@ pub struct MetaPreview {
     pub name: String,
     #[serde(default)]
     pub poster: Option<String>,
-    #[serde(default)]
+    #[serde(default, alias = "poster_shape")]
     pub posterShape: Option<String>,
     #[serde(default)]
     pub background: Option<String>,
@@ -292,12 +376,39 @@ pub struct MetaPreview {
     pub logo: Option<String>,
     #[serde(default)]
     pub description: Option<String>,
-    #[serde(default)]
+    #[serde(default, alias = "release_info")]
     pub releaseInfo: Option<String>,
-    #[serde(default, deserialize_with = "deserialize_optional_float_string")]
+    #[serde(
+        default,
+        alias = "imdb_rating",
+        deserialize_with = "deserialize_optional_float_string"
+    )]
     pub imdbRating: Option<f32>,
 }
 
+impl MetaPreview {
+    /// Whether the item has a non-empty poster or background image
+    pub fn has_artwork(&self) -> bool {
+        [&self.poster, &self.background]
+            .into_iter()
+            .any(|image| image.as_deref().is_some_and(|url| !url.trim().is_empty()))
+    }
+
+    /// Why the item fails strict validation, if it does: the protocol
+    /// requires a non-empty id, type and name
+    pub fn validation_error(&self) -> Option<&'static str> {
+        if self.id.trim().is_empty() {
+            Some("missing id")
+        } else if self.media_type.0.trim().is_empty() {
+            Some("missing type")
+        } else if self.name.trim().is_empty() {
+            Some("missing name")
+        } else {
+            None
+        }
+    }
+}
+
 /// Stream response - list of available streams
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamResponse {
@@ -316,7 +427,10 @@ pub struct MetaResponse {
     pub meta: MetaItem,
 }
 
-/// MetaItem - full metadata with all Stremio fields
+/// MetaItem - full metadata with all Stremio fields.
+///
+/// Deserialization is tolerant: `null` lists become empty, numeric
+/// `runtime`/`releaseInfo` values are kept as strings and unknown fields are ignored.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(non_snake_case)] // Stremio protocol uses camelCase
 pub struct MetaItem {
@@ -326,7 +440,7 @@ pub struct MetaItem {
     pub name: String,
     #[serde(default)]
     pub poster: Option<String>,
-    #[serde(default)]
+    #[serde(default, alias = "poster_shape")]
     pub posterShape: Option<String>,
     #[serde(default)]
     pub background: Option<String>,
@@ -334,19 +448,27 @@ pub struct MetaItem {
     pub logo: Option<String>,
     #[serde(default)]
     pub description: Option<String>,
-    #[serde(default)]
+    #[serde(
+        default,
+        alias = "release_info",
+        deserialize_with = "deserialize_optional_string_or_number"
+    )]
     pub releaseInfo: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_optional_string_or_number")]
     pub runtime: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub genres: Vec<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub director: Vec<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub cast: Vec<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub writer: Vec<String>,
-    #[serde(default, deserialize_with = "deserialize_optional_float_string")]
+    #[serde(
+        default,
+        alias = "imdb_rating",
+        deserialize_with = "deserialize_optional_float_string"
+    )]
     pub imdbRating: Option<f32>,
     #[serde(default)]
     pub country: Option<String>,
@@ -356,14 +478,18 @@ pub struct MetaItem {
     pub awards: Option<String>,
     #[serde(default)]
     pub website: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub trailers: Vec<Trailer>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub videos: Vec<Video>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub links: Vec<MetaLink>,
     // Series-specific fields
-    #[serde(default)]
+    #[serde(
+        default,
+        alias = "behavior_hints",
+        deserialize_with = "deserialize_null_default"
+    )]
     pub behaviorHints: MetaBehaviorHints,
 }
 
@@ -371,12 +497,13 @@ pub struct MetaItem {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trailer {
     pub source: String, // e.g. "youtube:dQw4w9WgXcQ"
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default)]
     pub trailer_type: String, // e.g. "Trailer", "Clip"
 }
 
 /// Video episode info for series
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "RawVideo")]
 #[allow(non_snake_case)]
 pub struct Video {
     pub id: String,
@@ -393,11 +520,49 @@ pub struct Video {
     pub overview: Option<String>,
 }
 
+/// Wire form of [`Video`]: Cinemeta names episodes with `name` rather than `title`
+#[derive(Deserialize)]
+struct RawVideo {
+    id: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    released: Option<String>,
+    #[serde(default)]
+    season: Option<u32>,
+    #[serde(default)]
+    episode: Option<u32>,
+    #[serde(default)]
+    thumbnail: Option<String>,
+    #[serde(default)]
+    overview: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+impl From<RawVideo> for Video {
+    fn from(raw: RawVideo) -> Self {
+        Video {
+            id: raw.id,
+            title: raw.title.or(raw.name).unwrap_or_default(),
+            released: raw.released,
+            season: raw.season,
+            episode: raw.episode,
+            thumbnail: raw.thumbnail,
+            overview: raw.overview.or(raw.description),
+        }
+    }
+}
+
 /// External links for meta
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetaLink {
     pub name: String,
+    #[serde(default)]
     pub category: String,
+    #[serde(default)]
     pub url: String,
 }
 
@@ -431,7 +596,7 @@ pub struct Stream {
     pub description: Option<String>,
 
     /// Behavioral hints
-    #[serde(default)]
+    #[serde(default, alias = "behavior_hints")]
     pub behaviorHints: StreamBehaviorHints,
 
     /// Subtitles available for this stream
@@ -439,16 +604,37 @@ pub struct Stream {
     pub subtitles: Vec<Subtitle>,
 }
 
+impl Stream {
+    /// Why the stream fails strict validation, if it does: besides a URL it
+    /// needs a name or title, or the user can't tell it from the others
+    pub fn validation_error(&self) -> Option<&'static str> {
+        let is_blank = |s: &Option<String>| s.as_deref().map_or(true, |s| s.trim().is_empty());
+        if self.url.trim().is_empty() {
+            Some("missing url")
+        } else if is_blank(&self.name) && is_blank(&self.title) {
+            Some("missing name and title")
+        } else {
+            None
+        }
+    }
+}
+
 /// Stream behavior hints
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[allow(non_snake_case)] // Stremio protocol uses camelCase
 pub struct StreamBehaviorHints {
-    #[serde(default)]
+    #[serde(default, alias = "not_web_ready")]
     pub notWebReady: bool,
-    #[serde(default)]
+    #[serde(default, alias = "binge_group")]
     pub bingeGroup: Option<String>,
-    #[serde(default)]
+    #[serde(default, alias = "country_whitelist")]
     pub countryWhitelist: Option<Vec<String>>,
+    /// Stream is served peer-to-peer (torrent) by the addon
+    #[serde(default)]
+    pub p2p: bool,
+    /// Size of the video file in bytes
+    #[serde(default, alias = "video_size")]
+    pub videoSize: Option<u64>,
 }
 
 /// Subtitle track
@@ -456,6 +642,7 @@ pub struct StreamBehaviorHints {
 pub struct Subtitle {
     pub id: String,
     pub url: String,
+    #[serde(alias = "language")]
     pub lang: String,
 }
 
@@ -493,10 +680,78 @@ pub mod episode_id {
     }
 }
 
+/// Caps on what an addon may send back: catalog entries kept per response
+/// and the size of catalog, stream, subtitle and meta responses
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResponseLimits {
+    max_catalog_items: usize,
+    max_response_size: u64,
+}
+
+impl ResponseLimits {
+    /// Limits within sane bounds (10 to 10,000 catalog items, 1MB to 100MB
+    /// responses)
+    pub fn new(max_catalog_items: usize, max_response_size: u64) -> Result<Self, AddonError> {
+        if !CATALOG_ITEMS_BOUNDS.contains(&max_catalog_items) {
+            return Err(AddonError::ValidationError(format!(
+                "Catalog item limit {} must be between {} and {}",
+                max_catalog_items,
+                CATALOG_ITEMS_BOUNDS.start(),
+                CATALOG_ITEMS_BOUNDS.end()
+            )));
+        }
+        if !RESPONSE_SIZE_BOUNDS.contains(&max_response_size) {
+            return Err(AddonError::ValidationError(format!(
+                "Response size limit {} must be between {} and {}",
+                max_response_size,
+                RESPONSE_SIZE_BOUNDS.start(),
+                RESPONSE_SIZE_BOUNDS.end()
+            )));
+        }
+        Ok(Self {
+            max_catalog_items,
+            max_response_size,
+        })
+    }
+
+    /// The user's configured limits, or the defaults if they are out of bounds
+    pub fn from_preferences(preferences: &crate::models::UserPreferences) -> Self {
+        Self::new(
+            preferences.max_catalog_items as usize,
+            u64::from(preferences.max_addon_response_mb) * 1024 * 1024,
+        )
+        .unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Ignoring addon response limits from preferences");
+            Self::default()
+        })
+    }
+
+    fn check_size(&self, size: u64) -> Result<(), AddonError> {
+        if size > self.max_response_size {
+            return Err(AddonError::ValidationError(format!(
+                "Response size {} exceeds maximum {}",
+                size, self.max_response_size
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ResponseLimits {
+    fn default() -> Self {
+        Self {
+            max_catalog_items: DEFAULT_MAX_CATALOG_ITEMS,
+            max_response_size: DEFAULT_MAX_RESPONSE_SIZE,
+        }
+    }
+}
+
 /// Addon client for making HTTP requests
 pub struct AddonClient {
     client: reqwest::Client,
     base_url: String,
+    allow_private_hosts: bool,
+    limits: ResponseLimits,
 }
 
 impl AddonClient {
@@ -539,8 +794,14 @@ impl AddonClient {
         Err(last_error.unwrap_or_else(|| AddonError::HttpError("All retries failed".to_string())))
     }
 
-    /// Create a new addon client
+    /// Create a new addon client with the default request timeout
     pub fn new(base_url: String) -> Result<Self, AddonError> {
+        Self::with_timeout(base_url, Duration::from_secs(REQUEST_TIMEOUT_SECS))
+    }
+
+    /// Create an addon client with a custom per-request timeout
+    /// (slow torrent indexers need more than the default, local addons less)
+    pub fn with_timeout(base_url: String, timeout: Duration) -> Result<Self, AddonError> {
         // Validate URL
         if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
             return Err(AddonError::InvalidUrl(
@@ -548,8 +809,36 @@ impl AddonClient {
             ));
         }
 
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        Ok(Self {
+            client: Self::build_http_client(timeout)?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            allow_private_hosts: false,
+            limits: ResponseLimits::default(),
+        })
+    }
+
+    /// Apply response limits other than the defaults
+    pub fn with_limits(mut self, limits: ResponseLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Accept stream and subtitle URLs on private/loopback addresses.
+    /// Off unless the user opted in (`allow_private_addon_urls`), since any
+    /// addon could otherwise point the player at other hosts on the network.
+    pub fn set_allow_private_hosts(&mut self, allow: bool) {
+        self.allow_private_hosts = allow;
+    }
+
+    /// Change the request timeout, e.g. to one derived from the addon's health history
+    pub fn set_timeout(&mut self, timeout: Duration) -> Result<(), AddonError> {
+        self.client = Self::build_http_client(timeout)?;
+        Ok(())
+    }
+
+    fn build_http_client(timeout: Duration) -> Result<reqwest::Client, AddonError> {
+        reqwest::Client::builder()
+            .timeout(timeout)
             .user_agent(concat!(
                 env!("CARGO_PKG_NAME"),
                 "/",
@@ -557,12 +846,7 @@ impl AddonClient {
             ))
             .redirect(reqwest::redirect::Policy::limited(3))
             .build()
-            .map_err(|e| AddonError::HttpError(e.to_string()))?;
-
-        Ok(Self {
-            client,
-            base_url: base_url.trim_end_matches('/').to_string(),
-        })
+            .map_err(|e| AddonError::HttpError(e.to_string()))
     }
 
     /// Fetch addon manifest
@@ -678,28 +962,28 @@ impl AddonClient {
 
         // Check content length
         if let Some(length) = response.content_length() {
-            if length > MAX_RESPONSE_SIZE {
-                return Err(AddonError::ValidationError(format!(
-                    "Response size {} exceeds maximum {}",
-                    length, MAX_RESPONSE_SIZE
-                )));
-            }
+            self.limits.check_size(length)?;
         }
 
-        let catalog = response
-            .json::<CatalogResponse>()
+        let body = response
+            .bytes()
             .await
-            .map_err(|e| AddonError::ParseError(e.to_string()))?;
+            .map_err(|e| AddonError::HttpError(e.to_string()))?;
+        self.limits.check_size(body.len() as u64)?;
+        let catalog = CatalogResponse {
+            metas: parse_lenient_list(&body, "metas")?,
+        };
 
         // Limit catalog size
-        if catalog.metas.len() > MAX_CATALOG_ITEMS {
+        let max_items = self.limits.max_catalog_items;
+        if catalog.metas.len() > max_items {
             tracing::warn!(
                 "Catalog has {} items, limiting to {}",
                 catalog.metas.len(),
-                MAX_CATALOG_ITEMS
+                max_items
             );
             let mut limited_catalog = catalog;
-            limited_catalog.metas.truncate(MAX_CATALOG_ITEMS);
+            limited_catalog.metas.truncate(max_items);
             return Ok(limited_catalog);
         }
 
@@ -745,23 +1029,24 @@ impl AddonClient {
 
         // Check content length
         if let Some(length) = response.content_length() {
-            if length > MAX_RESPONSE_SIZE {
-                return Err(AddonError::ValidationError(format!(
-                    "Response size {} exceeds maximum {}",
-                    length, MAX_RESPONSE_SIZE
-                )));
-            }
+            self.limits.check_size(length)?;
         }
 
-        let mut streams = response
-            .json::<StreamResponse>()
+        let body = response
+            .bytes()
             .await
-            .map_err(|e| AddonError::ParseError(e.to_string()))?;
+            .map_err(|e| AddonError::HttpError(e.to_string()))?;
+        self.limits.check_size(body.len() as u64)?;
+        let mut streams = StreamResponse {
+            streams: parse_lenient_list(&body, "streams")?,
+        };
 
         // Validate stream URLs (security check)
         streams
             .streams
-            .retain(|stream| Self::validate_stream_url(&stream.url));
+            .retain(|stream| self.validate_stream_url(&stream.url));
+        #[cfg(feature = "ssrf-dns-check")]
+        self.drop_privately_resolving(&mut streams.streams, |stream| &stream.url).await;
 
         if streams.streams.is_empty() {
             return Err(AddonError::ValidationError(
@@ -813,21 +1098,21 @@ impl AddonClient {
         }
 
         if let Some(length) = response.content_length() {
-            if length > MAX_RESPONSE_SIZE {
-                return Err(AddonError::ValidationError(format!(
-                    "Response size {} exceeds maximum {}",
-                    length, MAX_RESPONSE_SIZE
-                )));
-            }
+            self.limits.check_size(length)?;
         }
 
-        let mut subs = response
-            .json::<SubtitlesResponse>()
+        let body = response
+            .bytes()
             .await
+            .map_err(|e| AddonError::HttpError(e.to_string()))?;
+        self.limits.check_size(body.len() as u64)?;
+        let mut subs = serde_json::from_slice::<SubtitlesResponse>(&body)
             .map_err(|e| AddonError::ParseError(e.to_string()))?;
 
         // Validate subtitle URLs
-        subs.subtitles.retain(|s| Self::validate_stream_url(&s.url));
+        subs.subtitles.retain(|s| self.validate_stream_url(&s.url));
+        #[cfg(feature = "ssrf-dns-check")]
+        self.drop_privately_resolving(&mut subs.subtitles, |s| &s.url).await;
 
         Ok(subs)
     }
@@ -838,9 +1123,59 @@ impl AddonClient {
         media_type: &str,
         media_id: &str,
     ) -> Result<MetaResponse, AddonError> {
-        let url = format!("{}/meta/{}/{}.json", self.base_url, media_type, media_id);
+        let meta: MetaResponse = self.fetch_json("meta", media_type, media_id, None).await?;
+
+        tracing::info!(
+            media_type = %media_type,
+            media_id = %media_id,
+            meta_name = %meta.meta.name,
+            "Successfully fetched meta"
+        );
+
+        Ok(meta)
+    }
+
+    /// Fetch the meta response exactly as the addon returned it (for debugging)
+    pub async fn get_meta_raw(
+        &self,
+        media_type: &str,
+        media_id: &str,
+    ) -> Result<serde_json::Value, AddonError> {
+        self.fetch_json("meta", media_type, media_id, None).await
+    }
+
+    /// Fetch any resource (`catalog`, `stream`, `meta` or `subtitles`)
+    /// exactly as the addon returned it, without validating its contents
+    pub async fn get_resource_raw(
+        &self,
+        resource: &str,
+        media_type: &str,
+        id: &str,
+        extra: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<serde_json::Value, AddonError> {
+        if !["catalog", "stream", "meta", "subtitles"].contains(&resource) {
+            return Err(AddonError::ValidationError(format!(
+                "Unknown addon resource: {}",
+                resource
+            )));
+        }
+        self.fetch_json(resource, media_type, id, extra).await
+    }
 
-        tracing::info!(url = %url, "Fetching meta");
+    async fn fetch_json<T: serde::de::DeserializeOwned>(
+        &self,
+        resource: &str,
+        media_type: &str,
+        id: &str,
+        extra: Option<&std::collections::HashMap<String, String>>,
+    ) -> Result<T, AddonError> {
+        let base_url = format!("{}/{}/{}/{}.json", self.base_url, resource, media_type, id);
+        let mut url = Url::parse(&base_url).map_err(|e| AddonError::InvalidUrl(e.to_string()))?;
+        for (k, v) in extra.into_iter().flatten() {
+            url.query_pairs_mut().append_pair(k, v);
+        }
+
+        tracing::info!(url = %url, resource = %resource, "Fetching addon resource");
 
         let client = self.client.clone();
         let url_clone = url.clone();
@@ -863,27 +1198,17 @@ impl AddonClient {
         }
 
         if let Some(length) = response.content_length() {
-            if length > MAX_RESPONSE_SIZE {
-                return Err(AddonError::ValidationError(format!(
-                    "Response size {} exceeds maximum {}",
-                    length, MAX_RESPONSE_SIZE
-                )));
-            }
+            self.limits.check_size(length)?;
         }
 
-        let meta = response
-            .json::<MetaResponse>()
+        // Chunked responses carry no Content-Length, so check the body as well
+        let body = response
+            .bytes()
             .await
-            .map_err(|e| AddonError::ParseError(e.to_string()))?;
-
-        tracing::info!(
-            media_type = %media_type,
-            media_id = %media_id,
-            meta_name = %meta.meta.name,
-            "Successfully fetched meta"
-        );
+            .map_err(|e| AddonError::HttpError(e.to_string()))?;
+        self.limits.check_size(body.len() as u64)?;
 
-        Ok(meta)
+        serde_json::from_slice::<T>(&body).map_err(|e| AddonError::ParseError(e.to_string()))
     }
 
     /// Validate manifest with comprehensive checks
@@ -935,31 +1260,12 @@ impl AddonClient {
         }
 
         // Version validation (semver)
-        if manifest.version.is_empty() {
-            return Err(AddonError::ValidationError(
-                "Manifest version is required".to_string(),
-            ));
-        }
-
-        let version_parts: Vec<&str> = manifest.version.split('.').collect();
-        if version_parts.len() < 2 || version_parts.len() > 4 {
-            return Err(AddonError::ValidationError(
-                "Manifest version must follow semver format (e.g., 1.0.0 or 1.0.0-beta)"
-                    .to_string(),
-            ));
-        }
-
-        // Validate major.minor.patch are numeric
-        for (i, part) in version_parts.iter().take(3).enumerate() {
-            let clean_part = part.split('-').next().unwrap_or(part);
-            if clean_part.parse::<u32>().is_err() {
-                return Err(AddonError::ValidationError(format!(
-                    "Version part {} must be numeric: {}",
-                    i + 1,
-                    part
-                )));
-            }
-        }
+        Version::parse(&manifest.version).map_err(|e| {
+            AddonError::ValidationError(format!(
+                "Invalid manifest version '{}': {}",
+                manifest.version, e
+            ))
+        })?;
 
         // Resources validation
         if manifest.resources.is_empty() {
@@ -1131,7 +1437,7 @@ impl AddonClient {
     }
 
     /// Validate stream URL (security check)
-    fn validate_stream_url(url_str: &str) -> bool {
+    fn validate_stream_url(&self, url_str: &str) -> bool {
         // Accept magnet links as valid (handled via WebTorrent in the client)
         if url_str.starts_with("magnet:") {
             return true;
@@ -1152,6 +1458,12 @@ impl AddonClient {
                     return false;
                 }
 
+                // Don't let remote addons point the player at the local network
+                if !self.allow_private_hosts && is_private_host(&url) {
+                    tracing::warn!(url = %url_str, "Rejected stream URL on a private network");
+                    return false;
+                }
+
                 true
             }
             Err(e) => {
@@ -1161,6 +1473,78 @@ impl AddonClient {
             }
         }
     }
+
+    /// Drop entries whose hostname resolves to a private address. Runs after
+    /// `validate_stream_url`, which only sees literal IPs and `localhost`.
+    #[cfg(feature = "ssrf-dns-check")]
+    async fn drop_privately_resolving<T>(&self, entries: &mut Vec<T>, url_of: fn(&T) -> &String) {
+        if self.allow_private_hosts {
+            return;
+        }
+        let mut keep = Vec::with_capacity(entries.len());
+        for entry in entries.iter() {
+            let private = match Url::parse(url_of(entry)) {
+                Ok(url) => resolves_to_private_ip(&url).await,
+                Err(_) => false,
+            };
+            if private {
+                tracing::warn!(url = %url_of(entry), "Rejected URL resolving to a private network");
+            }
+            keep.push(!private);
+        }
+        let mut keep = keep.into_iter();
+        entries.retain(|_| keep.next().unwrap_or(true));
+    }
+}
+
+/// Whether a URL points at a loopback, private or link-local address.
+/// Only literal IPs and `localhost` are checked here; other hostnames are
+/// resolved separately (and asynchronously) with the `ssrf-dns-check` feature.
+fn is_private_host(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Ipv4(ip)) => is_private_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_private_ip(IpAddr::V6(ip)),
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        None => false,
+    }
+}
+
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_private_ip(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || (first & 0xfe00) == 0xfc00 // unique local fc00::/7
+                || (first & 0xffc0) == 0xfe80 // link-local fe80::/10
+        }
+    }
+}
+
+#[cfg(feature = "ssrf-dns-check")]
+async fn resolves_to_private_ip(url: &Url) -> bool {
+    let Some(Host::Domain(domain)) = url.host() else {
+        return false;
+    };
+    let port = url.port_or_known_default().unwrap_or(80);
+    match tokio::net::lookup_host((domain, port)).await {
+        Ok(mut addrs) => addrs.any(|addr| is_private_ip(addr.ip())),
+        // Unresolvable hosts will fail to play anyway
+        Err(_) => false,
+    }
 }
 
 /// Addon errors
@@ -1203,6 +1587,9 @@ mod tests {
             resources: vec![ResourceType::Catalog, ResourceType::Stream],
             id_prefixes: vec![],
             behavior_hints: BehaviorHints::default(),
+            manifest_version: None,
+            language: vec![],
+            countries: vec![],
         };
 
         let json = serde_json::to_string(&manifest).unwrap();
@@ -1229,4 +1616,170 @@ mod tests {
         assert_eq!(stream.url, deserialized.url);
         assert_eq!(stream.name, deserialized.name);
     }
+
+    #[test]
+    fn test_meta_preview_accepts_snake_case_fields() {
+        let camel: MetaPreview = serde_json::from_str(
+            r#"{"id":"tt1","type":"movie","name":"A","posterShape":"poster",
+                "releaseInfo":"2020","imdbRating":"7.5"}"#,
+        )
+        .unwrap();
+        let snake: MetaPreview = serde_json::from_str(
+            r#"{"id":"tt1","type":"movie","name":"A","poster_shape":"poster",
+                "release_info":"2020","imdb_rating":7.5}"#,
+        )
+        .unwrap();
+
+        for meta in [camel, snake] {
+            assert_eq!(meta.posterShape.as_deref(), Some("poster"));
+            assert_eq!(meta.releaseInfo.as_deref(), Some("2020"));
+            assert_eq!(meta.imdbRating, Some(7.5));
+        }
+    }
+
+    #[test]
+    fn test_stream_and_subtitle_accept_snake_case_fields() {
+        let camel: Stream = serde_json::from_str(
+            r#"{"url":"https://example.com/a.mp4",
+                "behaviorHints":{"notWebReady":true,"bingeGroup":"g","countryWhitelist":["us"]},
+                "subtitles":[{"id":"1","url":"https://example.com/a.srt","lang":"eng"}]}"#,
+        )
+        .unwrap();
+        let snake: Stream = serde_json::from_str(
+            r#"{"url":"https://example.com/a.mp4",
+                "behavior_hints":{"not_web_ready":true,"binge_group":"g","country_whitelist":["us"]},
+                "subtitles":[{"id":"1","url":"https://example.com/a.srt","language":"eng"}]}"#,
+        )
+        .unwrap();
+
+        for stream in [camel, snake] {
+            assert!(stream.behaviorHints.notWebReady);
+            assert_eq!(stream.behaviorHints.bingeGroup.as_deref(), Some("g"));
+            assert_eq!(
+                stream.behaviorHints.countryWhitelist,
+                Some(vec!["us".to_string()])
+            );
+            assert_eq!(stream.subtitles[0].lang, "eng");
+        }
+    }
+
+    #[test]
+    fn test_lenient_catalog_bare_array() {
+        let body =
+            br#"[{"id":"tt1","type":"movie","name":"A"},{"id":"tt2","type":"movie","name":"B"}]"#;
+        let metas: Vec<MetaPreview> = parse_lenient_list(body, "metas").unwrap();
+        assert_eq!(metas.len(), 2);
+        assert_eq!(metas[1].id, "tt2");
+
+        let standard = br#"{"metas":[{"id":"tt1","type":"movie","name":"A"}]}"#;
+        let metas: Vec<MetaPreview> = parse_lenient_list(standard, "metas").unwrap();
+        assert_eq!(metas.len(), 1);
+    }
+
+    #[test]
+    fn test_lenient_streams_data_wrapper() {
+        let body = br#"{"data":[{"url":"https://example.com/a.mp4"}]}"#;
+        let streams: Vec<Stream> = parse_lenient_list(body, "streams").unwrap();
+        assert_eq!(streams[0].url, "https://example.com/a.mp4");
+
+        let nested = br#"{"data":{"streams":[{"url":"https://example.com/b.mp4"}]}}"#;
+        let streams: Vec<Stream> = parse_lenient_list(nested, "streams").unwrap();
+        assert_eq!(streams[0].url, "https://example.com/b.mp4");
+    }
+
+    #[test]
+    fn test_lenient_rejects_unknown_shapes() {
+        assert!(parse_lenient_list::<Stream>(br#"{"foo":[]}"#, "streams").is_err());
+        assert!(parse_lenient_list::<Stream>(br#""streams""#, "streams").is_err());
+        assert!(parse_lenient_list::<Stream>(b"not json", "streams").is_err());
+    }
+
+    #[test]
+    fn test_parse_cinemeta_meta() {
+        // Trimmed from a real Cinemeta /meta/series/tt0903747.json response
+        let body = r#"{"meta":{
+            "id":"tt0903747","imdb_id":"tt0903747","type":"series","name":"Breaking Bad",
+            "poster":"https://images.metahub.space/poster/medium/tt0903747/img",
+            "background":"https://images.metahub.space/background/medium/tt0903747/img",
+            "logo":"https://images.metahub.space/logo/medium/tt0903747/img",
+            "description":"A chemistry teacher diagnosed with inoperable lung cancer turns to manufacturing methamphetamine.",
+            "releaseInfo":"2008-2013","runtime":"49 min","year":"2008-2013",
+            "genres":["Crime","Drama","Thriller"],"genre":["Crime","Drama","Thriller"],
+            "director":null,"writer":["Vince Gilligan"],
+            "cast":["Bryan Cranston","Aaron Paul","Anna Gunn"],
+            "imdbRating":"9.5","popularity":47.2,"country":"USA",
+            "trailers":[{"source":"HhesaQXLuRY","type":"Trailer"}],
+            "links":[{"name":"9.5","category":"imdb","url":"https://imdb.com/title/tt0903747"},
+                     {"name":"Crime","category":"Genres","url":"stremio:///discover/..."}],
+            "videos":[
+                {"name":"Pilot","season":1,"number":1,"episode":1,"firstAired":"2008-01-20T00:00:00.000Z",
+                 "released":"2008-01-20T00:00:00.000Z","id":"tt0903747:1:1",
+                 "thumbnail":"https://episodes.metahub.space/tt0903747/1/1/w780.jpg",
+                 "description":"Walter White, a struggling high school chemistry teacher..."},
+                {"name":"Cat's in the Bag...","season":1,"number":2,"episode":2,
+                 "released":"2008-01-27T00:00:00.000Z","id":"tt0903747:1:2"}
+            ],
+            "behaviorHints":{"defaultVideoId":null,"hasScheduledVideos":false}
+        }}"#;
+
+        let meta = serde_json::from_str::<MetaResponse>(body).unwrap().meta;
+        assert_eq!(meta.id, "tt0903747");
+        assert_eq!(meta.media_type.0, "series");
+        assert_eq!(meta.name, "Breaking Bad");
+        assert_eq!(meta.releaseInfo.as_deref(), Some("2008-2013"));
+        assert_eq!(meta.runtime.as_deref(), Some("49 min"));
+        assert_eq!(meta.genres, vec!["Crime", "Drama", "Thriller"]);
+        assert!(meta.director.is_empty());
+        assert_eq!(meta.cast.len(), 3);
+        assert_eq!(meta.imdbRating, Some(9.5));
+        assert_eq!(meta.videos.len(), 2);
+        assert_eq!(meta.videos[0].title, "Pilot");
+        assert_eq!(meta.videos[0].season, Some(1));
+        assert_eq!(meta.videos[0].episode, Some(1));
+        assert!(meta.videos[0].overview.is_some());
+        assert_eq!(meta.videos[1].id, "tt0903747:1:2");
+    }
+
+    #[test]
+    fn test_parse_meta_numeric_runtime() {
+        let body = r#"{"meta":{"id":"tt1","type":"movie","name":"A","runtime":142,
+            "releaseInfo":1994,"trailers":null,"videos":null}}"#;
+        let meta = serde_json::from_str::<MetaResponse>(body).unwrap().meta;
+        assert_eq!(meta.runtime.as_deref(), Some("142"));
+        assert_eq!(meta.releaseInfo.as_deref(), Some("1994"));
+        assert!(meta.trailers.is_empty());
+        assert!(meta.videos.is_empty());
+    }
+
+    #[test]
+    fn test_stream_urls_on_private_networks_rejected() {
+        let client = AddonClient::new("https://addon.example.com".to_string()).unwrap();
+
+        assert!(!client.validate_stream_url("http://169.254.169.254/latest/meta-data/"));
+        assert!(!client.validate_stream_url("http://192.168.1.1/admin"));
+        assert!(!client.validate_stream_url("http://10.0.0.5/movie.mp4"));
+        assert!(!client.validate_stream_url("http://172.16.0.1/movie.mp4"));
+        assert!(!client.validate_stream_url("http://127.0.0.1:8080/movie.mp4"));
+        assert!(!client.validate_stream_url("http://localhost/movie.mp4"));
+        assert!(!client.validate_stream_url("http://[::1]/movie.mp4"));
+        assert!(!client.validate_stream_url("http://[fd12:3456::1]/movie.mp4"));
+        assert!(!client.validate_stream_url("http://[::ffff:192.168.0.1]/movie.mp4"));
+
+        assert!(client.validate_stream_url("https://cdn.example.com/movie.mp4"));
+        assert!(client.validate_stream_url("http://93.184.216.34/movie.mp4"));
+        assert!(client.validate_stream_url("magnet:?xt=urn:btih:0123456789abcdef"));
+    }
+
+    #[test]
+    fn test_private_stream_urls_allowed_only_when_opted_in() {
+        // Running on the local network doesn't make an addon trusted
+        let mut local = AddonClient::new("http://192.168.1.20:7000".to_string()).unwrap();
+        assert!(!local.validate_stream_url("http://192.168.1.20:7000/stream/movie.mkv"));
+        local.set_allow_private_hosts(true);
+        assert!(local.validate_stream_url("http://192.168.1.20:7000/stream/movie.mkv"));
+
+        let mut remote = AddonClient::new("https://addon.example.com".to_string()).unwrap();
+        remote.set_allow_private_hosts(true);
+        assert!(remote.validate_stream_url("http://127.0.0.1:11470/movie.mkv"));
+    }
 }
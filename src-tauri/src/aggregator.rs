@@ -3,13 +3,21 @@
  *
  * Queries multiple addons in parallel and merges results
  */
-use crate::addon_protocol::{AddonClient, MetaPreview};
+use crate::addon_protocol::{AddonClient, MetaPreview, ResponseLimits, Subtitle};
 use crate::cache::{ttl, CacheManager};
 use crate::models::Addon;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 use tokio::time::timeout;
+use tracing::Instrument;
+
+/// Addons queried at the same time unless configured otherwise
+const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// Streams buffered for a slow consumer of `query_streams_stream`
+const STREAM_CHANNEL_CAPACITY: usize = 64;
 
 /// Aggregation result with health metrics
 #[derive(Debug)]
@@ -17,6 +25,8 @@ pub struct AggregationResult {
     pub items: Vec<MetaPreview>,
     pub sources: Vec<SourceHealth>,
     pub total_time_ms: u128,
+    /// Id carried by every log line of this aggregation
+    pub request_id: String,
 }
 
 /// Health information for a content source
@@ -29,12 +39,25 @@ pub struct SourceHealth {
     pub error: Option<String>,
     pub item_count: usize,
     pub priority: i32,
+    /// Entries dropped by strict validation
+    #[serde(default)]
+    pub invalid_count: usize,
+    /// Aggregation this result belongs to (catalog aggregations only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 /// Content aggregator for querying multiple addons
 pub struct ContentAggregator {
     timeout_duration: Duration,
     cache: Option<Arc<Mutex<CacheManager>>>,
+    max_concurrency: usize,
+    sort_streams_by_quality: bool,
+    negative_ttl: Duration,
+    strict_validation: bool,
+    artwork_first: bool,
+    response_limits: ResponseLimits,
+    allow_private_hosts: bool,
 }
 
 impl ContentAggregator {
@@ -43,6 +66,13 @@ impl ContentAggregator {
         Self {
             timeout_duration: Duration::from_secs(3),
             cache: None,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            sort_streams_by_quality: false,
+            negative_ttl: ttl::ADDON_FAILURE_TTL,
+            strict_validation: false,
+            artwork_first: false,
+            response_limits: ResponseLimits::default(),
+            allow_private_hosts: false,
         }
     }
 
@@ -51,6 +81,13 @@ impl ContentAggregator {
         Self {
             timeout_duration: Duration::from_secs(3),
             cache: Some(cache),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            sort_streams_by_quality: false,
+            negative_ttl: ttl::ADDON_FAILURE_TTL,
+            strict_validation: false,
+            artwork_first: false,
+            response_limits: ResponseLimits::default(),
+            allow_private_hosts: false,
         }
     }
 
@@ -60,13 +97,81 @@ impl ContentAggregator {
         self
     }
 
-    /// Query multiple addons for catalog content
+    /// Number of addons queried at the same time (at least 1). The rest
+    /// wait for a free slot; their timeout starts once they get one.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Return aggregated streams best quality first instead of in addon
+    /// priority order. Streams of equal quality keep priority order.
+    pub fn with_quality_sort(mut self, enabled: bool) -> Self {
+        self.sort_streams_by_quality = enabled;
+        self
+    }
+
+    /// How long an addon that failed or timed out is skipped for the same
+    /// query (whole seconds; zero disables). Only applies with a cache.
+    pub fn with_negative_ttl(mut self, negative_ttl: Duration) -> Self {
+        self.negative_ttl = negative_ttl;
+        self
+    }
+
+    /// Drop catalog items and streams missing required fields (see
+    /// `MetaPreview::validation_error` and `Stream::validation_error`)
+    /// instead of passing them on. Dropped entries are counted in the
+    /// source's `invalid_count`.
+    pub fn with_strict_validation(mut self, enabled: bool) -> Self {
+        self.strict_validation = enabled;
+        self
+    }
+
+    /// List catalog items with a poster or background before posterless
+    /// stubs. Order is otherwise kept, so priority order holds within each group.
+    pub fn with_artwork_first(mut self, enabled: bool) -> Self {
+        self.artwork_first = enabled;
+        self
+    }
+
+    /// Catalog size and response size caps applied to every addon queried
+    pub fn with_response_limits(mut self, limits: ResponseLimits) -> Self {
+        self.response_limits = limits;
+        self
+    }
+
+    /// Accept stream and subtitle URLs on private/loopback addresses (see
+    /// `AddonClient::set_allow_private_hosts`)
+    pub fn with_private_hosts(mut self, enabled: bool) -> Self {
+        self.allow_private_hosts = enabled;
+        self
+    }
+
+    /// Query multiple addons for catalog content. The aggregation gets a
+    /// fresh request id, attached to every log line down through the
+    /// per-addon queries and returned in the result and each source's
+    /// health, so a bug report can point at the matching logs.
     pub async fn query_catalogs(
         &self,
         addons: &[Addon],
         media_type: &str,
         catalog_id: &str,
         extra: &Option<HashMap<String, String>>,
+    ) -> AggregationResult {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let span = tracing::info_span!("catalog_aggregation", request_id = %request_id);
+        self.collect_catalogs(addons, media_type, catalog_id, extra, request_id)
+            .instrument(span)
+            .await
+    }
+
+    async fn collect_catalogs(
+        &self,
+        addons: &[Addon],
+        media_type: &str,
+        catalog_id: &str,
+        extra: &Option<HashMap<String, String>>,
+        request_id: String,
     ) -> AggregationResult {
         let start = Instant::now();
 
@@ -106,10 +211,12 @@ impl ContentAggregator {
                 items: vec![],
                 sources: vec![],
                 total_time_ms: start.elapsed().as_millis(),
+                request_id,
             };
         }
 
-        // Query all addons in parallel
+        // Query addons in parallel, at most `max_concurrency` at a time
+        let permits = Arc::new(Semaphore::new(self.max_concurrency));
         let mut tasks = Vec::new();
 
         for addon in enabled_addons {
@@ -117,18 +224,28 @@ impl ContentAggregator {
             let media_type = media_type.to_string();
             let catalog_id = catalog_id.to_string();
             let timeout_duration = self.timeout_duration;
+            let response_limits = self.response_limits;
+            let negative_ttl = self.negative_ttl;
             let extra_clone = extra.clone();
             let cache_clone = self.cache.clone();
+            let permits = permits.clone();
+            // Spawned tasks don't inherit the span; carry it over for the request id
+            let span = tracing::Span::current();
 
             let task = tokio::spawn(async move {
+                // Held until the addon has answered
+                let _permit = permits.acquire_owned().await;
                 Self::query_single_addon(
                     &addon_clone,
                     &media_type,
                     &catalog_id,
                     &extra_clone,
                     timeout_duration,
+                    response_limits,
+                    negative_ttl,
                     &cache_clone,
                 )
+                .instrument(span)
                 .await
             });
 
@@ -143,7 +260,14 @@ impl ContentAggregator {
 
         for (addon_id, addon_name, task) in tasks {
             match task.await {
-                Ok((items, health)) => {
+                Ok((mut items, mut health)) => {
+                    health.request_id = Some(request_id.clone());
+                    Self::drop_invalid(
+                        self.strict_validation,
+                        &mut items,
+                        &mut health,
+                        MetaPreview::validation_error,
+                    );
                     let original_count = items.len();
 
                     // Deduplicate by ID (keep first occurrence from highest priority addon)
@@ -194,11 +318,18 @@ impl ContentAggregator {
                         error: Some(format!("Task error: {}", e)),
                         item_count: 0,
                         priority: 0,
+                        invalid_count: 0,
+                        request_id: Some(request_id.clone()),
                     });
                 }
             }
         }
 
+        if self.artwork_first {
+            // Stable sort, so items keep their order within each group
+            all_items.sort_by_key(|item| !item.has_artwork());
+        }
+
         let total_time = start.elapsed();
 
         tracing::info!(
@@ -213,16 +344,20 @@ impl ContentAggregator {
             items: all_items,
             sources,
             total_time_ms: total_time.as_millis(),
+            request_id,
         }
     }
 
     /// Query a single addon with timeout
+    #[allow(clippy::too_many_arguments)] // Per-query settings copied out of the aggregator
     async fn query_single_addon(
         addon: &Addon,
         media_type: &str,
         catalog_id: &str,
         extra: &Option<HashMap<String, String>>,
         timeout_duration: Duration,
+        response_limits: ResponseLimits,
+        negative_ttl: Duration,
         cache: &Option<Arc<Mutex<CacheManager>>>,
     ) -> (Vec<MetaPreview>, SourceHealth) {
         let start = Instant::now();
@@ -264,12 +399,18 @@ impl ContentAggregator {
                             error: None,
                             item_count,
                             priority: addon.priority,
+                            invalid_count: 0,
+                            request_id: None,
                         },
                     );
                 }
             }
         }
 
+        if let Some(health) = Self::recent_failure(addon, &cache_key, cache) {
+            return (vec![], health);
+        }
+
         tracing::debug!(
             addon_id = %addon.id,
             addon_name = %addon.name,
@@ -277,17 +418,11 @@ impl ContentAggregator {
             "Querying addon (cache miss)"
         );
 
-        // Use addon URL directly (manifest is already a struct, not JSON string)
-        let base_url = if addon.url.ends_with("/manifest.json") {
-            addon.url.replace("/manifest.json", "")
-        } else if addon.url.ends_with("manifest.json") {
-            addon.url.replace("manifest.json", "")
-        } else {
-            addon.url.clone()
-        };
+        // Addon URL with the user's configuration applied
+        let base_url = addon.base_url();
 
         // Create client
-        let client = match AddonClient::new(base_url) {
+        let client = match AddonClient::new(base_url).map(|c| c.with_limits(response_limits)) {
             Ok(client) => client,
             Err(e) => {
                 let elapsed = start.elapsed();
@@ -301,6 +436,8 @@ impl ContentAggregator {
                         error: Some(format!("Client creation failed: {}", e)),
                         item_count: 0,
                         priority: addon.priority,
+                        invalid_count: 0,
+                        request_id: None,
                     },
                 );
             }
@@ -315,7 +452,7 @@ impl ContentAggregator {
 
         let elapsed = start.elapsed();
 
-        match result {
+        let (items, health) = match result {
             Ok(Ok(response)) => {
                 let item_count = response.metas.len();
                 tracing::debug!(
@@ -347,6 +484,8 @@ impl ContentAggregator {
                         error: None,
                         item_count,
                         priority: addon.priority,
+                        invalid_count: 0,
+                        request_id: None,
                     },
                 )
             }
@@ -368,6 +507,8 @@ impl ContentAggregator {
                         error: Some(e.to_string()),
                         item_count: 0,
                         priority: addon.priority,
+                        invalid_count: 0,
+                        request_id: None,
                     },
                 )
             }
@@ -388,10 +529,15 @@ impl ContentAggregator {
                         error: Some("Timeout".to_string()),
                         item_count: 0,
                         priority: addon.priority,
+                        invalid_count: 0,
+                        request_id: None,
                     },
                 )
             }
-        }
+        };
+
+        Self::remember_failure(addon, &cache_key, &health, negative_ttl, cache);
+        (items, health)
     }
 
     /// Query multiple addons for streams
@@ -437,7 +583,8 @@ impl ContentAggregator {
             };
         }
 
-        // Query all addons in parallel
+        // Query addons in parallel, at most `max_concurrency` at a time
+        let permits = Arc::new(Semaphore::new(self.max_concurrency));
         let mut tasks = Vec::new();
 
         for addon in enabled_addons {
@@ -445,14 +592,23 @@ impl ContentAggregator {
             let media_type = media_type.to_string();
             let media_id = media_id.to_string();
             let timeout_duration = self.timeout_duration;
+            let response_limits = self.response_limits;
+            let allow_private_hosts = self.allow_private_hosts;
+            let negative_ttl = self.negative_ttl;
             let cache_clone = self.cache.clone();
+            let permits = permits.clone();
 
             let task = tokio::spawn(async move {
+                // Held until the addon has answered
+                let _permit = permits.acquire_owned().await;
                 Self::query_single_addon_streams(
                     &addon_clone,
                     &media_type,
                     &media_id,
                     timeout_duration,
+                    response_limits,
+                    allow_private_hosts,
+                    negative_ttl,
                     &cache_clone,
                 )
                 .await
@@ -468,14 +624,20 @@ impl ContentAggregator {
 
         for (addon_id, addon_name, task) in tasks {
             match task.await {
-                Ok((streams, health)) => {
-                    // Deduplicate streams by URL (keep first occurrence from highest priority addon)
+                Ok((mut streams, mut health)) => {
+                    Self::drop_invalid(
+                        self.strict_validation,
+                        &mut streams,
+                        &mut health,
+                        crate::addon_protocol::Stream::validation_error,
+                    );
+                    // Deduplicate streams by infohash or URL (keep first occurrence
+                    // from highest priority addon)
                     let unique_streams: Vec<_> = streams
                         .into_iter()
                         .filter(|stream| {
-                            // Normalize URL for comparison
-                            let normalized_url = stream.url.trim().to_lowercase();
-                            match seen_urls.entry(normalized_url) {
+                            let key = crate::stream_filter::stream_dedup_key(stream);
+                            match seen_urls.entry(key) {
                                 std::collections::hash_map::Entry::Vacant(e) => {
                                     e.insert(addon_id.clone());
                                     true
@@ -509,11 +671,17 @@ impl ContentAggregator {
                         error: Some(format!("Task error: {}", e)),
                         item_count: 0,
                         priority: 0,
+                        invalid_count: 0,
+                        request_id: None,
                     });
                 }
             }
         }
 
+        if self.sort_streams_by_quality {
+            crate::quality::sort_streams(&mut all_streams);
+        }
+
         let total_time = start.elapsed();
 
         tracing::info!(
@@ -540,6 +708,168 @@ impl ContentAggregator {
         let start = Instant::now();
 
         // Reuse the same logic as query_streams but attach addon info
+        let enabled_addons = Self::stream_addons(addons);
+
+        if enabled_addons.is_empty() {
+            return StreamAggregationResultDetailed { streams: vec![], sources: vec![], total_time_ms: 0 };
+        }
+
+        let permits = Arc::new(Semaphore::new(self.max_concurrency));
+        let mut tasks = Vec::new();
+        for addon in enabled_addons {
+            let addon_clone = addon.clone();
+            let media_type = media_type.to_string();
+            let media_id = media_id.to_string();
+            let timeout_duration = self.timeout_duration;
+            let response_limits = self.response_limits;
+            let allow_private_hosts = self.allow_private_hosts;
+            let negative_ttl = self.negative_ttl;
+            let cache_clone = self.cache.clone();
+            let permits = permits.clone();
+            let task = tokio::spawn(async move {
+                // Held until the addon has answered
+                let _permit = permits.acquire_owned().await;
+                let (streams, health) = Self::query_single_addon_streams(
+                    &addon_clone,
+                    &media_type,
+                    &media_id,
+                    timeout_duration,
+                    response_limits,
+                    allow_private_hosts,
+                    negative_ttl,
+                    &cache_clone,
+                )
+                .await;
+                (addon_clone.id.clone(), addon_clone.name.clone(), streams, health)
+            });
+            tasks.push(task);
+        }
+
+        let mut all_streams: Vec<crate::models::StreamWithSource> = Vec::new();
+        let mut sources = Vec::new();
+        let mut seen_urls = std::collections::HashSet::new();
+
+        for task in tasks {
+            match task.await {
+                Ok((addon_id, addon_name, mut streams, mut health)) => {
+                    Self::drop_invalid(
+                        self.strict_validation,
+                        &mut streams,
+                        &mut health,
+                        crate::addon_protocol::Stream::validation_error,
+                    );
+                    for s in streams {
+                        if seen_urls.insert(crate::stream_filter::stream_dedup_key(&s)) {
+                            all_streams.push(Self::with_source(s, &addon_id, &addon_name));
+                        }
+                    }
+                    sources.push(health);
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Task join error (detailed)");
+                }
+            }
+        }
+
+        if self.sort_streams_by_quality {
+            crate::quality::sort_streams_with_source(&mut all_streams);
+        }
+
+        StreamAggregationResultDetailed {
+            streams: all_streams,
+            sources,
+            total_time_ms: start.elapsed().as_millis(),
+        }
+    }
+
+    /// Query stream addons in parallel like `query_streams_detailed`, but send
+    /// each addon's streams to the returned channel as soon as that addon
+    /// answers instead of waiting for the slowest one. Streams are
+    /// deduplicated and sent in arrival order, regardless of the quality
+    /// sort. Each addon's health goes to the second channel as it answers;
+    /// both close once every addon has answered or timed out.
+    pub fn query_streams_stream(
+        &self,
+        addons: &[Addon],
+        media_type: &str,
+        media_id: &str,
+    ) -> (
+        tokio::sync::mpsc::Receiver<crate::models::StreamWithSource>,
+        tokio::sync::mpsc::UnboundedReceiver<SourceHealth>,
+    ) {
+        let (tx, rx) = tokio::sync::mpsc::channel(STREAM_CHANNEL_CAPACITY);
+        let (addon_tx, mut addon_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (health_tx, health_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let permits = Arc::new(Semaphore::new(self.max_concurrency));
+        for addon in Self::stream_addons(addons) {
+            let addon = addon.clone();
+            let media_type = media_type.to_string();
+            let media_id = media_id.to_string();
+            let timeout_duration = self.timeout_duration;
+            let response_limits = self.response_limits;
+            let allow_private_hosts = self.allow_private_hosts;
+            let negative_ttl = self.negative_ttl;
+            let cache_clone = self.cache.clone();
+            let permits = permits.clone();
+            let addon_tx = addon_tx.clone();
+            let health_tx = health_tx.clone();
+            let strict_validation = self.strict_validation;
+            tokio::spawn(async move {
+                // Held until the addon has answered
+                let _permit = permits.acquire_owned().await;
+                let (mut streams, mut health) = Self::query_single_addon_streams(
+                    &addon,
+                    &media_type,
+                    &media_id,
+                    timeout_duration,
+                    response_limits,
+                    allow_private_hosts,
+                    negative_ttl,
+                    &cache_clone,
+                )
+                .await;
+                Self::drop_invalid(
+                    strict_validation,
+                    &mut streams,
+                    &mut health,
+                    crate::addon_protocol::Stream::validation_error,
+                );
+                tracing::debug!(
+                    addon_id = %addon.id,
+                    stream_count = streams.len(),
+                    success = health.success,
+                    "Addon streams ready"
+                );
+                let _ = health_tx.send(health);
+                let _ = addon_tx.send((addon.id, addon.name, streams));
+            });
+        }
+        // The forwarding task ends once every addon task has dropped its sender
+        drop(addon_tx);
+        drop(health_tx);
+
+        tokio::spawn(async move {
+            let mut seen = std::collections::HashSet::new();
+            while let Some((addon_id, addon_name, streams)) = addon_rx.recv().await {
+                for s in streams {
+                    if !seen.insert(crate::stream_filter::stream_dedup_key(&s)) {
+                        continue;
+                    }
+                    let item = Self::with_source(s, &addon_id, &addon_name);
+                    if tx.send(item).await.is_err() {
+                        // Receiver dropped; nobody is listening any more
+                        return;
+                    }
+                }
+            }
+        });
+
+        (rx, health_rx)
+    }
+
+    /// Enabled addons that serve streams, highest priority first
+    fn stream_addons(addons: &[Addon]) -> Vec<&Addon> {
         let mut enabled_addons: Vec<_> = addons
             .iter()
             .filter(|a| {
@@ -556,73 +886,218 @@ impl ContentAggregator {
             })
             .collect();
         enabled_addons.sort_by(|a, b| b.priority.cmp(&a.priority));
+        enabled_addons
+    }
+
+    /// Attach the source addon and parsed hints to a stream
+    fn with_source(
+        s: crate::addon_protocol::Stream,
+        addon_id: &str,
+        addon_name: &str,
+    ) -> crate::models::StreamWithSource {
+        let source_type = crate::models::StreamSourceType::classify(&s.url, s.behaviorHints.p2p);
+        let size_bytes = crate::stream_filter::stream_size_bytes(&s);
+        let seeders = crate::stream_filter::stream_seeders(&s);
+        crate::models::StreamWithSource {
+            url: s.url,
+            title: s.title,
+            name: s.name,
+            description: s.description,
+            addon_id: addon_id.to_string(),
+            addon_name: addon_name.to_string(),
+            source_type,
+            size_bytes,
+            seeders,
+            not_web_ready: s.behaviorHints.notWebReady,
+            subtitles: s.subtitles,
+        }
+    }
+
+    /// Query all subtitle addons in parallel, deduplicating by URL
+    pub async fn query_subtitles_detailed(
+        &self,
+        addons: &[Addon],
+        media_type: &str,
+        media_id: &str,
+    ) -> SubtitleAggregationResult {
+        let start = Instant::now();
+
+        let mut enabled_addons: Vec<_> = addons
+            .iter()
+            .filter(|a| {
+                a.enabled
+                    && !a.url.is_empty()
+                    && a.manifest.resources.contains(&"subtitles".to_string())
+            })
+            .collect();
+        enabled_addons.sort_by_key(|a| std::cmp::Reverse(a.priority));
 
         if enabled_addons.is_empty() {
-            return StreamAggregationResultDetailed { streams: vec![], sources: vec![], total_time_ms: 0 };
+            return SubtitleAggregationResult {
+                subtitles: vec![],
+                sources: vec![],
+                total_time_ms: 0,
+            };
         }
 
+        let permits = Arc::new(Semaphore::new(self.max_concurrency));
         let mut tasks = Vec::new();
         for addon in enabled_addons {
             let addon_clone = addon.clone();
             let media_type = media_type.to_string();
             let media_id = media_id.to_string();
             let timeout_duration = self.timeout_duration;
+            let response_limits = self.response_limits;
+            let allow_private_hosts = self.allow_private_hosts;
+            let negative_ttl = self.negative_ttl;
             let cache_clone = self.cache.clone();
+            let permits = permits.clone();
             let task = tokio::spawn(async move {
-                let (streams, health) = Self::query_single_addon_streams(
+                // Held until the addon has answered
+                let _permit = permits.acquire_owned().await;
+                Self::query_single_addon_subtitles(
                     &addon_clone,
                     &media_type,
                     &media_id,
                     timeout_duration,
+                    response_limits,
+                    allow_private_hosts,
+                    negative_ttl,
                     &cache_clone,
                 )
-                .await;
-                (addon_clone.id.clone(), addon_clone.name.clone(), streams, health)
+                .await
             });
             tasks.push(task);
         }
 
-        let mut all_streams: Vec<crate::models::StreamWithSource> = Vec::new();
+        let mut all_subtitles = Vec::new();
         let mut sources = Vec::new();
         let mut seen_urls = std::collections::HashSet::new();
 
         for task in tasks {
             match task.await {
-                Ok((addon_id, addon_name, streams, health)) => {
-                    for s in streams {
-                        let normalized = s.url.trim().to_lowercase();
-                        if seen_urls.insert(normalized) {
-                            all_streams.push(crate::models::StreamWithSource {
-                                url: s.url,
-                                title: s.title,
-                                name: s.name,
-                                description: s.description,
-                                addon_id: addon_id.clone(),
-                                addon_name: addon_name.clone(),
-                            });
+                Ok((subtitles, health)) => {
+                    for subtitle in subtitles {
+                        if seen_urls.insert(subtitle.url.trim().to_lowercase()) {
+                            all_subtitles.push(subtitle);
                         }
                     }
                     sources.push(health);
                 }
                 Err(e) => {
-                    tracing::error!(error = %e, "Task join error (detailed)");
+                    tracing::error!(error = %e, "Task join error (subtitles)");
                 }
             }
         }
 
-        StreamAggregationResultDetailed {
-            streams: all_streams,
+        tracing::info!(
+            total_subtitles = all_subtitles.len(),
+            source_count = sources.len(),
+            duration_ms = start.elapsed().as_millis(),
+            "Subtitle aggregation completed"
+        );
+
+        SubtitleAggregationResult {
+            subtitles: all_subtitles,
             sources,
             total_time_ms: start.elapsed().as_millis(),
         }
     }
 
+    /// Query single addon for subtitles
+    #[allow(clippy::too_many_arguments)] // Per-query settings copied out of the aggregator
+    async fn query_single_addon_subtitles(
+        addon: &Addon,
+        media_type: &str,
+        media_id: &str,
+        timeout_duration: Duration,
+        response_limits: ResponseLimits,
+        allow_private_hosts: bool,
+        negative_ttl: Duration,
+        cache: &Option<Arc<Mutex<CacheManager>>>,
+    ) -> (Vec<Subtitle>, SourceHealth) {
+        let start = Instant::now();
+        let health = |success: bool, error: Option<String>, item_count: usize| SourceHealth {
+            addon_id: addon.id.clone(),
+            addon_name: addon.name.clone(),
+            response_time_ms: start.elapsed().as_millis(),
+            success,
+            error,
+            item_count,
+            priority: addon.priority,
+            invalid_count: 0,
+            request_id: None,
+        };
+
+        let cache_key = format!("addon:subtitles:{}:{}:{}", addon.id, media_type, media_id);
+
+        if let Some(cache_manager) = cache {
+            if let Ok(cache_guard) = cache_manager.lock() {
+                if let Ok(Some(cached)) =
+                    cache_guard.get_addon_response::<Vec<Subtitle>>(&cache_key, &addon.id)
+                {
+                    tracing::debug!(
+                        addon_id = %addon.id,
+                        subtitle_count = cached.len(),
+                        "Subtitles from cache"
+                    );
+                    let count = cached.len();
+                    return (cached, health(true, None, count));
+                }
+            }
+        }
+
+        if let Some(health) = Self::recent_failure(addon, &cache_key, cache) {
+            return (vec![], health);
+        }
+
+        let base_url = addon.base_url();
+
+        let client = match AddonClient::new(base_url).map(|c| c.with_limits(response_limits)) {
+            Ok(mut client) => {
+                client.set_allow_private_hosts(allow_private_hosts);
+                client
+            }
+            Err(e) => {
+                let error = format!("Client error: {}", e);
+                return (vec![], health(false, Some(error), 0));
+            }
+        };
+
+        let result = timeout(timeout_duration, client.get_subtitles(media_type, media_id)).await;
+        let (subtitles, health) = match result {
+            Ok(Ok(response)) => {
+                if let Some(cache_manager) = cache {
+                    if let Ok(cache_guard) = cache_manager.lock() {
+                        let _ = cache_guard.set_addon_response(
+                            &cache_key,
+                            &addon.id,
+                            &response.subtitles,
+                            ttl::ADDON_SUBTITLE_TTL,
+                        );
+                    }
+                }
+                let count = response.subtitles.len();
+                (response.subtitles, health(true, None, count))
+            }
+            Ok(Err(e)) => (vec![], health(false, Some(e.to_string()), 0)),
+            Err(_) => (vec![], health(false, Some("Timeout".to_string()), 0)),
+        };
+
+        Self::remember_failure(addon, &cache_key, &health, negative_ttl, cache);
+        (subtitles, health)
+    }
+
     /// Query single addon for streams
+    #[allow(clippy::too_many_arguments)] // Per-query settings copied out of the aggregator
     async fn query_single_addon_streams(
         addon: &Addon,
         media_type: &str,
         media_id: &str,
         timeout_duration: Duration,
+        response_limits: ResponseLimits,
+        allow_private_hosts: bool,
+        negative_ttl: Duration,
         cache: &Option<Arc<Mutex<CacheManager>>>,
     ) -> (Vec<crate::addon_protocol::Stream>, SourceHealth) {
         let start = Instant::now();
@@ -653,23 +1128,26 @@ impl ContentAggregator {
                             error: None,
                             item_count: stream_count,
                             priority: addon.priority,
+                            invalid_count: 0,
+                            request_id: None,
                         },
                     );
                 }
             }
         }
 
-        // Use addon URL directly (manifest is already a struct, not JSON string)
-        let base_url = if addon.url.ends_with("/manifest.json") {
-            addon.url.replace("/manifest.json", "")
-        } else if addon.url.ends_with("manifest.json") {
-            addon.url.replace("manifest.json", "")
-        } else {
-            addon.url.clone()
-        };
+        if let Some(health) = Self::recent_failure(addon, &cache_key, cache) {
+            return (vec![], health);
+        }
 
-        let client = match AddonClient::new(base_url) {
-            Ok(client) => client,
+        // Addon URL with the user's configuration applied
+        let base_url = addon.base_url();
+
+        let client = match AddonClient::new(base_url).map(|c| c.with_limits(response_limits)) {
+            Ok(mut client) => {
+                client.set_allow_private_hosts(allow_private_hosts);
+                client
+            }
             Err(e) => {
                 return (
                     vec![],
@@ -681,6 +1159,8 @@ impl ContentAggregator {
                         error: Some(format!("Client error: {}", e)),
                         item_count: 0,
                         priority: addon.priority,
+                        invalid_count: 0,
+                        request_id: None,
                     },
                 );
             }
@@ -690,7 +1170,7 @@ impl ContentAggregator {
 
         let elapsed = start.elapsed();
 
-        match result {
+        let (streams, health) = match result {
             Ok(Ok(response)) => {
                 let stream_count = response.streams.len();
 
@@ -716,6 +1196,8 @@ impl ContentAggregator {
                         error: None,
                         item_count: stream_count,
                         priority: addon.priority,
+                        invalid_count: 0,
+                        request_id: None,
                     },
                 )
             }
@@ -729,6 +1211,8 @@ impl ContentAggregator {
                     error: Some(e.to_string()),
                     item_count: 0,
                     priority: addon.priority,
+                    invalid_count: 0,
+                    request_id: None,
                 },
             ),
             Err(_) => (
@@ -741,8 +1225,90 @@ impl ContentAggregator {
                     error: Some("Timeout".to_string()),
                     item_count: 0,
                     priority: addon.priority,
+                    invalid_count: 0,
+                    request_id: None,
                 },
             ),
+        };
+
+        Self::remember_failure(addon, &cache_key, &health, negative_ttl, cache);
+        (streams, health)
+    }
+
+    /// With strict validation on, drop the entries `validation_error`
+    /// rejects and count them against the source
+    fn drop_invalid<T>(
+        strict: bool,
+        items: &mut Vec<T>,
+        health: &mut SourceHealth,
+        validation_error: impl Fn(&T) -> Option<&'static str>,
+    ) {
+        if !strict {
+            return;
+        }
+        let before = items.len();
+        items.retain(|item| match validation_error(item) {
+            Some(reason) => {
+                tracing::debug!(addon_id = %health.addon_id, reason, "Dropping invalid addon entry");
+                false
+            }
+            None => true,
+        });
+
+        let dropped = before - items.len();
+        if dropped > 0 {
+            tracing::warn!(
+                addon_id = %health.addon_id,
+                dropped,
+                "Addon returned entries missing required fields"
+            );
+            health.invalid_count += dropped;
+            health.item_count = health.item_count.saturating_sub(dropped);
+        }
+    }
+
+    /// Failed health for an addon that failed this same query recently, so
+    /// it can be skipped without another network round trip
+    fn recent_failure(
+        addon: &Addon,
+        cache_key: &str,
+        cache: &Option<Arc<Mutex<CacheManager>>>,
+    ) -> Option<SourceHealth> {
+        let cache_guard = cache.as_ref()?.lock().ok()?;
+        let error = cache_guard.get_addon_failure(cache_key, &addon.id).ok()??;
+        tracing::debug!(
+            addon_id = %addon.id,
+            error = %error,
+            "Skipping recently failed addon"
+        );
+        Some(SourceHealth {
+            addon_id: addon.id.clone(),
+            addon_name: addon.name.clone(),
+            response_time_ms: 0,
+            success: false,
+            error: Some(error),
+            item_count: 0,
+            priority: addon.priority,
+            invalid_count: 0,
+            request_id: None,
+        })
+    }
+
+    /// Remember a failed query so it is skipped for `negative_ttl`
+    fn remember_failure(
+        addon: &Addon,
+        cache_key: &str,
+        health: &SourceHealth,
+        negative_ttl: Duration,
+        cache: &Option<Arc<Mutex<CacheManager>>>,
+    ) {
+        if health.success || negative_ttl.is_zero() {
+            return;
+        }
+        if let (Some(cache_manager), Some(error)) = (cache, &health.error) {
+            if let Ok(cache_guard) = cache_manager.lock() {
+                let _ = cache_guard.set_addon_failure(cache_key, &addon.id, error, negative_ttl);
+            }
         }
     }
 }
@@ -767,3 +1333,11 @@ pub struct StreamAggregationResultDetailed {
     pub sources: Vec<SourceHealth>,
     pub total_time_ms: u128,
 }
+
+/// Subtitle aggregation result
+#[derive(Debug)]
+pub struct SubtitleAggregationResult {
+    pub subtitles: Vec<Subtitle>,
+    pub sources: Vec<SourceHealth>,
+    pub total_time_ms: u128,
+}
@@ -1,8 +1,12 @@
 use crate::addon_protocol::{AddonClient, ResourceType};
 use crate::cache::{ttl, CacheManager};
+use crate::database::Database;
 use crate::models::*;
+use crate::version::is_newer_version;
+use crate::DEFAULT_PROFILE_ID;
 use anyhow::{anyhow, Result};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 // Mock TMDB API integration (in a real app, you'd use actual API keys)
@@ -137,6 +141,7 @@ fn parse_tmdb_movie_details(result: &Value, media_type: &MediaType) -> Option<Me
             .collect()
     });
 
+    let artwork = tmdb_artwork(result);
     Some(MediaItem {
         id: result["id"].as_u64()?.to_string(),
         title: title.to_string(),
@@ -144,17 +149,16 @@ fn parse_tmdb_movie_details(result: &Value, media_type: &MediaType) -> Option<Me
         year,
         genre: genres,
         description: result["overview"].as_str().map(|s| s.to_string()),
-        poster_url: result["poster_path"]
-            .as_str()
-            .map(|path| format!("https://image.tmdb.org/t/p/w500{}", path)),
-        backdrop_url: result["backdrop_path"]
-            .as_str()
-            .map(|path| format!("https://image.tmdb.org/t/p/w1280{}", path)),
+        poster_url: artwork.poster_url,
+        backdrop_url: artwork.backdrop_url,
+        logo_url: artwork.logo_url,
+        thumb_url: artwork.thumb_url,
         rating: result["vote_average"].as_f64().map(|r| r as f32),
         duration: result["runtime"].as_i64().map(|d| d as i32),
         added_to_library: None,
         watched: false,
         progress: None,
+        progress_percent: None,
     })
 }
 
@@ -179,9 +183,8 @@ pub async fn get_streaming_url(content_id: &str) -> Result<String> {
     )
 }
 
-pub async fn install_addon(addon_url: &str) -> Result<Addon> {
-    log::info!("Installing addon from: {}", addon_url);
-
+/// Strip an addon URL down to its base (without /manifest.json)
+fn normalize_addon_url(addon_url: &str) -> Result<String> {
     // Validate input URL is not empty or just whitespace
     let trimmed_url = addon_url.trim();
     if trimmed_url.is_empty() {
@@ -199,6 +202,13 @@ pub async fn install_addon(addon_url: &str) -> Result<Addon> {
     } else {
         trimmed_url.trim_end_matches('/').to_string()
     };
+    Ok(base)
+}
+
+/// Normalize an addon URL to its base (without /manifest.json) and reject
+/// URLs that aren't safe to install: non-https, private hosts, oversized
+fn addon_base_url(addon_url: &str) -> Result<String> {
+    let base = normalize_addon_url(addon_url)?;
 
     // Validate base URL format and scheme
     let parsed_url = url::Url::parse(&base).map_err(|e| anyhow!("Invalid addon URL: {}", e))?;
@@ -244,6 +254,14 @@ pub async fn install_addon(addon_url: &str) -> Result<Addon> {
         return Err(anyhow!("Addon URL exceeds maximum length of 2048 characters"));
     }
 
+    Ok(base)
+}
+
+pub async fn install_addon(addon_url: &str) -> Result<Addon> {
+    log::info!("Installing addon from: {}", addon_url);
+
+    let base = addon_base_url(addon_url)?;
+
     // Use protocol client for strict validation and size limits
     let client = AddonClient::new(base.clone())
         .map_err(|e| anyhow!("Failed to create addon client: {}", e))?;
@@ -253,17 +271,7 @@ pub async fn install_addon(addon_url: &str) -> Result<Addon> {
         .map_err(|e| anyhow!("Failed to fetch addon manifest: {}", e))?;
 
     // Map protocol manifest to storage model
-    let resources: Vec<String> = p_manifest
-        .resources
-        .iter()
-        .map(|r| match r {
-            ResourceType::Catalog => "catalog".to_string(),
-            ResourceType::Stream => "stream".to_string(),
-            ResourceType::Meta => "meta".to_string(),
-            ResourceType::Subtitles => "subtitles".to_string(),
-            ResourceType::AddonCatalog => "addon_catalog".to_string(),
-        })
-        .collect();
+    let resources: Vec<String> = p_manifest.resources.iter().map(resource_name).collect();
 
     let types: Vec<String> = p_manifest.types.iter().map(|t| t.0.clone()).collect();
 
@@ -315,6 +323,7 @@ pub async fn install_addon(addon_url: &str) -> Result<Addon> {
         addon_type,
         manifest,
         priority: 0,
+        config: Default::default(),
     };
 
     log::info!(
@@ -326,6 +335,197 @@ pub async fn install_addon(addon_url: &str) -> Result<Addon> {
     Ok(addon)
 }
 
+fn resource_name(resource: &ResourceType) -> String {
+    match resource {
+        ResourceType::Catalog => "catalog".to_string(),
+        ResourceType::Stream => "stream".to_string(),
+        ResourceType::Meta => "meta".to_string(),
+        ResourceType::Subtitles => "subtitles".to_string(),
+        ResourceType::AddonCatalog => "addon_catalog".to_string(),
+    }
+}
+
+/// Well known IDs used to probe stream/meta/subtitle resources
+const PROBE_MOVIE_ID: &str = "tt0111161";
+const PROBE_SERIES_ID: &str = "tt0944947";
+const PROBE_EPISODE_ID: &str = "tt0944947:1:1";
+
+/// Per-request timeout while testing an addon
+const PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// What an addon claims to provide
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ManifestSummary {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub resources: Vec<String>,
+    pub types: Vec<String>,
+}
+
+/// Outcome of one probe request
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ResourceCheck {
+    pub resource: String,
+    pub success: bool,
+    pub latency_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Items the resource returned (catalog metas, streams, subtitles)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_count: Option<usize>,
+}
+
+/// Result of `test_addon`: the manifest check and one check per declared
+/// resource. `manifest` is None when the manifest couldn't be fetched.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AddonTestReport {
+    pub url: String,
+    pub manifest: Option<ManifestSummary>,
+    pub checks: Vec<ResourceCheck>,
+}
+
+impl AddonTestReport {
+    /// Every check passed
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|c| c.success)
+    }
+}
+
+/// Query one resource of the addon at `addon_url` and return the response
+/// exactly as the addon sent it. Nothing touches the database or cache (no
+/// health records, cached responses or installs), and the install URL policy
+/// is skipped so addon developers can point it at addons on their machine.
+pub async fn sandbox_query(
+    addon_url: &str,
+    resource: &str,
+    media_type: &str,
+    id: &str,
+    extra: Option<&HashMap<String, String>>,
+) -> Result<Value> {
+    let client = AddonClient::new(normalize_addon_url(addon_url)?)
+        .map_err(|e| anyhow!("Failed to create addon client: {}", e))?;
+    client
+        .get_resource_raw(resource, media_type, id, extra)
+        .await
+        .map_err(|e| anyhow!("Sandbox {} query failed: {}", resource, e))
+}
+
+/// Check that an addon URL would install and that each resource it declares
+/// actually answers, without installing it
+pub async fn test_addon(addon_url: &str) -> Result<AddonTestReport> {
+    let base = addon_base_url(addon_url)?;
+    Ok(probe_addon(&base).await)
+}
+
+/// Fetch the manifest at `base_url` and probe each declared resource with a
+/// sample catalog or well known ID. Skips the URL policy checks of
+/// `test_addon`, so it can be pointed at local addons.
+pub async fn probe_addon(base_url: &str) -> AddonTestReport {
+    let mut report = AddonTestReport {
+        url: base_url.to_string(),
+        manifest: None,
+        checks: Vec::new(),
+    };
+
+    let client = match AddonClient::with_timeout(base_url.to_string(), PROBE_TIMEOUT) {
+        Ok(client) => client,
+        Err(e) => {
+            report.checks.push(ResourceCheck {
+                resource: "manifest".to_string(),
+                success: false,
+                latency_ms: 0,
+                error: Some(e.to_string()),
+                item_count: None,
+            });
+            return report;
+        }
+    };
+
+    let start = std::time::Instant::now();
+    let manifest = client.get_manifest().await;
+    report.checks.push(ResourceCheck {
+        resource: "manifest".to_string(),
+        success: manifest.is_ok(),
+        latency_ms: start.elapsed().as_millis(),
+        error: manifest.as_ref().err().map(|e| e.to_string()),
+        item_count: None,
+    });
+    let Ok(manifest) = manifest else {
+        return report;
+    };
+
+    report.manifest = Some(ManifestSummary {
+        id: manifest.id.clone(),
+        name: manifest.name.clone(),
+        version: manifest.version.clone(),
+        resources: manifest.resources.iter().map(resource_name).collect(),
+        types: manifest.types.iter().map(|t| t.0.clone()).collect(),
+    });
+
+    // Prefer probing movies, the type almost every addon serves
+    let media_type = manifest
+        .types
+        .iter()
+        .map(|t| t.0.as_str())
+        .find(|t| *t == "movie")
+        .or_else(|| manifest.types.first().map(|t| t.0.as_str()))
+        .unwrap_or("movie");
+    let (item_id, meta_id) = if media_type == "series" {
+        (PROBE_EPISODE_ID, PROBE_SERIES_ID)
+    } else {
+        (PROBE_MOVIE_ID, PROBE_MOVIE_ID)
+    };
+
+    for resource in &manifest.resources {
+        let start = std::time::Instant::now();
+        let result: Result<Option<usize>, String> = match resource {
+            ResourceType::Catalog => match manifest.catalogs.first() {
+                Some(catalog) => client
+                    .get_catalog(&catalog.media_type.0, &catalog.id, None)
+                    .await
+                    .map(|r| Some(r.metas.len()))
+                    .map_err(|e| e.to_string()),
+                None => Err("No catalogs declared".to_string()),
+            },
+            ResourceType::Stream => client
+                .get_streams(media_type, item_id)
+                .await
+                .map(|r| Some(r.streams.len()))
+                .map_err(|e| e.to_string()),
+            ResourceType::Meta => client
+                .get_meta(media_type, meta_id)
+                .await
+                .map(|_| None)
+                .map_err(|e| e.to_string()),
+            ResourceType::Subtitles => client
+                .get_subtitles(media_type, item_id)
+                .await
+                .map(|r| Some(r.subtitles.len()))
+                .map_err(|e| e.to_string()),
+            // Addon catalogs have no well known request to probe with
+            ResourceType::AddonCatalog => continue,
+        };
+
+        report.checks.push(ResourceCheck {
+            resource: resource_name(resource),
+            success: result.is_ok(),
+            latency_ms: start.elapsed().as_millis(),
+            error: result.as_ref().err().cloned(),
+            item_count: result.unwrap_or(None),
+        });
+    }
+
+    log::info!(
+        "Tested addon {}: {}/{} checks passed",
+        base_url,
+        report.checks.iter().filter(|c| c.success).count(),
+        report.checks.len()
+    );
+
+    report
+}
+
 /// Get real working Stremio community addons
 /// These are actual production addons with real manifests
 pub async fn get_builtin_addons() -> Result<Vec<Addon>> {
@@ -367,6 +567,101 @@ pub async fn get_builtin_addons() -> Result<Vec<Addon>> {
     Ok(addons)
 }
 
+/// Compare an installed addon against the latest known version
+pub fn addon_update_status(addon: &Addon, latest_version: &str) -> AddonUpdate {
+    AddonUpdate {
+        addon_id: addon.id.clone(),
+        current_version: addon.version.clone(),
+        latest_version: latest_version.to_string(),
+        has_update: is_newer_version(&addon.version, latest_version),
+    }
+}
+
+/// Fetch the remote manifest of an installed addon and report whether it has an update
+pub async fn check_addon_update(addon: &Addon) -> Result<AddonUpdate> {
+    let latest = install_addon(&addon.url).await?;
+    Ok(addon_update_status(addon, &latest.version))
+}
+
+/// Re-fetch an installed addon's manifest, keeping the user's enabled state and priority
+pub async fn fetch_updated_addon(current: &Addon) -> Result<Addon> {
+    let mut updated = install_addon(&current.url).await?;
+
+    if updated.id != current.id {
+        return Err(anyhow!(
+            "Addon manifest id changed from '{}' to '{}'",
+            current.id,
+            updated.id
+        ));
+    }
+
+    updated.enabled = current.enabled;
+    updated.priority = current.priority;
+    updated.author = current.author.clone();
+
+    Ok(updated)
+}
+
+/// Report catalogs, resources and types added or removed between two manifests
+pub fn diff_manifests(old: &AddonManifest, new: &AddonManifest) -> ManifestDiff {
+    let catalog_key = |c: &Catalog| (c.catalog_type.clone(), c.id.clone());
+    let old_catalogs: std::collections::HashSet<_> = old.catalogs.iter().map(catalog_key).collect();
+    let new_catalogs: std::collections::HashSet<_> = new.catalogs.iter().map(catalog_key).collect();
+
+    let only_in = |items: &[String], other: &[String]| -> Vec<String> {
+        items
+            .iter()
+            .filter(|item| !other.contains(item))
+            .cloned()
+            .collect()
+    };
+
+    ManifestDiff {
+        old_version: old.version.clone(),
+        new_version: new.version.clone(),
+        added_catalogs: new
+            .catalogs
+            .iter()
+            .filter(|c| !old_catalogs.contains(&catalog_key(c)))
+            .cloned()
+            .collect(),
+        removed_catalogs: old
+            .catalogs
+            .iter()
+            .filter(|c| !new_catalogs.contains(&catalog_key(c)))
+            .cloned()
+            .collect(),
+        added_resources: only_in(&new.resources, &old.resources),
+        removed_resources: only_in(&old.resources, &new.resources),
+        added_types: only_in(&new.types, &old.types),
+        removed_types: only_in(&old.types, &new.types),
+    }
+}
+
+/// Seed the built-in addons on first run, if the user has opted in.
+/// Called once at startup; returns the addons that were seeded.
+pub async fn ensure_builtin_addons(db: Arc<Mutex<Database>>) -> Result<Vec<Addon>> {
+    let needs_seed = {
+        let db = db.lock().map_err(|e| anyhow!("Database lock poisoned: {}", e))?;
+        // Installs from before the seeding flag existed already have addons;
+        // record them as seeded so removing every addon later sticks.
+        if !db.get_addons()?.is_empty() {
+            db.mark_builtins_seeded(DEFAULT_PROFILE_ID)?;
+        }
+        db.should_seed_builtin_addons(DEFAULT_PROFILE_ID)?
+    };
+
+    if !needs_seed {
+        log::info!("Built-in addon seeding not needed");
+        return Ok(Vec::new());
+    }
+
+    let builtin = get_builtin_addons().await?;
+
+    let db = db.lock().map_err(|e| anyhow!("Database lock poisoned: {}", e))?;
+    db.seed_builtin_addons(DEFAULT_PROFILE_ID, &builtin)
+}
+
 // Real TMDB integration function (commented out for demo)
 async fn search_tmdb(query: &str) -> Result<Vec<MediaItem>> {
     let api_key = std::env::var("TMDB_API_KEY")
@@ -395,6 +690,247 @@ async fn search_tmdb(query: &str) -> Result<Vec<MediaItem>> {
     Ok(media_items)
 }
 
+// Keep TMDB lookups well under its per-second request limit
+const TMDB_LOOKUP_CONCURRENCY: usize = 4;
+
+/// How long to wait before asking TMDB again for artwork it didn't have
+pub const ARTWORK_RECHECK_DAYS: i64 = 7;
+
+/// Outcome of `hydrate_artwork`
+#[derive(Debug, Default)]
+pub struct ArtworkHydration {
+    /// Items that gained artwork
+    pub items: Vec<MediaItem>,
+    /// Ids of every item whose lookup got an answer, artwork or not. Items
+    /// whose lookup failed on the network or a TMDB error aren't included.
+    pub checked: Vec<String>,
+}
+
+/// Look up missing artwork on TMDB for library items whose id is an IMDb id
+/// ("tt…") or a TMDB id ("tmdb:…"). Artwork that is already set is kept.
+pub async fn hydrate_artwork(items: Vec<MediaItem>) -> Result<ArtworkHydration> {
+    let api_key = std::env::var("TMDB_API_KEY")
+        .map_err(|_| anyhow!("TMDB_API_KEY environment variable not set"))?;
+    Ok(hydrate_artwork_from(TMDB_BASE_URL, &api_key, items).await)
+}
+
+async fn hydrate_artwork_from(
+    base_url: &str,
+    api_key: &str,
+    items: Vec<MediaItem>,
+) -> ArtworkHydration {
+    use futures::stream::{self, StreamExt};
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+
+    // (id, item if it gained artwork) for every lookup that got an answer
+    let answered: Vec<_> = stream::iter(items.into_iter().filter(missing_artwork))
+        .map(|mut item| {
+            let client = &client;
+            async move {
+                match lookup_artwork(client, base_url, api_key, &item).await {
+                    Ok(artwork) => {
+                        let id = item.id.clone();
+                        Some((id, item.fill_missing_artwork(artwork).then_some(item)))
+                    }
+                    Err(e) if is_transient(&e) => {
+                        tracing::debug!(media_id = %item.id, error = %e, "TMDB artwork lookup failed");
+                        None
+                    }
+                    Err(e) => {
+                        tracing::debug!(media_id = %item.id, error = %e, "No TMDB artwork found");
+                        Some((item.id, None))
+                    }
+                }
+            }
+        })
+        .buffer_unordered(TMDB_LOOKUP_CONCURRENCY)
+        .filter_map(|answer| async move { answer })
+        .collect()
+        .await;
+
+    let mut hydration = ArtworkHydration::default();
+    for (id, item) in answered {
+        hydration.checked.push(id);
+        hydration.items.extend(item);
+    }
+    hydration
+}
+
+/// Whether a failed lookup is worth retrying soon: the network or TMDB
+/// failed, rather than TMDB not knowing the item
+fn is_transient(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.status() != Some(reqwest::StatusCode::NOT_FOUND))
+}
+
+fn is_blank(url: &Option<String>) -> bool {
+    url.as_deref().map_or(true, str::is_empty)
+}
+
+// Logos only come with the details endpoint, so a missing logo alone doesn't
+// warrant a lookup
+fn missing_artwork(item: &MediaItem) -> bool {
+    is_blank(&item.poster_url) || is_blank(&item.backdrop_url) || is_blank(&item.thumb_url)
+}
+
+/// Artwork for an item, via TMDB's external id lookup for IMDb ids or the
+/// movie/tv details endpoint (with images, for the logo) for TMDB ids
+async fn lookup_artwork(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+    item: &MediaItem,
+) -> Result<Artwork> {
+    let result: Value = if item.id.starts_with("tt") {
+        let json: Value = client
+            .get(format!("{}/find/{}", base_url, item.id))
+            .query(&[("api_key", api_key), ("external_source", "imdb_id")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        ["movie_results", "tv_results"]
+            .iter()
+            .find_map(|key| json[key].as_array().and_then(|r| r.first()).cloned())
+            .ok_or_else(|| anyhow!("No TMDB match for {}", item.id))?
+    } else if let Some(tmdb_id) = item.id.strip_prefix("tmdb:") {
+        let endpoint = match item.media_type {
+            MediaType::TvShow => "tv",
+            _ => "movie",
+        };
+        client
+            .get(format!("{}/{}/{}", base_url, endpoint, tmdb_id))
+            .query(&[
+                ("api_key", api_key),
+                ("append_to_response", "images"),
+                ("include_image_language", "en,null"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?
+    } else {
+        return Err(anyhow!("{} is not an IMDb or TMDB id", item.id));
+    };
+
+    Ok(tmdb_artwork(&result))
+}
+
+/// Artwork from a TMDB movie/tv result. The thumbnail is a small rendition
+/// of the backdrop; the logo needs `images` appended to the response.
+fn tmdb_artwork(result: &Value) -> Artwork {
+    let image = |path: Option<&str>, size: &str| {
+        path.map(|path| format!("https://image.tmdb.org/t/p/{}{}", size, path))
+    };
+    let backdrop_path = result["backdrop_path"].as_str();
+    let logo_path = result["images"]["logos"]
+        .as_array()
+        .and_then(|logos| logos.first())
+        .and_then(|logo| logo["file_path"].as_str());
+
+    Artwork {
+        poster_url: image(result["poster_path"].as_str(), "w500"),
+        backdrop_url: image(backdrop_path, "w1280"),
+        logo_url: image(logo_path, "w500"),
+        thumb_url: image(backdrop_path, "w300"),
+    }
+}
+
+/// Look up runtimes on TMDB for items whose id is an IMDb or TMDB id.
+/// Returns `(media_id, minutes)` for every item a runtime was found for.
+pub async fn lookup_durations(items: Vec<MediaItem>) -> Result<Vec<(String, i32)>> {
+    let api_key = std::env::var("TMDB_API_KEY")
+        .map_err(|_| anyhow!("TMDB_API_KEY environment variable not set"))?;
+    Ok(lookup_durations_from(TMDB_BASE_URL, &api_key, items).await)
+}
+
+async fn lookup_durations_from(
+    base_url: &str,
+    api_key: &str,
+    items: Vec<MediaItem>,
+) -> Vec<(String, i32)> {
+    use futures::stream::{self, StreamExt};
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+
+    stream::iter(items)
+        .map(|item| {
+            let client = &client;
+            async move {
+                match lookup_runtime(client, base_url, api_key, &item).await {
+                    Ok(minutes) => Some((item.id, minutes)),
+                    Err(e) => {
+                        tracing::debug!(media_id = %item.id, error = %e, "No TMDB runtime found");
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(TMDB_LOOKUP_CONCURRENCY)
+        .filter_map(|found| async move { found })
+        .collect()
+        .await
+}
+
+/// Runtime in minutes from the movie/tv details endpoint. IMDb ids are
+/// resolved to a TMDB id first; series use their first episode runtime.
+async fn lookup_runtime(
+    client: &reqwest::Client,
+    base_url: &str,
+    api_key: &str,
+    item: &MediaItem,
+) -> Result<i32> {
+    let (endpoint, tmdb_id) = if item.id.starts_with("tt") {
+        let json: Value = client
+            .get(format!("{}/find/{}", base_url, item.id))
+            .query(&[("api_key", api_key), ("external_source", "imdb_id")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        [("movie", "movie_results"), ("tv", "tv_results")]
+            .iter()
+            .find_map(|(endpoint, key)| {
+                let id = json[key].as_array()?.first()?["id"].as_u64()?;
+                Some((*endpoint, id.to_string()))
+            })
+            .ok_or_else(|| anyhow!("No TMDB match for {}", item.id))?
+    } else if let Some(tmdb_id) = item.id.strip_prefix("tmdb:") {
+        let endpoint = match item.media_type {
+            MediaType::TvShow => "tv",
+            _ => "movie",
+        };
+        (endpoint, tmdb_id.to_string())
+    } else {
+        return Err(anyhow!("{} is not an IMDb or TMDB id", item.id));
+    };
+
+    let details: Value = client
+        .get(format!("{}/{}/{}", base_url, endpoint, tmdb_id))
+        .query(&[("api_key", api_key)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    details["runtime"]
+        .as_i64()
+        .or_else(|| details["episode_run_time"].as_array()?.first()?.as_i64())
+        .filter(|minutes| *minutes > 0)
+        .map(|minutes| minutes as i32)
+        .ok_or_else(|| anyhow!("TMDB has no runtime for {}", item.id))
+}
+
 fn parse_tmdb_result(result: &Value) -> Option<MediaItem> {
     let media_type_str = result["media_type"].as_str()?;
     let media_type = match media_type_str {
@@ -421,6 +957,7 @@ fn parse_tmdb_result(result: &Value) -> Option<MediaItem> {
         _ => None,
     };
 
+    let artwork = tmdb_artwork(result);
     Some(MediaItem {
         id: result["id"].as_u64()?.to_string(),
         title: title.to_string(),
@@ -428,16 +965,259 @@ fn parse_tmdb_result(result: &Value) -> Option<MediaItem> {
         year,
         genre: vec![], // Would need additional API call to get genres
         description: result["overview"].as_str().map(|s| s.to_string()),
-        poster_url: result["poster_path"]
-            .as_str()
-            .map(|path| format!("https://image.tmdb.org/t/p/w500{}", path)),
-        backdrop_url: result["backdrop_path"]
-            .as_str()
-            .map(|path| format!("https://image.tmdb.org/t/p/w1280{}", path)),
+        poster_url: artwork.poster_url,
+        backdrop_url: artwork.backdrop_url,
+        logo_url: artwork.logo_url,
+        thumb_url: artwork.thumb_url,
         rating: result["vote_average"].as_f64().map(|r| r as f32),
         duration: None, // Would need additional API call
         added_to_library: None,
         watched: false,
         progress: None,
+        progress_percent: None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(resources: &[&str], types: &[&str], catalogs: &[(&str, &str)]) -> AddonManifest {
+        AddonManifest {
+            id: "org.example.addon".to_string(),
+            name: "Example".to_string(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            resources: resources.iter().map(|r| r.to_string()).collect(),
+            types: types.iter().map(|t| t.to_string()).collect(),
+            catalogs: catalogs
+                .iter()
+                .map(|(catalog_type, id)| Catalog {
+                    catalog_type: catalog_type.to_string(),
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    genres: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_manifests_reports_changes() {
+        let old = manifest(
+            &["catalog", "stream"],
+            &["movie"],
+            &[("movie", "top"), ("movie", "popular")],
+        );
+        let mut new = manifest(
+            &["catalog", "meta"],
+            &["movie", "series"],
+            &[("movie", "top"), ("series", "top")],
+        );
+        new.version = "1.1.0".to_string();
+
+        let diff = diff_manifests(&old, &new);
+        assert_eq!(diff.old_version, "1.0.0");
+        assert_eq!(diff.new_version, "1.1.0");
+        assert_eq!(diff.added_resources, vec!["meta"]);
+        assert_eq!(diff.removed_resources, vec!["stream"]);
+        assert_eq!(diff.added_types, vec!["series"]);
+        assert!(diff.removed_types.is_empty());
+        assert_eq!(diff.added_catalogs.len(), 1);
+        assert_eq!(diff.added_catalogs[0].catalog_type, "series");
+        assert_eq!(diff.removed_catalogs.len(), 1);
+        assert_eq!(diff.removed_catalogs[0].id, "popular");
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn test_diff_manifests_unchanged() {
+        let old = manifest(&["stream"], &["movie"], &[("movie", "top")]);
+        let diff = diff_manifests(&old, &old.clone());
+        assert!(diff.is_empty());
+    }
+
+    async fn spawn_mock_tmdb() -> String {
+        use axum::{extract::Path, routing::get, Json, Router};
+        use serde_json::json;
+
+        let app = Router::new()
+            .route(
+                "/find/:id",
+                get(|Path(id): Path<String>| async move {
+                    Json(json!({
+                        "movie_results": [{
+                            "id": 1,
+                            "poster_path": format!("/{}-poster.jpg", id),
+                            "backdrop_path": format!("/{}-backdrop.jpg", id)
+                        }],
+                        "tv_results": []
+                    }))
+                }),
+            )
+            .route(
+                "/tv/:id",
+                get(|Path(id): Path<String>| async move {
+                    Json(json!({
+                        "id": id,
+                        "poster_path": "/show-poster.jpg",
+                        "episode_run_time": [45],
+                        "images": { "logos": [{ "file_path": "/show-logo.png" }] }
+                    }))
+                }),
+            )
+            .route(
+                "/movie/:id",
+                get(|Path(id): Path<String>| async move {
+                    Json(json!({
+                        "id": id,
+                        "runtime": 142
+                    }))
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_hydrate_artwork_fills_only_missing() {
+        let base_url = spawn_mock_tmdb().await;
+        let db = Database::new_in_memory().unwrap();
+        let items = vec![
+            MediaItemBuilder::new("tt0000001", "Bare", MediaType::Movie)
+                .build()
+                .unwrap(),
+            MediaItemBuilder::new("tt0000002", "Has Poster", MediaType::Movie)
+                .poster_url("https://example.com/own-poster.jpg")
+                .build()
+                .unwrap(),
+            MediaItemBuilder::new("tmdb:42", "Show", MediaType::TvShow)
+                .build()
+                .unwrap(),
+            MediaItemBuilder::new("local-file", "Home Video", MediaType::Movie)
+                .build()
+                .unwrap(),
+        ];
+        for item in &items {
+            db.add_to_library(item.clone()).unwrap();
+        }
+
+        let hydrated = hydrate_artwork_from(&base_url, "test-key", items.clone()).await;
+        assert_eq!(hydrated.items.len(), 3);
+        for item in &hydrated.items {
+            db.fill_missing_artwork(&item.id, &item.artwork()).unwrap();
+        }
+        // A local id can never match, so it counts as checked too
+        let mut checked = hydrated.checked.clone();
+        checked.sort();
+        assert_eq!(
+            checked,
+            vec!["local-file", "tmdb:42", "tt0000001", "tt0000002"]
+        );
+
+        // Lookups that fail on the network are retried next time
+        let unreachable = hydrate_artwork_from("http://127.0.0.1:1", "test-key", items).await;
+        assert!(unreachable.items.is_empty());
+        assert_eq!(unreachable.checked, vec!["local-file"]);
+
+        let ids: Vec<String> = ["tt0000001", "tt0000002", "tmdb:42", "local-file"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        let stored: std::collections::HashMap<String, MediaItem> = db
+            .get_media_items_by_ids(&ids)
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.id.clone(), item))
+            .collect();
+
+        let bare = &stored["tt0000001"];
+        assert_eq!(
+            bare.poster_url.as_deref(),
+            Some("https://image.tmdb.org/t/p/w500/tt0000001-poster.jpg")
+        );
+        assert_eq!(
+            bare.backdrop_url.as_deref(),
+            Some("https://image.tmdb.org/t/p/w1280/tt0000001-backdrop.jpg")
+        );
+        assert_eq!(
+            bare.thumb_url.as_deref(),
+            Some("https://image.tmdb.org/t/p/w300/tt0000001-backdrop.jpg")
+        );
+
+        let has_poster = &stored["tt0000002"];
+        assert_eq!(
+            has_poster.poster_url.as_deref(),
+            Some("https://example.com/own-poster.jpg")
+        );
+        assert_eq!(
+            has_poster.backdrop_url.as_deref(),
+            Some("https://image.tmdb.org/t/p/w1280/tt0000002-backdrop.jpg")
+        );
+
+        let show = &stored["tmdb:42"];
+        assert_eq!(
+            show.poster_url.as_deref(),
+            Some("https://image.tmdb.org/t/p/w500/show-poster.jpg")
+        );
+        assert_eq!(show.backdrop_url, None);
+        assert_eq!(show.thumb_url, None);
+        assert_eq!(
+            show.logo_url.as_deref(),
+            Some("https://image.tmdb.org/t/p/w500/show-logo.png")
+        );
+
+        let local = &stored["local-file"];
+        assert_eq!(local.poster_url, None);
+        assert_eq!(local.backdrop_url, None);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_durations_fills_only_missing() {
+        let base_url = spawn_mock_tmdb().await;
+        let db = Database::new_in_memory().unwrap();
+        for item in [
+            MediaItemBuilder::new("tt0000001", "No Runtime", MediaType::Movie)
+                .build()
+                .unwrap(),
+            MediaItemBuilder::new("tt0000002", "Has Runtime", MediaType::Movie)
+                .duration(90)
+                .build()
+                .unwrap(),
+            MediaItemBuilder::new("tmdb:42", "Show", MediaType::TvShow)
+                .build()
+                .unwrap(),
+            MediaItemBuilder::new("local-file", "Home Video", MediaType::Movie)
+                .build()
+                .unwrap(),
+        ] {
+            db.add_to_library(item).unwrap();
+        }
+
+        let missing = db.get_items_missing_duration().unwrap();
+        assert_eq!(missing.len(), 3);
+        let found = lookup_durations_from(&base_url, "test-key", missing).await;
+        assert_eq!(found.len(), 2);
+        for (media_id, minutes) in &found {
+            assert!(db.fill_missing_duration(media_id, *minutes).unwrap());
+        }
+        assert!(!db.fill_missing_duration("tt0000002", 142).unwrap());
+
+        let durations: std::collections::HashMap<String, Option<i32>> = db
+            .get_library_items()
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.id, item.duration))
+            .collect();
+        assert_eq!(durations["tt0000001"], Some(142));
+        assert_eq!(durations["tt0000002"], Some(90));
+        assert_eq!(durations["tmdb:42"], Some(45));
+        assert_eq!(durations["local-file"], None);
+        assert_eq!(db.get_items_missing_duration().unwrap().len(), 1);
+    }
+}
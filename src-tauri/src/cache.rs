@@ -1,15 +1,27 @@
 /**
  * Cache Module
  *
- * Provides caching for metadata and addon responses with TTL support
+ * Provides caching for metadata and addon responses with TTL support,
+ * evicting the least recently used entries once over a size budget
  */
-use anyhow::Result;
+use crate::subtitle_providers::SubtitleResult;
+use anyhow::{anyhow, Result};
 use rusqlite::{params, Connection};
 use serde::{de::DeserializeOwned, Serialize};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+/// Default budget for cached values: 256 MB
+pub const DEFAULT_MAX_SIZE_BYTES: u64 = 256 * 1024 * 1024;
+/// Default budget for metadata and addon response entries
+pub const DEFAULT_MAX_ENTRIES: usize = 50_000;
+/// How often the background task evicts entries over budget
+const EVICTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
 pub struct CacheManager {
     conn: Connection,
+    max_size_bytes: u64,
+    max_entries: usize,
 }
 
 impl CacheManager {
@@ -21,11 +33,34 @@ impl CacheManager {
             Connection::open_in_memory()?
         };
 
-        let cache = Self { conn };
+        let cache = Self {
+            conn,
+            max_size_bytes: DEFAULT_MAX_SIZE_BYTES,
+            max_entries: DEFAULT_MAX_ENTRIES,
+        };
         cache.init_tables()?;
         Ok(cache)
     }
 
+    /// Budget for the total size of cached metadata and addon responses.
+    /// Stream selections don't count towards it: each is a single URL and
+    /// expires after `ttl::STREAM_SELECTION` anyway.
+    pub fn with_max_size_bytes(mut self, max_size_bytes: u64) -> Self {
+        self.set_max_size_bytes(max_size_bytes);
+        self
+    }
+
+    /// Change the size budget; it applies from the next eviction
+    pub fn set_max_size_bytes(&mut self, max_size_bytes: u64) {
+        self.max_size_bytes = max_size_bytes;
+    }
+
+    /// Budget for the number of cached metadata and addon responses
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
     fn init_tables(&self) -> Result<()> {
         // Metadata cache table
         self.conn.execute(
@@ -33,7 +68,8 @@ impl CacheManager {
                 key TEXT PRIMARY KEY,
                 value TEXT NOT NULL,
                 expires_at INTEGER NOT NULL,
-                created_at INTEGER NOT NULL
+                created_at INTEGER NOT NULL,
+                last_accessed INTEGER NOT NULL DEFAULT 0
             )",
             [],
         )?;
@@ -45,7 +81,21 @@ impl CacheManager {
                 value TEXT NOT NULL,
                 expires_at INTEGER NOT NULL,
                 created_at INTEGER NOT NULL,
-                addon_id TEXT NOT NULL
+                addon_id TEXT NOT NULL,
+                last_accessed INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+
+        // Chosen stream per (media, quality preference) for quick reopen
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS stream_selection_cache (
+                media_id TEXT NOT NULL,
+                quality TEXT NOT NULL,
+                url TEXT NOT NULL,
+                expires_at INTEGER NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (media_id, quality)
             )",
             [],
         )?;
@@ -69,9 +119,54 @@ impl CacheManager {
             [],
         )?;
 
+        // Caches created before LRU eviction lack `last_accessed`; treat
+        // their entries as last used when they were written
+        for table in ["metadata_cache", "addon_response_cache"] {
+            if !self.has_column(table, "last_accessed")? {
+                self.conn.execute(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN last_accessed INTEGER NOT NULL DEFAULT 0",
+                        table
+                    ),
+                    [],
+                )?;
+                self.conn.execute(
+                    &format!("UPDATE {} SET last_accessed = created_at * 1000", table),
+                    [],
+                )?;
+            }
+        }
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_metadata_last_accessed
+             ON metadata_cache(last_accessed)",
+            [],
+        )?;
+
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_addon_last_accessed
+             ON addon_response_cache(last_accessed)",
+            [],
+        )?;
+
         Ok(())
     }
 
+    fn has_column(&self, table: &str, column: &str) -> Result<bool> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("PRAGMA table_info({})", table))?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            // PRAGMA table_info columns: cid, name, type, notnull, dflt_value, pk
+            let name: String = row.get(1)?;
+            if name == column {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     /// Get current Unix timestamp in seconds
     fn now() -> u64 {
         SystemTime::now()
@@ -83,6 +178,14 @@ impl CacheManager {
             })
     }
 
+    /// Current Unix timestamp in milliseconds, for ordering cache reads
+    fn now_millis() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+
     /// Get metadata from cache
     pub fn get_metadata<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
         let now = Self::now();
@@ -100,6 +203,10 @@ impl CacheManager {
         match result {
             Ok(value) => {
                 let deserialized: T = serde_json::from_str(&value)?;
+                self.conn.execute(
+                    "UPDATE metadata_cache SET last_accessed = ?1 WHERE key = ?2",
+                    params![Self::now_millis(), key],
+                )?;
                 Ok(Some(deserialized))
             }
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
@@ -114,9 +221,10 @@ impl CacheManager {
         let value_json = serde_json::to_string(value)?;
 
         self.conn.execute(
-            "INSERT OR REPLACE INTO metadata_cache (key, value, expires_at, created_at)
-             VALUES (?1, ?2, ?3, ?4)",
-            params![key, value_json, expires_at, now],
+            "INSERT OR REPLACE INTO metadata_cache
+             (key, value, expires_at, created_at, last_accessed)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![key, value_json, expires_at, now, Self::now_millis()],
         )?;
 
         Ok(())
@@ -143,6 +251,10 @@ impl CacheManager {
         match result {
             Ok(value) => {
                 let deserialized: T = serde_json::from_str(&value)?;
+                self.conn.execute(
+                    "UPDATE addon_response_cache SET last_accessed = ?1 WHERE key = ?2",
+                    params![Self::now_millis(), key],
+                )?;
                 Ok(Some(deserialized))
             }
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
@@ -164,14 +276,119 @@ impl CacheManager {
 
         self.conn.execute(
             "INSERT OR REPLACE INTO addon_response_cache 
-             (key, value, expires_at, created_at, addon_id)
+             (key, value, expires_at, created_at, addon_id, last_accessed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                key,
+                value_json,
+                expires_at,
+                now,
+                addon_id,
+                Self::now_millis()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Error from a recent failed query to this addon, stored alongside the
+    /// successful responses so it is cleared with the rest of the addon's cache
+    pub fn get_addon_failure(&self, key: &str, addon_id: &str) -> Result<Option<String>> {
+        self.get_addon_response(&Self::failure_key(key), addon_id)
+    }
+
+    /// Remember that a query to this addon failed, so it can be skipped
+    /// until `ttl` passes
+    pub fn set_addon_failure(
+        &self,
+        key: &str,
+        addon_id: &str,
+        error: &str,
+        ttl: Duration,
+    ) -> Result<()> {
+        self.set_addon_response(&Self::failure_key(key), addon_id, &error, ttl)
+    }
+
+    fn failure_key(key: &str) -> String {
+        format!("failed:{}", key)
+    }
+
+    fn subtitle_key(content_id: &str, language: &str) -> String {
+        format!("subtitle:{}:{}", content_id, language)
+    }
+
+    /// Get a downloaded subtitle (content and its search result) for a
+    /// content id and language code
+    pub fn get_subtitle(
+        &self,
+        content_id: &str,
+        language: &str,
+    ) -> Result<Option<(String, SubtitleResult)>> {
+        self.get_metadata(&Self::subtitle_key(content_id, language))
+    }
+
+    /// Cache a downloaded subtitle for a content id and language code
+    pub fn set_subtitle(
+        &self,
+        content_id: &str,
+        language: &str,
+        subtitle: &(String, SubtitleResult),
+    ) -> Result<()> {
+        self.set_metadata(
+            &Self::subtitle_key(content_id, language),
+            subtitle,
+            ttl::SUBTITLE,
+        )
+    }
+
+    /// Get the previously selected stream URL for a media item and quality
+    pub fn get_stream_selection(&self, media_id: &str, quality: &str) -> Result<Option<String>> {
+        let now = Self::now();
+
+        let result = self.conn.query_row(
+            "SELECT url FROM stream_selection_cache
+             WHERE media_id = ?1 AND quality = ?2 AND expires_at > ?3",
+            params![media_id, quality, now],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(url) => Ok(Some(url)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Remember the selected stream URL for a media item and quality with TTL
+    pub fn set_stream_selection(
+        &self,
+        media_id: &str,
+        quality: &str,
+        url: &str,
+        ttl: Duration,
+    ) -> Result<()> {
+        let now = Self::now();
+        let expires_at = now + ttl.as_secs();
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO stream_selection_cache
+             (media_id, quality, url, expires_at, created_at)
              VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![key, value_json, expires_at, now, addon_id],
+            params![media_id, quality, url, expires_at, now],
         )?;
 
         Ok(())
     }
 
+    /// Drop cached stream selections for a media item (all quality preferences)
+    pub fn invalidate_stream_selection(&self, media_id: &str) -> Result<usize> {
+        let deleted = self.conn.execute(
+            "DELETE FROM stream_selection_cache WHERE media_id = ?1",
+            params![media_id],
+        )?;
+        Ok(deleted)
+    }
+
     /// Clear all expired entries
     pub fn clear_expired(&self) -> Result<usize> {
         let now = Self::now();
@@ -186,13 +403,83 @@ impl CacheManager {
             params![now],
         )?;
 
-        Ok(metadata_deleted + addon_deleted)
+        let selection_deleted = self.conn.execute(
+            "DELETE FROM stream_selection_cache WHERE expires_at <= ?1",
+            params![now],
+        )?;
+
+        Ok(metadata_deleted + addon_deleted + selection_deleted)
+    }
+
+    /// Bring the cache back under its size and entry budgets. Expired
+    /// entries go first, then the least recently read or written ones.
+    /// Stream selections are only removed once expired. Returns how many
+    /// entries were deleted.
+    pub fn evict_to_limit(&self) -> Result<usize> {
+        let mut deleted = self.clear_expired()?;
+
+        let (mut entries, mut size): (i64, i64) = self.conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(size), 0) FROM (
+                 SELECT length(CAST(value AS BLOB)) AS size FROM metadata_cache
+                 UNION ALL
+                 SELECT length(CAST(value AS BLOB)) AS size FROM addon_response_cache
+             )",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let over_budget = |entries: i64, size: i64| {
+            entries as u64 > self.max_entries as u64 || size as u64 > self.max_size_bytes
+        };
+        if !over_budget(entries, size) {
+            return Ok(deleted);
+        }
+
+        let mut victims: Vec<(String, String)> = Vec::new();
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT 'metadata_cache', key, length(CAST(value AS BLOB)), last_accessed, expires_at
+                 FROM metadata_cache
+                 UNION ALL
+                 SELECT 'addon_response_cache', key, length(CAST(value AS BLOB)), last_accessed, expires_at
+                 FROM addon_response_cache
+                 ORDER BY 4 ASC, 5 ASC",
+            )?;
+            let mut rows = stmt.query([])?;
+            while over_budget(entries, size) {
+                let Some(row) = rows.next()? else {
+                    break;
+                };
+                let entry_size: i64 = row.get(2)?;
+                victims.push((row.get(0)?, row.get(1)?));
+                entries -= 1;
+                size -= entry_size;
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        for (table, key) in &victims {
+            deleted += tx.execute(
+                &format!("DELETE FROM {} WHERE key = ?1", table),
+                params![key],
+            )?;
+        }
+        tx.commit()?;
+
+        tracing::debug!(
+            evicted = victims.len(),
+            entries,
+            size_bytes = size,
+            "Evicted least recently used cache entries"
+        );
+        Ok(deleted)
     }
 
     /// Clear all cache entries
     pub fn clear_all(&self) -> Result<()> {
         self.conn.execute("DELETE FROM metadata_cache", [])?;
         self.conn.execute("DELETE FROM addon_response_cache", [])?;
+        self.conn
+            .execute("DELETE FROM stream_selection_cache", [])?;
         Ok(())
     }
 
@@ -231,6 +518,14 @@ impl CacheManager {
             |row| row.get(0),
         )?;
 
+        let size_bytes: i64 = self.conn.query_row(
+            "SELECT
+                 (SELECT COALESCE(SUM(length(CAST(value AS BLOB))), 0) FROM metadata_cache)
+                 + (SELECT COALESCE(SUM(length(CAST(value AS BLOB))), 0) FROM addon_response_cache)",
+            [],
+            |row| row.get(0),
+        )?;
+
         Ok(CacheStats {
             metadata_total: metadata_total as usize,
             metadata_valid: (metadata_total - metadata_expired) as usize,
@@ -238,7 +533,33 @@ impl CacheManager {
             addon_total: addon_total as usize,
             addon_valid: (addon_total - addon_expired) as usize,
             addon_expired: addon_expired as usize,
+            size_bytes: size_bytes as u64,
+        })
+    }
+}
+
+/// Evict cache entries over budget every `EVICTION_INTERVAL`, starting
+/// right away so a cache that grew large is trimmed soon after launch
+pub async fn run_cache_eviction(cache: Arc<Mutex<CacheManager>>) {
+    let mut interval = tokio::time::interval(EVICTION_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let cache = cache.clone();
+        let evicted = tokio::task::spawn_blocking(move || {
+            let cache = cache
+                .lock()
+                .map_err(|e| anyhow!("Cache lock poisoned: {}", e))?;
+            cache.evict_to_limit()
         })
+        .await;
+
+        match evicted {
+            Ok(Ok(0)) => {}
+            Ok(Ok(evicted)) => tracing::info!(evicted, "Evicted cache entries"),
+            Ok(Err(e)) => tracing::warn!(error = %e, "Failed to evict cache entries"),
+            Err(e) => tracing::warn!(error = %e, "Cache eviction task failed"),
+        }
     }
 }
 
@@ -251,6 +572,8 @@ pub struct CacheStats {
     pub addon_total: usize,
     pub addon_valid: usize,
     pub addon_expired: usize,
+    /// Bytes taken up by cached metadata and addon response values
+    pub size_bytes: u64,
 }
 
 /// Default cache TTL values
@@ -275,6 +598,18 @@ pub mod ttl {
 
     /// Addon stream responses: 5 minutes
     pub const ADDON_STREAM_TTL: Duration = Duration::from_secs(5 * 60);
+
+    /// Addon subtitle responses: 1 hour
+    pub const ADDON_SUBTITLE_TTL: Duration = Duration::from_secs(3600);
+
+    /// Failed or timed out addon queries: 30 seconds
+    pub const ADDON_FAILURE_TTL: Duration = Duration::from_secs(30);
+
+    /// Selected best stream per media item: 10 minutes
+    pub const STREAM_SELECTION: Duration = Duration::from_secs(10 * 60);
+
+    /// Downloaded subtitles, fetched on demand or prefetched: 1 week
+    pub const SUBTITLE: Duration = Duration::from_secs(7 * 24 * 3600);
 }
 
 #[cfg(test)]
@@ -426,4 +761,144 @@ mod tests {
         assert_eq!(stats.metadata_total, 1);
         assert_eq!(stats.metadata_expired, 0);
     }
+
+    #[test]
+    fn test_evict_to_limit_drops_least_recently_used() {
+        let cache = CacheManager::new(None).unwrap().with_max_entries(3);
+        let data = TestData {
+            id: "test".to_string(),
+            value: 42,
+        };
+
+        for key in ["oldest", "older", "old"] {
+            cache
+                .set_metadata(key, &data, Duration::from_secs(60))
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        // Reading "oldest" makes it the most recently used entry
+        let _: Option<TestData> = cache.get_metadata("oldest").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        cache
+            .set_addon_response("fresh", "addon1", &data, Duration::from_secs(60))
+            .unwrap();
+        cache
+            .set_metadata("expired", &data, Duration::from_nanos(1))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+
+        // The expired entry and the least recently used one go
+        assert_eq!(cache.evict_to_limit().unwrap(), 2);
+        let missing: Option<TestData> = cache.get_metadata("older").unwrap();
+        assert_eq!(missing, None);
+        for key in ["oldest", "old"] {
+            let kept: Option<TestData> = cache.get_metadata(key).unwrap();
+            assert!(kept.is_some(), "{} should survive eviction", key);
+        }
+        let fresh: Option<TestData> = cache.get_addon_response("fresh", "addon1").unwrap();
+        assert!(fresh.is_some());
+
+        // Already under budget
+        assert_eq!(cache.evict_to_limit().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_evict_to_limit_by_size() {
+        let value = "x".repeat(1000);
+        let cache = CacheManager::new(None).unwrap().with_max_size_bytes(2500);
+
+        for key in ["first", "second", "third", "fourth"] {
+            cache
+                .set_metadata(key, &value, Duration::from_secs(60))
+                .unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(cache.get_stats().unwrap().size_bytes > 4000);
+
+        assert_eq!(cache.evict_to_limit().unwrap(), 2);
+        let stats = cache.get_stats().unwrap();
+        assert_eq!(stats.metadata_total, 2);
+        assert!(stats.size_bytes <= 2500);
+        let first: Option<String> = cache.get_metadata("first").unwrap();
+        assert_eq!(first, None);
+        let fourth: Option<String> = cache.get_metadata("fourth").unwrap();
+        assert_eq!(fourth, Some(value));
+    }
+
+    #[test]
+    fn test_last_accessed_added_to_existing_cache() {
+        let path = std::env::temp_dir().join(format!(
+            "streamgo_cache_last_accessed_{}.db",
+            uuid::Uuid::new_v4()
+        ));
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute(
+                "CREATE TABLE metadata_cache (
+                    key TEXT PRIMARY KEY,
+                    value TEXT NOT NULL,
+                    expires_at INTEGER NOT NULL,
+                    created_at INTEGER NOT NULL
+                )",
+                [],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO metadata_cache VALUES ('old', '1', 9999999999, 1700000000)",
+                [],
+            )
+            .unwrap();
+        }
+
+        let cache = CacheManager::new(path.to_str()).unwrap();
+        let last_accessed: i64 = cache
+            .conn
+            .query_row(
+                "SELECT last_accessed FROM metadata_cache WHERE key = 'old'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(last_accessed, 1_700_000_000_000);
+        drop(cache);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_stream_selection_cache() {
+        let cache = CacheManager::new(None).unwrap();
+
+        cache
+            .set_stream_selection(
+                "tt0111161",
+                "1080p",
+                "https://a/1080.mp4",
+                Duration::from_secs(60),
+            )
+            .unwrap();
+        cache
+            .set_stream_selection(
+                "tt0111161",
+                "720p",
+                "https://a/720.mp4",
+                Duration::from_secs(60),
+            )
+            .unwrap();
+
+        assert_eq!(
+            cache.get_stream_selection("tt0111161", "1080p").unwrap(),
+            Some("https://a/1080.mp4".to_string())
+        );
+        assert_eq!(
+            cache.get_stream_selection("tt0111161", "480p").unwrap(),
+            None
+        );
+
+        // Invalidation covers every quality for the item
+        assert_eq!(cache.invalidate_stream_selection("tt0111161").unwrap(), 2);
+        assert_eq!(
+            cache.get_stream_selection("tt0111161", "720p").unwrap(),
+            None
+        );
+    }
 }
@@ -16,6 +16,9 @@ pub struct CalendarEntry {
     pub poster_url: Option<String>,
     pub backdrop_url: Option<String>,
     pub description: Option<String>,
+    /// Whether `air_date` carries a real release time rather than just a day
+    #[serde(default)]
+    pub air_time_known: bool,
 }
 
 /// Get upcoming episodes for TV series in the user's library
@@ -92,13 +95,7 @@ async fn fetch_episodes_for_show(
 
     // Try each addon until we get episode data
     for addon in addons {
-        let base_url = if addon.url.ends_with("/manifest.json") {
-            addon.url.replace("/manifest.json", "")
-        } else if addon.url.ends_with("manifest.json") {
-            addon.url.replace("manifest.json", "")
-        } else {
-            addon.url.clone()
-        };
+        let base_url = addon.base_url();
 
         let client = match AddonClient::new(base_url) {
             Ok(c) => c,
@@ -139,8 +136,8 @@ async fn fetch_episodes_for_show(
         // Process episodes (videos)
         for video in meta.videos {
             // Parse air date from released field
-            let air_date = match parse_air_date(&video.released) {
-                Some(date) => date,
+            let (air_date, air_time_known) = match parse_air_date(&video.released) {
+                Some(parsed) => parsed,
                 None => continue, // Skip episodes without air date
             };
 
@@ -157,6 +154,7 @@ async fn fetch_episodes_for_show(
                     poster_url: video.thumbnail.clone().or_else(|| show.poster_url.clone()),
                     backdrop_url: show.backdrop_url.clone(),
                     description: video.overview.clone(),
+                    air_time_known,
                 });
             }
         }
@@ -177,38 +175,126 @@ async fn fetch_episodes_for_show(
     entries
 }
 
-/// Parse air date from various date formats
-fn parse_air_date(released: &Option<String>) -> Option<DateTime<Utc>> {
+/// Parse air date from various date formats, along with whether it has a
+/// release time. Addons give date-only releases as midnight UTC, so that
+/// time counts as unknown.
+fn parse_air_date(released: &Option<String>) -> Option<(DateTime<Utc>, bool)> {
     let date_str = released.as_ref()?;
 
     // Try ISO 8601 datetime first (e.g., "2024-01-15T20:00:00Z")
     if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
-        return Some(dt.with_timezone(&Utc));
+        let dt = dt.with_timezone(&Utc);
+        return Some((dt, dt.time() != chrono::NaiveTime::MIN));
     }
 
     // Try date only format (e.g., "2024-01-15")
     if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-        return Some(
+        return Some((
             date.and_hms_opt(0, 0, 0)
                 .unwrap()
                 .and_local_timezone(Utc)
                 .single()?,
-        );
+            false,
+        ));
     }
 
     // Try other common formats
     if let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y/%m/%d") {
-        return Some(
+        return Some((
             date.and_hms_opt(0, 0, 0)
                 .unwrap()
                 .and_local_timezone(Utc)
                 .single()?,
-        );
+            false,
+        ));
     }
 
     None
 }
 
+/// Render calendar entries as an iCalendar (RFC 5545) file with one VEVENT
+/// per episode. Episodes with a known release time become timed events,
+/// the rest all-day events on their air date.
+pub fn to_ics(entries: &[CalendarEntry]) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//StreamGo//Calendar//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    for entry in entries {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@streamgo", ics_escape(&entry.episode_id)));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        if entry.air_time_known {
+            lines.push(format!(
+                "DTSTART:{}",
+                entry.air_date.format("%Y%m%dT%H%M%SZ")
+            ));
+        } else {
+            let day = entry.air_date.date_naive();
+            lines.push(format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")));
+            if let Some(next_day) = day.succ_opt() {
+                lines.push(format!("DTEND;VALUE=DATE:{}", next_day.format("%Y%m%d")));
+            }
+        }
+        lines.push(format!(
+            "SUMMARY:{}",
+            ics_escape(&format!(
+                "{} S{:02}E{:02}",
+                entry.series_name, entry.season, entry.episode
+            ))
+        ));
+        let description: Vec<&str> = [Some(entry.title.as_str()), entry.description.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+        if !description.is_empty() {
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                ics_escape(&description.join("\n"))
+            ));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines
+        .iter()
+        .map(|line| fold_ics_line(line))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+/// Escape text for an iCalendar property value
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets, continuing with a leading space,
+/// without splitting UTF-8 characters
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded
+}
+
 /// Group calendar entries by date for UI display
 #[allow(dead_code)]
 pub fn group_by_date(entries: Vec<CalendarEntry>) -> Vec<(String, Vec<CalendarEntry>)> {
@@ -241,3 +327,75 @@ pub fn format_relative_date(air_date: &DateTime<Utc>) -> String {
         _ => air_date.format("%Y-%m-%d").to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(episode_id: &str, air_date: DateTime<Utc>, air_time_known: bool) -> CalendarEntry {
+        CalendarEntry {
+            series_id: "tt0903747".to_string(),
+            series_name: "Breaking Bad".to_string(),
+            episode_id: episode_id.to_string(),
+            season: 2,
+            episode: 5,
+            title: "Breakage".to_string(),
+            air_date,
+            poster_url: None,
+            backdrop_url: None,
+            description: None,
+            air_time_known,
+        }
+    }
+
+    #[test]
+    fn test_parse_air_date_time_known() {
+        let released = |s: &str| Some(s.to_string());
+        let (date, known) = parse_air_date(&released("2024-01-15T20:00:00Z")).unwrap();
+        assert_eq!(date, Utc.with_ymd_and_hms(2024, 1, 15, 20, 0, 0).unwrap());
+        assert!(known);
+        let (_, known) = parse_air_date(&released("2024-01-15T00:00:00.000Z")).unwrap();
+        assert!(!known);
+        let (_, known) = parse_air_date(&released("2024-01-15")).unwrap();
+        assert!(!known);
+    }
+
+    #[test]
+    fn test_to_ics() {
+        let mut timed = entry(
+            "tt0903747:2:5",
+            Utc.with_ymd_and_hms(2024, 3, 10, 21, 30, 0).unwrap(),
+            true,
+        );
+        timed.description = Some("Walt and Jesse, again; with feeling".to_string());
+        let mut all_day = entry(
+            "tt0903747:2:6",
+            Utc.with_ymd_and_hms(2024, 3, 17, 0, 0, 0).unwrap(),
+            false,
+        );
+        all_day.series_name = "Law, Order; SVU".to_string();
+        all_day.episode = 6;
+
+        let ics = to_ics(&[timed, all_day]);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert_eq!(ics.matches("END:VEVENT").count(), 2);
+
+        assert!(ics.contains("UID:tt0903747:2:5@streamgo\r\n"));
+        assert!(ics.contains("DTSTART:20240310T213000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Breaking Bad S02E05\r\n"));
+        assert!(ics.contains("DESCRIPTION:Breakage\\nWalt and Jesse\\, again\\; with feeling\r\n"));
+
+        assert!(ics.contains("DTSTART;VALUE=DATE:20240317\r\nDTEND;VALUE=DATE:20240318\r\n"));
+        assert!(ics.contains("SUMMARY:Law\\, Order\\; SVU S02E06\r\n"));
+
+        // Long lines are folded
+        let mut long = entry("long", Utc::now(), true);
+        long.description = Some("x".repeat(200));
+        let ics = to_ics(&[long]);
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert!(ics.contains("\r\n x"));
+    }
+}
@@ -3,6 +3,7 @@
  *
  * Supports streaming to Chromecast, DLNA, and UPnP devices
  */
+use crate::events::{AppEvent, EventBus};
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -14,6 +15,126 @@ use std::{
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
+// Devices don't push playback updates to us, so active sessions are polled
+const SESSION_POLL_SECS: u64 = 3;
+
+/// Connection attempts for a Chromecast before giving up
+const CHROMECAST_ATTEMPTS: u32 = 3;
+
+/// Delay before the first Chromecast retry, doubled after each failure
+const CHROMECAST_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// How long a launched receiver app may take to become ready for media
+const CHROMECAST_READY_TIMEOUT: Duration = Duration::from_secs(10);
+const CHROMECAST_READY_POLL: Duration = Duration::from_millis(250);
+
+/// How often a device test asks the device whether the sample is playing
+const DEVICE_TEST_POLL: Duration = Duration::from_millis(250);
+
+/// Id of the pseudo-device that plays casts in a web view on this machine
+pub const LOCAL_DEVICE_ID: &str = "local";
+
+/// Public sample clip used to test a device
+const TEST_CAST_MEDIA_URL: &str =
+    "https://commondatastorage.googleapis.com/gtv-videos-bucket/sample/BigBuckBunny.mp4";
+
+/// MIME type and Chromecast stream type for a media URL, from its file
+/// extension. Raw MPEG-TS is treated as a live feed; everything else is a
+/// file the receiver can buffer.
+fn infer_cast_content_type(url: &str) -> (&'static str, rust_cast::channels::media::StreamType) {
+    use rust_cast::channels::media::StreamType;
+
+    let path = url::Url::parse(url)
+        .map(|u| u.path().to_string())
+        .unwrap_or_else(|_| url.split(['?', '#']).next().unwrap_or_default().to_string());
+    let extension = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "m3u8" => ("application/x-mpegurl", StreamType::Buffered),
+        "mpd" => ("application/dash+xml", StreamType::Buffered),
+        "mkv" => ("video/x-matroska", StreamType::Buffered),
+        "webm" => ("video/webm", StreamType::Buffered),
+        "ts" => ("video/mp2t", StreamType::Live),
+        _ => ("video/mp4", StreamType::Buffered),
+    }
+}
+
+/// Bit in a Chromecast's `ca` TXT record set when the device has video output
+const CA_VIDEO_OUT: u32 = 1;
+
+/// Google Cast models without a screen, for devices that don't advertise `ca`
+const AUDIO_ONLY_MODELS: &[&str] = &[
+    "Google Home",
+    "Google Home Mini",
+    "Google Home Max",
+    "Google Nest Mini",
+    "Google Nest Audio",
+    "Chromecast Audio",
+    "Google Cast Group",
+];
+
+/// Run a blocking `operation` up to `attempts` times, sleeping with
+/// exponential backoff between failures
+fn retry_with_backoff<T>(
+    attempts: u32,
+    initial_backoff: Duration,
+    what: &str,
+    mut operation: impl FnMut() -> Result<T>,
+) -> Result<T> {
+    let mut backoff = initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < attempts => {
+                warn!(attempt, error = %e, "{} failed, retrying in {:?}", what, backoff);
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Poll `is_ready` every `interval` until it reports true or `timeout`
+/// passes. Errors from a poll count as not ready yet.
+fn wait_until_ready(
+    timeout: Duration,
+    interval: Duration,
+    mut is_ready: impl FnMut() -> Result<bool>,
+) -> Result<()> {
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        match is_ready() {
+            Ok(true) => return Ok(()),
+            Ok(false) => {}
+            Err(e) => debug!(error = %e, "Readiness check failed"),
+        }
+        if std::time::Instant::now() + interval > deadline {
+            return Err(anyhow!("Not ready after {:?}", timeout));
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// Whether the receiver reports app session `session_id` as running with
+/// its media channel available
+fn media_receiver_ready(status: &rust_cast::channels::receiver::Status, session_id: &str) -> bool {
+    status.applications.iter().any(|app| {
+        app.session_id == session_id
+            && app
+                .namespaces
+                .iter()
+                .any(|ns| ns == "urn:x-cast:com.google.cast.media")
+    })
+}
+
 /// Supported casting protocols
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -21,6 +142,8 @@ pub enum CastProtocol {
     Chromecast,
     DLNA,
     AirPlay,
+    /// A web view on this machine, for testing or a second monitor
+    Local,
 }
 
 /// Cast device information
@@ -34,6 +157,17 @@ pub struct CastDevice {
     pub model: Option<String>,
     pub manufacturer: Option<String>,
     pub status: DeviceStatus,
+    #[serde(default)]
+    pub capability: DeviceCapability,
+}
+
+/// What a device can play
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceCapability {
+    #[default]
+    Video,
+    AudioOnly,
 }
 
 /// Device connection status
@@ -59,6 +193,22 @@ pub struct CastSession {
     pub position: f64,
     pub duration: f64,
     pub state: PlaybackState,
+    /// Device volume from 0.0 to 1.0, once set through StreamGo
+    #[serde(default)]
+    pub volume: Option<f64>,
+    #[serde(default)]
+    pub muted: bool,
+    /// Library media ids queued on this session (see `CastManager::cast_queue`)
+    #[serde(default)]
+    pub queue: Vec<String>,
+    #[serde(default)]
+    pub queue_index: usize,
+    /// Whether the device has reported the current item playing
+    #[serde(skip)]
+    seen_playing: bool,
+    /// Whether the device went idle because the current item played to its end
+    #[serde(skip)]
+    ended: bool,
 }
 
 /// Playback state
@@ -71,12 +221,153 @@ pub enum PlaybackState {
     Idle,
 }
 
+/// Result of playing a sample clip on a device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CastDeviceTest {
+    pub device_id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Transport command for an active cast session
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CastControl {
+    Pause,
+    Resume,
+    /// Seek to a position in seconds
+    Seek(f64),
+    /// Device volume from 0.0 to 1.0
+    Volume(f64),
+    Mute(bool),
+}
+
+impl CastSession {
+    /// Update state and position after the device accepted a command.
+    /// A position reported by the device wins over the requested one.
+    fn apply_control(&mut self, control: CastControl, reported_position: Option<f64>) {
+        match control {
+            CastControl::Pause => self.state = PlaybackState::Paused,
+            CastControl::Resume => self.state = PlaybackState::Playing,
+            CastControl::Seek(position) => self.position = position,
+            CastControl::Volume(level) => self.volume = Some(level),
+            CastControl::Mute(muted) => self.muted = muted,
+        }
+        if let Some(position) = reported_position {
+            self.position = position;
+        }
+    }
+
+    /// Store the media status read from a Chromecast
+    fn apply_media_status(&mut self, status: ChromecastStatus) {
+        if status.state == PlaybackState::Playing {
+            self.seen_playing = true;
+        }
+        self.state = status.state;
+        self.ended = status.finished;
+        if let Some(position) = status.position {
+            self.position = position;
+        }
+        if let Some(duration) = status.duration {
+            self.duration = duration;
+        }
+    }
+
+    /// Whether the current item has played to the end. Going idle only
+    /// counts when playback finished on its own after the device was seen
+    /// playing; a stopped, interrupted or failed item is not finished.
+    fn is_finished(&self) -> bool {
+        (self.seen_playing && self.ended)
+            || (self.duration > 0.0 && self.position >= self.duration - 1.0)
+    }
+}
+
+/// Media status read from a Chromecast
+#[derive(Debug, Clone, PartialEq)]
+struct ChromecastStatus {
+    state: PlaybackState,
+    /// IDLE with idleReason FINISHED
+    finished: bool,
+    position: Option<f64>,
+    duration: Option<f64>,
+}
+
+/// Reads Chromecast media status over one connection that is kept open
+/// between reads and reopened when it drops. Cast connections aren't
+/// `Send`, so the connection lives on its own thread, which exits when the
+/// reader is dropped.
+struct ChromecastStatusReader {
+    requests: std::sync::mpsc::Sender<StatusReply>,
+}
+
+type StatusReply = tokio::sync::oneshot::Sender<Result<ChromecastStatus>>;
+
+impl ChromecastStatusReader {
+    fn spawn(device_ip: String, device_port: u16) -> Self {
+        let (requests, incoming) = std::sync::mpsc::channel::<StatusReply>();
+        std::thread::spawn(move || {
+            let mut connection = None;
+            for reply in incoming {
+                let _ = reply.send(Self::read(&mut connection, &device_ip, device_port));
+            }
+        });
+        Self { requests }
+    }
+
+    /// Read over the kept connection, or a new one if that fails
+    fn read(
+        connection: &mut Option<rust_cast::CastDevice<'static>>,
+        device_ip: &str,
+        device_port: u16,
+    ) -> Result<ChromecastStatus> {
+        if let Some(cast_device) = connection.take() {
+            // The ping keeps the device from timing the connection out
+            if cast_device.heartbeat.ping().is_ok() {
+                if let Ok(status) = CastManager::read_chromecast_status(&cast_device) {
+                    *connection = Some(cast_device);
+                    return Ok(status);
+                }
+            }
+            debug!(ip = %device_ip, "Chromecast connection dropped, reconnecting");
+        }
+
+        let cast_device = rust_cast::CastDevice::connect_without_host_verification(
+            device_ip.to_string(),
+            device_port,
+        )
+        .map_err(|e| anyhow!("Failed to connect to Chromecast: {}", e))?;
+        let status = CastManager::read_chromecast_status(&cast_device)?;
+        *connection = Some(cast_device);
+        Ok(status)
+    }
+
+    async fn status(&self) -> Result<ChromecastStatus> {
+        let (reply, response) = tokio::sync::oneshot::channel();
+        self.requests
+            .send(reply)
+            .map_err(|_| anyhow!("Chromecast status reader has stopped"))?;
+        response
+            .await
+            .map_err(|_| anyhow!("Chromecast status reader has stopped"))?
+    }
+}
+
+/// Resolves a queued media id to a playable URL and an optional title
+pub type QueueResolver = Arc<
+    dyn Fn(String) -> futures::future::BoxFuture<'static, Result<(String, Option<String>)>>
+        + Send
+        + Sync,
+>;
+
 /// Cast manager handling device discovery and sessions
+#[derive(Clone)]
 pub struct CastManager {
     devices: Arc<RwLock<HashMap<String, CastDevice>>>,
     sessions: Arc<RwLock<HashMap<String, CastSession>>>,
+    queue_resolvers: Arc<RwLock<HashMap<String, QueueResolver>>>,
     local_ip: String,
     streaming_port: u16,
+    /// Receives `cast-state-changed` events
+    events: EventBus,
 }
 
 impl CastManager {
@@ -92,12 +383,50 @@ impl CastManager {
             "Initializing cast manager"
         );
 
-        Ok(Self {
+        let manager = Self {
             devices: Arc::new(RwLock::new(HashMap::new())),
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            queue_resolvers: Arc::new(RwLock::new(HashMap::new())),
             local_ip,
             streaming_port,
-        })
+            events: EventBus::new(),
+        };
+
+        // This machine is always available as a target
+        let local = manager.local_device();
+        manager
+            .devices
+            .try_write()
+            .map_err(|_| anyhow!("Cast device list is locked"))?
+            .insert(local.id.clone(), local);
+
+        Ok(manager)
+    }
+
+    /// Report session state changes on `events`
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    fn emit_state(&self, session: &CastSession) {
+        self.events
+            .emit(AppEvent::CastStateChanged(session.clone()));
+    }
+
+    /// The pseudo-device that plays casts in a local web view
+    fn local_device(&self) -> CastDevice {
+        CastDevice {
+            id: LOCAL_DEVICE_ID.to_string(),
+            name: "This computer".to_string(),
+            protocol: CastProtocol::Local,
+            ip_address: self.local_ip.clone(),
+            port: self.streaming_port,
+            model: None,
+            manufacturer: Some("StreamGo".to_string()),
+            status: DeviceStatus::Available,
+            capability: DeviceCapability::Video,
+        }
     }
 
     /// Discover available cast devices on the network
@@ -149,23 +478,13 @@ impl CastManager {
                     Ok(mdns_sd::ServiceEvent::ServiceResolved(info)) => {
                         debug!("Found Chromecast device: {}", info.get_fullname());
 
-                        if let Some(address) = info.get_addresses().iter().next() {
-                            let ip = match address {
-                                IpAddr::V4(ipv4) => ipv4.to_string(),
-                                IpAddr::V6(ipv6) => ipv6.to_string(),
-                            };
-
-                            let device = CastDevice {
-                                id: format!("chromecast-{}", ip.replace('.', "-")),
-                                name: info.get_hostname().trim_end_matches('.').to_string(),
-                                protocol: CastProtocol::Chromecast,
-                                ip_address: ip,
-                                port: info.get_port(),
-                                model: info.get_property("md").map(|v| v.val_str().to_string()),
-                                manufacturer: Some("Google".to_string()),
-                                status: DeviceStatus::Available,
-                            };
-
+                        if let Some(device) = Self::chromecast_device_from_service(&info) {
+                            if device.capability == DeviceCapability::AudioOnly {
+                                debug!(
+                                    "Chromecast device {} is audio-only ({:?})",
+                                    device.name, device.model
+                                );
+                            }
                             devices.push(device);
                         }
                     }
@@ -183,6 +502,47 @@ impl CastManager {
         Ok(devices)
     }
 
+    /// Build a device from a resolved `_googlecast._tcp` service
+    fn chromecast_device_from_service(info: &mdns_sd::ServiceInfo) -> Option<CastDevice> {
+        let ip = match info.get_addresses().iter().next()? {
+            IpAddr::V4(ipv4) => ipv4.to_string(),
+            IpAddr::V6(ipv6) => ipv6.to_string(),
+        };
+        let model = info.get_property_val_str("md").map(str::to_string);
+        let capability =
+            Self::chromecast_capability(model.as_deref(), info.get_property_val_str("ca"));
+
+        Some(CastDevice {
+            id: format!("chromecast-{}", ip.replace('.', "-")),
+            name: info.get_hostname().trim_end_matches('.').to_string(),
+            protocol: CastProtocol::Chromecast,
+            ip_address: ip,
+            port: info.get_port(),
+            model,
+            manufacturer: Some("Google".to_string()),
+            status: DeviceStatus::Available,
+            capability,
+        })
+    }
+
+    /// Work out whether a Chromecast can show video. The `ca` capability
+    /// bitmask is authoritative; the model name is a fallback for devices
+    /// that omit it.
+    fn chromecast_capability(model: Option<&str>, ca: Option<&str>) -> DeviceCapability {
+        if let Some(flags) = ca.and_then(|ca| ca.trim().parse::<u32>().ok()) {
+            return if flags & CA_VIDEO_OUT != 0 {
+                DeviceCapability::Video
+            } else {
+                DeviceCapability::AudioOnly
+            };
+        }
+
+        match model {
+            Some(model) if AUDIO_ONLY_MODELS.contains(&model) => DeviceCapability::AudioOnly,
+            _ => DeviceCapability::Video,
+        }
+    }
+
     /// Discover DLNA/UPnP devices using SSDP
     async fn discover_dlna_devices(&self, timeout: Duration) -> Result<Vec<CastDevice>> {
         debug!("Discovering DLNA/UPnP devices via SSDP");
@@ -291,6 +651,7 @@ impl CastManager {
             model: model_name,
             manufacturer,
             status: DeviceStatus::Available,
+            capability: DeviceCapability::Video,
         })
     }
 
@@ -320,46 +681,367 @@ impl CastManager {
         );
 
         let session_id = uuid::Uuid::new_v4().to_string();
+        let session = self
+            .load_media(
+                device,
+                &session_id,
+                media_url,
+                title.as_deref(),
+                subtitle_url.as_deref(),
+            )
+            .await?;
+
+        // Store session
+        self.sessions.write().await.insert(session_id.clone(), session.clone());
+        self.emit_state(&session);
+
+        if device.protocol == CastProtocol::DLNA {
+            self.spawn_session_poller(session_id.clone());
+        }
+
+        info!(session_id = %session_id, "Cast session started successfully");
+        Ok(session)
+    }
+
+    /// Load media on a device under `session_id`
+    async fn load_media(
+        &self,
+        device: &CastDevice,
+        session_id: &str,
+        media_url: &str,
+        title: Option<&str>,
+        subtitle_url: Option<&str>,
+    ) -> Result<CastSession> {
+        if device.capability == DeviceCapability::AudioOnly {
+            return Err(anyhow!(
+                "{} is an audio-only device and can't play video",
+                device.name
+            ));
+        }
 
         // Convert local URLs to accessible network URLs
         let accessible_media_url = self.make_url_accessible(media_url);
-        let accessible_subtitle_url = subtitle_url.as_ref().map(|url| self.make_url_accessible(url));
+        let accessible_subtitle_url = subtitle_url.map(|url| self.make_url_accessible(url));
 
-        let session = match device.protocol {
+        match device.protocol {
             CastProtocol::Chromecast => {
                 self.start_chromecast_session(
                     device,
-                    &session_id,
+                    session_id,
                     &accessible_media_url,
-                    title.as_deref(),
+                    title,
                     accessible_subtitle_url.as_deref(),
                 )
-                .await?
+                .await
             }
             CastProtocol::DLNA => {
                 self.start_dlna_session(
                     device,
-                    &session_id,
+                    session_id,
                     &accessible_media_url,
-                    title.as_deref(),
+                    title,
                     accessible_subtitle_url.as_deref(),
                 )
-                .await?
-            }
-            CastProtocol::AirPlay => {
-                return Err(anyhow!("AirPlay not yet implemented"));
+                .await
             }
-        };
+            CastProtocol::AirPlay => Err(anyhow!("AirPlay not yet implemented")),
+            // Nothing to send: the frontend opens the URL in a web view
+            CastProtocol::Local => Ok(CastSession {
+                session_id: session_id.to_string(),
+                device_id: device.id.clone(),
+                media_url: accessible_media_url,
+                title: title.map(String::from),
+                subtitle_url: accessible_subtitle_url,
+                position: 0.0,
+                duration: 0.0,
+                state: PlaybackState::Playing,
+                volume: None,
+                muted: false,
+                queue: Vec::new(),
+                queue_index: 0,
+                seen_playing: false,
+                ended: false,
+            }),
+        }
+    }
 
-        // Store session
-        self.sessions.write().await.insert(session_id.clone(), session.clone());
+    /// A session together with the device it is casting to
+    async fn session_device(&self, session_id: &str) -> Result<(CastSession, CastDevice)> {
+        let session = self
+            .get_session_status(session_id)
+            .await
+            .ok_or_else(|| anyhow!("Session not found: {}", session_id))?;
+        let device = self
+            .devices
+            .read()
+            .await
+            .get(&session.device_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Device not found: {}", session.device_id))?;
+        Ok((session, device))
+    }
 
-        info!(session_id = %session_id, "Cast session started successfully");
+    /// Cast library items to a device one after another. Each item's stream
+    /// is resolved with `resolver` only when it comes up.
+    pub async fn cast_queue(
+        &self,
+        device_id: &str,
+        media_ids: Vec<String>,
+        resolver: QueueResolver,
+    ) -> Result<CastSession> {
+        let first = media_ids
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow!("Cast queue is empty"))?;
+        let (media_url, title) = resolver(first).await?;
+
+        let mut session = self.start_cast(device_id, &media_url, title, None).await?;
+        session.queue = media_ids;
+        session.queue_index = 0;
+
+        self.queue_resolvers
+            .write()
+            .await
+            .insert(session.session_id.clone(), resolver);
+        self.sessions
+            .write()
+            .await
+            .insert(session.session_id.clone(), session.clone());
+        self.emit_state(&session);
+
+        // DLNA sessions are already polled by start_cast
+        let (_, device) = self.session_device(&session.session_id).await?;
+        if device.protocol != CastProtocol::DLNA {
+            self.spawn_session_poller(session.session_id.clone());
+        }
+
+        info!(
+            session_id = %session.session_id,
+            items = session.queue.len(),
+            "Cast queue started"
+        );
+        Ok(session)
+    }
+
+    /// Skip to the next item in a session's queue
+    pub async fn cast_next(&self, session_id: &str) -> Result<CastSession> {
+        let (session, _) = self.session_device(session_id).await?;
+        if session.queue_index + 1 >= session.queue.len() {
+            return Err(anyhow!("No next item in the cast queue"));
+        }
+        self.load_queue_item(session_id, session.queue_index + 1)
+            .await
+    }
+
+    /// Go back to the previous item in a session's queue
+    pub async fn cast_previous(&self, session_id: &str) -> Result<CastSession> {
+        let (session, _) = self.session_device(session_id).await?;
+        if session.queue.is_empty() || session.queue_index == 0 {
+            return Err(anyhow!("No previous item in the cast queue"));
+        }
+        self.load_queue_item(session_id, session.queue_index - 1)
+            .await
+    }
+
+    /// Resolve and load queue item `index` on an existing session
+    async fn load_queue_item(&self, session_id: &str, index: usize) -> Result<CastSession> {
+        let (session, device) = self.session_device(session_id).await?;
+        let media_id = session
+            .queue
+            .get(index)
+            .cloned()
+            .ok_or_else(|| anyhow!("Queue index {} out of range", index))?;
+        let resolver = self
+            .queue_resolvers
+            .read()
+            .await
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Session {} has no cast queue", session_id))?;
+
+        info!(session_id = %session_id, media_id = %media_id, index, "Loading next queue item");
+        let (media_url, title) = resolver(media_id).await?;
+        let mut loaded = self
+            .load_media(&device, session_id, &media_url, title.as_deref(), None)
+            .await?;
+        loaded.queue = session.queue;
+        loaded.queue_index = index;
+        loaded.volume = session.volume;
+        loaded.muted = session.muted;
+
+        // The session may have been stopped while the item was loading
+        let mut sessions = self.sessions.write().await;
+        let stored = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| anyhow!("Session not found: {}", session_id))?;
+        *stored = loaded.clone();
+        self.emit_state(&loaded);
+        Ok(loaded)
+    }
+
+    /// Refresh a session from its device, then advance its queue when the
+    /// current item has finished
+    pub async fn poll_session(&self, session_id: &str) -> Result<CastSession> {
+        self.poll_session_with(session_id, &mut None).await
+    }
+
+    /// `poll_session`, reading Chromecast status through `reader` so that
+    /// repeated polls share one connection
+    async fn poll_session_with(
+        &self,
+        session_id: &str,
+        reader: &mut Option<ChromecastStatusReader>,
+    ) -> Result<CastSession> {
+        let (_, device) = self.session_device(session_id).await?;
+        let session = match device.protocol {
+            CastProtocol::DLNA => self.refresh_dlna_position(session_id).await?,
+            CastProtocol::Chromecast => self.refresh_chromecast_status(session_id, reader).await?,
+            CastProtocol::AirPlay => return Err(anyhow!("AirPlay not yet implemented")),
+            // The local web view owns playback, so there is nothing to poll
+            CastProtocol::Local => return self.session_device(session_id).await.map(|(s, _)| s),
+        };
+
+        if session.is_finished() && session.queue_index + 1 < session.queue.len() {
+            return self
+                .load_queue_item(session_id, session.queue_index + 1)
+                .await;
+        }
         Ok(session)
     }
 
+    /// Keep a session's state up to date until the session ends
+    fn spawn_session_poller(&self, session_id: String) {
+        let manager = self.clone();
+        let period = Duration::from_secs(SESSION_POLL_SECS);
+
+        tokio::spawn(async move {
+            let mut reader = None;
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                interval.tick().await;
+                if !manager.sessions.read().await.contains_key(&session_id) {
+                    break;
+                }
+                if let Err(e) = manager.poll_session_with(&session_id, &mut reader).await {
+                    debug!(session_id = %session_id, error = %e, "Failed to poll cast session");
+                }
+            }
+        });
+    }
+
+    /// Query a DLNA renderer for the current position and duration of a
+    /// session and store them on the session
+    pub async fn refresh_dlna_position(&self, session_id: &str) -> Result<CastSession> {
+        let (_, device) = self.session_device(session_id).await?;
+        if device.protocol != CastProtocol::DLNA {
+            return Err(anyhow!("Session {} is not a DLNA session", session_id));
+        }
+
+        let soap_body = r#"<?xml version="1.0" encoding="utf-8"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/">
+  <s:Body>
+    <u:GetPositionInfo xmlns:u="urn:schemas-upnp-org:service:AVTransport:1">
+      <InstanceID>0</InstanceID>
+    </u:GetPositionInfo>
+  </s:Body>
+</s:Envelope>"#;
+
+        let control_url = format!(
+            "http://{}:{}/AVTransport/control",
+            device.ip_address, device.port
+        );
+        let response = reqwest::Client::new()
+            .post(&control_url)
+            .header("Content-Type", "text/xml; charset=utf-8")
+            .header(
+                "SOAPAction",
+                "\"urn:schemas-upnp-org:service:AVTransport:1#GetPositionInfo\"",
+            )
+            .body(soap_body)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to send DLNA GetPositionInfo: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "DLNA GetPositionInfo failed: {}",
+                response.status()
+            ));
+        }
+        let xml = response
+            .text()
+            .await
+            .map_err(|e| anyhow!("Failed to read GetPositionInfo response: {}", e))?;
+        let (position, duration) = Self::parse_position_info(&xml)?;
+
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| anyhow!("Session not found: {}", session_id))?;
+        if let Some(position) = position {
+            session.position = position;
+        }
+        if let Some(duration) = duration {
+            session.duration = duration;
+        }
+        Ok(session.clone())
+    }
+
+    /// Parse `RelTime` and `TrackDuration` (in seconds) from a
+    /// GetPositionInfo response
+    fn parse_position_info(xml: &str) -> Result<(Option<f64>, Option<f64>)> {
+        use quick_xml::events::Event;
+        use quick_xml::Reader;
+
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+
+        let mut position = None;
+        let mut duration = None;
+        let mut current_tag = String::new();
+
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(e)) => {
+                    current_tag = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                }
+                Ok(Event::End(_)) => current_tag.clear(),
+                Ok(Event::Text(e)) => {
+                    let text = e.unescape().unwrap_or_default();
+                    match current_tag.as_str() {
+                        "RelTime" => position = Self::parse_dlna_time(&text),
+                        "TrackDuration" => duration = Self::parse_dlna_time(&text),
+                        _ => {}
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(anyhow!("XML parsing error: {}", e)),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok((position, duration))
+    }
+
+    /// Convert a DLNA `H+:MM:SS[.F]` time to seconds.
+    /// Renderers report "NOT_IMPLEMENTED" when they don't track time.
+    fn parse_dlna_time(time: &str) -> Option<f64> {
+        let mut parts = time.trim().split(':');
+        let hours: f64 = parts.next()?.parse().ok()?;
+        let minutes: f64 = parts.next()?.parse().ok()?;
+        let seconds: f64 = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(hours * 3600.0 + minutes * 60.0 + seconds)
+    }
+
     /// Convert localhost URLs to network-accessible URLs
-    fn make_url_accessible(&self, url: &str) -> String {
+    pub fn make_url_accessible(&self, url: &str) -> String {
         if let Ok(mut parsed_url) = url::Url::parse(url) {
             if parsed_url.host_str() == Some("127.0.0.1") || parsed_url.host_str() == Some("localhost") {
                             if let Err(e) = parsed_url.set_host(Some(&self.local_ip)) {
@@ -375,6 +1057,85 @@ impl CastManager {
         url.to_string()
     }
 
+    /// Connect to a Chromecast, launch the Default Media Receiver and load
+    /// `media_url`. Returns the reported position and duration. Blocking.
+    fn load_on_chromecast(
+        device_ip: &str,
+        device_port: u16,
+        media_url: &str,
+    ) -> Result<(f64, f64)> {
+        use rust_cast::channels::media::Media;
+
+        // Connect to Chromecast device
+        info!(ip = %device_ip, port = device_port, "Connecting to Chromecast");
+
+        let cast_device =
+            rust_cast::CastDevice::connect_without_host_verification(device_ip, device_port)
+                .map_err(|e| anyhow!("Failed to connect to Chromecast: {}", e))?;
+
+        info!("Cast device connected, launching Default Media Receiver app");
+
+        // Launch the Default Media Receiver app (required for playing media)
+        let app = cast_device
+            .receiver
+            .launch_app(&rust_cast::channels::receiver::CastDeviceApp::DefaultMediaReceiver)
+            .map_err(|e| anyhow!("Failed to launch media receiver app: {}", e))?;
+
+        info!("Media receiver app launched: {}", app.display_name);
+
+        // Wait for the app to come up before talking to it
+        wait_until_ready(CHROMECAST_READY_TIMEOUT, CHROMECAST_READY_POLL, || {
+            let status = cast_device
+                .receiver
+                .get_status()
+                .map_err(|e| anyhow!("Failed to get receiver status: {}", e))?;
+            Ok(media_receiver_ready(&status, &app.session_id))
+        })
+        .map_err(|e| anyhow!("Media receiver app did not start: {}", e))?;
+
+        // Connect to transport (required before loading media)
+        cast_device
+            .connection
+            .connect(&app.transport_id)
+            .map_err(|e| anyhow!("Failed to connect to transport: {}", e))?;
+
+        info!("Connected to transport, loading media");
+
+        // Build media metadata
+        let (content_type, stream_type) = infer_cast_content_type(media_url);
+        let media = Media {
+            content_id: media_url.to_string(),
+            content_type: content_type.to_string(),
+            stream_type,
+            duration: None,
+            metadata: None,
+        };
+
+        // Load media on Chromecast using the media channel
+        let media_status = cast_device
+            .media
+            .load(&app.transport_id, &app.session_id, &media)
+            .map_err(|e| anyhow!("Failed to load media: {}", e))?;
+
+        info!("Media loaded successfully on Chromecast");
+
+        // Extract duration and position from media status if available
+        let duration = media_status
+            .entries
+            .first()
+            .and_then(|e| e.media.as_ref())
+            .and_then(|m| m.duration)
+            .unwrap_or(0.0) as f64;
+
+        let position = media_status
+            .entries
+            .first()
+            .and_then(|e| e.current_time)
+            .unwrap_or(0.0) as f64;
+
+        Ok((position, duration))
+    }
+
     /// Start Chromecast session
     async fn start_chromecast_session(
         &self,
@@ -394,75 +1155,15 @@ impl CastManager {
         let title = title.map(String::from);
         let subtitle_url = subtitle_url.map(String::from);
 
-        // Perform entire Cast protocol flow in a single blocking task
+        // Perform entire Cast protocol flow in a single blocking task,
+        // starting over from a fresh connection if any step fails
         tokio::task::spawn_blocking(move || {
-            use rust_cast::channels::media::{Media, StreamType};
-            use std::time::Duration as StdDuration;
-
-            // Connect to Chromecast device
-            info!(ip = %device_ip, port = device_port, "Connecting to Chromecast");
-            
-            let cast_device = rust_cast::CastDevice::connect_without_host_verification(
-                &device_ip,
-                device_port,
-            )
-            .map_err(|e| anyhow!("Failed to connect to Chromecast: {}", e))?;
-
-            info!("Cast device connected, launching Default Media Receiver app");
-
-            // Launch the Default Media Receiver app (required for playing media)
-            let app = cast_device
-                .receiver
-                .launch_app(&rust_cast::channels::receiver::CastDeviceApp::DefaultMediaReceiver)
-                .map_err(|e| anyhow!("Failed to launch media receiver app: {}", e))?;
-
-            info!("Media receiver app launched: {}", app.display_name);
-
-            // Wait for app to initialize
-            std::thread::sleep(StdDuration::from_millis(2000));
-
-            // Connect to transport (required before loading media)
-            cast_device
-                .connection
-                .connect(&app.transport_id)
-                .map_err(|e| anyhow!("Failed to connect to transport: {}", e))?;
-
-            info!("Connected to transport, loading media");
-
-            // Build media metadata
-            let media = Media {
-                content_id: media_url.clone(),
-                content_type: "video/mp4".to_string(),
-                stream_type: StreamType::Live,
-                duration: None,
-                metadata: None,
-            };
-
-            // Load media on Chromecast using the media channel
-            let media_status = cast_device
-                .media
-                .load(
-                    &app.transport_id,
-                    &app.session_id,
-                    &media,
-                )
-                .map_err(|e| anyhow!("Failed to load media: {}", e))?;
-
-            info!("Media loaded successfully on Chromecast");
-
-            // Extract duration and position from media status if available
-            let duration = media_status
-                .entries
-                .first()
-                .and_then(|e| e.media.as_ref())
-                .and_then(|m| m.duration)
-                .unwrap_or(0.0) as f64;
-
-            let position = media_status
-                .entries
-                .first()
-                .and_then(|e| e.current_time)
-                .unwrap_or(0.0) as f64;
+            let (position, duration) = retry_with_backoff(
+                CHROMECAST_ATTEMPTS,
+                CHROMECAST_RETRY_BACKOFF,
+                "Chromecast load",
+                || Self::load_on_chromecast(&device_ip, device_port, &media_url),
+            )?;
 
             Ok(CastSession {
                 session_id,
@@ -473,6 +1174,12 @@ impl CastManager {
                 position,
                 duration,
                 state: PlaybackState::Playing,
+                volume: None,
+                muted: false,
+                queue: Vec::new(),
+                queue_index: 0,
+                seen_playing: false,
+                ended: false,
             })
         })
         .await
@@ -550,6 +1257,12 @@ impl CastManager {
                     position: 0.0,
                     duration: 0.0,
                     state: PlaybackState::Playing,
+                    volume: None,
+                    muted: false,
+                    queue: Vec::new(),
+                    queue_index: 0,
+                    seen_playing: false,
+                    ended: false,
                 })
             }
             Ok(resp) => {
@@ -569,8 +1282,10 @@ impl CastManager {
     /// Build DIDL-Lite metadata XML for DLNA
     fn build_didl_metadata(url: &str, title: &str) -> String {
         format!(
-            r#"&lt;DIDL-Lite xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/"&gt;&lt;item id="0" parentID="-1" restricted="1"&gt;&lt;dc:title&gt;{}&lt;/dc:title&gt;&lt;res protocolInfo="http-get:*:video/*:*"&gt;{}&lt;/res&gt;&lt;upnp:class&gt;object.item.videoItem&lt;/upnp:class&gt;&lt;/item&gt;&lt;/DIDL-Lite&gt;"#,
-            title, url
+            r#"&lt;DIDL-Lite xmlns="urn:schemas-upnp-org:metadata-1-0/DIDL-Lite/" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:upnp="urn:schemas-upnp-org:metadata-1-0/upnp/"&gt;&lt;item id="0" parentID="-1" restricted="1"&gt;&lt;dc:title&gt;{}&lt;/dc:title&gt;&lt;res protocolInfo="http-get:*:{}:*"&gt;{}&lt;/res&gt;&lt;upnp:class&gt;object.item.videoItem&lt;/upnp:class&gt;&lt;/item&gt;&lt;/DIDL-Lite&gt;"#,
+            title,
+            infer_cast_content_type(url).0,
+            url
         )
     }
 
@@ -600,6 +1315,11 @@ impl CastManager {
         }
 
         sessions.remove(session_id);
+        self.queue_resolvers.write().await.remove(session_id);
+        self.emit_state(&CastSession {
+            state: PlaybackState::Idle,
+            ..session
+        });
         info!("Cast session stopped");
         Ok(())
     }
@@ -637,12 +1357,19 @@ impl CastManager {
         tokio::task::spawn_blocking(move || {
             info!(ip = %device_ip, port = device_port, "Stopping Chromecast playback");
 
-            // Connect to Chromecast
-            let cast_device = rust_cast::CastDevice::connect_without_host_verification(
-                &device_ip,
-                device_port,
-            )
-            .map_err(|e| anyhow!("Failed to connect to Chromecast for stop: {}", e))?;
+            // Connect to Chromecast, retrying dropped connections
+            let cast_device = retry_with_backoff(
+                CHROMECAST_ATTEMPTS,
+                CHROMECAST_RETRY_BACKOFF,
+                "Chromecast connect",
+                || {
+                    rust_cast::CastDevice::connect_without_host_verification(
+                        &device_ip,
+                        device_port,
+                    )
+                    .map_err(|e| anyhow!("Failed to connect to Chromecast for stop: {}", e))
+                },
+            )?;
 
             // Get current receiver status to find active app
             let status = cast_device
@@ -696,4 +1423,1055 @@ impl CastManager {
     pub async fn get_session_status(&self, session_id: &str) -> Option<CastSession> {
         self.sessions.read().await.get(session_id).cloned()
     }
+
+    /// Cast a short public sample clip to a device, wait up to `play_for`
+    /// for the device to report it playing, then stop it again
+    pub async fn test_device(&self, device_id: &str, play_for: Duration) -> CastDeviceTest {
+        let result = self.run_device_test(device_id, play_for).await;
+        if let Err(e) = &result {
+            warn!(device_id = %device_id, error = %e, "Cast device test failed");
+        }
+
+        CastDeviceTest {
+            device_id: device_id.to_string(),
+            success: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+
+    async fn run_device_test(&self, device_id: &str, play_for: Duration) -> Result<()> {
+        let session = self
+            .start_cast(
+                device_id,
+                TEST_CAST_MEDIA_URL,
+                Some("StreamGo cast test".to_string()),
+                None,
+            )
+            .await?;
+
+        // The session starts out as Playing, so only the device's own
+        // report counts
+        let deadline = tokio::time::Instant::now() + play_for;
+        let mut reader = None;
+        let state = loop {
+            let state = self
+                .device_playback_state(&session.session_id, &mut reader)
+                .await;
+            if matches!(state, Ok(PlaybackState::Playing))
+                || tokio::time::Instant::now() >= deadline
+            {
+                break state;
+            }
+            tokio::time::sleep(DEVICE_TEST_POLL).await;
+        };
+
+        // Always stop the sample, even if it never started playing
+        let stopped = self.stop_cast(&session.session_id).await;
+        match state {
+            Ok(PlaybackState::Playing) => stopped,
+            Ok(other) => Err(anyhow!("Device did not start playing (state: {:?})", other)),
+            Err(e) => Err(anyhow!("Failed to read playback state from device: {}", e)),
+        }
+    }
+
+    /// Playback state of a session as reported by its device
+    async fn device_playback_state(
+        &self,
+        session_id: &str,
+        reader: &mut Option<ChromecastStatusReader>,
+    ) -> Result<PlaybackState> {
+        let (session, device) = self.session_device(session_id).await?;
+        match device.protocol {
+            CastProtocol::Chromecast => Ok(self
+                .refresh_chromecast_status(session_id, reader)
+                .await?
+                .state),
+            CastProtocol::DLNA => self.dlna_transport_state(&device).await,
+            CastProtocol::AirPlay => Err(anyhow!("AirPlay not yet implemented")),
+            // The local web view plays whatever the session says
+            CastProtocol::Local => Ok(session.state),
+        }
+    }
+
+    /// Ask a DLNA renderer for its transport state (GetTransportInfo)
+    async fn dlna_transport_state(&self, device: &CastDevice) -> Result<PlaybackState> {
+        let soap_body = r#"<?xml version="1.0" encoding="utf-8"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/">
+  <s:Body>
+    <u:GetTransportInfo xmlns:u="urn:schemas-upnp-org:service:AVTransport:1">
+      <InstanceID>0</InstanceID>
+    </u:GetTransportInfo>
+  </s:Body>
+</s:Envelope>"#;
+
+        let control_url = format!(
+            "http://{}:{}/AVTransport/control",
+            device.ip_address, device.port
+        );
+        let response = reqwest::Client::new()
+            .post(&control_url)
+            .header("Content-Type", "text/xml; charset=utf-8")
+            .header(
+                "SOAPAction",
+                "\"urn:schemas-upnp-org:service:AVTransport:1#GetTransportInfo\"",
+            )
+            .body(soap_body)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to send DLNA GetTransportInfo: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "DLNA GetTransportInfo failed: {}",
+                response.status()
+            ));
+        }
+        let xml = response
+            .text()
+            .await
+            .map_err(|e| anyhow!("Failed to read GetTransportInfo response: {}", e))?;
+        Self::parse_transport_state(&xml)
+    }
+
+    /// Map `CurrentTransportState` from a GetTransportInfo response to a
+    /// playback state
+    fn parse_transport_state(xml: &str) -> Result<PlaybackState> {
+        use quick_xml::events::Event;
+        use quick_xml::Reader;
+
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+
+        let mut in_state = false;
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(e)) => {
+                    in_state = e.local_name().as_ref() == b"CurrentTransportState";
+                }
+                Ok(Event::End(_)) => in_state = false,
+                Ok(Event::Text(e)) if in_state => {
+                    let text = e.unescape().unwrap_or_default();
+                    return match text.trim() {
+                        "PLAYING" => Ok(PlaybackState::Playing),
+                        "PAUSED_PLAYBACK" | "PAUSED_RECORDING" => Ok(PlaybackState::Paused),
+                        "TRANSITIONING" => Ok(PlaybackState::Buffering),
+                        "STOPPED" | "NO_MEDIA_PRESENT" => Ok(PlaybackState::Idle),
+                        other => Err(anyhow!("Unknown DLNA transport state: {}", other)),
+                    };
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(anyhow!("XML parsing error: {}", e)),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Err(anyhow!(
+            "GetTransportInfo response has no CurrentTransportState"
+        ))
+    }
+
+    /// Pause playback of a cast session
+    pub async fn pause_cast(&self, session_id: &str) -> Result<CastSession> {
+        self.control_cast(session_id, CastControl::Pause).await
+    }
+
+    /// Resume playback of a paused cast session
+    pub async fn resume_cast(&self, session_id: &str) -> Result<CastSession> {
+        self.control_cast(session_id, CastControl::Resume).await
+    }
+
+    /// Seek a cast session to a position in seconds
+    pub async fn seek_cast(&self, session_id: &str, position_secs: f64) -> Result<CastSession> {
+        if !position_secs.is_finite() || position_secs < 0.0 {
+            return Err(anyhow!("Invalid seek position: {}", position_secs));
+        }
+        self.control_cast(session_id, CastControl::Seek(position_secs))
+            .await
+    }
+
+    /// Set the volume of the device a session is casting to.
+    /// The level is clamped to 0.0 - 1.0.
+    pub async fn set_cast_volume(&self, session_id: &str, level: f64) -> Result<CastSession> {
+        if level.is_nan() {
+            return Err(anyhow!("Invalid volume level"));
+        }
+        self.control_cast(session_id, CastControl::Volume(level.clamp(0.0, 1.0)))
+            .await
+    }
+
+    /// Mute or unmute the device a session is casting to
+    pub async fn set_cast_mute(&self, session_id: &str, muted: bool) -> Result<CastSession> {
+        self.control_cast(session_id, CastControl::Mute(muted))
+            .await
+    }
+
+    async fn control_cast(&self, session_id: &str, control: CastControl) -> Result<CastSession> {
+        let (_, device) = self.session_device(session_id).await?;
+
+        debug!(session_id = %session_id, control = ?control, "Sending cast control");
+
+        let reported_position = match (&device.protocol, control) {
+            (CastProtocol::Chromecast, _) => self.send_chromecast_control(&device, control).await?,
+            (CastProtocol::DLNA, CastControl::Volume(_) | CastControl::Mute(_)) => {
+                self.send_dlna_rendering_control(&device, control).await?;
+                None
+            }
+            // The local web view follows the session state
+            (CastProtocol::Local, _) => None,
+            _ => {
+                return Err(anyhow!(
+                    "Playback controls are not supported for {:?} devices",
+                    device.protocol
+                ))
+            }
+        };
+
+        // The session may have been stopped while the command was in flight
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| anyhow!("Session not found: {}", session_id))?;
+        session.apply_control(control, reported_position);
+        self.emit_state(session);
+        Ok(session.clone())
+    }
+
+    /// Send a transport command to the active Chromecast media session.
+    /// Returns the playback position reported by the device, if any.
+    async fn send_chromecast_control(
+        &self,
+        device: &CastDevice,
+        control: CastControl,
+    ) -> Result<Option<f64>> {
+        let device_ip = device.ip_address.clone();
+        let device_port = device.port;
+
+        tokio::task::spawn_blocking(move || {
+            let cast_device =
+                rust_cast::CastDevice::connect_without_host_verification(&device_ip, device_port)
+                    .map_err(|e| anyhow!("Failed to connect to Chromecast: {}", e))?;
+
+            // Volume belongs to the receiver, not the media session
+            let volume = match control {
+                CastControl::Volume(level) => {
+                    Some(rust_cast::channels::receiver::Volume::from(level as f32))
+                }
+                CastControl::Mute(muted) => Some(muted.into()),
+                _ => None,
+            };
+            if let Some(volume) = volume {
+                cast_device
+                    .receiver
+                    .set_volume(volume)
+                    .map_err(|e| anyhow!("Failed to set Chromecast volume: {}", e))?;
+                return Ok(None);
+            }
+
+            let status = cast_device
+                .receiver
+                .get_status()
+                .map_err(|e| anyhow!("Failed to get receiver status: {}", e))?;
+
+            // Find the app that is playing media and its media session
+            for app in status.applications {
+                if cast_device.connection.connect(&app.transport_id).is_err() {
+                    continue;
+                }
+                let media_session_id = match cast_device.media.get_status(&app.transport_id, None) {
+                    Ok(media_status) => match media_status.entries.first() {
+                        Some(entry) => entry.media_session_id,
+                        None => continue,
+                    },
+                    Err(e) => {
+                        warn!("Failed to get media status: {}", e);
+                        continue;
+                    }
+                };
+
+                let entry = match control {
+                    CastControl::Pause => cast_device
+                        .media
+                        .pause(app.transport_id.as_str(), media_session_id),
+                    CastControl::Resume => cast_device
+                        .media
+                        .play(app.transport_id.as_str(), media_session_id),
+                    CastControl::Seek(position) => cast_device.media.seek(
+                        app.transport_id.as_str(),
+                        media_session_id,
+                        Some(position as f32),
+                        None,
+                    ),
+                    CastControl::Volume(_) | CastControl::Mute(_) => {
+                        unreachable!("volume is set on the receiver")
+                    }
+                }
+                .map_err(|e| anyhow!("Chromecast {:?} failed: {}", control, e))?;
+
+                return Ok(entry.current_time.map(f64::from));
+            }
+
+            Err(anyhow!("No active media session on Chromecast"))
+        })
+        .await
+        .map_err(|e| anyhow!("Failed to execute Chromecast control: {}", e))?
+    }
+
+    /// Read the player state, position and duration of a Chromecast session
+    /// from the device through `reader` (started on first use) and store
+    /// them on the session
+    async fn refresh_chromecast_status(
+        &self,
+        session_id: &str,
+        reader: &mut Option<ChromecastStatusReader>,
+    ) -> Result<CastSession> {
+        let (_, device) = self.session_device(session_id).await?;
+        let status = reader
+            .get_or_insert_with(|| {
+                ChromecastStatusReader::spawn(device.ip_address.clone(), device.port)
+            })
+            .status()
+            .await?;
+
+        let mut sessions = self.sessions.write().await;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| anyhow!("Session not found: {}", session_id))?;
+        let previous = session.state.clone();
+        session.apply_media_status(status);
+        if session.state != previous {
+            self.emit_state(session);
+        }
+        Ok(session.clone())
+    }
+
+    /// Media status of whichever app on a connected Chromecast has media
+    /// loaded. Blocking.
+    fn read_chromecast_status(cast_device: &rust_cast::CastDevice) -> Result<ChromecastStatus> {
+        use rust_cast::channels::media::{IdleReason, PlayerState};
+
+        let status = cast_device
+            .receiver
+            .get_status()
+            .map_err(|e| anyhow!("Failed to get receiver status: {}", e))?;
+
+        for app in status.applications {
+            if cast_device.connection.connect(&app.transport_id).is_err() {
+                continue;
+            }
+            let Ok(media_status) = cast_device.media.get_status(&app.transport_id, None) else {
+                continue;
+            };
+            if let Some(entry) = media_status.entries.first() {
+                let state = match entry.player_state {
+                    PlayerState::Playing => PlaybackState::Playing,
+                    PlayerState::Paused => PlaybackState::Paused,
+                    PlayerState::Buffering => PlaybackState::Buffering,
+                    PlayerState::Idle => PlaybackState::Idle,
+                };
+                return Ok(ChromecastStatus {
+                    state,
+                    finished: entry.player_state == PlayerState::Idle
+                        && entry.idle_reason == Some(IdleReason::Finished),
+                    position: entry.current_time.map(f64::from),
+                    duration: entry.media.as_ref().and_then(|m| m.duration).map(f64::from),
+                });
+            }
+        }
+
+        // No app has media loaded any more
+        Ok(ChromecastStatus {
+            state: PlaybackState::Idle,
+            finished: false,
+            position: None,
+            duration: None,
+        })
+    }
+
+    /// Send a volume or mute command to a DLNA renderer's RenderingControl service
+    async fn send_dlna_rendering_control(
+        &self,
+        device: &CastDevice,
+        control: CastControl,
+    ) -> Result<()> {
+        let (action, body) = match control {
+            CastControl::Volume(level) => ("SetVolume", Self::build_set_volume_body(level)),
+            CastControl::Mute(muted) => ("SetMute", Self::build_set_mute_body(muted)),
+            _ => return Err(anyhow!("{:?} is not a RenderingControl action", control)),
+        };
+
+        let control_url = format!(
+            "http://{}:{}/RenderingControl/control",
+            device.ip_address, device.port
+        );
+        let response = reqwest::Client::new()
+            .post(&control_url)
+            .header("Content-Type", "text/xml; charset=utf-8")
+            .header(
+                "SOAPAction",
+                format!(
+                    "\"urn:schemas-upnp-org:service:RenderingControl:1#{}\"",
+                    action
+                ),
+            )
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to send DLNA {}: {}", action, e))?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("DLNA {} failed: {}", action, response.status()));
+        }
+        Ok(())
+    }
+
+    /// Build the SOAP body for RenderingControl SetVolume (0 - 100)
+    fn build_set_volume_body(level: f64) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/">
+  <s:Body>
+    <u:SetVolume xmlns:u="urn:schemas-upnp-org:service:RenderingControl:1">
+      <InstanceID>0</InstanceID>
+      <Channel>Master</Channel>
+      <DesiredVolume>{}</DesiredVolume>
+    </u:SetVolume>
+  </s:Body>
+</s:Envelope>"#,
+            (level.clamp(0.0, 1.0) * 100.0).round() as u8
+        )
+    }
+
+    /// Build the SOAP body for RenderingControl SetMute
+    fn build_set_mute_body(muted: bool) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/">
+  <s:Body>
+    <u:SetMute xmlns:u="urn:schemas-upnp-org:service:RenderingControl:1">
+      <InstanceID>0</InstanceID>
+      <Channel>Master</Channel>
+      <DesiredMute>{}</DesiredMute>
+    </u:SetMute>
+  </s:Body>
+</s:Envelope>"#,
+            u8::from(muted)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(device_id: &str) -> CastSession {
+        CastSession {
+            session_id: "session-1".to_string(),
+            device_id: device_id.to_string(),
+            media_url: "http://192.168.1.2:8765/stream.mp4".to_string(),
+            title: Some("Sample".to_string()),
+            subtitle_url: None,
+            position: 12.0,
+            duration: 600.0,
+            state: PlaybackState::Playing,
+            volume: None,
+            muted: false,
+            queue: Vec::new(),
+            queue_index: 0,
+            seen_playing: false,
+            ended: false,
+        }
+    }
+
+    #[test]
+    fn test_apply_control_state_transitions() {
+        let mut session = session("chromecast-1");
+
+        session.apply_control(CastControl::Pause, Some(15.5));
+        assert_eq!(session.state, PlaybackState::Paused);
+        assert_eq!(session.position, 15.5);
+
+        session.apply_control(CastControl::Resume, None);
+        assert_eq!(session.state, PlaybackState::Playing);
+        assert_eq!(session.position, 15.5);
+
+        session.apply_control(CastControl::Seek(120.0), None);
+        assert_eq!(session.state, PlaybackState::Playing);
+        assert_eq!(session.position, 120.0);
+
+        session.apply_control(CastControl::Volume(0.5), None);
+        session.apply_control(CastControl::Mute(true), None);
+        assert_eq!(session.state, PlaybackState::Playing);
+        assert_eq!(session.position, 120.0);
+        assert_eq!(session.volume, Some(0.5));
+        assert!(session.muted);
+    }
+
+    #[test]
+    fn test_only_naturally_ended_items_are_finished() {
+        let status = |state, finished| ChromecastStatus {
+            state,
+            finished,
+            position: Some(30.0),
+            duration: Some(600.0),
+        };
+
+        // Idle before the device ever reported playing (still loading)
+        let mut session = session("chromecast-1");
+        session.apply_media_status(status(PlaybackState::Idle, false));
+        assert!(!session.is_finished());
+        session.apply_media_status(status(PlaybackState::Idle, true));
+        assert!(!session.is_finished());
+
+        session.apply_media_status(status(PlaybackState::Playing, false));
+        assert!(!session.is_finished());
+        assert_eq!(session.position, 30.0);
+
+        // Stopped, interrupted or failed: idle, but not finished
+        session.apply_media_status(status(PlaybackState::Idle, false));
+        assert_eq!(session.state, PlaybackState::Idle);
+        assert!(!session.is_finished());
+
+        session.apply_media_status(status(PlaybackState::Idle, true));
+        assert!(session.is_finished());
+    }
+
+    #[test]
+    fn test_rendering_control_bodies() {
+        let body = CastManager::build_set_volume_body(0.42);
+        assert!(body.contains(
+            r#"<u:SetVolume xmlns:u="urn:schemas-upnp-org:service:RenderingControl:1">"#
+        ));
+        assert!(body.contains("<Channel>Master</Channel>"));
+        assert!(body.contains("<DesiredVolume>42</DesiredVolume>"));
+
+        let loud = CastManager::build_set_volume_body(1.7);
+        assert!(loud.contains("<DesiredVolume>100</DesiredVolume>"));
+        let negative = CastManager::build_set_volume_body(-0.2);
+        assert!(negative.contains("<DesiredVolume>0</DesiredVolume>"));
+
+        let body = CastManager::build_set_mute_body(true);
+        assert!(body
+            .contains(r#"<u:SetMute xmlns:u="urn:schemas-upnp-org:service:RenderingControl:1">"#));
+        assert!(body.contains("<DesiredMute>1</DesiredMute>"));
+        assert!(CastManager::build_set_mute_body(false).contains("<DesiredMute>0</DesiredMute>"));
+    }
+
+    fn dlna_device(id: &str, ip_address: &str, port: u16) -> CastDevice {
+        CastDevice {
+            id: id.to_string(),
+            name: "TV".to_string(),
+            protocol: CastProtocol::DLNA,
+            ip_address: ip_address.to_string(),
+            port,
+            model: None,
+            manufacturer: None,
+            status: DeviceStatus::Available,
+            capability: DeviceCapability::Video,
+        }
+    }
+
+    /// Records every event emitted through it
+    #[derive(Default)]
+    struct EventLog(std::sync::Mutex<Vec<(String, serde_json::Value)>>);
+
+    impl crate::events::EventSink for EventLog {
+        fn emit_json(&self, event: &str, payload: serde_json::Value) -> Result<()> {
+            self.0.lock().unwrap().push((event.to_string(), payload));
+            Ok(())
+        }
+    }
+
+    /// Mock DLNA renderer that answers AVTransport actions with `status`
+    /// and reports `transport_state` from GetTransportInfo. GetPositionInfo
+    /// always reports the current item as played to the end.
+    async fn spawn_mock_renderer(
+        status: u16,
+        transport_state: &'static str,
+    ) -> (u16, Arc<std::sync::Mutex<Vec<String>>>) {
+        use axum::{http::HeaderMap, http::StatusCode, routing::post, Router};
+
+        let actions = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = actions.clone();
+        let app = Router::new().route(
+            "/AVTransport/control",
+            post(move |headers: HeaderMap| async move {
+                let action = headers
+                    .get("SOAPAction")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim_matches('"').rsplit('#').next())
+                    .unwrap_or_default()
+                    .to_string();
+                let body = match action.as_str() {
+                    "GetPositionInfo" => "<s:Envelope><s:Body><u:GetPositionInfoResponse>\
+                         <TrackDuration>0:01:00</TrackDuration><RelTime>0:01:00</RelTime>\
+                         </u:GetPositionInfoResponse></s:Body></s:Envelope>"
+                        .to_string(),
+                    "GetTransportInfo" => format!(
+                        "<s:Envelope><s:Body><u:GetTransportInfoResponse>\
+                         <CurrentTransportState>{}</CurrentTransportState>\
+                         <CurrentTransportStatus>OK</CurrentTransportStatus>\
+                         </u:GetTransportInfoResponse></s:Body></s:Envelope>",
+                        transport_state
+                    ),
+                    _ => String::new(),
+                };
+                recorded.lock().unwrap().push(action);
+                (StatusCode::from_u16(status).unwrap(), body)
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (port, actions)
+    }
+
+    #[tokio::test]
+    async fn test_device_test_plays_and_stops_sample() {
+        let (port, actions) = spawn_mock_renderer(200, "PLAYING").await;
+        let manager = CastManager::new(8765).unwrap();
+        manager.devices.write().await.insert(
+            "dlna-1".to_string(),
+            dlna_device("dlna-1", "127.0.0.1", port),
+        );
+
+        let result = manager
+            .test_device("dlna-1", Duration::from_millis(10))
+            .await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.error, None);
+        assert!(manager.get_sessions().await.is_empty());
+        assert_eq!(
+            *actions.lock().unwrap(),
+            vec!["SetAVTransportURI", "Play", "GetTransportInfo", "Stop"]
+        );
+
+        // A renderer that accepts the commands but never plays fails the
+        // test, and the sample is still stopped
+        let (port, actions) = spawn_mock_renderer(200, "STOPPED").await;
+        manager.devices.write().await.insert(
+            "dlna-idle".to_string(),
+            dlna_device("dlna-idle", "127.0.0.1", port),
+        );
+
+        let result = manager
+            .test_device("dlna-idle", Duration::from_millis(10))
+            .await;
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("did not start playing"));
+        assert_eq!(actions.lock().unwrap().last().unwrap(), "Stop");
+        assert!(manager.get_sessions().await.is_empty());
+
+        let (port, _) = spawn_mock_renderer(500, "PLAYING").await;
+        manager.devices.write().await.insert(
+            "dlna-2".to_string(),
+            dlna_device("dlna-2", "127.0.0.1", port),
+        );
+
+        let result = manager
+            .test_device("dlna-2", Duration::from_millis(10))
+            .await;
+        assert!(!result.success);
+        assert!(result.error.is_some());
+
+        let result = manager
+            .test_device("missing", Duration::from_millis(10))
+            .await;
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn test_infer_cast_content_type() {
+        use rust_cast::channels::media::StreamType::{Buffered, Live};
+
+        let cases = [
+            (
+                "https://cdn.tv/live/master.m3u8",
+                "application/x-mpegurl",
+                Buffered,
+            ),
+            (
+                "https://cdn.tv/dash/manifest.MPD",
+                "application/dash+xml",
+                Buffered,
+            ),
+            (
+                "http://192.168.1.2/Movie.2020.mkv",
+                "video/x-matroska",
+                Buffered,
+            ),
+            ("http://192.168.1.2/clip.webm", "video/webm", Buffered),
+            ("http://iptv.example.com/channel/42.ts", "video/mp2t", Live),
+            ("https://example.com/video.mp4", "video/mp4", Buffered),
+            ("https://example.com/play/12345", "video/mp4", Buffered),
+            (
+                "https://cdn.example.com/hls/index.m3u8?token=abc.mp4&expires=1700000000",
+                "application/x-mpegurl",
+                Buffered,
+            ),
+        ];
+
+        for (url, content_type, stream_type) in cases {
+            assert_eq!(
+                infer_cast_content_type(url),
+                (content_type, stream_type),
+                "infer_cast_content_type({:?})",
+                url
+            );
+        }
+
+        let didl = CastManager::build_didl_metadata("https://example.com/movie.mkv", "Movie");
+        assert!(didl.contains("http-get:*:video/x-matroska:*"));
+    }
+
+    #[test]
+    fn test_parse_position_info() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/">
+  <s:Body>
+    <u:GetPositionInfoResponse xmlns:u="urn:schemas-upnp-org:service:AVTransport:1">
+      <Track>1</Track>
+      <TrackDuration>1:32:10</TrackDuration>
+      <TrackMetaData>&lt;DIDL-Lite&gt;&lt;/DIDL-Lite&gt;</TrackMetaData>
+      <TrackURI>http://192.168.1.2:8765/stream.mp4</TrackURI>
+      <RelTime>00:12:34.500</RelTime>
+      <AbsTime>NOT_IMPLEMENTED</AbsTime>
+      <RelCount>2147483647</RelCount>
+      <AbsCount>2147483647</AbsCount>
+    </u:GetPositionInfoResponse>
+  </s:Body>
+</s:Envelope>"#;
+
+        let (position, duration) = CastManager::parse_position_info(xml).unwrap();
+        assert_eq!(position, Some(754.5));
+        assert_eq!(duration, Some(5530.0));
+
+        let unknown = xml
+            .replace("1:32:10", "NOT_IMPLEMENTED")
+            .replace("00:12:34.500", "");
+        assert_eq!(
+            CastManager::parse_position_info(&unknown).unwrap(),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn test_parse_transport_state() {
+        let xml = |state: &str| {
+            format!(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+  <s:Body>
+    <u:GetTransportInfoResponse xmlns:u="urn:schemas-upnp-org:service:AVTransport:1">
+      <CurrentTransportState>{}</CurrentTransportState>
+      <CurrentTransportStatus>OK</CurrentTransportStatus>
+      <CurrentSpeed>1</CurrentSpeed>
+    </u:GetTransportInfoResponse>
+  </s:Body>
+</s:Envelope>"#,
+                state
+            )
+        };
+
+        let parse = |state: &str| CastManager::parse_transport_state(&xml(state)).ok();
+        assert_eq!(parse("PLAYING"), Some(PlaybackState::Playing));
+        assert_eq!(parse("PAUSED_PLAYBACK"), Some(PlaybackState::Paused));
+        assert_eq!(parse("TRANSITIONING"), Some(PlaybackState::Buffering));
+        assert_eq!(parse("STOPPED"), Some(PlaybackState::Idle));
+        assert_eq!(parse("NO_MEDIA_PRESENT"), Some(PlaybackState::Idle));
+        assert_eq!(parse("WARMING_UP"), None);
+        assert!(CastManager::parse_transport_state("<s:Envelope/>").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_queue_advances_when_item_finishes() {
+        let (port, _) = spawn_mock_renderer(200, "PLAYING").await;
+        let log = Arc::new(EventLog::default());
+        let events = EventBus::new();
+        events.attach(log.clone());
+        let manager = CastManager::new(8765).unwrap().with_events(events);
+        manager.devices.write().await.insert(
+            "dlna-1".to_string(),
+            dlna_device("dlna-1", "127.0.0.1", port),
+        );
+
+        let resolver: QueueResolver = Arc::new(|media_id: String| {
+            Box::pin(async move {
+                Ok((
+                    format!("https://media.example.com/{}.mp4", media_id),
+                    Some(media_id),
+                ))
+            })
+        });
+        let queue = vec!["ep1".to_string(), "ep2".to_string()];
+        let session = manager.cast_queue("dlna-1", queue, resolver).await.unwrap();
+        assert_eq!(session.media_url, "https://media.example.com/ep1.mp4");
+        assert_eq!(session.queue_index, 0);
+
+        // The renderer reports ep1 as finished, so polling loads ep2
+        let session = manager.poll_session(&session.session_id).await.unwrap();
+        assert_eq!(session.media_url, "https://media.example.com/ep2.mp4");
+        assert_eq!(session.title.as_deref(), Some("ep2"));
+        assert_eq!(session.queue_index, 1);
+
+        // The last item finishing leaves the queue where it is
+        let session = manager.poll_session(&session.session_id).await.unwrap();
+        assert_eq!(session.queue_index, 1);
+        assert!(manager.cast_next(&session.session_id).await.is_err());
+
+        let session = manager.cast_previous(&session.session_id).await.unwrap();
+        assert_eq!(session.media_url, "https://media.example.com/ep1.mp4");
+        assert_eq!(session.queue_index, 0);
+        assert!(manager.cast_previous(&session.session_id).await.is_err());
+
+        manager.stop_cast(&session.session_id).await.unwrap();
+        assert!(manager.queue_resolvers.read().await.is_empty());
+
+        // Each item change and the stop reach the frontend
+        let emitted: Vec<_> = log
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(event, payload)| {
+                assert_eq!(event, "cast-state-changed");
+                (
+                    payload["title"].as_str().unwrap().to_string(),
+                    payload["state"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        let expected = [
+            ("ep1", "PLAYING"),
+            ("ep1", "PLAYING"),
+            ("ep2", "PLAYING"),
+            ("ep1", "PLAYING"),
+            ("ep1", "IDLE"),
+        ];
+        assert_eq!(
+            emitted,
+            expected.map(|(title, state)| (title.to_string(), state.to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_controls_reject_unknown_and_unsupported_sessions() {
+        let manager = CastManager::new(8765).unwrap();
+        assert!(manager.pause_cast("missing").await.is_err());
+
+        manager.devices.write().await.insert(
+            "dlna-1".to_string(),
+            dlna_device("dlna-1", "192.168.1.50", 8080),
+        );
+        manager
+            .sessions
+            .write()
+            .await
+            .insert("session-1".to_string(), session("dlna-1"));
+
+        assert!(manager.pause_cast("session-1").await.is_err());
+        assert!(manager
+            .set_cast_volume("session-1", f64::NAN)
+            .await
+            .is_err());
+        assert!(manager.seek_cast("session-1", -1.0).await.is_err());
+
+        let unchanged = manager.get_session_status("session-1").await.unwrap();
+        assert_eq!(unchanged.state, PlaybackState::Playing);
+        assert_eq!(unchanged.position, 12.0);
+    }
+
+    fn googlecast_service(model: &str, ca: Option<&str>) -> mdns_sd::ServiceInfo {
+        let mut properties = vec![("md", model), ("fn", "Living Room")];
+        if let Some(ca) = ca {
+            properties.push(("ca", ca));
+        }
+        mdns_sd::ServiceInfo::new(
+            "_googlecast._tcp.local.",
+            "device-1",
+            "device-1.local.",
+            "192.168.1.40",
+            8009,
+            &properties[..],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_chromecast_capability_from_txt_records() {
+        let video = CastManager::chromecast_device_from_service(&googlecast_service(
+            "Chromecast",
+            Some("4101"),
+        ))
+        .unwrap();
+        assert_eq!(video.id, "chromecast-192-168-1-40");
+        assert_eq!(video.model.as_deref(), Some("Chromecast"));
+        assert_eq!(video.capability, DeviceCapability::Video);
+
+        let speaker = CastManager::chromecast_device_from_service(&googlecast_service(
+            "Google Home",
+            Some("2052"),
+        ))
+        .unwrap();
+        assert_eq!(speaker.capability, DeviceCapability::AudioOnly);
+
+        // Without `ca`, known speaker models are still caught
+        let group = CastManager::chromecast_device_from_service(&googlecast_service(
+            "Google Cast Group",
+            None,
+        ))
+        .unwrap();
+        assert_eq!(group.capability, DeviceCapability::AudioOnly);
+        let unknown =
+            CastManager::chromecast_device_from_service(&googlecast_service("Chromecast", None))
+                .unwrap();
+        assert_eq!(unknown.capability, DeviceCapability::Video);
+    }
+
+    #[tokio::test]
+    async fn test_start_cast_rejects_audio_only_device() {
+        let manager = CastManager::new(8765).unwrap();
+        let mut speaker = dlna_device("speaker", "127.0.0.1", 1);
+        speaker.capability = DeviceCapability::AudioOnly;
+        let mut devices = manager.devices.write().await;
+        devices.insert(speaker.id.clone(), speaker);
+        drop(devices);
+
+        let err = manager
+            .start_cast("speaker", "http://127.0.0.1:8765/stream.mp4", None, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("audio-only"));
+        assert!(manager.sessions.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_local_device_receives_accessible_url() {
+        let manager = CastManager::new(8765).unwrap();
+
+        let local = manager
+            .get_devices()
+            .await
+            .into_iter()
+            .find(|d| d.id == LOCAL_DEVICE_ID)
+            .expect("local pseudo-device should always be listed");
+        assert_eq!(local.protocol, CastProtocol::Local);
+
+        let media_url = "http://127.0.0.1:8080/stream/movie.mp4";
+        let session = manager
+            .start_cast(LOCAL_DEVICE_ID, media_url, Some("Movie".to_string()), None)
+            .await
+            .unwrap();
+        assert_eq!(session.media_url, manager.make_url_accessible(media_url));
+        assert!(session.media_url.ends_with(":8765/stream/movie.mp4"));
+        assert_eq!(session.state, PlaybackState::Playing);
+
+        let paused = manager.pause_cast(&session.session_id).await.unwrap();
+        assert_eq!(paused.state, PlaybackState::Paused);
+
+        manager.stop_cast(&session.session_id).await.unwrap();
+        assert!(manager.get_session_status(&session.session_id).await.is_none());
+    }
+
+    #[test]
+    fn test_wait_until_ready_polls_until_status_is_ready() {
+        let started = std::time::Instant::now();
+        let mut polls = 0;
+        let result = wait_until_ready(Duration::from_secs(2), Duration::from_millis(20), || {
+            polls += 1;
+            if polls == 2 {
+                return Err(anyhow!("connection reset"));
+            }
+            Ok(started.elapsed() >= Duration::from_millis(150))
+        });
+        assert!(result.is_ok());
+        assert!(polls > 2);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        let never = wait_until_ready(
+            Duration::from_millis(100),
+            Duration::from_millis(20),
+            || Ok(false),
+        );
+        assert!(never.unwrap_err().to_string().contains("Not ready"));
+    }
+
+    #[test]
+    fn test_retry_with_backoff() {
+        let mut calls = 0;
+        let result = retry_with_backoff(3, Duration::from_millis(1), "test", || {
+            calls += 1;
+            if calls < 3 {
+                Err(anyhow!("dropped"))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<()> = retry_with_backoff(2, Duration::from_millis(1), "test", || {
+            calls += 1;
+            Err(anyhow!("dropped"))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_local_file_url_made_reachable_for_cast_devices() {
+        let manager = CastManager::new(8765).unwrap();
+        assert_eq!(
+            manager.make_url_accessible("http://127.0.0.1:8765/local/abc-123"),
+            format!("http://{}:8765/local/abc-123", manager.local_ip)
+        );
+        assert_eq!(
+            manager.make_url_accessible("https://cdn.example.com/movie.mp4"),
+            "https://cdn.example.com/movie.mp4"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_file_url_is_served_on_the_lan_address() {
+        let dir =
+            std::env::temp_dir().join(format!("streamgo_cast_serve_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let movie = dir.join("movie.mp4");
+        std::fs::write(&movie, b"0123456789").unwrap();
+
+        let listener = tokio::net::TcpListener::bind("0.0.0.0:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = crate::streaming_server::StreamingServer::new_offline(dir.clone(), port)
+            .await
+            .unwrap();
+        let url = server.register_local_file(movie).await;
+        let serving = server.clone();
+        tokio::spawn(async move { serving.serve(listener).await });
+
+        // The URL the serve_local_file command hands to cast devices
+        let manager = CastManager::new(port).unwrap();
+        let cast_url = manager.make_url_accessible(&url);
+        let response = reqwest::get(&cast_url).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(&response.bytes().await.unwrap()[..], b"0123456789");
+
+        // Torrent controls only answer this machine
+        let local = reqwest::get(format!("http://127.0.0.1:{}/streams", port))
+            .await
+            .unwrap();
+        assert_eq!(local.status(), reqwest::StatusCode::OK);
+        if manager.local_ip != "127.0.0.1" {
+            let remote = reqwest::get(format!("http://{}:{}/streams", manager.local_ip, port))
+                .await
+                .unwrap();
+            assert_eq!(remote.status(), reqwest::StatusCode::FORBIDDEN);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
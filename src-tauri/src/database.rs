@@ -37,13 +37,16 @@ impl Database {
         migration_runner.run_migrations(&conn)?;
 
         let db = Database { conn };
+        if let Err(e) = db.ensure_search_index() {
+            tracing::warn!(error = %e, "Failed to check library search index");
+        }
         Ok(db)
     }
 
     pub fn get_library_items(&self) -> Result<Vec<MediaItem>, anyhow::Error> {
         let mut stmt = self.conn.prepare(
             "SELECT id, title, media_type, year, genre, description, poster_url, backdrop_url, 
-                    rating, duration, added_to_library, watched, progress 
+                    rating, duration, added_to_library, watched, progress, logo_url, thumb_url 
              FROM media_items",
         )?;
 
@@ -83,12 +86,16 @@ impl Database {
                 description: row.get(5)?,
                 poster_url: row.get(6)?,
                 backdrop_url: row.get(7)?,
+                logo_url: row.get(13)?,
+                thumb_url: row.get(14)?,
                 rating: row.get(8)?,
                 duration: row.get(9)?,
                 added_to_library,
                 watched: row.get(11)?,
                 progress: row.get(12)?,
-            })
+                progress_percent: None,
+            }
+            .with_progress_percent())
         })?;
 
         let mut items = Vec::new();
@@ -98,45 +105,176 @@ impl Database {
         Ok(items)
     }
 
+    /// One page of the library plus the total number of items, using the
+    /// same sort options as `search_library_with_filters`
+    pub fn get_library_items_paged(
+        &self,
+        offset: i64,
+        limit: i64,
+        sort: Option<&str>,
+    ) -> Result<(Vec<MediaItem>, i64), anyhow::Error> {
+        let total: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM media_items", [], |row| row.get(0))?;
+
+        // Break ties on id so items never shift between pages
+        let query = format!(
+            "SELECT id, title, media_type, year, genre, description, poster_url, backdrop_url,
+                    rating, duration, added_to_library, watched, progress, logo_url, thumb_url
+             FROM media_items{}, id ASC
+             LIMIT ?1 OFFSET ?2",
+            library_sort_clause(sort)
+        );
+        let stmt = self.conn.prepare(&query)?;
+        let items = self.query_media_items(stmt, params![limit.max(0), offset.max(0)])?;
+
+        Ok((items, total))
+    }
+
     pub fn add_to_library(&self, item: MediaItem) -> Result<(), anyhow::Error> {
-        let genre_str = item.genre.join(",");
-        let media_type_str = match item.media_type {
-            MediaType::Movie => "Movie",
-            MediaType::TvShow => "TvShow",
-            MediaType::Episode => "Episode",
-            MediaType::Documentary => "Documentary",
-            MediaType::LiveTv => "LiveTv",
-            MediaType::Podcast => "Podcast",
-        };
+        insert_media_item(&self.conn, &item)
+    }
 
-        let added_to_library_str = item
-            .added_to_library
-            .map(|dt| dt.to_rfc3339())
-            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    /// Add many media items in a single transaction, updating existing ones
+    /// in place like `add_to_library`. If any item is
+    /// invalid, nothing is written. Returns the number of items added.
+    pub fn add_library_items_batch(&self, items: Vec<MediaItem>) -> Result<usize, anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        for item in &items {
+            insert_media_item(&tx, item)?;
+        }
+        tx.commit()?;
+        Ok(items.len())
+    }
 
-        self.conn.execute(
-            "INSERT OR REPLACE INTO media_items 
-             (id, title, media_type, year, genre, description, poster_url, backdrop_url, 
-              rating, duration, added_to_library, watched, progress)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+    /// Remove a media item from the library, along with every user's
+    /// watchlist/favorites entries for it. Playlist items, episodes and watch
+    /// history go with the row via ON DELETE CASCADE. Returns whether the item
+    /// existed.
+    pub fn delete_media_item(&self, media_id: &str) -> Result<bool, anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM library_items WHERE media_id = ?1",
+            params![media_id],
+        )?;
+        let deleted = tx.execute("DELETE FROM media_items WHERE id = ?1", params![media_id])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    /// Library items with the given ids, in no particular order.
+    /// Unknown ids are skipped.
+    pub fn get_media_items_by_ids(&self, ids: &[String]) -> Result<Vec<MediaItem>, anyhow::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let stmt = self.conn.prepare(&format!(
+            "SELECT id, title, media_type, year, genre, description, poster_url, backdrop_url,
+                    rating, duration, added_to_library, watched, progress, logo_url, thumb_url
+             FROM media_items
+             WHERE id IN ({})",
+            placeholders
+        ))?;
+
+        self.query_media_items(stmt, rusqlite::params_from_iter(ids))
+    }
+
+    /// Library items with the given ids whose artwork wasn't looked up since
+    /// `checked_before`, see `mark_artwork_checked`
+    pub fn get_media_items_due_artwork(
+        &self,
+        ids: &[String],
+        checked_before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<MediaItem>, anyhow::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let stmt = self.conn.prepare(&format!(
+            "SELECT id, title, media_type, year, genre, description, poster_url, backdrop_url,
+                    rating, duration, added_to_library, watched, progress, logo_url, thumb_url
+             FROM media_items
+             WHERE id IN ({})
+               AND (artwork_checked_at IS NULL OR artwork_checked_at < ?)",
+            placeholders
+        ))?;
+
+        let mut params: Vec<rusqlite::types::Value> =
+            ids.iter().map(|id| id.clone().into()).collect();
+        params.push(checked_before.timestamp().into());
+        self.query_media_items(stmt, rusqlite::params_from_iter(params))
+    }
+
+    /// Remember that artwork was looked up for these items at `at`, whether
+    /// or not any was found, so misses aren't retried on every load
+    pub fn mark_artwork_checked(
+        &self,
+        ids: &[String],
+        at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        for id in ids {
+            tx.execute(
+                "UPDATE media_items SET artwork_checked_at = ?1 WHERE id = ?2",
+                params![at.timestamp(), id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Fill in a library item's artwork where it is missing.
+    /// Existing artwork is never overwritten.
+    pub fn fill_missing_artwork(
+        &self,
+        media_id: &str,
+        artwork: &Artwork,
+    ) -> Result<bool, anyhow::Error> {
+        let updated = self.conn.execute(
+            "UPDATE media_items
+             SET poster_url = COALESCE(NULLIF(poster_url, ''), ?2),
+                 backdrop_url = COALESCE(NULLIF(backdrop_url, ''), ?3),
+                 logo_url = COALESCE(NULLIF(logo_url, ''), ?4),
+                 thumb_url = COALESCE(NULLIF(thumb_url, ''), ?5)
+             WHERE id = ?1",
             params![
-                item.id,
-                item.title,
-                media_type_str,
-                item.year,
-                genre_str,
-                item.description,
-                item.poster_url,
-                item.backdrop_url,
-                item.rating,
-                item.duration,
-                added_to_library_str,
-                item.watched,
-                item.progress
+                media_id,
+                artwork.poster_url,
+                artwork.backdrop_url,
+                artwork.logo_url,
+                artwork.thumb_url
             ],
         )?;
+        Ok(updated > 0)
+    }
+
+    /// Library items with no known duration
+    pub fn get_items_missing_duration(&self) -> Result<Vec<MediaItem>, anyhow::Error> {
+        let stmt = self.conn.prepare(
+            "SELECT id, title, media_type, year, genre, description, poster_url, backdrop_url,
+                    rating, duration, added_to_library, watched, progress, logo_url, thumb_url
+             FROM media_items
+             WHERE duration IS NULL OR duration <= 0",
+        )?;
 
-        Ok(())
+        self.query_media_items(stmt, [])
+    }
+
+    /// Set an item's duration (minutes) unless it already has one
+    pub fn fill_missing_duration(
+        &self,
+        media_id: &str,
+        duration: i32,
+    ) -> Result<bool, anyhow::Error> {
+        let updated = self.conn.execute(
+            "UPDATE media_items SET duration = ?2
+             WHERE id = ?1 AND (duration IS NULL OR duration <= 0)",
+            params![media_id, duration],
+        )?;
+        Ok(updated > 0)
     }
 
     pub fn get_user_profile(&self, user_id: &str) -> Result<Option<UserProfile>, anyhow::Error> {
@@ -184,6 +322,57 @@ impl Database {
         Ok(())
     }
 
+    /// All user profiles, by username
+    pub fn list_user_profiles(&self) -> Result<Vec<UserProfile>, anyhow::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, username, email, preferences FROM user_profiles
+             ORDER BY username COLLATE NOCASE ASC",
+        )?;
+
+        let profiles = stmt
+            .query_map([], |row| {
+                let preferences_json: String = row.get(3)?;
+                let preferences: UserPreferences =
+                    serde_json::from_str(&preferences_json).unwrap_or_default();
+
+                Ok(UserProfile {
+                    id: row.get(0)?,
+                    username: row.get(1)?,
+                    email: row.get(2)?,
+                    preferences,
+                    library_items: Vec::new(),
+                    watchlist: Vec::new(),
+                    favorites: Vec::new(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(profiles)
+    }
+
+    /// Delete a profile along with its watchlist, favorites, playlists and
+    /// favorite channels. Returns whether the profile existed.
+    pub fn delete_user_profile(&self, user_id: &str) -> Result<bool, anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM playlist_items
+             WHERE playlist_id IN (SELECT id FROM playlists WHERE user_id = ?1)",
+            params![user_id],
+        )?;
+        tx.execute("DELETE FROM playlists WHERE user_id = ?1", params![user_id])?;
+        tx.execute(
+            "DELETE FROM library_items WHERE user_id = ?1",
+            params![user_id],
+        )?;
+        tx.execute(
+            "DELETE FROM favorite_channels WHERE user_id = ?1",
+            params![user_id],
+        )?;
+        let deleted = tx.execute("DELETE FROM user_profiles WHERE id = ?1", params![user_id])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
     pub fn get_addons(&self) -> Result<Vec<Addon>, anyhow::Error> {
         // Only return addons with a valid HTTP(S) URL; this avoids legacy rows with missing/placeholder URLs
         let mut stmt = self.conn.prepare(
@@ -222,6 +411,7 @@ impl Database {
                 addon_type,
                 manifest,
                 priority: row.get(9).unwrap_or(0),
+                config: Default::default(),
             })
         })?;
 
@@ -229,6 +419,13 @@ impl Database {
         for a in addon_iter.flatten() {
             addons.push(a);
         }
+
+        let mut configs = self.get_addon_configs()?;
+        for addon in &mut addons {
+            if let Some(config) = configs.remove(&addon.id) {
+                addon.config = config;
+            }
+        }
         Ok(addons)
     }
 
@@ -243,10 +440,23 @@ impl Database {
         let manifest_json = serde_json::to_string(&addon.manifest)?;
         let installed_at_str = chrono::Utc::now().to_rfc3339();
 
+        // Upsert rather than INSERT OR REPLACE: replacing deletes the row,
+        // which cascades to the addon's config
         self.conn.execute(
-            "INSERT OR REPLACE INTO addons 
+            "INSERT INTO addons 
              (id, name, version, description, author, url, enabled, addon_type, manifest, installed_at, priority)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                version = excluded.version,
+                description = excluded.description,
+                author = excluded.author,
+                url = excluded.url,
+                enabled = excluded.enabled,
+                addon_type = excluded.addon_type,
+                manifest = excluded.manifest,
+                installed_at = excluded.installed_at,
+                priority = excluded.priority",
             params![
                 addon.id,
                 addon.name,
@@ -265,12 +475,307 @@ impl Database {
         Ok(())
     }
 
+    /// Save a newly fetched addon unless its manifest id is already installed
+    /// from a different URL. On such a conflict nothing is written unless
+    /// `replace_existing` is set, in which case the existing entry moves to
+    /// the new URL and keeps its enabled state and priority.
+    pub fn install_addon(
+        &self,
+        addon: &Addon,
+        replace_existing: bool,
+    ) -> Result<AddonInstallResult, anyhow::Error> {
+        let existing = self
+            .get_addons()?
+            .into_iter()
+            .find(|a| a.id == addon.id || a.manifest.id == addon.manifest.id);
+
+        let mut addon = addon.clone();
+        if let Some(existing) = existing {
+            if normalize_addon_url(&existing.url) != normalize_addon_url(&addon.url) {
+                let conflict = AddonConflict {
+                    addon_id: existing.id.clone(),
+                    existing_url: existing.url.clone(),
+                    new_url: addon.url.clone(),
+                };
+
+                if !replace_existing {
+                    tracing::warn!(
+                        addon_id = %conflict.addon_id,
+                        existing_url = %conflict.existing_url,
+                        new_url = %conflict.new_url,
+                        "Addon already installed from a different URL"
+                    );
+                    return Ok(AddonInstallResult {
+                        addon_id: existing.id,
+                        installed: false,
+                        conflict: Some(conflict),
+                    });
+                }
+
+                addon.id = existing.id;
+                addon.enabled = existing.enabled;
+                addon.priority = existing.priority;
+            }
+        }
+
+        self.save_addon(&addon)?;
+        Ok(AddonInstallResult {
+            addon_id: addon.id,
+            installed: true,
+            conflict: None,
+        })
+    }
+
+    /// Enable or disable several addons in one transaction.
+    /// Returns how many addons were updated; unknown ids are ignored.
+    pub fn set_addons_enabled(
+        &self,
+        addon_ids: &[String],
+        enabled: bool,
+    ) -> Result<usize, anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut updated = 0;
+        {
+            let mut stmt = tx.prepare("UPDATE addons SET enabled = ?1 WHERE id = ?2")?;
+            for addon_id in addon_ids {
+                updated += stmt.execute(params![enabled, addon_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Enable or disable every installed addon
+    pub fn set_all_addons_enabled(&self, enabled: bool) -> Result<usize, anyhow::Error> {
+        let updated = self
+            .conn
+            .execute("UPDATE addons SET enabled = ?1", params![enabled])?;
+        Ok(updated)
+    }
+
+    /// Set one addon's priority. Returns how many addons were updated.
+    pub fn update_addon_priority(
+        &self,
+        addon_id: &str,
+        priority: i32,
+    ) -> Result<usize, anyhow::Error> {
+        let updated = self.conn.execute(
+            "UPDATE addons SET priority = ?1 WHERE id = ?2",
+            params![priority, addon_id],
+        )?;
+        Ok(updated)
+    }
+
+    /// Reorder addons so the first id gets the highest priority. Every
+    /// installed addon must be listed exactly once, otherwise nothing is
+    /// changed.
+    pub fn reorder_addons(&self, ordered_ids: &[String]) -> Result<(), anyhow::Error> {
+        let mut seen = std::collections::HashSet::new();
+        if let Some(duplicate) = ordered_ids.iter().find(|id| !seen.insert(id.as_str())) {
+            return Err(anyhow::anyhow!("Addon listed twice: {}", duplicate));
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let installed: i64 = tx.query_row("SELECT COUNT(*) FROM addons", [], |row| row.get(0))?;
+        {
+            let mut stmt = tx.prepare("UPDATE addons SET priority = ?1 WHERE id = ?2")?;
+            for (position, addon_id) in ordered_ids.iter().enumerate() {
+                let priority = (ordered_ids.len() - 1 - position) as i32;
+                if stmt.execute(params![priority, addon_id])? == 0 {
+                    return Err(anyhow::anyhow!("Addon not found: {}", addon_id));
+                }
+            }
+        }
+        // All listed ids are distinct and installed, so only a short list is left
+        if ordered_ids.len() as i64 != installed {
+            return Err(anyhow::anyhow!(
+                "Reorder lists {} of {} installed addons",
+                ordered_ids.len(),
+                installed
+            ));
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     pub fn delete_addon(&self, addon_id: &str) -> Result<(), anyhow::Error> {
         self.conn
             .execute("DELETE FROM addons WHERE id = ?1", params![addon_id])?;
         Ok(())
     }
 
+    // Addon configuration methods
+
+    pub fn set_addon_config(
+        &self,
+        addon_id: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO addon_config (addon_id, config_key, config_value, updated_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![addon_id, key, value, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_addon_config(
+        &self,
+        addon_id: &str,
+        key: &str,
+    ) -> Result<Option<String>, anyhow::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT config_value FROM addon_config WHERE addon_id = ?1 AND config_key = ?2",
+        )?;
+        let mut rows = stmt.query(params![addon_id, key])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_all_addon_config(
+        &self,
+        addon_id: &str,
+    ) -> Result<std::collections::HashMap<String, String>, anyhow::Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT config_key, config_value FROM addon_config WHERE addon_id = ?1")?;
+        let config = stmt
+            .query_map(params![addon_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(config)
+    }
+
+    pub fn delete_addon_config(&self, addon_id: &str, key: &str) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "DELETE FROM addon_config WHERE addon_id = ?1 AND config_key = ?2",
+            params![addon_id, key],
+        )?;
+        Ok(())
+    }
+
+    /// Store app state that isn't a user preference (open tab, window
+    /// position, onboarding flags). `value` must be a JSON document.
+    pub fn set_app_setting(&self, key: &str, value: &str) -> Result<(), anyhow::Error> {
+        serde_json::from_str::<serde_json::Value>(value)
+            .map_err(|e| anyhow::anyhow!("App setting {} is not valid JSON: {}", key, e))?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value, updated_at) VALUES (?1, ?2, ?3)",
+            params![key, value, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_app_setting(&self, key: &str) -> Result<Option<String>, anyhow::Error> {
+        let result = self.conn.query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn get_all_app_settings(
+        &self,
+    ) -> Result<std::collections::HashMap<String, String>, anyhow::Error> {
+        let mut stmt = self.conn.prepare("SELECT key, value FROM app_settings")?;
+        let settings = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(settings)
+    }
+
+    /// Configuration of every addon, keyed by addon id
+    fn get_addon_configs(
+        &self,
+    ) -> Result<
+        std::collections::HashMap<String, std::collections::HashMap<String, String>>,
+        anyhow::Error,
+    > {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT addon_id, config_key, config_value FROM addon_config")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        let mut configs = std::collections::HashMap::new();
+        for row in rows {
+            let (addon_id, key, value) = row?;
+            configs
+                .entry(addon_id)
+                .or_insert_with(std::collections::HashMap::new)
+                .insert(key, value);
+        }
+        Ok(configs)
+    }
+
+    /// Whether built-in addons should be seeded: this is a genuine first run
+    /// (never seeded before, no addons installed) and the user has not opted out.
+    pub fn should_seed_builtin_addons(&self, user_id: &str) -> Result<bool, anyhow::Error> {
+        let preferences = self
+            .get_user_profile(user_id)?
+            .map(|profile| profile.preferences)
+            .unwrap_or_default();
+
+        if preferences.builtins_seeded || !preferences.install_builtin_addons {
+            return Ok(false);
+        }
+
+        Ok(self.get_addons()?.is_empty())
+    }
+
+    /// Save the given built-in addons if seeding is needed.
+    /// Returns the addons that were seeded (empty when nothing was written).
+    pub fn seed_builtin_addons(
+        &self,
+        user_id: &str,
+        builtin: &[Addon],
+    ) -> Result<Vec<Addon>, anyhow::Error> {
+        if !self.should_seed_builtin_addons(user_id)? {
+            return Ok(Vec::new());
+        }
+
+        for addon in builtin {
+            self.save_addon(addon)?;
+        }
+        self.mark_builtins_seeded(user_id)?;
+
+        Ok(builtin.to_vec())
+    }
+
+    /// Record that built-in seeding has happened, so built-ins the user
+    /// uninstalls are never silently re-added.
+    pub fn mark_builtins_seeded(&self, user_id: &str) -> Result<(), anyhow::Error> {
+        let mut profile = self.get_user_profile(user_id)?.unwrap_or_else(|| UserProfile {
+            id: user_id.to_string(),
+            username: "User".to_string(),
+            email: None,
+            preferences: UserPreferences::default(),
+            library_items: Vec::new(),
+            watchlist: Vec::new(),
+            favorites: Vec::new(),
+        });
+
+        if profile.preferences.builtins_seeded {
+            return Ok(());
+        }
+
+        profile.preferences.builtins_seeded = true;
+        self.save_user_profile(&profile)
+    }
+
     // Watchlist methods
     pub fn add_to_watchlist(&self, user_id: &str, media_id: &str) -> Result<(), anyhow::Error> {
         let now = chrono::Utc::now().to_rfc3339();
@@ -298,7 +803,7 @@ impl Database {
         let stmt = self.conn.prepare(
             "SELECT m.id, m.title, m.media_type, m.year, m.genre, m.description, 
                     m.poster_url, m.backdrop_url, m.rating, m.duration, 
-                    m.added_to_library, m.watched, m.progress
+                    m.added_to_library, m.watched, m.progress, m.logo_url, m.thumb_url
              FROM media_items m
              INNER JOIN library_items li ON m.id = li.media_id
              WHERE li.user_id = ?1 AND li.list_type = 'watchlist'
@@ -335,7 +840,7 @@ impl Database {
         let stmt = self.conn.prepare(
             "SELECT m.id, m.title, m.media_type, m.year, m.genre, m.description, 
                     m.poster_url, m.backdrop_url, m.rating, m.duration, 
-                    m.added_to_library, m.watched, m.progress
+                    m.added_to_library, m.watched, m.progress, m.logo_url, m.thumb_url
              FROM media_items m
              INNER JOIN library_items li ON m.id = li.media_id
              WHERE li.user_id = ?1 AND li.list_type = 'favorites'
@@ -363,17 +868,157 @@ impl Database {
         let stmt = self.conn.prepare(
             "SELECT m.id, m.title, m.media_type, m.year, m.genre, m.description, 
                     m.poster_url, m.backdrop_url, m.rating, m.duration, 
-                    m.added_to_library, m.watched, m.progress
+                    m.added_to_library, m.watched, m.progress, m.logo_url, m.thumb_url
              FROM media_items m
              INNER JOIN library_items li ON m.id = li.media_id
              WHERE li.user_id = ?1 AND m.progress > 0 AND m.watched = 0
-             ORDER BY m.added_to_library DESC
+             ORDER BY (SELECT MAX(h.watched_at) FROM watch_history h WHERE h.media_id = m.id) DESC,
+                      m.added_to_library DESC
              LIMIT 20",
         )?;
 
         self.query_media_items(stmt, params![user_id])
     }
 
+    /// Log that `media_id` was played up to `position` seconds at
+    /// `watched_at`. Progress saved within `WATCH_SESSION_GAP_SECS` of the
+    /// item's latest entry updates that entry, so one viewing is one entry.
+    pub fn record_watch_event(
+        &self,
+        media_id: &str,
+        position: i32,
+        duration: Option<i32>,
+        watched_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), anyhow::Error> {
+        let watched_at = watched_at.timestamp();
+        let extended = self.conn.execute(
+            "UPDATE watch_history
+             SET watched_at = ?2, position = ?3, duration = COALESCE(?4, duration)
+             WHERE id = (SELECT id FROM watch_history WHERE media_id = ?1
+                         ORDER BY watched_at DESC, id DESC LIMIT 1)
+               AND watched_at BETWEEN ?2 - ?5 AND ?2",
+            params![
+                media_id,
+                watched_at,
+                position,
+                duration,
+                WATCH_SESSION_GAP_SECS
+            ],
+        )?;
+        if extended == 0 {
+            self.conn.execute(
+                "INSERT INTO watch_history (media_id, watched_at, position, duration)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![media_id, watched_at, position, duration],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Apply watch history exported from another app. Each entry is matched
+    /// to a library item by `external_id`, then by title and year; an entry
+    /// whose id isn't in the library adds a new item. Matched items are
+    /// marked watched, or get the entry's position, and a watch event at the
+    /// entry's date unless the item already has one then, so importing the
+    /// same file twice doesn't double the history. Entries matching nothing
+    /// and without an `external_id` can't be added, as there is no id to
+    /// store them under; they are reported back as unmatched.
+    pub fn import_watch_history(
+        &self,
+        entries: &[ImportedHistory],
+    ) -> Result<WatchHistoryImport, anyhow::Error> {
+        let mut library = self.get_library_items()?;
+        let mut report = WatchHistoryImport::default();
+        let now = chrono::Utc::now();
+
+        let tx = self.conn.unchecked_transaction()?;
+        for entry in entries {
+            let index = match find_imported_item(entry, &library) {
+                Some(index) => {
+                    report.matched += 1;
+                    index
+                }
+                None => match new_imported_item(entry) {
+                    Some(item) => {
+                        insert_media_item(&tx, &item)?;
+                        library.push(item);
+                        report.created += 1;
+                        library.len() - 1
+                    }
+                    None => {
+                        report.unmatched += 1;
+                        report.unmatched_titles.push(entry.title.clone());
+                        continue;
+                    }
+                },
+            };
+
+            let item = &mut library[index];
+            item.watched |= entry.watched;
+            item.progress = entry.position.or(item.progress);
+            tx.execute(
+                "UPDATE media_items SET progress = ?1, watched = ?2 WHERE id = ?3",
+                params![item.progress.unwrap_or(0), item.watched, item.id],
+            )?;
+
+            let duration = item.duration.map(|minutes| minutes * 60);
+            let position = entry
+                .position
+                .or(if entry.watched { duration } else { None })
+                .unwrap_or(0);
+            let watched_at = entry.watched_at.unwrap_or(now).timestamp();
+            let already_recorded: bool = tx.query_row(
+                "SELECT EXISTS(SELECT 1 FROM watch_history WHERE media_id = ?1 AND watched_at = ?2)",
+                params![item.id, watched_at],
+                |row| row.get(0),
+            )?;
+            if already_recorded {
+                report.duplicates += 1;
+                continue;
+            }
+            tx.execute(
+                "INSERT INTO watch_history (media_id, watched_at, position, duration)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![item.id, watched_at, position, duration],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(report)
+    }
+
+    /// Most recent watch events first
+    pub fn get_recent_watch_history(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<crate::models::WatchHistoryEntry>, anyhow::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT h.id, h.media_id, m.title, m.poster_url, h.watched_at, h.position, h.duration
+             FROM watch_history h
+             INNER JOIN media_items m ON m.id = h.media_id
+             ORDER BY h.watched_at DESC, h.id DESC
+             LIMIT ?1",
+        )?;
+
+        let entries = stmt
+            .query_map(params![limit as i64], |row| {
+                let watched_at: i64 = row.get(4)?;
+                Ok(crate::models::WatchHistoryEntry {
+                    id: row.get(0)?,
+                    media_id: row.get(1)?,
+                    title: row.get(2)?,
+                    poster_url: row.get(3)?,
+                    watched_at: chrono::DateTime::from_timestamp(watched_at, 0)
+                        .unwrap_or_default(),
+                    position: row.get(5)?,
+                    duration: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
     // Playlist methods
     pub fn create_playlist(
         &self,
@@ -554,7 +1199,7 @@ impl Database {
         let stmt = self.conn.prepare(
             "SELECT m.id, m.title, m.media_type, m.year, m.genre, m.description, 
                     m.poster_url, m.backdrop_url, m.rating, m.duration, 
-                    m.added_to_library, m.watched, m.progress
+                    m.added_to_library, m.watched, m.progress, m.logo_url, m.thumb_url
              FROM media_items m
              INNER JOIN playlist_items pi ON m.id = pi.media_id
              WHERE pi.playlist_id = ?1
@@ -592,40 +1237,48 @@ impl Database {
         &self,
         filters: &crate::models::SearchFilters,
     ) -> Result<Vec<MediaItem>, anyhow::Error> {
-        let use_fts = filters.query.as_ref().map_or(false, |q| !q.is_empty());
-        
+        // Fast path: a plain text search needs no filter SQL
+        let text_only = filters.genres.is_empty()
+            && filters.media_types.is_empty()
+            && filters.year_min.is_none()
+            && filters.year_max.is_none()
+            && filters.rating_min.is_none()
+            && filters.watched.is_none()
+            && matches!(filters.sort_by.as_deref(), None | Some("relevance"));
+        if let (true, Some(q)) = (text_only, filters.query.as_deref()) {
+            if !q.trim().is_empty() {
+                return self.search_library_fts(q, usize::MAX);
+            }
+        }
+
+        let fts_query = filters.query.as_deref().and_then(escape_fts_query);
+        let use_fts = fts_query.is_some();
+
         let mut query = if use_fts {
-            // Use FTS5 for full-text search with BM25 ranking
+            // Use FTS5 for full-text search with BM25 ranking. The join is
+            // wrapped so the filters below don't hit the FTS table's
+            // identically named columns.
             String::from(
-                "SELECT m.id, m.title, m.media_type, m.year, m.genre, m.description, m.poster_url, m.backdrop_url, 
-                        m.rating, m.duration, m.added_to_library, m.watched, m.progress, fts.rank 
-                 FROM media_items m
-                 INNER JOIN media_items_fts fts ON m.rowid = fts.rowid
-                 WHERE fts.media_items_fts MATCH ?1",
+                "SELECT * FROM (
+                    SELECT m.id, m.title, m.media_type, m.year, m.genre, m.description, m.poster_url, m.backdrop_url, 
+                           m.rating, m.duration, m.added_to_library, m.watched, m.progress, m.logo_url, m.thumb_url, fts.rank AS rank
+                    FROM media_items m
+                    INNER JOIN media_items_fts fts ON m.rowid = fts.rowid
+                    WHERE media_items_fts MATCH ?1
+                 ) WHERE 1=1",
             )
         } else {
             String::from(
                 "SELECT id, title, media_type, year, genre, description, poster_url, backdrop_url, 
-                        rating, duration, added_to_library, watched, progress, 0 as rank 
+                        rating, duration, added_to_library, watched, progress, logo_url, thumb_url, 0 as rank 
                  FROM media_items WHERE 1=1",
             )
         };
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
-        // Text search
-        if let Some(q) = &filters.query {
-            if !q.is_empty() {
-                if use_fts {
-                    // FTS5 query syntax - escape special characters and build query
-                    // For simple queries, search in all fields
-                    let fts_query = format!("{{{}}}", q); // {} searches across all columns
-                    params.push(Box::new(fts_query));
-                } else {
-                    // Fallback LIKE search (shouldn't reach here)
-                    query.push_str(" AND (title LIKE ?1 OR description LIKE ?1)");
-                    params.push(Box::new(format!("%{}%", q)));
-                }
-            }
+        // Text search (quoted terms, so user input can't break the FTS syntax)
+        if let Some(fts_query) = fts_query {
+            params.push(Box::new(fts_query));
         }
 
         // Genre filter
@@ -688,14 +1341,8 @@ impl Database {
             " ORDER BY rank"
         } else {
             match filters.sort_by.as_deref() {
-                Some("title_asc") => " ORDER BY title ASC",
-                Some("title_desc") => " ORDER BY title DESC",
-                Some("year_asc") => " ORDER BY year ASC",
-                Some("year_desc") => " ORDER BY year DESC",
-                Some("rating_desc") => " ORDER BY rating DESC",
-                Some("added_desc") => " ORDER BY added_to_library DESC",
                 Some("relevance") => " ORDER BY rank", // Explicit relevance sort
-                _ => " ORDER BY added_to_library DESC", // Default for non-FTS
+                sort_by => library_sort_clause(sort_by),
             }
         };
         query.push_str(sort_clause);
@@ -706,49 +1353,7 @@ impl Database {
             .map(|p| p.as_ref() as &dyn rusqlite::ToSql)
             .collect();
 
-        let media_iter = stmt.query_map(params_refs.as_slice(), |row| {
-            let genre_str: String = row.get(4)?;
-            let genres: Vec<String> = if genre_str.is_empty() {
-                Vec::new()
-            } else {
-                genre_str.split(',').map(|s| s.to_string()).collect()
-            };
-
-            let media_type_str: String = row.get(2)?;
-            let media_type = match media_type_str.as_str() {
-                "Movie" => MediaType::Movie,
-                "TvShow" => MediaType::TvShow,
-                "Episode" => MediaType::Episode,
-                "Documentary" => MediaType::Documentary,
-                "LiveTv" => MediaType::LiveTv,
-                "Podcast" => MediaType::Podcast,
-                _ => MediaType::Movie,
-            };
-
-            let added_to_library = if let Ok(date_str) = row.get::<_, String>(10) {
-                chrono::DateTime::parse_from_rfc3339(&date_str)
-                    .ok()
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-            } else {
-                None
-            };
-
-            Ok(MediaItem {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                media_type,
-                year: row.get(3)?,
-                genre: genres,
-                description: row.get(5)?,
-                poster_url: row.get(6)?,
-                backdrop_url: row.get(7)?,
-                rating: row.get(8)?,
-                duration: row.get(9)?,
-                added_to_library,
-                watched: row.get(11)?,
-                progress: row.get(12)?,
-            })
-        })?;
+        let media_iter = stmt.query_map(params_refs.as_slice(), media_item_from_row)?;
 
         let mut items = Vec::new();
         for item in media_iter {
@@ -756,56 +1361,263 @@ impl Database {
         }
         Ok(items)
     }
-    // Ratings and skip segments
-    pub fn upsert_addon_rating(&self, user_id: &str, addon_id: &str, rating: i32) -> Result<crate::models::AddonRatingSummary, anyhow::Error> {
-        if rating < 1 || rating > 5 {
-            return Err(anyhow!("Rating must be between 1 and 5"));
-        }
-        let now = chrono::Utc::now().to_rfc3339();
-        self.conn.execute(
-            "INSERT OR REPLACE INTO addon_ratings (addon_id, user_id, rating, rated_at) VALUES (?1, ?2, ?3, ?4)",
-            params![addon_id, user_id, rating, now],
-        )?;
-        // Recompute summary
-        self.recompute_addon_rating_summary(addon_id)?;
-        self.get_addon_rating_summary(addon_id).map(|opt| opt.unwrap_or(crate::models::AddonRatingSummary{ addon_id: addon_id.to_string(), rating_avg: 0.0, rating_count: 0, weighted_rating: 0.0 }))
-    }
-
-    fn recompute_addon_rating_summary(&self, addon_id: &str) -> Result<(), anyhow::Error> {
-        // Compute average and count
-        let mut stmt = self.conn.prepare("SELECT COUNT(*), AVG(rating) FROM addon_ratings WHERE addon_id = ?1")?;
-        let (count, avg):(i64, Option<f64>) = stmt.query_row(params![addon_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
-        let avg_val = avg.unwrap_or(0.0);
-        // Bayesian weighted rating: prior m=5 votes at C=3.7
-        let m = 5.0;
-        let c = 3.7;
-        let v = count as f64;
-        let r = avg_val;
-        let weighted = if v > 0.0 { (v/(v+m))*r + (m/(v+m))*c } else { 0.0 };
-        let now = chrono::Utc::now().to_rfc3339();
-        self.conn.execute(
-            "INSERT OR REPLACE INTO addon_rating_summary (addon_id, rating_avg, rating_count, weighted_rating, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![addon_id, avg_val, count, weighted, now],
+
+    /// Clear and repopulate the library full-text index from `media_items`.
+    /// Returns the number of indexed items.
+    pub fn rebuild_search_index(&self) -> Result<usize, anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO media_items_fts(media_items_fts) VALUES('delete-all')",
+            [],
+        )?;
+        let indexed = tx.execute(
+            "INSERT INTO media_items_fts(rowid, title, description, genre)
+             SELECT rowid, title, COALESCE(description, ''), genre
+             FROM media_items",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(indexed)
+    }
+
+    /// Rebuild the search index if it doesn't cover every library item.
+    /// Returns whether a rebuild was needed.
+    pub fn ensure_search_index(&self) -> Result<bool, anyhow::Error> {
+        let count = |sql: &str| -> Result<i64, rusqlite::Error> {
+            self.conn.query_row(sql, [], |row| row.get(0))
+        };
+        let items = count("SELECT COUNT(*) FROM media_items")?;
+        // media_items_fts reads its rows from media_items, so count the
+        // docsize shadow table, which has one row per indexed item
+        let indexed = count("SELECT COUNT(*) FROM media_items_fts_docsize")?;
+        if items == indexed {
+            return Ok(false);
+        }
+
+        tracing::warn!(
+            items,
+            indexed,
+            "Search index out of sync with library; rebuilding"
+        );
+        self.rebuild_search_index()?;
+        Ok(true)
+    }
+
+    /// Full-text search over title, description and genre, best matches
+    /// first (BM25). The query may use FTS5 syntax (`mat*`, `"exact phrase"`);
+    /// if it doesn't parse, every word is matched with LIKE instead.
+    pub fn search_library_fts(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<MediaItem>, anyhow::Error> {
+        // SQLite treats a negative LIMIT as no limit
+        let limit = i64::try_from(limit).unwrap_or(-1);
+        let stmt = self.conn.prepare(
+            "SELECT m.id, m.title, m.media_type, m.year, m.genre, m.description, m.poster_url,
+                    m.backdrop_url, m.rating, m.duration, m.added_to_library, m.watched, m.progress, m.logo_url, m.thumb_url
+             FROM media_items_fts
+             INNER JOIN media_items m ON m.rowid = media_items_fts.rowid
+             WHERE media_items_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2",
+        )?;
+
+        // The statement itself is fixed, so a failure here means the user's
+        // query isn't valid FTS5 (e.g. `a AND`, unbalanced quotes)
+        self.query_media_items(stmt, params![query.trim(), limit])
+            .or_else(|e| {
+                tracing::debug!(
+                    error = %e,
+                    query = %query,
+                    "Invalid FTS query; falling back to LIKE"
+                );
+                self.search_library_like(query, limit)
+            })
+    }
+
+    /// Substring search requiring every word in the title or description
+    fn search_library_like(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<MediaItem>, anyhow::Error> {
+        let words: Vec<String> = query
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c: char| "\"*()".contains(c)))
+            .filter(|word| !word.is_empty())
+            .map(|word| format!("%{}%", word))
+            .collect();
+
+        let mut sql = String::from(
+            "SELECT id, title, media_type, year, genre, description, poster_url, backdrop_url,
+                    rating, duration, added_to_library, watched, progress, logo_url, thumb_url
+             FROM media_items WHERE 1=1",
+        );
+        for i in 0..words.len() {
+            sql.push_str(&format!(
+                " AND (title LIKE ?{n} OR description LIKE ?{n})",
+                n = i + 1
+            ));
+        }
+        sql.push_str(&format!(" ORDER BY title ASC LIMIT {}", limit));
+
+        let stmt = self.conn.prepare(&sql)?;
+        self.query_media_items(stmt, rusqlite::params_from_iter(words))
+    }
+
+    /// Full-text search returning the best `limit` matches with highlighted
+    /// title and a short description excerpt around the matched terms
+    pub fn search_library_highlighted(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>, anyhow::Error> {
+        let Some(fts_query) = escape_fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT m.id, m.title, m.media_type, m.year, m.genre, m.description, m.poster_url,
+                    m.backdrop_url, m.rating, m.duration, m.added_to_library, m.watched, m.progress, m.logo_url, m.thumb_url,
+                    highlight(media_items_fts, 0, ?2, ?3),
+                    snippet(media_items_fts, 1, ?2, ?3, '…', 16)
+             FROM media_items_fts
+             INNER JOIN media_items m ON m.rowid = media_items_fts.rowid
+             WHERE media_items_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?4",
+        )?;
+
+        let hits = stmt
+            .query_map(
+                params![fts_query, HIGHLIGHT_OPEN, HIGHLIGHT_CLOSE, limit as i64],
+                |row| {
+                    let item = media_item_from_row(row)?;
+                    let title_snippet: Option<String> = row.get(15)?;
+                    let description_snippet: Option<String> = row.get(16)?;
+                    let title_snippet = title_snippet.unwrap_or_else(|| item.title.clone());
+                    Ok(SearchHit {
+                        title_snippet: mark_highlights(&title_snippet),
+                        description_snippet: description_snippet
+                            .filter(|s| !s.is_empty())
+                            .map(|s| mark_highlights(&s)),
+                        item,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(hits)
+    }
+
+    // Ratings and skip segments
+
+    /// Store a user's 1-5 rating for an addon, replacing their earlier one,
+    /// and return the addon's updated summary
+    pub fn rate_addon(
+        &self,
+        addon_id: &str,
+        user_id: &str,
+        rating: i32,
+    ) -> Result<crate::models::AddonRatingSummary, anyhow::Error> {
+        if !(1..=5).contains(&rating) {
+            return Err(anyhow!("Rating must be between 1 and 5"));
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO addon_ratings (addon_id, user_id, rating, rated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(addon_id, user_id) DO UPDATE SET
+                rating = excluded.rating,
+                rated_at = excluded.rated_at",
+            params![addon_id, user_id, rating, chrono::Utc::now().to_rfc3339()],
+        )?;
+        // Every vote moves the global mean the weighted ratings lean on
+        self.recompute_addon_rating_summary(ADDON_RATING_PRIOR_VOTES)?;
+        tx.commit()?;
+
+        self.get_addon_rating(addon_id)?
+            .ok_or_else(|| anyhow!("No rating summary for addon {}", addon_id))
+    }
+
+    /// Rebuild every addon's rating summary. The weighted rating is the
+    /// IMDb-style Bayesian average `(v*R + m*C) / (v + m)`: `v` votes
+    /// averaging `R`, pulled toward the mean `C` of all ratings as if the
+    /// addon had `min_votes` extra votes at `C`.
+    fn recompute_addon_rating_summary(&self, min_votes: f64) -> Result<(), anyhow::Error> {
+        let mut mean_stmt = self.conn.prepare("SELECT AVG(rating) FROM addon_ratings")?;
+        let global_mean: Option<f64> = mean_stmt.query_row([], |row| row.get(0))?;
+        let Some(global_mean) = global_mean else {
+            return Ok(());
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT addon_id, COUNT(*), AVG(rating) FROM addon_ratings GROUP BY addon_id",
         )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, f64>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let now = chrono::Utc::now().to_rfc3339();
+        for (addon_id, count, avg) in rows {
+            let votes = count as f64;
+            let weighted = (votes * avg + min_votes * global_mean) / (votes + min_votes);
+            self.conn.execute(
+                "INSERT OR REPLACE INTO addon_rating_summary
+                 (addon_id, rating_avg, rating_count, weighted_rating, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![addon_id, avg, count, weighted, now],
+            )?;
+        }
         Ok(())
     }
 
-    pub fn get_addon_rating_summary(&self, addon_id: &str) -> Result<Option<crate::models::AddonRatingSummary>, anyhow::Error> {
+    pub fn get_addon_rating(
+        &self,
+        addon_id: &str,
+    ) -> Result<Option<crate::models::AddonRatingSummary>, anyhow::Error> {
+        let result = self.conn.query_row(
+            "SELECT addon_id, rating_avg, rating_count, weighted_rating
+             FROM addon_rating_summary WHERE addon_id = ?1",
+            params![addon_id],
+            addon_rating_from_row,
+        );
+
+        match result {
+            Ok(summary) => Ok(Some(summary)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Summaries for every rated addon, best weighted rating first
+    pub fn get_addon_ratings(
+        &self,
+    ) -> Result<Vec<crate::models::AddonRatingSummary>, anyhow::Error> {
         let mut stmt = self.conn.prepare(
-            "SELECT addon_id, rating_avg, rating_count, weighted_rating FROM addon_rating_summary WHERE addon_id = ?1"
+            "SELECT addon_id, rating_avg, rating_count, weighted_rating
+             FROM addon_rating_summary
+             ORDER BY weighted_rating DESC, rating_count DESC",
         )?;
-        let mut rows = stmt.query(params![addon_id])?;
-        if let Some(row) = rows.next()? {
-            Ok(Some(crate::models::AddonRatingSummary{
-                addon_id: row.get(0)?,
-                rating_avg: row.get(1)?,
-                rating_count: row.get(2)?,
-                weighted_rating: row.get(3)?,
-            }))
-        } else { Ok(None) }
+        let ratings = stmt
+            .query_map([], addon_rating_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ratings)
     }
 
-    pub fn upsert_skip_segments(&self, media_id: &str, segments: &crate::models::SkipSegments) -> Result<(), anyhow::Error> {
+    /// Store the skip segments of a media item, replacing any previous ones
+    pub fn set_skip_segments(
+        &self,
+        media_id: &str,
+        segments: &crate::models::SkipSegments,
+    ) -> Result<(), anyhow::Error> {
+        segments.validate()?;
         let now = chrono::Utc::now().to_rfc3339();
         self.conn.execute(
             "INSERT OR REPLACE INTO skip_segments (media_id, intro_start, intro_end, outro_start, outro_end, updated_at)
@@ -822,6 +1634,7 @@ impl Database {
         Ok(())
     }
 
+    /// Skip segments of a media item, or `None` when none were saved
     pub fn get_skip_segments(&self, media_id: &str) -> Result<Option<crate::models::SkipSegments>, anyhow::Error> {
         let mut stmt = self.conn.prepare(
             "SELECT intro_start, intro_end, outro_start, outro_end FROM skip_segments WHERE media_id = ?1"
@@ -837,54 +1650,128 @@ impl Database {
         } else { Ok(None) }
     }
 
+    // Episode methods
+
+    /// Insert an episode or refresh its metadata. Watched state and progress
+    /// of an existing episode are kept; use `update_episode_progress` for those.
+    pub fn upsert_episode(&self, episode: &Episode) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "INSERT INTO episodes
+             (id, series_id, season, episode, title, overview, thumbnail, released, runtime,
+              watched, progress, added_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+             ON CONFLICT(id) DO UPDATE SET
+                series_id = excluded.series_id,
+                season = excluded.season,
+                episode = excluded.episode,
+                title = excluded.title,
+                overview = excluded.overview,
+                thumbnail = excluded.thumbnail,
+                released = excluded.released,
+                runtime = excluded.runtime",
+            params![
+                episode.id,
+                episode.series_id,
+                episode.season,
+                episode.episode,
+                episode.title,
+                episode.overview,
+                episode.thumbnail,
+                episode.released,
+                episode.runtime,
+                episode.watched,
+                episode.progress,
+                chrono::Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// All episodes of a series ordered by season, then episode number
+    pub fn get_episodes(&self, series_id: &str) -> Result<Vec<Episode>, anyhow::Error> {
+        let stmt = self.conn.prepare(
+            "SELECT id, series_id, season, episode, title, overview, thumbnail, released,
+                    runtime, watched, progress
+             FROM episodes
+             WHERE series_id = ?1
+             ORDER BY season ASC, episode ASC",
+        )?;
+        self.query_episodes(stmt, params![series_id])
+    }
+
+    pub fn get_episode(&self, episode_id: &str) -> Result<Option<Episode>, anyhow::Error> {
+        let stmt = self.conn.prepare(
+            "SELECT id, series_id, season, episode, title, overview, thumbnail, released,
+                    runtime, watched, progress
+             FROM episodes
+             WHERE id = ?1",
+        )?;
+        Ok(self.query_episodes(stmt, params![episode_id])?.pop())
+    }
+
+    pub fn update_episode_progress(
+        &self,
+        episode_id: &str,
+        progress: i32,
+        watched: bool,
+    ) -> Result<(), anyhow::Error> {
+        let updated = self.conn.execute(
+            "UPDATE episodes SET progress = ?1, watched = ?2 WHERE id = ?3",
+            params![progress, watched, episode_id],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!("Episode not found: {}", episode_id));
+        }
+        Ok(())
+    }
+
+    /// First unwatched episode of a series in (season, episode) order
+    pub fn get_next_unwatched_episode(
+        &self,
+        series_id: &str,
+    ) -> Result<Option<Episode>, anyhow::Error> {
+        let stmt = self.conn.prepare(
+            "SELECT id, series_id, season, episode, title, overview, thumbnail, released,
+                    runtime, watched, progress
+             FROM episodes
+             WHERE series_id = ?1 AND watched = 0
+             ORDER BY season ASC, episode ASC
+             LIMIT 1",
+        )?;
+        Ok(self.query_episodes(stmt, params![series_id])?.pop())
+    }
+
+    fn query_episodes(
+        &self,
+        mut stmt: rusqlite::Statement,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<Episode>, anyhow::Error> {
+        let episodes = stmt
+            .query_map(params, |row| {
+                Ok(Episode {
+                    id: row.get(0)?,
+                    series_id: row.get(1)?,
+                    season: row.get(2)?,
+                    episode: row.get(3)?,
+                    title: row.get(4)?,
+                    overview: row.get(5)?,
+                    thumbnail: row.get(6)?,
+                    released: row.get(7)?,
+                    runtime: row.get(8)?,
+                    watched: row.get(9)?,
+                    progress: row.get(10)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(episodes)
+    }
+
     fn query_media_items(
         &self,
         mut stmt: rusqlite::Statement,
         params: impl rusqlite::Params,
     ) -> Result<Vec<MediaItem>, anyhow::Error> {
-        let media_iter = stmt.query_map(params, |row| {
-            let genre_str: String = row.get(4)?;
-            let genres: Vec<String> = if genre_str.is_empty() {
-                Vec::new()
-            } else {
-                genre_str.split(',').map(|s| s.to_string()).collect()
-            };
-
-            let media_type_str: String = row.get(2)?;
-            let media_type = match media_type_str.as_str() {
-                "Movie" => MediaType::Movie,
-                "TvShow" => MediaType::TvShow,
-                "Episode" => MediaType::Episode,
-                "Documentary" => MediaType::Documentary,
-                "LiveTv" => MediaType::LiveTv,
-                "Podcast" => MediaType::Podcast,
-                _ => MediaType::Movie,
-            };
-
-            let added_to_library = if let Ok(date_str) = row.get::<_, String>(10) {
-                chrono::DateTime::parse_from_rfc3339(&date_str)
-                    .ok()
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-            } else {
-                None
-            };
-
-            Ok(MediaItem {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                media_type,
-                year: row.get(3)?,
-                genre: genres,
-                description: row.get(5)?,
-                poster_url: row.get(6)?,
-                backdrop_url: row.get(7)?,
-                rating: row.get(8)?,
-                duration: row.get(9)?,
-                added_to_library,
-                watched: row.get(11)?,
-                progress: row.get(12)?,
-            })
-        })?;
+        let media_iter = stmt.query_map(params, media_item_from_row)?;
 
         let mut items = Vec::new();
         for item in media_iter {
@@ -895,7 +1782,9 @@ impl Database {
 
     // Addon health tracking methods
 
-    /// Record a single health check event for an addon
+    /// Record a single health check event for an addon. `invalid_count` is
+    /// the number of entries strict validation dropped from the response.
+    #[allow(clippy::too_many_arguments)]
     pub fn record_addon_health(
         &self,
         addon_id: &str,
@@ -903,24 +1792,50 @@ impl Database {
         success: bool,
         error_message: Option<&str>,
         item_count: usize,
+        invalid_count: usize,
         operation_type: &str,
     ) -> Result<(), anyhow::Error> {
         let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        self.record_addon_health_at(
+            addon_id,
+            response_time_ms,
+            success,
+            error_message,
+            item_count,
+            invalid_count,
+            operation_type,
+            now,
+        )
+    }
 
+    /// `record_addon_health` with an explicit event time (Unix seconds)
+    #[allow(clippy::too_many_arguments)]
+    fn record_addon_health_at(
+        &self,
+        addon_id: &str,
+        response_time_ms: u128,
+        success: bool,
+        error_message: Option<&str>,
+        item_count: usize,
+        invalid_count: usize,
+        operation_type: &str,
+        timestamp: i64,
+    ) -> Result<(), anyhow::Error> {
         self.conn.execute(
             "INSERT INTO addon_health 
-             (addon_id, timestamp, response_time_ms, success, error_message, item_count, operation_type)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+             (addon_id, timestamp, response_time_ms, success, error_message, item_count,
+              invalid_count, operation_type)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             params![
                 addon_id,
-                now as i64,
+                timestamp,
                 response_time_ms as i64,
                 success,
                 error_message,
                 item_count as i64,
+                invalid_count as i64,
                 operation_type,
             ],
         )?;
@@ -928,14 +1843,105 @@ impl Database {
         // Update summary statistics
         self.update_addon_health_summary(addon_id)?;
 
+        // Only a response that actually contributed results counts as use
+        if success && item_count > 0 {
+            self.mark_addon_used(addon_id, timestamp)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether an addon's most recent recorded request succeeded, if it
+    /// has any
+    pub fn last_addon_result(&self, addon_id: &str) -> Result<Option<bool>, anyhow::Error> {
+        let result = self.conn.query_row(
+            "SELECT success FROM addon_health WHERE addon_id = ?1
+             ORDER BY timestamp DESC LIMIT 1",
+            params![addon_id],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(success) => Ok(Some(success)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Record that an addon contributed results at `timestamp` (Unix seconds)
+    fn mark_addon_used(&self, addon_id: &str, timestamp: i64) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "INSERT INTO addon_usage (addon_id, last_used) VALUES (?1, ?2)
+             ON CONFLICT(addon_id) DO UPDATE SET last_used = MAX(last_used, excluded.last_used)",
+            params![addon_id, timestamp],
+        )?;
         Ok(())
     }
 
+    /// Last time (Unix seconds) an addon contributed results, if ever
+    pub fn get_addon_last_used(&self, addon_id: &str) -> Result<Option<i64>, anyhow::Error> {
+        let result = self.conn.query_row(
+            "SELECT last_used FROM addon_usage WHERE addon_id = ?1",
+            params![addon_id],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(last_used) => Ok(Some(last_used)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Installed addons that have not contributed results in the last `days` days.
+    /// Addons that were never used only count once they have been installed that long.
+    pub fn get_unused_addons(&self, days: u32) -> Result<Vec<Addon>, anyhow::Error> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        let cutoff = now - i64::from(days) * 24 * 3600;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT a.id, a.installed_at, u.last_used
+             FROM addons a
+             LEFT JOIN addon_usage u ON u.addon_id = a.id",
+        )?;
+        let activity = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let unused: std::collections::HashSet<String> = activity
+            .into_iter()
+            .filter(|(_, installed_at, last_used)| {
+                let last_active = last_used.or_else(|| {
+                    installed_at
+                        .as_deref()
+                        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                        .map(|dt| dt.timestamp())
+                });
+                last_active.map(|t| t < cutoff).unwrap_or(true)
+            })
+            .map(|(id, _, _)| id)
+            .collect();
+
+        Ok(self
+            .get_addons()?
+            .into_iter()
+            .filter(|addon| unused.contains(&addon.id))
+            .collect())
+    }
+
     /// Update health summary statistics for an addon
     fn update_addon_health_summary(&self, addon_id: &str) -> Result<(), anyhow::Error> {
         // Calculate statistics from recent health records (last 100 records)
         let mut stmt = self.conn.prepare(
-            "SELECT response_time_ms, success, error_message
+            "SELECT response_time_ms, success, error_message, invalid_count
              FROM addon_health
              WHERE addon_id = ?1
              ORDER BY timestamp DESC
@@ -945,23 +1951,30 @@ impl Database {
         let mut total = 0;
         let mut successful = 0;
         let mut total_response_time: i64 = 0;
+        let mut invalid: i64 = 0;
+        // Error message of the newest failed record (None if it had no message)
         let mut last_error: Option<String> = None;
+        let mut seen_failure = false;
 
         let rows = stmt.query_map(params![addon_id], |row| {
             Ok((
                 row.get::<_, i64>(0)?,
                 row.get::<_, bool>(1)?,
                 row.get::<_, Option<String>>(2)?,
+                row.get::<_, i64>(3)?,
             ))
         })?;
 
         for row in rows {
-            let (response_time, success, error) = row?;
+            let (response_time, success, error, invalid_count) = row?;
             total += 1;
             total_response_time += response_time;
+            invalid += invalid_count;
             if success {
                 successful += 1;
-            } else if last_error.is_none() && error.is_some() {
+            } else if !seen_failure {
+                // Rows are newest first, so the first failure is the latest one
+                seen_failure = true;
                 last_error = error;
             }
         }
@@ -998,8 +2011,9 @@ impl Database {
         self.conn.execute(
             "INSERT OR REPLACE INTO addon_health_summary 
              (addon_id, last_check, success_rate, avg_response_time_ms, 
-              total_requests, successful_requests, failed_requests, last_error, health_score)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+              total_requests, successful_requests, failed_requests, last_error, health_score,
+              invalid_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 addon_id,
                 now as i64,
@@ -1010,6 +2024,7 @@ impl Database {
                 failed,
                 last_error,
                 health_score,
+                invalid,
             ],
         )?;
 
@@ -1023,7 +2038,8 @@ impl Database {
     ) -> Result<Option<AddonHealthSummary>, anyhow::Error> {
         let mut stmt = self.conn.prepare(
             "SELECT h.addon_id, a.name, h.last_check, h.success_rate, h.avg_response_time_ms, 
-                    h.total_requests, h.successful_requests, h.failed_requests, h.last_error, h.health_score
+                    h.total_requests, h.successful_requests, h.failed_requests, h.last_error, h.health_score,
+                    h.invalid_count
              FROM addon_health_summary h
              LEFT JOIN addons a ON h.addon_id = a.id
              WHERE h.addon_id = ?1",
@@ -1041,6 +2057,7 @@ impl Database {
                 failed_requests: row.get(7)?,
                 last_error: row.get(8)?,
                 health_score: row.get(9)?,
+                invalid_count: row.get(10)?,
             })
         });
 
@@ -1051,14 +2068,17 @@ impl Database {
         }
     }
 
-    /// Get health summaries for all addons
+    /// Get health summaries for all addons.
+    /// Ordered by health score (best first); ties go to the faster addon, then by addon id,
+    /// so the order is stable between refreshes.
     pub fn get_all_addon_health_summaries(&self) -> Result<Vec<AddonHealthSummary>, anyhow::Error> {
         let mut stmt = self.conn.prepare(
             "SELECT h.addon_id, a.name, h.last_check, h.success_rate, h.avg_response_time_ms, 
-                    h.total_requests, h.successful_requests, h.failed_requests, h.last_error, h.health_score
+                    h.total_requests, h.successful_requests, h.failed_requests, h.last_error, h.health_score,
+                    h.invalid_count
              FROM addon_health_summary h
              LEFT JOIN addons a ON h.addon_id = a.id
-             ORDER BY h.health_score DESC",
+             ORDER BY h.health_score DESC, h.avg_response_time_ms ASC, h.addon_id ASC",
         )?;
 
         let summaries = stmt.query_map([], |row| {
@@ -1073,6 +2093,7 @@ impl Database {
                 failed_requests: row.get(7)?,
                 last_error: row.get(8)?,
                 health_score: row.get(9)?,
+                invalid_count: row.get(10)?,
             })
         })?;
 
@@ -1099,14 +2120,81 @@ impl Database {
         Ok(deleted)
     }
 
+    /// Routine maintenance for long-lived installs: drops old health records,
+    /// refreshes query planner statistics and, if asked, rebuilds the file
+    /// with VACUUM (skipped for in-memory databases).
+    pub fn optimize(&self, vacuum: bool) -> Result<OptimizeReport, anyhow::Error> {
+        let size_before = self.database_size()?;
+
+        let health_records_removed = self.cleanup_old_health_records()?;
+        self.conn.execute_batch("ANALYZE; PRAGMA optimize;")?;
+
+        let in_memory = self.conn.path().map_or(true, |path| path.is_empty());
+        let vacuumed = vacuum && !in_memory;
+        if vacuumed {
+            self.conn.execute_batch("VACUUM")?;
+        }
+
+        let size_after = self.database_size()?;
+        Ok(OptimizeReport {
+            health_records_removed,
+            vacuumed,
+            size_before,
+            size_after,
+            reclaimed_bytes: size_before.saturating_sub(size_after),
+        })
+    }
+
+    /// Size of the main database file in bytes
+    fn database_size(&self) -> Result<u64, anyhow::Error> {
+        let size: i64 = self.conn.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(size.max(0) as u64)
+    }
+
+    /// Export raw addon health events as CSV, oldest first.
+    /// `since` limits the export to events at or after that Unix timestamp.
+    pub fn export_addon_health_csv(&self, since: Option<i64>) -> Result<String, anyhow::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT addon_id, timestamp, response_time_ms, success, operation_type,
+                    item_count, error_message
+             FROM addon_health
+             WHERE timestamp >= ?1
+             ORDER BY timestamp, addon_id",
+        )?;
+
+        let mut csv = String::from(
+            "addon_id,timestamp,response_time_ms,success,operation_type,item_count,error\n",
+        );
+        let mut rows = stmt.query(params![since.unwrap_or(i64::MIN)])?;
+        while let Some(row) = rows.next()? {
+            let fields = [
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?.to_string(),
+                row.get::<_, i64>(2)?.to_string(),
+                row.get::<_, bool>(3)?.to_string(),
+                row.get::<_, String>(4)?,
+                row.get::<_, Option<i64>>(5)?.unwrap_or(0).to_string(),
+                row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+            ];
+            let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+            csv.push_str(&line.join(","));
+            csv.push('\n');
+        }
+        Ok(csv)
+    }
+
     // Local media methods
     pub fn upsert_local_media_file(&self, file: &crate::local_media::LocalMediaFile) -> Result<(), anyhow::Error> {
         self.conn.execute(
             "INSERT OR REPLACE INTO local_media_files 
              (id, file_path, file_name, file_size, title, year, season, episode, 
               duration, resolution, video_codec, audio_codec, tmdb_id, imdb_id, 
-              poster_url, added_at, last_modified, last_scanned)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+              poster_url, added_at, last_modified, last_scanned, plot)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
             params![
                 file.id,
                 file.file_path,
@@ -1126,6 +2214,7 @@ impl Database {
                 file.added_at.to_rfc3339(),
                 file.last_modified.to_rfc3339(),
                 chrono::Utc::now().to_rfc3339(),
+                file.plot,
             ],
         )?;
         Ok(())
@@ -1143,7 +2232,7 @@ impl Database {
         let mut stmt = self.conn.prepare(
             "SELECT id, file_path, file_name, file_size, title, year, season, episode,
                     duration, resolution, video_codec, audio_codec, tmdb_id, imdb_id,
-                    poster_url, added_at, last_modified
+                    poster_url, added_at, last_modified, plot
              FROM local_media_files
              ORDER BY title ASC"
         )?;
@@ -1165,6 +2254,7 @@ impl Database {
                 tmdb_id: row.get(12)?,
                 imdb_id: row.get(13)?,
                 poster_url: row.get(14)?,
+                plot: row.get(17)?,
                 added_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(15)?)
                     .unwrap_or_else(|_| chrono::Utc::now().into())
                     .with_timezone(&chrono::Utc),
@@ -1178,9 +2268,43 @@ impl Database {
         Ok(files)
     }
 
-    pub fn add_scanned_directory(&self, path: &str) -> Result<(), anyhow::Error> {
+    /// Whether `path` lies inside a recursively scanned directory or
+    /// contains scanned directories, so its files would be scanned twice
+    pub fn scanned_directory_overlap(
+        &self,
+        path: &str,
+    ) -> Result<Option<DirectoryOverlap>, anyhow::Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, recursive FROM scanned_directories ORDER BY path ASC")?;
+        let existing = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(String, bool)>, _>>()?;
+        Ok(directory_overlap(path, &existing))
+    }
+
+    /// Add a scanned directory, or refresh its last scan. Fails if it is
+    /// inside a recursively scanned directory; scanned directories inside
+    /// it are merged into it. Returns the merged paths.
+    pub fn add_scanned_directory(&self, path: &str) -> Result<Vec<String>, anyhow::Error> {
+        let merged = match self.scanned_directory_overlap(path)? {
+            Some(DirectoryOverlap::Inside(parent)) => {
+                anyhow::bail!("{} is already scanned as part of {}", path, parent)
+            }
+            Some(DirectoryOverlap::Contains(children)) => children,
+            None => Vec::new(),
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        for child in &merged {
+            tx.execute(
+                "DELETE FROM scanned_directories WHERE path = ?1",
+                params![child],
+            )?;
+        }
+
         let now = chrono::Utc::now().to_rfc3339();
-        self.conn.execute(
+        tx.execute(
             "INSERT OR REPLACE INTO scanned_directories (path, enabled, recursive, last_scan, file_count, added_at)
              VALUES (
                  ?1,
@@ -1192,7 +2316,12 @@ impl Database {
              )",
             params![path, now.clone(), now],
         )?;
-        Ok(())
+        tx.commit()?;
+
+        if !merged.is_empty() {
+            tracing::info!(path, merged = ?merged, "Merged nested scanned directories");
+        }
+        Ok(merged)
     }
 
     pub fn get_scanned_directories(&self) -> Result<Vec<(String, String, bool)>, anyhow::Error> {
@@ -1210,33 +2339,36 @@ impl Database {
 
     // Live TV methods
     pub fn upsert_live_tv_channels(&self, channels: &[crate::models::LiveTvChannel]) -> Result<(), anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
         for channel in channels {
-            self.conn.execute(
-                "INSERT OR REPLACE INTO live_tv_channels
-                 (id, name, logo, channel_group, tvg_id, stream_url, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params![
-                    channel.id,
-                    channel.name,
-                    channel.logo,
-                    channel.group,
-                    channel.tvg_id,
-                    channel.stream_url,
-                    chrono::Utc::now().to_rfc3339(),
-                ],
-            )?;
+            upsert_live_tv_channel_row(&tx, channel)?;
         }
+        tx.commit()?;
         Ok(())
     }
 
-    pub fn get_live_tv_channels(&self) -> Result<Vec<crate::models::LiveTvChannel>, anyhow::Error> {
+    /// Add a channel, or update it in place if the id exists (keeping its
+    /// EPG, reminders and last stream check)
+    pub fn upsert_live_tv_channel(
+        &self,
+        channel: &crate::models::LiveTvChannel,
+    ) -> Result<(), anyhow::Error> {
+        upsert_live_tv_channel_row(&self.conn, channel)
+    }
+
+    /// All channels by name, or only those in `group`
+    pub fn get_live_tv_channels(
+        &self,
+        group: Option<&str>,
+    ) -> Result<Vec<crate::models::LiveTvChannel>, anyhow::Error> {
         let mut stmt = self.conn.prepare(
             "SELECT id, name, logo, channel_group, tvg_id, stream_url
              FROM live_tv_channels
+             WHERE ?1 IS NULL OR channel_group = ?1
              ORDER BY name ASC"
         )?;
 
-        let channels = stmt.query_map([], |row| {
+        let channels = stmt.query_map(params![group], |row| {
             Ok(crate::models::LiveTvChannel {
                 id: row.get(0)?,
                 name: row.get(1)?,
@@ -1251,53 +2383,258 @@ impl Database {
         Ok(channels)
     }
 
-    pub fn upsert_epg_programs(&self, programs: &[crate::models::EpgProgram]) -> Result<(), anyhow::Error> {
-        for program in programs {
-            self.conn.execute(
-                "INSERT OR REPLACE INTO epg_programs
-                 (channel_id, start_time, end_time, title, description, category, season, episode, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-                params![
-                    program.channel_id,
-                    program.start,
-                    program.end,
-                    program.title,
-                    program.description,
-                    program.category,
-                    program.season,
-                    program.episode,
-                    chrono::Utc::now().to_rfc3339(),
-                ],
-            )?;
-        }
-        Ok(())
-    }
-
-    pub fn get_epg_for_channel(
+    pub fn get_live_tv_channel(
         &self,
         channel_id: &str,
-        since: Option<i64>,
-        until: Option<i64>,
-    ) -> Result<Vec<crate::models::EpgProgram>, anyhow::Error> {
-        let mut query = String::from(
-            "SELECT channel_id, start_time, end_time, title, description, category, season, episode
-             FROM epg_programs
+    ) -> Result<Option<crate::models::LiveTvChannel>, anyhow::Error> {
+        let result = self.conn.query_row(
+            "SELECT id, name, logo, channel_group, tvg_id, stream_url
+             FROM live_tv_channels
+             WHERE id = ?1",
+            params![channel_id],
+            |row| {
+                Ok(crate::models::LiveTvChannel {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    logo: row.get(2)?,
+                    group: row.get(3)?,
+                    tvg_id: row.get(4)?,
+                    stream_url: row.get(5)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(channel) => Ok(Some(channel)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Delete a channel along with its EPG, reminders and favorite entries.
+    /// Returns whether the channel existed.
+    pub fn delete_live_tv_channel(&self, channel_id: &str) -> Result<bool, anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM favorite_channels WHERE channel_id = ?1",
+            params![channel_id],
+        )?;
+        let deleted = tx.execute(
+            "DELETE FROM live_tv_channels WHERE id = ?1",
+            params![channel_id],
+        )?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    /// Store stream check results. Statuses for unknown channels are ignored.
+    pub fn update_channel_statuses(
+        &self,
+        statuses: &[crate::models::ChannelStatus],
+    ) -> Result<usize, anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut updated = 0;
+        {
+            let mut stmt = tx.prepare(
+                "UPDATE live_tv_channels
+                 SET stream_online = ?1, stream_checked_at = ?2, stream_error = ?3
+                 WHERE id = ?4",
+            )?;
+            for status in statuses {
+                updated += stmt.execute(params![
+                    status.online,
+                    status.checked_at,
+                    status.error,
+                    status.channel_id
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Last stream check of every channel that has been checked
+    pub fn get_channel_statuses(&self) -> Result<Vec<crate::models::ChannelStatus>, anyhow::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, stream_online, stream_checked_at, stream_error
+             FROM live_tv_channels
+             WHERE stream_checked_at IS NOT NULL
+             ORDER BY name ASC",
+        )?;
+
+        let statuses = stmt
+            .query_map([], |row| {
+                Ok(crate::models::ChannelStatus {
+                    channel_id: row.get(0)?,
+                    online: row.get(1)?,
+                    checked_at: row.get(2)?,
+                    error: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(statuses)
+    }
+
+    pub fn add_favorite_channel(
+        &self,
+        user_id: &str,
+        channel_id: &str,
+    ) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO favorite_channels (user_id, channel_id, added_at)
+             VALUES (?1, ?2, ?3)",
+            params![user_id, channel_id, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Returns whether the channel was a favorite
+    pub fn remove_favorite_channel(
+        &self,
+        user_id: &str,
+        channel_id: &str,
+    ) -> Result<bool, anyhow::Error> {
+        let removed = self.conn.execute(
+            "DELETE FROM favorite_channels WHERE user_id = ?1 AND channel_id = ?2",
+            params![user_id, channel_id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Favorite channels that are still in the channel list, by name
+    pub fn get_favorite_channels(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<crate::models::LiveTvChannel>, anyhow::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.id, c.name, c.logo, c.channel_group, c.tvg_id, c.stream_url
+             FROM live_tv_channels c
+             INNER JOIN favorite_channels f ON f.channel_id = c.id
+             WHERE f.user_id = ?1
+             ORDER BY c.name ASC",
+        )?;
+
+        let channels = stmt
+            .query_map(params![user_id], |row| {
+                Ok(crate::models::LiveTvChannel {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    logo: row.get(2)?,
+                    group: row.get(3)?,
+                    tvg_id: row.get(4)?,
+                    stream_url: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(channels)
+    }
+
+    /// Distinct channel groups with their channel counts, by name. Channels
+    /// without a group are counted under a `None` group listed last.
+    pub fn get_channel_groups(&self) -> Result<Vec<crate::models::ChannelGroup>, anyhow::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT NULLIF(channel_group, '') AS grp, COUNT(*)
+             FROM live_tv_channels
+             GROUP BY grp
+             ORDER BY grp IS NULL, grp COLLATE NOCASE ASC",
+        )?;
+
+        let groups = stmt
+            .query_map([], |row| {
+                Ok(crate::models::ChannelGroup {
+                    name: row.get(0)?,
+                    channel_count: row.get::<_, i64>(1)? as usize,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(groups)
+    }
+
+    pub fn upsert_epg_programs(&self, programs: &[crate::models::EpgProgram]) -> Result<(), anyhow::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        for program in programs {
+            upsert_epg_program_row(&tx, program)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Store a program, updating the one with the same channel and start
+    /// time if present (its id, and so any reminder, is kept). Returns the id.
+    pub fn upsert_epg_program(
+        &self,
+        program: &crate::models::EpgProgram,
+    ) -> Result<i64, anyhow::Error> {
+        upsert_epg_program_row(&self.conn, program)
+    }
+
+    /// The program airing on a channel at `now` and the one after it
+    pub fn get_now_next(
+        &self,
+        channel_id: &str,
+        now: i64,
+    ) -> Result<
+        (
+            Option<crate::models::EpgProgram>,
+            Option<crate::models::EpgProgram>,
+        ),
+        anyhow::Error,
+    > {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, channel_id, start, end, title, description, category, season, episode
+             FROM epg_programs
+             WHERE channel_id = ?1 AND start <= ?2 AND end > ?2
+             ORDER BY start DESC
+             LIMIT 1",
+        )?;
+        let current = stmt
+            .query_map(params![channel_id, now], epg_program_from_row)?
+            .next()
+            .transpose()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, channel_id, start, end, title, description, category, season, episode
+             FROM epg_programs
+             WHERE channel_id = ?1 AND start > ?2
+             ORDER BY start ASC
+             LIMIT 1",
+        )?;
+        let next = stmt
+            .query_map(params![channel_id, now], epg_program_from_row)?
+            .next()
+            .transpose()?;
+
+        Ok((current, next))
+    }
+
+    pub fn get_epg_for_channel(
+        &self,
+        channel_id: &str,
+        since: Option<i64>,
+        until: Option<i64>,
+    ) -> Result<Vec<crate::models::EpgProgram>, anyhow::Error> {
+        let mut query = String::from(
+            "SELECT id, channel_id, start, end, title, description, category, season, episode
+             FROM epg_programs
              WHERE channel_id = ?1"
         );
 
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(channel_id)];
 
         if let Some(since_ts) = since {
-            query.push_str(" AND end_time >= ?2");
+            query.push_str(" AND end >= ?2");
             params.push(Box::new(since_ts));
         }
 
         if let Some(until_ts) = until {
-            query.push_str(&format!(" AND start_time <= ?{}", params.len() + 1));
+            query.push_str(&format!(" AND start <= ?{}", params.len() + 1));
             params.push(Box::new(until_ts));
         }
 
-        query.push_str(" ORDER BY start_time ASC");
+        query.push_str(" ORDER BY start ASC");
 
         let mut stmt = self.conn.prepare(&query)?;
         let params_refs: Vec<&dyn rusqlite::ToSql> = params
@@ -1307,20 +2644,506 @@ impl Database {
 
         let programs = stmt.query_map(params_refs.as_slice(), |row| {
             Ok(crate::models::EpgProgram {
-                channel_id: row.get(0)?,
-                start: row.get(1)?,
-                end: row.get(2)?,
-                title: row.get(3)?,
-                description: row.get(4)?,
-                category: row.get(5)?,
-                season: row.get(6)?,
-                episode: row.get(7)?,
+                id: row.get(0)?,
+                channel_id: row.get(1)?,
+                start: row.get(2)?,
+                end: row.get(3)?,
+                title: row.get(4)?,
+                description: row.get(5)?,
+                category: row.get(6)?,
+                season: row.get(7)?,
+                episode: row.get(8)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
 
         Ok(programs)
     }
+
+    pub fn get_epg_program(
+        &self,
+        program_id: i64,
+    ) -> Result<Option<crate::models::EpgProgram>, anyhow::Error> {
+        let result = self.conn.query_row(
+            "SELECT id, channel_id, start, end, title, description, category, season, episode
+             FROM epg_programs
+             WHERE id = ?1",
+            params![program_id],
+            |row| {
+                Ok(crate::models::EpgProgram {
+                    id: row.get(0)?,
+                    channel_id: row.get(1)?,
+                    start: row.get(2)?,
+                    end: row.get(3)?,
+                    title: row.get(4)?,
+                    description: row.get(5)?,
+                    category: row.get(6)?,
+                    season: row.get(7)?,
+                    episode: row.get(8)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(program) => Ok(Some(program)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Remind the user before a program on `channel_id` starts
+    pub fn set_program_reminder(
+        &self,
+        channel_id: &str,
+        program_id: i64,
+    ) -> Result<(), anyhow::Error> {
+        let exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM epg_programs WHERE id = ?1 AND channel_id = ?2)",
+            params![program_id, channel_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(anyhow::anyhow!(
+                "Program {} not found on channel {}",
+                program_id,
+                channel_id
+            ));
+        }
+
+        self.conn.execute(
+            "INSERT OR IGNORE INTO program_reminders (program_id, channel_id, created_at)
+             VALUES (?1, ?2, ?3)",
+            params![program_id, channel_id, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Returns whether a reminder was removed
+    pub fn remove_program_reminder(&self, program_id: i64) -> Result<bool, anyhow::Error> {
+        let removed = self.conn.execute(
+            "DELETE FROM program_reminders WHERE program_id = ?1",
+            params![program_id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Reminders for programs that haven't ended by `now`, soonest first
+    pub fn get_upcoming_reminders(
+        &self,
+        now: i64,
+    ) -> Result<Vec<crate::models::ProgramReminder>, anyhow::Error> {
+        self.query_reminders("p.end > ?1", params![now])
+    }
+
+    /// Reminders not yet notified whose program starts within `lead_secs`
+    /// of `now` (or has started but not ended)
+    pub fn get_due_reminders(
+        &self,
+        now: i64,
+        lead_secs: i64,
+    ) -> Result<Vec<crate::models::ProgramReminder>, anyhow::Error> {
+        self.query_reminders(
+            "r.notified = 0 AND p.start <= ?1 + ?2 AND p.end > ?1",
+            params![now, lead_secs],
+        )
+    }
+
+    pub fn mark_reminder_notified(&self, program_id: i64) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "UPDATE program_reminders SET notified = 1 WHERE program_id = ?1",
+            params![program_id],
+        )?;
+        Ok(())
+    }
+
+    /// Drop reminders for programs that ended by `now`; returns how many
+    pub fn remove_past_reminders(&self, now: i64) -> Result<usize, anyhow::Error> {
+        let removed = self.conn.execute(
+            "DELETE FROM program_reminders
+             WHERE program_id IN (SELECT id FROM epg_programs WHERE end <= ?1)",
+            params![now],
+        )?;
+        Ok(removed)
+    }
+
+    fn query_reminders(
+        &self,
+        condition: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<crate::models::ProgramReminder>, anyhow::Error> {
+        let query = format!(
+            "SELECT r.program_id, r.channel_id, COALESCE(c.name, r.channel_id), p.title,
+                    p.start, p.end, r.notified
+             FROM program_reminders r
+             JOIN epg_programs p ON p.id = r.program_id
+             LEFT JOIN live_tv_channels c ON c.id = r.channel_id
+             WHERE {}
+             ORDER BY p.start ASC",
+            condition
+        );
+        let mut stmt = self.conn.prepare(&query)?;
+        let reminders = stmt
+            .query_map(params, |row| {
+                Ok(crate::models::ProgramReminder {
+                    program_id: row.get(0)?,
+                    channel_id: row.get(1)?,
+                    channel_name: row.get(2)?,
+                    title: row.get(3)?,
+                    start: row.get(4)?,
+                    end: row.get(5)?,
+                    notified: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(reminders)
+    }
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Progress saves at most this many seconds apart belong to one viewing
+/// session in the watch history
+const WATCH_SESSION_GAP_SECS: i64 = 30 * 60;
+
+/// Votes at the global mean every addon's weighted rating starts from, so
+/// an addon with one or two ratings can't top the list on its own
+pub const ADDON_RATING_PRIOR_VOTES: f64 = 5.0;
+
+// FTS5 wraps matches in these control characters rather than `<mark>`
+// directly, so the text around them can be HTML-escaped first
+const HIGHLIGHT_OPEN: &str = "\u{2}";
+const HIGHLIGHT_CLOSE: &str = "\u{3}";
+
+/// HTML-escape an FTS highlight/snippet and turn its match sentinels into
+/// `<mark>`/`</mark>`
+fn mark_highlights(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\u{2}' => out.push_str("<mark>"),
+            '\u{3}' => out.push_str("</mark>"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Turn free text into an FTS5 query that always parses: every word is
+/// quoted, so operators and punctuation are matched literally. A trailing
+/// `*` keeps prefix search. Returns None when there is nothing to search for.
+fn escape_fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter_map(|word| {
+            let (word, prefix) = match word.strip_suffix('*') {
+                Some(stripped) => (stripped, "*"),
+                None => (word, ""),
+            };
+            let word = word.trim_matches('"');
+            if word.is_empty() {
+                return None;
+            }
+            Some(format!("\"{}\"{}", word.replace('"', "\"\""), prefix))
+        })
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Validate and upsert one row into `media_items`
+fn insert_media_item(conn: &Connection, item: &MediaItem) -> Result<(), anyhow::Error> {
+    item.validate()?;
+
+    let genre_str = item.genre.join(",");
+    let media_type_str = match item.media_type {
+        MediaType::Movie => "Movie",
+        MediaType::TvShow => "TvShow",
+        MediaType::Episode => "Episode",
+        MediaType::Documentary => "Documentary",
+        MediaType::LiveTv => "LiveTv",
+        MediaType::Podcast => "Podcast",
+    };
+
+    let added_to_library_str = item
+        .added_to_library
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    // An upsert, not INSERT OR REPLACE: replacing deletes the old row, and
+    // the delete cascades to the item's watch history and playlist entries
+    conn.execute(
+        "INSERT INTO media_items 
+         (id, title, media_type, year, genre, description, poster_url, backdrop_url, 
+          rating, duration, added_to_library, watched, progress, logo_url, thumb_url)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+         ON CONFLICT(id) DO UPDATE SET
+             title = excluded.title,
+             media_type = excluded.media_type,
+             year = excluded.year,
+             genre = excluded.genre,
+             description = excluded.description,
+             poster_url = excluded.poster_url,
+             backdrop_url = excluded.backdrop_url,
+             rating = excluded.rating,
+             duration = excluded.duration,
+             added_to_library = excluded.added_to_library,
+             watched = excluded.watched,
+             progress = excluded.progress,
+             logo_url = excluded.logo_url,
+             thumb_url = excluded.thumb_url",
+        params![
+            item.id,
+            item.title,
+            media_type_str,
+            item.year,
+            genre_str,
+            item.description,
+            item.poster_url,
+            item.backdrop_url,
+            item.rating,
+            item.duration,
+            added_to_library_str,
+            item.watched,
+            item.progress,
+            item.logo_url,
+            item.thumb_url
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Library item an imported history entry refers to: the one with its
+/// external id, else the one with its title (ignoring case) and year. A
+/// title without a year only matches if no other item has that title.
+fn find_imported_item(entry: &ImportedHistory, library: &[MediaItem]) -> Option<usize> {
+    let external_id = entry
+        .external_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty());
+    if let Some(id) = external_id {
+        if let Some(index) = library.iter().position(|item| item.id == id) {
+            return Some(index);
+        }
+    }
+
+    let title = entry.title.trim().to_lowercase();
+    let mut candidates = library.iter().enumerate().filter(|(_, item)| {
+        item.title.trim().to_lowercase() == title
+            && (entry.year.is_none() || item.year == entry.year)
+    });
+    let (first, _) = candidates.next()?;
+    if entry.year.is_none() && candidates.next().is_some() {
+        return None;
+    }
+    Some(first)
+}
+
+/// Library item for an imported entry that matched nothing, if it has an
+/// external id to store it under and valid details
+fn new_imported_item(entry: &ImportedHistory) -> Option<MediaItem> {
+    let id = entry
+        .external_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())?;
+    let media_type = entry.media_type.clone().unwrap_or(MediaType::Movie);
+    let mut builder = MediaItem::builder(id, entry.title.trim(), media_type);
+    if let Some(year) = entry.year {
+        builder = builder.year(year);
+    }
+    builder.build().ok()
+}
+
+/// How `path` relates to `existing` scanned directories, given as (path,
+/// recursive). Paths are compared by component, so `/media2` is not inside
+/// `/media`, and the same directory spelled differently counts as contained
+/// so its old entry is replaced.
+fn directory_overlap(path: &str, existing: &[(String, bool)]) -> Option<DirectoryOverlap> {
+    let new = std::path::Path::new(path);
+    let others = existing.iter().filter(|(other, _)| other != path);
+
+    let parent = others.clone().find(|(other, recursive)| {
+        let other = std::path::Path::new(other);
+        *recursive && new != other && new.starts_with(other)
+    });
+    if let Some((parent, _)) = parent {
+        return Some(DirectoryOverlap::Inside(parent.clone()));
+    }
+
+    let children: Vec<String> = others
+        .filter(|(other, _)| std::path::Path::new(other).starts_with(new))
+        .map(|(other, _)| other.clone())
+        .collect();
+    (!children.is_empty()).then_some(DirectoryOverlap::Contains(children))
+}
+
+fn upsert_live_tv_channel_row(
+    conn: &Connection,
+    channel: &LiveTvChannel,
+) -> Result<(), anyhow::Error> {
+    conn.execute(
+        "INSERT INTO live_tv_channels (id, name, logo, channel_group, tvg_id, stream_url)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(id) DO UPDATE SET
+             name = excluded.name,
+             logo = excluded.logo,
+             channel_group = excluded.channel_group,
+             tvg_id = excluded.tvg_id,
+             stream_url = excluded.stream_url",
+        params![
+            channel.id,
+            channel.name,
+            channel.logo,
+            channel.group,
+            channel.tvg_id,
+            channel.stream_url,
+        ],
+    )?;
+    Ok(())
+}
+
+fn upsert_epg_program_row(conn: &Connection, program: &EpgProgram) -> Result<i64, anyhow::Error> {
+    let id = conn.query_row(
+        "INSERT INTO epg_programs
+         (channel_id, start, end, title, description, category, season, episode)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+         ON CONFLICT(channel_id, start) DO UPDATE SET
+             end = excluded.end,
+             title = excluded.title,
+             description = excluded.description,
+             category = excluded.category,
+             season = excluded.season,
+             episode = excluded.episode
+         RETURNING id",
+        params![
+            program.channel_id,
+            program.start,
+            program.end,
+            program.title,
+            program.description,
+            program.category,
+            program.season,
+            program.episode,
+        ],
+        |row| row.get(0),
+    )?;
+    Ok(id)
+}
+
+/// Map a row of (id, channel_id, start, end, title, description, category,
+/// season, episode) from `epg_programs`
+fn epg_program_from_row(row: &rusqlite::Row) -> rusqlite::Result<EpgProgram> {
+    Ok(EpgProgram {
+        id: row.get(0)?,
+        channel_id: row.get(1)?,
+        start: row.get(2)?,
+        end: row.get(3)?,
+        title: row.get(4)?,
+        description: row.get(5)?,
+        category: row.get(6)?,
+        season: row.get(7)?,
+        episode: row.get(8)?,
+    })
+}
+
+/// ORDER BY clause for a library sort option, newest first by default
+fn library_sort_clause(sort_by: Option<&str>) -> &'static str {
+    match sort_by {
+        Some("title_asc") => " ORDER BY title ASC",
+        Some("title_desc") => " ORDER BY title DESC",
+        Some("year_asc") => " ORDER BY year ASC",
+        Some("year_desc") => " ORDER BY year DESC",
+        Some("rating_desc") => " ORDER BY rating DESC",
+        _ => " ORDER BY added_to_library DESC",
+    }
+}
+
+fn addon_rating_from_row(
+    row: &rusqlite::Row,
+) -> rusqlite::Result<crate::models::AddonRatingSummary> {
+    Ok(crate::models::AddonRatingSummary {
+        addon_id: row.get(0)?,
+        rating_avg: row.get(1)?,
+        rating_count: row.get(2)?,
+        weighted_rating: row.get(3)?,
+    })
+}
+
+/// Map a row starting with the 15 standard `media_items` columns
+/// (id, title, media_type, ..., watched, progress, logo_url, thumb_url)
+fn media_item_from_row(row: &rusqlite::Row) -> rusqlite::Result<MediaItem> {
+    let genre_str: String = row.get(4)?;
+    let genres: Vec<String> = if genre_str.is_empty() {
+        Vec::new()
+    } else {
+        genre_str.split(',').map(|s| s.to_string()).collect()
+    };
+
+    let media_type_str: String = row.get(2)?;
+    let media_type = match media_type_str.as_str() {
+        "Movie" => MediaType::Movie,
+        "TvShow" => MediaType::TvShow,
+        "Episode" => MediaType::Episode,
+        "Documentary" => MediaType::Documentary,
+        "LiveTv" => MediaType::LiveTv,
+        "Podcast" => MediaType::Podcast,
+        _ => MediaType::Movie,
+    };
+
+    let added_to_library = if let Ok(date_str) = row.get::<_, String>(10) {
+        chrono::DateTime::parse_from_rfc3339(&date_str)
+            .ok()
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+    } else {
+        None
+    };
+
+    Ok(MediaItem {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        media_type,
+        year: row.get(3)?,
+        genre: genres,
+        description: row.get(5)?,
+        poster_url: row.get(6)?,
+        backdrop_url: row.get(7)?,
+        logo_url: row.get(13)?,
+        thumb_url: row.get(14)?,
+        rating: row.get(8)?,
+        duration: row.get(9)?,
+        added_to_library,
+        watched: row.get(11)?,
+        progress: row.get(12)?,
+        progress_percent: None,
+    }
+    .with_progress_percent())
+}
+
+/// Compare addon URLs ignoring case, trailing slashes and `/manifest.json`
+fn normalize_addon_url(url: &str) -> String {
+    url.trim()
+        .trim_end_matches('/')
+        .trim_end_matches("/manifest.json")
+        .trim_end_matches('/')
+        .to_lowercase()
 }
 
 #[cfg(test)]
@@ -1341,20 +3164,56 @@ mod tests {
     }
 
     fn create_test_media_item(id: &str, title: &str) -> MediaItem {
-        MediaItem {
+        MediaItem::builder(id, title, MediaType::Movie)
+            .year(2024)
+            .genre(vec!["Action".to_string()])
+            .description("Test description")
+            .poster_url("https://example.com/poster.jpg")
+            .backdrop_url("https://example.com/backdrop.jpg")
+            .rating(8.5)
+            .duration(120)
+            .progress(0)
+            .build()
+            .unwrap()
+    }
+
+    fn create_test_addon(id: &str, priority: i32) -> Addon {
+        Addon {
             id: id.to_string(),
-            title: title.to_string(),
-            media_type: MediaType::Movie,
-            year: Some(2024),
-            genre: vec!["Action".to_string()],
-            description: Some("Test description".to_string()),
-            poster_url: Some("https://example.com/poster.jpg".to_string()),
-            backdrop_url: Some("https://example.com/backdrop.jpg".to_string()),
-            rating: Some(8.5),
-            duration: Some(120),
-            added_to_library: None,
+            name: format!("{} addon", id),
+            version: "1.0.0".to_string(),
+            description: "Test addon".to_string(),
+            author: "Test".to_string(),
+            url: format!("https://{}.example.com", id),
+            enabled: true,
+            addon_type: AddonType::ContentProvider,
+            manifest: AddonManifest {
+                id: id.to_string(),
+                name: format!("{} addon", id),
+                version: "1.0.0".to_string(),
+                description: "Test addon".to_string(),
+                resources: vec!["stream".to_string()],
+                types: vec!["movie".to_string()],
+                catalogs: vec![],
+            },
+            priority,
+            config: Default::default(),
+        }
+    }
+
+    fn create_test_episode(series_id: &str, season: i32, episode: i32) -> Episode {
+        Episode {
+            id: format!("{}:{}:{}", series_id, season, episode),
+            series_id: series_id.to_string(),
+            season,
+            episode,
+            title: format!("Episode {}x{}", season, episode),
+            overview: None,
+            thumbnail: None,
+            released: None,
+            runtime: Some("45 min".to_string()),
             watched: false,
-            progress: Some(0),
+            progress: 0,
         }
     }
 
@@ -1397,6 +3256,54 @@ mod tests {
         assert_eq!(watchlist.len(), 0);
     }
 
+    #[test]
+    fn test_profiles_have_isolated_lists() {
+        let db = create_test_db().unwrap();
+        let profile = |id: &str, username: &str| UserProfile {
+            id: id.to_string(),
+            username: username.to_string(),
+            email: None,
+            preferences: UserPreferences::default(),
+            library_items: Vec::new(),
+            watchlist: Vec::new(),
+            favorites: Vec::new(),
+        };
+        db.save_user_profile(&profile("alice", "Alice")).unwrap();
+        db.save_user_profile(&profile("bob", "bob")).unwrap();
+        let usernames: Vec<String> = db
+            .list_user_profiles()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.username)
+            .collect();
+        assert_eq!(usernames, vec!["Alice", "bob"]);
+
+        db.add_to_library(create_test_media_item("movie1", "Movie One"))
+            .unwrap();
+        db.add_to_library(create_test_media_item("movie2", "Movie Two"))
+            .unwrap();
+        db.add_to_watchlist("alice", "movie1").unwrap();
+        db.add_to_watchlist("bob", "movie2").unwrap();
+        db.create_playlist("bob-list", "Bob's", None, "bob")
+            .unwrap();
+        db.add_item_to_playlist("bob-list", "movie1").unwrap();
+
+        let ids = |items: Vec<MediaItem>| items.into_iter().map(|i| i.id).collect::<Vec<_>>();
+        assert_eq!(ids(db.get_watchlist("alice").unwrap()), vec!["movie1"]);
+        assert_eq!(ids(db.get_watchlist("bob").unwrap()), vec!["movie2"]);
+        assert!(db.get_playlists("alice").unwrap().is_empty());
+
+        // Deleting a profile removes its lists but not the other profile's
+        assert!(db.delete_user_profile("bob").unwrap());
+        assert!(!db.delete_user_profile("bob").unwrap());
+        assert!(db.get_user_profile("bob").unwrap().is_none());
+        assert!(db.get_watchlist("bob").unwrap().is_empty());
+        assert!(db.get_playlists("bob").unwrap().is_empty());
+        assert!(db.get_playlist_items("bob-list").unwrap().is_empty());
+        assert_eq!(ids(db.get_watchlist("alice").unwrap()), vec!["movie1"]);
+        assert_eq!(db.get_library_items().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_favorites() {
         let db = create_test_db().unwrap();
@@ -1421,6 +3328,48 @@ mod tests {
         assert_eq!(favorites.len(), 0);
     }
 
+    #[test]
+    fn test_library_items_paged() {
+        let db = create_test_db().unwrap();
+        for i in 0..50 {
+            let id = format!("movie{:02}", i);
+            db.add_to_library(create_test_media_item(&id, &format!("Item {:02}", i)))
+                .unwrap();
+        }
+        let page = |offset, limit, sort| db.get_library_items_paged(offset, limit, sort).unwrap();
+        let titles = |items: &[MediaItem]| -> Vec<String> {
+            items.iter().map(|item| item.title.clone()).collect()
+        };
+
+        let (first, total) = page(0, 20, Some("title_asc"));
+        assert_eq!(total, 50);
+        assert_eq!(first.len(), 20);
+        assert_eq!(first[0].title, "Item 00");
+        assert_eq!(first[19].title, "Item 19");
+
+        let (last, total) = page(40, 20, Some("title_asc"));
+        assert_eq!(total, 50);
+        assert_eq!(last.len(), 10);
+        assert_eq!(titles(&last).first().unwrap(), "Item 40");
+        assert_eq!(titles(&last).last().unwrap(), "Item 49");
+
+        let (past_end, total) = page(50, 20, None);
+        assert!(past_end.is_empty());
+        assert_eq!(total, 50);
+
+        let (desc, _) = page(0, 1, Some("title_desc"));
+        assert_eq!(titles(&desc), vec!["Item 49"]);
+
+        // Pages cover every item exactly once, even with tied sort keys
+        let mut seen: Vec<String> = (0..5)
+            .flat_map(|n| page(n * 10, 10, None).0)
+            .map(|item| item.id)
+            .collect();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 50);
+    }
+
     #[test]
     fn test_watch_progress() {
         let db = create_test_db().unwrap();
@@ -1475,41 +3424,169 @@ mod tests {
     }
 
     #[test]
-    fn test_duplicate_watchlist_entry() {
+    fn test_watch_history_recency() {
         let db = create_test_db().unwrap();
         let user_id = "test_user";
-        let media_id = "movie1";
-
-        let item = create_test_media_item(media_id, "Test Movie");
-        db.add_to_library(item).unwrap();
+        for (id, title) in [
+            ("movie1", "First"),
+            ("movie2", "Second"),
+            ("movie3", "Third"),
+        ] {
+            db.add_to_library(create_test_media_item(id, title))
+                .unwrap();
+            db.add_to_watchlist(user_id, id).unwrap();
+            db.update_watch_progress(id, 60, false).unwrap();
+        }
+        let at = |secs: i64| chrono::DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap();
+
+        // An hour apart, so each is its own viewing session
+        for (id, position, duration, secs) in [
+            ("movie2", 60, Some(7200), 0),
+            ("movie1", 60, Some(7200), 3600),
+            ("movie2", 900, Some(7200), 7200),
+            ("movie3", 30, None, 10800),
+        ] {
+            db.record_watch_event(id, position, duration, at(secs))
+                .unwrap();
+        }
 
-        // Add to watchlist twice (should not error)
-        db.add_to_watchlist(user_id, media_id).unwrap();
-        db.add_to_watchlist(user_id, media_id).unwrap();
+        let history = db.get_recent_watch_history(10).unwrap();
+        let events: Vec<(&str, i32)> = history
+            .iter()
+            .map(|h| (h.media_id.as_str(), h.position))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                ("movie3", 30),
+                ("movie2", 900),
+                ("movie1", 60),
+                ("movie2", 60)
+            ]
+        );
+        assert_eq!(history[0].title, "Third");
+        assert_eq!(history[0].watched_at, at(10800));
+        assert_eq!(db.get_recent_watch_history(2).unwrap().len(), 2);
 
-        // Should still only have one entry
-        let watchlist = db.get_watchlist(user_id).unwrap();
-        assert_eq!(watchlist.len(), 1);
+        // Continue watching follows the latest event per item
+        let ids: Vec<String> = db
+            .get_continue_watching(user_id)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, vec!["movie3", "movie2", "movie1"]);
     }
 
-    // ========================================
-    // Playlist Tests
-    // ========================================
-
     #[test]
-    fn test_create_and_get_playlist() {
+    fn test_repeated_progress_saves_are_one_watch_session() {
         let db = create_test_db().unwrap();
-        let user_id = "test_user";
-        let playlist_id = "playlist1";
-
-        // Create playlist
-        db.create_playlist(
-            playlist_id,
-            "My Playlist",
-            Some("Test description"),
-            user_id,
-        )
-        .unwrap();
+        db.add_to_library(create_test_media_item("movie1", "First"))
+            .unwrap();
+        let at = |secs: i64| chrono::DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap();
+
+        // Saved every ten seconds while playing
+        for (position, secs) in [(10, 0), (20, 10), (30, 20), (40, 30)] {
+            db.record_watch_event("movie1", position, Some(7200), at(secs))
+                .unwrap();
+        }
+        let history = db.get_recent_watch_history(10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].position, 40);
+        assert_eq!(history[0].watched_at, at(30));
+
+        // Picking the movie up again the next day starts a new session
+        db.record_watch_event("movie1", 50, None, at(86_400))
+            .unwrap();
+        let history = db.get_recent_watch_history(10).unwrap();
+        let events: Vec<(i32, Option<i32>)> =
+            history.iter().map(|h| (h.position, h.duration)).collect();
+        assert_eq!(events, vec![(50, None), (40, Some(7200))]);
+    }
+
+    #[test]
+    fn test_readding_item_keeps_its_history_and_playlists() {
+        let db = create_test_db().unwrap();
+        // As in Database::new; deleting an item cascades to both tables
+        db.conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+        db.add_to_library(create_test_media_item("movie1", "First"))
+            .unwrap();
+        db.record_watch_event("movie1", 600, Some(7200), chrono::Utc::now())
+            .unwrap();
+        db.create_playlist("p1", "Favourites", None, "user1")
+            .unwrap();
+        db.add_item_to_playlist("p1", "movie1").unwrap();
+
+        // Re-adding (or re-importing) the item updates it in place
+        let mut updated = create_test_media_item("movie1", "First (Remastered)");
+        updated.watched = true;
+        db.add_to_library(updated).unwrap();
+
+        let items = db.get_library_items().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "First (Remastered)");
+        assert!(items[0].watched);
+        let history = db.get_recent_watch_history(10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].position, 600);
+        let playlist = db.get_playlist_items("p1").unwrap();
+        assert_eq!(playlist.len(), 1);
+        assert_eq!(playlist[0].id, "movie1");
+    }
+
+    #[test]
+    fn test_progress_percent_serialized() {
+        let db = create_test_db().unwrap();
+
+        // 60 seconds into a two minute (120s) item
+        let mut item = create_test_media_item("movie1", "Half Watched");
+        item.duration = Some(2);
+        item.progress = Some(60);
+        db.add_to_library(item).unwrap();
+
+        let items = db.get_library_items().unwrap();
+        assert_eq!(items[0].progress_percent, Some(50.0));
+
+        let json = serde_json::to_value(&items[0]).unwrap();
+        assert_eq!(json["progress_percent"], 50.0);
+    }
+
+    #[test]
+    fn test_duplicate_watchlist_entry() {
+        let db = create_test_db().unwrap();
+        let user_id = "test_user";
+        let media_id = "movie1";
+
+        let item = create_test_media_item(media_id, "Test Movie");
+        db.add_to_library(item).unwrap();
+
+        // Add to watchlist twice (should not error)
+        db.add_to_watchlist(user_id, media_id).unwrap();
+        db.add_to_watchlist(user_id, media_id).unwrap();
+
+        // Should still only have one entry
+        let watchlist = db.get_watchlist(user_id).unwrap();
+        assert_eq!(watchlist.len(), 1);
+    }
+
+    // ========================================
+    // Playlist Tests
+    // ========================================
+
+    #[test]
+    fn test_create_and_get_playlist() {
+        let db = create_test_db().unwrap();
+        let user_id = "test_user";
+        let playlist_id = "playlist1";
+
+        // Create playlist
+        db.create_playlist(
+            playlist_id,
+            "My Playlist",
+            Some("Test description"),
+            user_id,
+        )
+        .unwrap();
 
         // Get playlists for user
         let playlists = db.get_playlists(user_id).unwrap();
@@ -1657,6 +3734,214 @@ mod tests {
         assert_eq!(playlist.item_count, 1);
     }
 
+    #[test]
+    fn test_deleting_media_item_updates_playlist_counts() {
+        let db = create_test_db().unwrap();
+        db.conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+
+        db.create_playlist("playlist1", "First", None, "test_user")
+            .unwrap();
+        db.create_playlist("playlist2", "Second", None, "test_user")
+            .unwrap();
+        db.add_to_library(create_test_media_item("movie1", "Movie 1"))
+            .unwrap();
+        db.add_to_library(create_test_media_item("movie2", "Movie 2"))
+            .unwrap();
+        for playlist_id in ["playlist1", "playlist2"] {
+            db.add_item_to_playlist(playlist_id, "movie1").unwrap();
+            db.add_item_to_playlist(playlist_id, "movie2").unwrap();
+        }
+
+        // playlist_items rows go with the media item via ON DELETE CASCADE
+        db.conn
+            .execute("DELETE FROM media_items WHERE id = ?1", params!["movie1"])
+            .unwrap();
+
+        for playlist_id in ["playlist1", "playlist2"] {
+            let playlist = db.get_playlist(playlist_id).unwrap().unwrap();
+            assert_eq!(playlist.item_count, 1);
+            assert_eq!(db.get_playlist_items(playlist_id).unwrap().len(), 1);
+        }
+
+        // Explicit removal still agrees with the trigger
+        db.remove_item_from_playlist("playlist1", "movie2").unwrap();
+        let playlist = db.get_playlist("playlist1").unwrap().unwrap();
+        assert_eq!(playlist.item_count, 0);
+    }
+
+    #[test]
+    fn test_add_library_items_batch() {
+        let db = create_test_db().unwrap();
+
+        let mut items: Vec<MediaItem> = (0..1000)
+            .map(|i| create_test_media_item(&format!("movie{}", i), &format!("Movie {}", i)))
+            .collect();
+        items[0].title = "Interstellar".to_string();
+        assert_eq!(db.add_library_items_batch(items).unwrap(), 1000);
+        assert_eq!(db.get_library_items().unwrap().len(), 1000);
+
+        // The FTS triggers still fire for batched inserts
+        let found = db.search_library_fts("interstellar", 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "movie0");
+
+        // INSERT OR REPLACE: re-adding an id updates it rather than failing
+        let mut replacement = create_test_media_item("movie1", "Movie One Remastered");
+        replacement.year = Some(2025);
+        db.add_library_items_batch(vec![replacement]).unwrap();
+        assert_eq!(db.get_library_items().unwrap().len(), 1000);
+        let updated = db.get_media_items_by_ids(&["movie1".to_string()]).unwrap();
+        assert_eq!(updated[0].title, "Movie One Remastered");
+
+        // Everything goes through one transaction: an invalid item at the end
+        // rolls back the rows written before it
+        let mut batch: Vec<MediaItem> = (1000..1010)
+            .map(|i| create_test_media_item(&format!("movie{}", i), &format!("Movie {}", i)))
+            .collect();
+        batch.push(MediaItem {
+            title: "  ".to_string(),
+            ..create_test_media_item("broken", "Broken")
+        });
+        assert!(db.add_library_items_batch(batch).is_err());
+        assert_eq!(db.get_library_items().unwrap().len(), 1000);
+        assert!(db.conn.is_autocommit());
+    }
+
+    #[test]
+    fn test_media_artwork_round_trip() {
+        let db = create_test_db().unwrap();
+        let item = MediaItem::builder("tt0903747", "Breaking Bad", MediaType::TvShow)
+            .poster_url("https://example.com/poster.jpg")
+            .logo_url("https://example.com/logo.png")
+            .thumb_url("https://example.com/thumb.jpg")
+            .build()
+            .unwrap();
+        db.add_to_library(item).unwrap();
+        db.add_to_favorites("default_user", "tt0903747").unwrap();
+
+        let stored = db.get_favorites("default_user").unwrap().remove(0);
+        assert_eq!(
+            stored.logo_url.as_deref(),
+            Some("https://example.com/logo.png")
+        );
+        assert_eq!(
+            stored.thumb_url.as_deref(),
+            Some("https://example.com/thumb.jpg")
+        );
+
+        // Exported JSON carries the artwork back in on import
+        let exported = serde_json::to_string(&db.get_library_items().unwrap()).unwrap();
+        let imported: Vec<MediaItem> = serde_json::from_str(&exported).unwrap();
+        let other = create_test_db().unwrap();
+        other.add_library_items_batch(imported).unwrap();
+        let ids = vec!["tt0903747".to_string()];
+        let restored = other.get_media_items_by_ids(&ids).unwrap().remove(0);
+        assert_eq!(restored.artwork(), stored.artwork());
+
+        // Exports from before these fields existed still load
+        let old: MediaItem = serde_json::from_str(
+            r#"{"id":"tt1","title":"Old","media_type":"Movie","year":null,"genre":[],
+                "description":null,"poster_url":null,"backdrop_url":null,"rating":null,
+                "duration":null,"added_to_library":null,"watched":false,"progress":null}"#,
+        )
+        .unwrap();
+        assert!(old.logo_url.is_none() && old.thumb_url.is_none());
+
+        // Filling only touches empty slots
+        let found = Artwork {
+            backdrop_url: Some("https://example.com/backdrop.jpg".to_string()),
+            logo_url: Some("https://example.com/other-logo.png".to_string()),
+            ..Default::default()
+        };
+        assert!(db.fill_missing_artwork("tt0903747", &found).unwrap());
+        let filled = db.get_media_items_by_ids(&ids).unwrap().remove(0);
+        assert_eq!(
+            filled.backdrop_url.as_deref(),
+            Some("https://example.com/backdrop.jpg")
+        );
+        assert_eq!(
+            filled.logo_url.as_deref(),
+            Some("https://example.com/logo.png")
+        );
+    }
+
+    #[test]
+    fn test_artwork_lookups_wait_before_retrying() {
+        let db = create_test_db().unwrap();
+        for (id, title) in [("tt0000001", "Looked Up"), ("tt0000002", "Never Looked Up")] {
+            db.add_to_library(create_test_media_item(id, title))
+                .unwrap();
+        }
+        let ids = vec!["tt0000001".to_string(), "tt0000002".to_string()];
+        let now = chrono::Utc::now();
+        let due = |checked_before| -> Vec<String> {
+            let mut due: Vec<String> = db
+                .get_media_items_due_artwork(&ids, checked_before)
+                .unwrap()
+                .into_iter()
+                .map(|item| item.id)
+                .collect();
+            due.sort();
+            due
+        };
+
+        assert_eq!(due(now), ids);
+        db.mark_artwork_checked(&ids[..1], now).unwrap();
+        assert_eq!(due(now), vec!["tt0000002"]);
+        // Once the lookup is old enough it is due again
+        assert_eq!(due(now + chrono::Duration::seconds(1)), ids);
+
+        // Re-adding the item keeps its lookup time
+        db.add_to_library(create_test_media_item("tt0000001", "Looked Up"))
+            .unwrap();
+        assert_eq!(due(now), vec!["tt0000002"]);
+    }
+
+    #[test]
+    fn test_delete_media_item_removes_all_references() {
+        let db = create_test_db().unwrap();
+        db.conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+
+        db.add_to_library(create_test_media_item("movie1", "Movie 1"))
+            .unwrap();
+        db.add_to_library(create_test_media_item("movie2", "Movie 2"))
+            .unwrap();
+        for user_id in ["user1", "user2"] {
+            db.add_to_watchlist(user_id, "movie1").unwrap();
+            db.add_to_favorites(user_id, "movie1").unwrap();
+            db.add_to_favorites(user_id, "movie2").unwrap();
+        }
+        db.create_playlist("playlist1", "First", None, "user1")
+            .unwrap();
+        db.add_item_to_playlist("playlist1", "movie1").unwrap();
+        db.add_item_to_playlist("playlist1", "movie2").unwrap();
+
+        assert!(db.delete_media_item("movie1").unwrap());
+        assert!(!db.delete_media_item("movie1").unwrap());
+
+        let ids = |items: Vec<MediaItem>| items.into_iter().map(|m| m.id).collect::<Vec<_>>();
+        assert_eq!(ids(db.get_library_items().unwrap()), vec!["movie2"]);
+        for user_id in ["user1", "user2"] {
+            assert!(db.get_watchlist(user_id).unwrap().is_empty());
+            assert_eq!(ids(db.get_favorites(user_id).unwrap()), vec!["movie2"]);
+        }
+        assert_eq!(
+            ids(db.get_playlist_items("playlist1").unwrap()),
+            vec!["movie2"]
+        );
+        assert_eq!(db.get_playlist("playlist1").unwrap().unwrap().item_count, 1);
+
+        let orphans: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM library_items WHERE media_id = 'movie1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(orphans, 0);
+    }
+
     #[test]
     fn test_reorder_playlist_items() {
         let db = create_test_db().unwrap();
@@ -1825,7 +4110,7 @@ mod tests {
         let addon_id = "test-addon";
 
         // Record successful request
-        db.record_addon_health(addon_id, 150, true, None, 10, "catalog")
+        db.record_addon_health(addon_id, 150, true, None, 10, 0, "catalog")
             .unwrap();
 
         // Get health summary
@@ -1840,6 +4125,29 @@ mod tests {
         assert_eq!(summary.success_rate, 1.0); // success_rate is a fraction (0.0-1.0)
         assert!(summary.avg_response_time_ms > 0);
         assert!(summary.health_score > 0.0);
+        assert_eq!(summary.invalid_count, 0);
+    }
+
+    #[test]
+    fn test_invalid_entries_add_up_across_requests() {
+        let db = create_test_db().unwrap();
+        db.record_addon_health_at("sloppy", 100, true, None, 8, 2, "catalog", 100)
+            .unwrap();
+        db.record_addon_health_at("sloppy", 100, true, None, 5, 3, "stream", 200)
+            .unwrap();
+        db.record_addon_health_at("tidy", 100, true, None, 5, 0, "stream", 200)
+            .unwrap();
+
+        let summary = db.get_addon_health_summary("sloppy").unwrap().unwrap();
+        assert_eq!(summary.invalid_count, 5);
+        let counts: Vec<(String, i64)> = db
+            .get_all_addon_health_summaries()
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.addon_id, s.invalid_count))
+            .collect();
+        assert!(counts.contains(&("sloppy".to_string(), 5)));
+        assert!(counts.contains(&("tidy".to_string(), 0)));
     }
 
     #[test]
@@ -1849,15 +4157,15 @@ mod tests {
 
         // Record 2 successful and 1 failed request (with delays to avoid timestamp collision)
         // Note: timestamps are in seconds, so we need 1+ second delays
-        db.record_addon_health(addon_id, 100, true, None, 5, "catalog")
+        db.record_addon_health(addon_id, 100, true, None, 5, 0, "catalog")
             .unwrap();
         std::thread::sleep(std::time::Duration::from_secs(1));
 
-        db.record_addon_health(addon_id, 120, true, None, 3, "stream")
+        db.record_addon_health(addon_id, 120, true, None, 3, 0, "stream")
             .unwrap();
         std::thread::sleep(std::time::Duration::from_secs(1));
 
-        db.record_addon_health(addon_id, 0, false, Some("Timeout"), 0, "catalog")
+        db.record_addon_health(addon_id, 0, false, Some("Timeout"), 0, 0, "catalog")
             .unwrap();
 
         // Get health summary
@@ -1869,16 +4177,111 @@ mod tests {
         assert_eq!(summary.last_error, Some("Timeout".to_string()));
     }
 
+    #[test]
+    fn test_addon_health_last_error_is_newest_failure() {
+        let db = create_test_db().unwrap();
+        let addon_id = "flaky-addon";
+
+        // Interleaved successes and failures, inserted out of timestamp order
+        for (timestamp, success, error) in [
+            (103, false, Some("Timeout")),
+            (100, false, Some("HTTP 500")),
+            (105, true, None),
+            (104, false, Some("Connection refused")),
+            (101, true, None),
+            (102, false, None),
+        ] {
+            db.conn
+                .execute(
+                    "INSERT INTO addon_health
+                     (addon_id, timestamp, response_time_ms, success, error_message, item_count, operation_type)
+                     VALUES (?1, ?2, 100, ?3, ?4, 0, 'stream')",
+                    params![addon_id, timestamp, success, error],
+                )
+                .unwrap();
+        }
+        db.update_addon_health_summary(addon_id).unwrap();
+
+        let summary = db.get_addon_health_summary(addon_id).unwrap().unwrap();
+        assert_eq!(summary.failed_requests, 4);
+        assert_eq!(summary.last_error, Some("Connection refused".to_string()));
+
+        // A newer failure without a message clears the stale error text
+        db.conn
+            .execute(
+                "INSERT INTO addon_health
+                 (addon_id, timestamp, response_time_ms, success, error_message, item_count, operation_type)
+                 VALUES (?1, 106, 100, 0, NULL, 0, 'stream')",
+                params![addon_id],
+            )
+            .unwrap();
+        db.update_addon_health_summary(addon_id).unwrap();
+
+        let summary = db.get_addon_health_summary(addon_id).unwrap().unwrap();
+        assert_eq!(summary.last_error, None);
+    }
+
+    #[test]
+    fn test_last_addon_result_is_newest_request() {
+        let db = create_test_db().unwrap();
+        assert_eq!(db.last_addon_result("flaky").unwrap(), None);
+
+        // Recorded out of order: the newest request decides
+        db.record_addon_health_at("flaky", 100, true, None, 3, 0, "stream", 200)
+            .unwrap();
+        db.record_addon_health_at("flaky", 100, false, Some("Timeout"), 0, 0, "stream", 100)
+            .unwrap();
+        assert_eq!(db.last_addon_result("flaky").unwrap(), Some(true));
+
+        db.record_addon_health_at("flaky", 100, false, Some("Timeout"), 0, 0, "stream", 300)
+            .unwrap();
+        assert_eq!(db.last_addon_result("flaky").unwrap(), Some(false));
+    }
+
+    #[test]
+    fn test_export_addon_health_csv() {
+        let db = create_test_db().unwrap();
+
+        for (addon_id, timestamp, success, item_count, error) in [
+            ("torrentio", 100, true, 12, None),
+            ("cinemeta", 200, false, 0, Some("HTTP 502: \"Bad Gateway\", retry later")),
+            ("cinemeta", 300, false, 0, Some("line one\nline two")),
+        ] {
+            db.conn
+                .execute(
+                    "INSERT INTO addon_health
+                     (addon_id, timestamp, response_time_ms, success, error_message, item_count, operation_type)
+                     VALUES (?1, ?2, 150, ?3, ?4, ?5, 'stream')",
+                    params![addon_id, timestamp, success, error, item_count],
+                )
+                .unwrap();
+        }
+
+        let csv = db.export_addon_health_csv(None).unwrap();
+        assert_eq!(
+            csv,
+            "addon_id,timestamp,response_time_ms,success,operation_type,item_count,error\n\
+             torrentio,100,150,true,stream,12,\n\
+             cinemeta,200,150,false,stream,0,\"HTTP 502: \"\"Bad Gateway\"\", retry later\"\n\
+             cinemeta,300,150,false,stream,0,\"line one\nline two\"\n"
+        );
+
+        let recent = db.export_addon_health_csv(Some(250)).unwrap();
+        assert_eq!(recent.lines().count(), 3); // header + one record spanning two lines
+        assert!(recent.contains("cinemeta,300,"));
+        assert!(!recent.contains("torrentio"));
+    }
+
     #[test]
     fn test_multiple_addon_health_summaries() {
         let db = create_test_db().unwrap();
 
         // Record health for multiple addons
-        db.record_addon_health("addon1", 50, true, None, 10, "catalog")
+        db.record_addon_health("addon1", 50, true, None, 10, 0, "catalog")
             .unwrap();
-        db.record_addon_health("addon2", 200, true, None, 5, "catalog")
+        db.record_addon_health("addon2", 200, true, None, 5, 0, "catalog")
             .unwrap();
-        db.record_addon_health("addon3", 100, false, Some("Error"), 0, "catalog")
+        db.record_addon_health("addon3", 100, false, Some("Error"), 0, 0, "catalog")
             .unwrap();
 
         // Get all summaries
@@ -1890,13 +4293,35 @@ mod tests {
         assert_eq!(summaries[0].addon_id, "addon1");
     }
 
+    #[test]
+    fn test_addon_health_summaries_tie_ordering() {
+        let db = create_test_db().unwrap();
+
+        // All three score 100 (fast and successful); recorded out of order on purpose
+        db.record_addon_health("beta", 400, true, None, 5, 0, "catalog")
+            .unwrap();
+        db.record_addon_health("alpha", 400, true, None, 5, 0, "catalog")
+            .unwrap();
+        db.record_addon_health("zeta", 100, true, None, 5, 0, "catalog")
+            .unwrap();
+
+        let summaries = db.get_all_addon_health_summaries().unwrap();
+        assert!(summaries
+            .windows(2)
+            .all(|w| w[0].health_score == w[1].health_score));
+
+        // Equal scores: faster average response first, then addon id
+        let ids: Vec<&str> = summaries.iter().map(|s| s.addon_id.as_str()).collect();
+        assert_eq!(ids, vec!["zeta", "alpha", "beta"]);
+    }
+
     #[test]
     fn test_addon_health_score_calculation() {
         let db = create_test_db().unwrap();
         let addon_id = "test-addon";
 
         // Record a perfect request (fast and successful)
-        db.record_addon_health(addon_id, 50, true, None, 10, "catalog")
+        db.record_addon_health(addon_id, 50, true, None, 10, 0, "catalog")
             .unwrap();
 
         let summary = db.get_addon_health_summary(addon_id).unwrap().unwrap();
@@ -1908,7 +4333,7 @@ mod tests {
         // Note: timestamps are in seconds, so we need 1+ second delays
         for _ in 0..5 {
             std::thread::sleep(std::time::Duration::from_secs(1));
-            db.record_addon_health(addon_id, 2000, true, None, 10, "catalog")
+            db.record_addon_health(addon_id, 2000, true, None, 10, 0, "catalog")
                 .unwrap();
         }
 
@@ -1926,7 +4351,7 @@ mod tests {
         let addon_id = "test-addon";
 
         // Record some health data
-        db.record_addon_health(addon_id, 100, true, None, 10, "catalog")
+        db.record_addon_health(addon_id, 100, true, None, 10, 0, "catalog")
             .unwrap();
 
         // Verify record exists
@@ -1940,6 +4365,38 @@ mod tests {
         assert_eq!(deleted, 0);
     }
 
+    #[test]
+    fn test_optimize_populated_db() {
+        let db = create_test_db().unwrap();
+        let items = (0..200)
+            .map(|i| create_test_media_item(&format!("movie{}", i), &format!("Movie {}", i)))
+            .collect();
+        db.add_library_items_batch(items).unwrap();
+        db.record_addon_health("cinemeta", 150, true, None, 10, 0, "catalog")
+            .unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO addon_health (addon_id, timestamp, response_time_ms, success, operation_type)
+                 VALUES ('cinemeta', 1000, 200, 0, 'stream')",
+                [],
+            )
+            .unwrap();
+        for i in 0..100 {
+            db.delete_media_item(&format!("movie{}", i)).unwrap();
+        }
+
+        let report = db.optimize(true).unwrap();
+        // VACUUM is skipped for in-memory databases
+        assert!(!report.vacuumed);
+        assert_eq!(report.health_records_removed, 1);
+        assert!(report.size_before > 0);
+        assert_eq!(
+            report.reclaimed_bytes,
+            report.size_before.saturating_sub(report.size_after)
+        );
+        assert_eq!(db.get_library_items().unwrap().len(), 100);
+    }
+
     #[test]
     fn test_addon_health_summary_for_nonexistent_addon() {
         let db = create_test_db().unwrap();
@@ -1948,4 +4405,995 @@ mod tests {
         let summary = db.get_addon_health_summary("nonexistent").unwrap();
         assert!(summary.is_none());
     }
+
+    #[test]
+    fn test_addon_last_used_tracking() {
+        let db = create_test_db().unwrap();
+        db.save_addon(&create_test_addon("busy", 0)).unwrap();
+        db.save_addon(&create_test_addon("idle", 0)).unwrap();
+
+        // Failures and empty responses don't count as use
+        let now = chrono::Utc::now().timestamp();
+        db.record_addon_health_at("busy", 100, false, Some("Timeout"), 0, 0, "stream", now - 2)
+            .unwrap();
+        assert_eq!(db.get_addon_last_used("busy").unwrap(), None);
+        db.record_addon_health_at("busy", 100, true, None, 0, 0, "stream", now - 1)
+            .unwrap();
+        assert_eq!(db.get_addon_last_used("busy").unwrap(), None);
+
+        db.record_addon_health_at("busy", 100, true, None, 4, 0, "stream", now)
+            .unwrap();
+        assert_eq!(db.get_addon_last_used("busy").unwrap(), Some(now));
+
+        // Both were just installed, so neither is unused yet
+        assert!(db.get_unused_addons(30).unwrap().is_empty());
+
+        // Backdate: "busy" last used 10 days ago, "idle" installed 40 days ago and never used
+        let ten_days_ago = chrono::Utc::now().timestamp() - 10 * 24 * 3600;
+        db.conn
+            .execute(
+                "UPDATE addon_usage SET last_used = ?1 WHERE addon_id = 'busy'",
+                params![ten_days_ago],
+            )
+            .unwrap();
+        let forty_days_ago = (chrono::Utc::now() - chrono::Duration::days(40)).to_rfc3339();
+        db.conn
+            .execute(
+                "UPDATE addons SET installed_at = ?1",
+                params![forty_days_ago],
+            )
+            .unwrap();
+
+        let unused: Vec<String> = db
+            .get_unused_addons(30)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(unused, vec!["idle".to_string()]);
+
+        let mut unused: Vec<String> = db
+            .get_unused_addons(7)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        unused.sort();
+        assert_eq!(unused, vec!["busy".to_string(), "idle".to_string()]);
+    }
+
+    #[test]
+    fn test_set_addons_enabled_in_bulk() {
+        let db = create_test_db().unwrap();
+        for id in ["one", "two", "three", "other"] {
+            db.save_addon(&create_test_addon(id, 0)).unwrap();
+        }
+
+        let ids = vec!["one".to_string(), "two".to_string(), "three".to_string()];
+        let updated = db.set_addons_enabled(&ids, false).unwrap();
+        assert_eq!(updated, 3);
+
+        let enabled = |db: &Database, id: &str| {
+            db.get_addons()
+                .unwrap()
+                .into_iter()
+                .find(|a| a.id == id)
+                .unwrap()
+                .enabled
+        };
+        assert!(!enabled(&db, "one"));
+        assert!(!enabled(&db, "two"));
+        assert!(!enabled(&db, "three"));
+        assert!(enabled(&db, "other"));
+
+        // Unknown ids are skipped without failing the batch
+        let ids = vec!["one".to_string(), "missing".to_string()];
+        assert_eq!(db.set_addons_enabled(&ids, true).unwrap(), 1);
+        assert!(enabled(&db, "one"));
+        assert!(!enabled(&db, "two"));
+
+        assert_eq!(db.set_all_addons_enabled(false).unwrap(), 4);
+        assert!(db.get_addons().unwrap().iter().all(|a| !a.enabled));
+    }
+
+    #[test]
+    fn test_install_addon_reports_url_conflict() {
+        let db = create_test_db().unwrap();
+        let mut original = create_test_addon("org.example.torrents", 5);
+        original.url = "https://mirror-a.example.com".to_string();
+        original.enabled = false;
+        assert!(db.install_addon(&original, false).unwrap().installed);
+
+        // Reinstalling from the same URL (with manifest.json) is not a conflict
+        let mut same = original.clone();
+        same.url = "https://mirror-a.example.com/manifest.json".to_string();
+        let result = db.install_addon(&same, false).unwrap();
+        assert!(result.installed);
+        assert!(result.conflict.is_none());
+        db.save_addon(&original).unwrap();
+
+        let mut mirror = create_test_addon("org.example.torrents", 0);
+        mirror.url = "https://mirror-b.example.com".to_string();
+        let result = db.install_addon(&mirror, false).unwrap();
+        assert!(!result.installed);
+        let conflict = result.conflict.unwrap();
+        assert_eq!(conflict.addon_id, "org.example.torrents");
+        assert_eq!(conflict.existing_url, "https://mirror-a.example.com");
+        assert_eq!(conflict.new_url, "https://mirror-b.example.com");
+
+        let addons = db.get_addons().unwrap();
+        assert_eq!(addons.len(), 1);
+        assert_eq!(addons[0].url, "https://mirror-a.example.com");
+
+        // Replacing moves the entry but keeps the user's settings
+        let result = db.install_addon(&mirror, true).unwrap();
+        assert!(result.installed);
+        let addons = db.get_addons().unwrap();
+        assert_eq!(addons.len(), 1);
+        assert_eq!(addons[0].url, "https://mirror-b.example.com");
+        assert!(!addons[0].enabled);
+        assert_eq!(addons[0].priority, 5);
+    }
+
+    #[test]
+    fn test_reorder_addons() {
+        let db = create_test_db().unwrap();
+        for (id, priority) in [("cinemeta", 10), ("torrentio", 5), ("opensubtitles", 1)] {
+            db.save_addon(&create_test_addon(id, priority)).unwrap();
+        }
+        let ids_by_priority = |db: &Database| {
+            let mut addons = db.get_addons().unwrap();
+            addons.sort_by(|a, b| b.priority.cmp(&a.priority));
+            addons.into_iter().map(|a| a.id).collect::<Vec<_>>()
+        };
+
+        let order = vec![
+            "opensubtitles".to_string(),
+            "cinemeta".to_string(),
+            "torrentio".to_string(),
+        ];
+        db.reorder_addons(&order).unwrap();
+        assert_eq!(ids_by_priority(&db), order);
+
+        // An unknown id rejects the whole reorder
+        let bad = vec!["torrentio".to_string(), "missing".to_string()];
+        assert!(db.reorder_addons(&bad).is_err());
+        assert_eq!(ids_by_priority(&db), order);
+
+        // So do duplicates and lists leaving an addon out
+        let twice = vec![
+            "torrentio".to_string(),
+            "torrentio".to_string(),
+            "cinemeta".to_string(),
+        ];
+        let err = db.reorder_addons(&twice).unwrap_err();
+        assert_eq!(err.to_string(), "Addon listed twice: torrentio");
+        let partial = vec!["torrentio".to_string(), "cinemeta".to_string()];
+        let err = db.reorder_addons(&partial).unwrap_err();
+        assert_eq!(err.to_string(), "Reorder lists 2 of 3 installed addons");
+        assert_eq!(ids_by_priority(&db), order);
+
+        assert_eq!(db.update_addon_priority("torrentio", 100).unwrap(), 1);
+        assert_eq!(db.update_addon_priority("missing", 100).unwrap(), 0);
+        assert_eq!(ids_by_priority(&db)[0], "torrentio");
+    }
+
+    #[test]
+    fn test_builtin_addons_seeded_once() {
+        let db = create_test_db().unwrap();
+        let builtin = vec![create_test_addon("cinemeta", 10), create_test_addon("watchhub", 9)];
+
+        assert!(db.should_seed_builtin_addons("default_user").unwrap());
+        let seeded = db.seed_builtin_addons("default_user", &builtin).unwrap();
+        assert_eq!(seeded.len(), 2);
+
+        // Second call is a no-op
+        let seeded_again = db.seed_builtin_addons("default_user", &builtin).unwrap();
+        assert!(seeded_again.is_empty());
+
+        // Plain queries never seed
+        assert_eq!(db.get_addons().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_builtin_addons_respect_opt_out() {
+        let db = create_test_db().unwrap();
+        let profile = UserProfile {
+            id: "default_user".to_string(),
+            username: "User".to_string(),
+            email: None,
+            preferences: UserPreferences {
+                install_builtin_addons: false,
+                ..UserPreferences::default()
+            },
+            library_items: vec![],
+            watchlist: vec![],
+            favorites: vec![],
+        };
+        db.save_user_profile(&profile).unwrap();
+
+        let seeded = db
+            .seed_builtin_addons("default_user", &[create_test_addon("cinemeta", 10)])
+            .unwrap();
+        assert!(seeded.is_empty());
+        assert!(db.get_addons().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_uninstalled_builtins_not_resurrected() {
+        let db = create_test_db().unwrap();
+        let builtin = vec![create_test_addon("cinemeta", 10), create_test_addon("watchhub", 9)];
+        db.seed_builtin_addons("default_user", &builtin).unwrap();
+
+        // User removes every addon
+        for addon in &builtin {
+            db.delete_addon(&addon.id).unwrap();
+        }
+
+        assert!(!db.should_seed_builtin_addons("default_user").unwrap());
+        let seeded = db.seed_builtin_addons("default_user", &builtin).unwrap();
+        assert!(seeded.is_empty());
+        assert!(db.get_addons().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_episodes_ordered_and_next_unwatched() {
+        let db = create_test_db().unwrap();
+        let series = MediaItem::builder("tt0903747", "Breaking Bad", MediaType::TvShow)
+            .build()
+            .unwrap();
+        db.add_to_library(series).unwrap();
+
+        // Inserted out of order on purpose
+        for (season, episode) in [(2, 1), (1, 2), (1, 1)] {
+            db.upsert_episode(&create_test_episode("tt0903747", season, episode))
+                .unwrap();
+        }
+
+        let episodes = db.get_episodes("tt0903747").unwrap();
+        let order: Vec<(i32, i32)> = episodes.iter().map(|e| (e.season, e.episode)).collect();
+        assert_eq!(order, vec![(1, 1), (1, 2), (2, 1)]);
+
+        let next = db.get_next_unwatched_episode("tt0903747").unwrap().unwrap();
+        assert_eq!(next.id, "tt0903747:1:1");
+
+        db.update_episode_progress("tt0903747:1:1", 2700, true).unwrap();
+        db.update_episode_progress("tt0903747:1:2", 2700, true).unwrap();
+        let next = db.get_next_unwatched_episode("tt0903747").unwrap().unwrap();
+        assert_eq!(next.id, "tt0903747:2:1");
+
+        // Refreshing metadata keeps the watched state
+        let mut refreshed = create_test_episode("tt0903747", 1, 1);
+        refreshed.title = "Pilot".to_string();
+        db.upsert_episode(&refreshed).unwrap();
+        let episode = db.get_episode("tt0903747:1:1").unwrap().unwrap();
+        assert_eq!(episode.title, "Pilot");
+        assert!(episode.watched);
+        assert_eq!(episode.progress, 2700);
+
+        db.update_episode_progress("tt0903747:2:1", 0, true).unwrap();
+        assert!(db.get_next_unwatched_episode("tt0903747").unwrap().is_none());
+        assert!(db.update_episode_progress("missing", 0, true).is_err());
+    }
+
+    #[test]
+    fn test_rebuild_search_index_after_corruption() {
+        let db = create_test_db().unwrap();
+        db.add_to_library(create_test_media_item("tt0133093", "The Matrix"))
+            .unwrap();
+        db.add_to_library(create_test_media_item("tt0816692", "Interstellar"))
+            .unwrap();
+
+        let search = |term: &str| -> Vec<String> {
+            let mut stmt = db
+                .conn
+                .prepare(
+                    "SELECT m.id FROM media_items m
+                     INNER JOIN media_items_fts fts ON m.rowid = fts.rowid
+                     WHERE media_items_fts MATCH ?1",
+                )
+                .unwrap();
+            stmt.query_map(params![term], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<Vec<String>, _>>()
+                .unwrap()
+        };
+        assert_eq!(search("matrix"), vec!["tt0133093"]);
+        assert!(!db.ensure_search_index().unwrap());
+
+        // Wipe the index behind the triggers' back
+        db.conn
+            .execute(
+                "INSERT INTO media_items_fts(media_items_fts) VALUES('delete-all')",
+                [],
+            )
+            .unwrap();
+        assert!(search("matrix").is_empty());
+
+        assert!(db.ensure_search_index().unwrap());
+        assert_eq!(search("matrix"), vec!["tt0133093"]);
+        assert_eq!(search("interstellar"), vec!["tt0816692"]);
+        assert_eq!(db.rebuild_search_index().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_addon_config_overwrite_and_cascade_delete() {
+        // new_in_memory enables foreign keys, which the cascade relies on
+        let db = Database::new_in_memory().unwrap();
+        let addon = create_test_addon("torrentio", 0);
+        db.save_addon(&addon).unwrap();
+
+        let set = |key: &str, value: &str| db.set_addon_config("torrentio", key, value).unwrap();
+        set("quality", "720p");
+        set("quality", "1080p");
+        set("providers", "yts,eztv");
+        set("debrid key", "a b/c");
+        let quality = db.get_addon_config("torrentio", "quality").unwrap();
+        assert_eq!(quality.as_deref(), Some("1080p"));
+        assert_eq!(db.get_all_addon_config("torrentio").unwrap().len(), 3);
+
+        // Re-saving the addon (e.g. toggling it) keeps its config
+        let disabled = Addon {
+            enabled: false,
+            ..addon
+        };
+        db.save_addon(&disabled).unwrap();
+        let loaded = db.get_addons().unwrap().pop().unwrap();
+        assert_eq!(loaded.config["providers"], "yts,eztv");
+        assert_eq!(
+            loaded.base_url(),
+            "https://torrentio.example.com/debrid%20key=a%20b%2Fc|providers=yts,eztv|quality=1080p"
+        );
+        // Config may hold secrets, so it never reaches the frontend
+        let json = serde_json::to_value(&loaded).unwrap();
+        assert!(json.get("config").is_none());
+
+        db.delete_addon_config("torrentio", "providers").unwrap();
+        assert_eq!(db.get_addon_config("torrentio", "providers").unwrap(), None);
+
+        db.delete_addon("torrentio").unwrap();
+        assert!(db.get_all_addon_config("torrentio").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_app_settings_set_overwrite_and_list() {
+        let db = create_test_db().unwrap();
+        assert_eq!(db.get_app_setting("last_tab").unwrap(), None);
+
+        db.set_app_setting("last_tab", r#""library""#).unwrap();
+        db.set_app_setting("last_tab", r#""discover""#).unwrap();
+        db.set_app_setting("window", r#"{"x":10,"y":20}"#).unwrap();
+        assert_eq!(
+            db.get_app_setting("last_tab").unwrap().as_deref(),
+            Some(r#""discover""#)
+        );
+
+        let all = db.get_all_app_settings().unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all["window"], r#"{"x":10,"y":20}"#);
+
+        // Values must be JSON, so a bare word is rejected
+        assert!(db.set_app_setting("onboarded", "yes").is_err());
+        assert_eq!(db.get_app_setting("onboarded").unwrap(), None);
+    }
+
+    #[test]
+    fn test_search_library_highlighted() {
+        let db = create_test_db().unwrap();
+        let mut matrix = create_test_media_item("tt0133093", "The Matrix");
+        matrix.description =
+            Some("A hacker discovers that reality is a simulation called the Matrix".to_string());
+        db.add_to_library(matrix).unwrap();
+        db.add_to_library(create_test_media_item("tt0816692", "Interstellar"))
+            .unwrap();
+
+        let hits = db.search_library_highlighted("matrix", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].item.id, "tt0133093");
+        assert_eq!(hits[0].title_snippet, "The <mark>Matrix</mark>");
+        assert!(hits[0]
+            .description_snippet
+            .as_deref()
+            .unwrap()
+            .contains("called the <mark>Matrix</mark>"));
+
+        // Prefix search and FTS syntax in user input
+        let prefixed = db.search_library_highlighted("inter*", 10).unwrap();
+        assert_eq!(prefixed[0].title_snippet, "<mark>Interstellar</mark>");
+        assert!(db
+            .search_library_highlighted("matrix\" AND (", 10)
+            .unwrap()
+            .is_empty());
+        assert!(db.search_library_highlighted("   ", 10).unwrap().is_empty());
+
+        // Addon-supplied text is escaped; only the highlight markers are HTML
+        let mut injected = create_test_media_item("tt9999999", "Dune <img src=x onerror=alert(1)>");
+        injected.description = Some("Spice & <script>dune</script>".to_string());
+        db.add_to_library(injected).unwrap();
+        let hits = db.search_library_highlighted("dune", 10).unwrap();
+        assert_eq!(
+            hits[0].title_snippet,
+            "<mark>Dune</mark> &lt;img src=x onerror=alert(1)&gt;"
+        );
+        assert_eq!(
+            hits[0].description_snippet.as_deref(),
+            Some("Spice &amp; &lt;script&gt;<mark>dune</mark>&lt;/script&gt;")
+        );
+    }
+
+    #[test]
+    fn test_search_library_fts() {
+        let db = create_test_db().unwrap();
+        for (id, title) in [
+            ("tt0468569", "The Dark Knight"),
+            ("tt0112817", "Dark City"),
+            ("tt0133093", "The Matrix"),
+            ("tt0234215", "The Matrix Reloaded"),
+        ] {
+            db.add_to_library(create_test_media_item(id, title)).unwrap();
+        }
+        let ids = |items: Vec<MediaItem>| -> Vec<String> {
+            let mut ids: Vec<String> = items.into_iter().map(|i| i.id).collect();
+            ids.sort();
+            ids
+        };
+
+        // Multi-word queries match every word, in any order
+        assert_eq!(
+            ids(db.search_library_fts("knight dark", 10).unwrap()),
+            vec!["tt0468569"]
+        );
+        assert_eq!(
+            ids(db.search_library_fts("mat*", 10).unwrap()),
+            vec!["tt0133093", "tt0234215"]
+        );
+        assert_eq!(db.search_library_fts("matrix", 1).unwrap().len(), 1);
+
+        // Invalid FTS syntax falls back to LIKE instead of failing
+        assert!(db.search_library_fts("\"a AND", 10).is_ok());
+        assert_eq!(
+            ids(db.search_library_fts("reloaded AND", 10).unwrap()),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            ids(db.search_library_fts("(city", 10).unwrap()),
+            vec!["tt0112817"]
+        );
+
+        // Text-only filters take the FTS path; combined filters still work
+        let filters = SearchFilters {
+            query: Some("dark".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(db.search_library_with_filters(&filters).unwrap().len(), 2);
+        let filters = SearchFilters {
+            query: Some("dark".to_string()),
+            genres: vec!["Action".to_string()],
+            sort_by: Some("title_asc".to_string()),
+            ..Default::default()
+        };
+        let titles: Vec<String> = db
+            .search_library_with_filters(&filters)
+            .unwrap()
+            .into_iter()
+            .map(|i| i.title)
+            .collect();
+        assert_eq!(titles, vec!["Dark City", "The Dark Knight"]);
+    }
+
+    #[test]
+    fn test_favorite_channels_and_groups() {
+        use crate::models::{ChannelGroup, LiveTvChannel};
+
+        let db = create_test_db().unwrap();
+        let channel = |id: &str, name: &str, group: Option<&str>| LiveTvChannel {
+            id: id.to_string(),
+            name: name.to_string(),
+            logo: None,
+            group: group.map(str::to_string),
+            tvg_id: None,
+            stream_url: format!("https://live.example.com/{}.m3u8", id),
+        };
+        let channels = vec![
+            channel("bbc-one", "BBC One", Some("UK")),
+            channel("itv", "ITV", Some("UK")),
+            channel("espn", "ESPN", Some("Sports")),
+            channel("local", "Local", None),
+        ];
+        db.upsert_live_tv_channels(&channels).unwrap();
+
+        let group = |name: Option<&str>, channel_count| ChannelGroup {
+            name: name.map(str::to_string),
+            channel_count,
+        };
+        assert_eq!(
+            db.get_channel_groups().unwrap(),
+            vec![
+                group(Some("Sports"), 1),
+                group(Some("UK"), 2),
+                group(None, 1)
+            ]
+        );
+
+        db.add_favorite_channel("default_user", "itv").unwrap();
+        db.add_favorite_channel("default_user", "bbc-one").unwrap();
+        db.add_favorite_channel("default_user", "bbc-one").unwrap();
+        db.add_favorite_channel("other_user", "espn").unwrap();
+        let ids = |db: &Database| {
+            db.get_favorite_channels("default_user")
+                .unwrap()
+                .into_iter()
+                .map(|c| c.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&db), vec!["bbc-one", "itv"]);
+
+        // Re-importing the playlist keeps favorites
+        db.upsert_live_tv_channels(&channels).unwrap();
+        assert_eq!(ids(&db), vec!["bbc-one", "itv"]);
+
+        assert!(db.remove_favorite_channel("default_user", "itv").unwrap());
+        assert!(!db.remove_favorite_channel("default_user", "itv").unwrap());
+        assert_eq!(ids(&db), vec!["bbc-one"]);
+        assert_eq!(db.get_favorite_channels("other_user").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_live_tv_channel_crud() {
+        use crate::models::{EpgProgram, LiveTvChannel};
+
+        let db = create_test_db().unwrap();
+        db.conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
+        let channel = |id: &str, name: &str, group: Option<&str>| LiveTvChannel {
+            id: id.to_string(),
+            name: name.to_string(),
+            logo: Some(format!("https://logos.example.com/{}.png", id)),
+            group: group.map(str::to_string),
+            tvg_id: Some(format!("{}.uk", id)),
+            stream_url: format!("https://live.example.com/{}.m3u8", id),
+        };
+        let names = |channels: Vec<LiveTvChannel>| -> Vec<String> {
+            channels.into_iter().map(|c| c.name).collect()
+        };
+
+        db.upsert_live_tv_channel(&channel("itv", "ITV", Some("UK")))
+            .unwrap();
+        db.upsert_live_tv_channel(&channel("bbc-one", "BBC One", Some("UK")))
+            .unwrap();
+        db.upsert_live_tv_channel(&channel("espn", "ESPN", Some("Sports")))
+            .unwrap();
+        assert_eq!(
+            names(db.get_live_tv_channels(None).unwrap()),
+            vec!["BBC One", "ESPN", "ITV"]
+        );
+        assert_eq!(
+            names(db.get_live_tv_channels(Some("UK")).unwrap()),
+            vec!["BBC One", "ITV"]
+        );
+        assert!(db.get_live_tv_channels(Some("Movies")).unwrap().is_empty());
+
+        // Updating in place keeps the channel's EPG
+        db.upsert_epg_programs(&[EpgProgram {
+            id: None,
+            channel_id: "bbc-one".to_string(),
+            start: 1_000,
+            end: 2_000,
+            title: "News".to_string(),
+            description: None,
+            category: None,
+            season: None,
+            episode: None,
+        }])
+        .unwrap();
+        db.upsert_live_tv_channel(&channel("bbc-one", "BBC One HD", Some("UK HD")))
+            .unwrap();
+        let updated = db.get_live_tv_channel("bbc-one").unwrap().unwrap();
+        assert_eq!(updated.name, "BBC One HD");
+        assert_eq!(updated.group.as_deref(), Some("UK HD"));
+        let epg = |db: &Database| db.get_epg_for_channel("bbc-one", None, None).unwrap();
+        assert_eq!(epg(&db).len(), 1);
+
+        db.add_favorite_channel("default_user", "bbc-one").unwrap();
+        assert!(db.delete_live_tv_channel("bbc-one").unwrap());
+        assert!(!db.delete_live_tv_channel("bbc-one").unwrap());
+        assert!(db.get_live_tv_channel("bbc-one").unwrap().is_none());
+        assert!(epg(&db).is_empty());
+        assert!(db.get_favorite_channels("default_user").unwrap().is_empty());
+        assert_eq!(db.get_live_tv_channels(None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_epg_upsert_and_now_next() {
+        use crate::models::{EpgProgram, LiveTvChannel};
+
+        let db = create_test_db().unwrap();
+        db.upsert_live_tv_channel(&LiveTvChannel {
+            id: "bbc-one".to_string(),
+            name: "BBC One".to_string(),
+            logo: None,
+            group: None,
+            tvg_id: None,
+            stream_url: "https://live.example.com/bbc-one.m3u8".to_string(),
+        })
+        .unwrap();
+        let program = |start: i64, end: i64, title: &str| EpgProgram {
+            id: None,
+            channel_id: "bbc-one".to_string(),
+            start,
+            end,
+            title: title.to_string(),
+            description: None,
+            category: None,
+            season: None,
+            episode: None,
+        };
+
+        let news = db
+            .upsert_epg_program(&program(1_000, 2_000, "News"))
+            .unwrap();
+        db.upsert_epg_programs(&[
+            program(2_000, 3_000, "Weather"),
+            program(3_000, 4_000, "Film"),
+        ])
+        .unwrap();
+
+        // Re-importing the same slot updates the row instead of duplicating it
+        let updated = db
+            .upsert_epg_program(&program(1_000, 2_100, "News at One"))
+            .unwrap();
+        assert_eq!(updated, news);
+        let all = db.get_epg_for_channel("bbc-one", None, None).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].title, "News at One");
+        assert_eq!(all[0].end, 2_100);
+
+        let window = db
+            .get_epg_for_channel("bbc-one", Some(2_500), Some(3_500))
+            .unwrap();
+        let titles: Vec<_> = window.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, vec!["Weather", "Film"]);
+
+        let (now, next) = db.get_now_next("bbc-one", 2_500).unwrap();
+        assert_eq!(now.unwrap().title, "Weather");
+        assert_eq!(next.unwrap().title, "Film");
+
+        let (now, next) = db.get_now_next("bbc-one", 500).unwrap();
+        assert!(now.is_none());
+        assert_eq!(next.unwrap().id, Some(news));
+
+        let (now, next) = db.get_now_next("bbc-one", 5_000).unwrap();
+        assert!(now.is_none() && next.is_none());
+    }
+
+    #[test]
+    fn test_addon_ratings_weighted_toward_global_mean() {
+        let db = create_test_db().unwrap();
+        for id in ["torrentio", "fresh"] {
+            db.save_addon(&create_test_addon(id, 0)).unwrap();
+        }
+        for i in 0..10 {
+            db.rate_addon("torrentio", &format!("user{}", i), 5)
+                .unwrap();
+        }
+        let newcomer = db.rate_addon("fresh", "user0", 1).unwrap();
+        assert_eq!(newcomer.rating_count, 1);
+        assert_eq!(newcomer.rating_avg, 1.0);
+
+        // Mean of all 11 votes is 51/11; the single 1-star vote only drags
+        // the newcomer part of the way down to it
+        let global_mean = 51.0 / 11.0;
+        let prior = ADDON_RATING_PRIOR_VOTES;
+        let expected = (1.0 + prior * global_mean) / (1.0 + prior);
+        assert!((newcomer.weighted_rating - expected).abs() < 1e-9);
+        assert!(newcomer.weighted_rating > 3.5);
+
+        // The popular addon was recomputed against the new mean too
+        let popular = db.get_addon_rating("torrentio").unwrap().unwrap();
+        assert_eq!(popular.rating_count, 10);
+        assert_eq!(popular.rating_avg, 5.0);
+        assert!(popular.weighted_rating < 5.0);
+        assert!(popular.weighted_rating > newcomer.weighted_rating);
+
+        // Re-rating replaces the user's vote
+        let rerated = db.rate_addon("fresh", "user0", 4).unwrap();
+        assert_eq!(rerated.rating_count, 1);
+        assert_eq!(rerated.rating_avg, 4.0);
+
+        assert!(db.rate_addon("fresh", "user1", 6).is_err());
+        assert!(db.rate_addon("fresh", "user1", 0).is_err());
+        assert!(db.get_addon_rating("unrated").unwrap().is_none());
+
+        let ids: Vec<String> = db
+            .get_addon_ratings()
+            .unwrap()
+            .into_iter()
+            .map(|r| r.addon_id)
+            .collect();
+        assert_eq!(ids, vec!["torrentio", "fresh"]);
+
+        // Without a prior the weighted rating is the plain average
+        db.recompute_addon_rating_summary(0.0).unwrap();
+        let plain = db.get_addon_rating("fresh").unwrap().unwrap();
+        assert_eq!(plain.weighted_rating, plain.rating_avg);
+    }
+
+    #[test]
+    fn test_skip_segments_upsert_overwrites() {
+        let db = create_test_db().unwrap();
+        assert!(db.get_skip_segments("tt0903747").unwrap().is_none());
+
+        let first = SkipSegments {
+            intro_start: Some(5.0),
+            intro_end: Some(65.0),
+            outro_start: Some(2700.0),
+            outro_end: Some(2820.0),
+        };
+        db.set_skip_segments("tt0903747", &first).unwrap();
+        assert_eq!(db.get_skip_segments("tt0903747").unwrap(), Some(first));
+
+        // A second save replaces the whole row, including dropping the outro
+        let second = SkipSegments {
+            intro_start: Some(0.0),
+            intro_end: Some(42.5),
+            outro_start: None,
+            outro_end: None,
+        };
+        db.set_skip_segments("tt0903747", &second).unwrap();
+        assert_eq!(
+            db.get_skip_segments("tt0903747").unwrap(),
+            Some(second.clone())
+        );
+        assert!(db.get_skip_segments("tt0000000").unwrap().is_none());
+
+        let invalid = [
+            SkipSegments {
+                intro_start: Some(30.0),
+                intro_end: Some(30.0),
+                ..second.clone()
+            },
+            SkipSegments {
+                intro_start: Some(-1.0),
+                intro_end: Some(30.0),
+                ..second.clone()
+            },
+            SkipSegments {
+                intro_start: Some(10.0),
+                intro_end: None,
+                ..second.clone()
+            },
+            SkipSegments {
+                outro_start: Some(100.0),
+                outro_end: Some(90.0),
+                ..second.clone()
+            },
+        ];
+        for segments in &invalid {
+            assert!(db.set_skip_segments("tt0903747", segments).is_err());
+        }
+        assert_eq!(db.get_skip_segments("tt0903747").unwrap(), Some(second));
+    }
+
+    #[test]
+    fn test_program_reminders() {
+        use crate::models::{EpgProgram, LiveTvChannel};
+
+        let db = create_test_db().unwrap();
+        db.upsert_live_tv_channels(&[LiveTvChannel {
+            id: "bbc-one".to_string(),
+            name: "BBC One".to_string(),
+            logo: None,
+            group: None,
+            tvg_id: None,
+            stream_url: "https://live.example.com/bbc-one.m3u8".to_string(),
+        }])
+        .unwrap();
+        let program = |title: &str, start: i64, end: i64| EpgProgram {
+            id: None,
+            channel_id: "bbc-one".to_string(),
+            start,
+            end,
+            title: title.to_string(),
+            description: None,
+            category: None,
+            season: None,
+            episode: None,
+        };
+        // Fixed clock: "now" is 10_000
+        let now = 10_000;
+        db.upsert_epg_programs(&[
+            program("News", now - 3600, now - 60),
+            program("Quiz", now + 120, now + 1800),
+            program("Film", now + 3600, now + 9000),
+        ])
+        .unwrap();
+        let epg = db.get_epg_for_channel("bbc-one", None, None).unwrap();
+        let id_of = |title: &str| epg.iter().find(|p| p.title == title).unwrap().id.unwrap();
+        let titles = |reminders: Vec<crate::models::ProgramReminder>| -> Vec<String> {
+            reminders.into_iter().map(|r| r.title).collect()
+        };
+
+        for title in ["News", "Quiz", "Film"] {
+            db.set_program_reminder("bbc-one", id_of(title)).unwrap();
+        }
+        // Setting twice is harmless; unknown programs or channels are rejected
+        db.set_program_reminder("bbc-one", id_of("Quiz")).unwrap();
+        assert!(db.set_program_reminder("bbc-one", 9999).is_err());
+        assert!(db.set_program_reminder("itv", id_of("Quiz")).is_err());
+
+        let upcoming = db.get_upcoming_reminders(now).unwrap();
+        assert_eq!(titles(upcoming.clone()), vec!["Quiz", "Film"]);
+        assert_eq!(upcoming[0].channel_name, "BBC One");
+        assert_eq!(upcoming[0].start, now + 120);
+
+        // Only the program starting within the lead time is due, and only once
+        assert_eq!(
+            titles(db.get_due_reminders(now, 300).unwrap()),
+            vec!["Quiz"]
+        );
+        db.mark_reminder_notified(id_of("Quiz")).unwrap();
+        assert!(db.get_due_reminders(now, 300).unwrap().is_empty());
+        assert_eq!(
+            titles(db.get_due_reminders(now + 3500, 300).unwrap()),
+            vec!["Film"]
+        );
+
+        // Finished programs lose their reminders
+        assert_eq!(db.remove_past_reminders(now).unwrap(), 1);
+        assert_eq!(db.remove_past_reminders(now + 1800).unwrap(), 1);
+        assert!(db.remove_program_reminder(id_of("Film")).unwrap());
+        assert!(!db.remove_program_reminder(id_of("Film")).unwrap());
+        assert!(db.get_upcoming_reminders(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_directory_overlap() {
+        let existing = vec![
+            ("/media".to_string(), true),
+            ("/shows/anime".to_string(), true),
+            ("/flat".to_string(), false),
+        ];
+
+        assert_eq!(
+            directory_overlap("/media/movies", &existing),
+            Some(DirectoryOverlap::Inside("/media".to_string()))
+        );
+        assert_eq!(
+            directory_overlap("/shows", &existing),
+            Some(DirectoryOverlap::Contains(vec!["/shows/anime".to_string()]))
+        );
+        // Rescanning a directory, a sibling with a shared prefix, and a
+        // child of a non-recursive directory don't overlap
+        assert_eq!(directory_overlap("/media", &existing), None);
+        assert_eq!(directory_overlap("/media2", &existing), None);
+        assert_eq!(directory_overlap("/flat/sub", &existing), None);
+        // The same directory with a trailing slash replaces the old entry
+        assert_eq!(
+            directory_overlap("/media/", &existing),
+            Some(DirectoryOverlap::Contains(vec!["/media".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_add_scanned_directory_rejects_and_merges_nested_paths() {
+        let db = create_test_db().unwrap();
+        let paths = |db: &Database| -> Vec<String> {
+            db.get_scanned_directories()
+                .unwrap()
+                .into_iter()
+                .map(|(path, _, _)| path)
+                .collect()
+        };
+
+        assert!(db.add_scanned_directory("/media/movies").unwrap().is_empty());
+        assert!(db.add_scanned_directory("/media/shows").unwrap().is_empty());
+        assert!(db.add_scanned_directory("/music").unwrap().is_empty());
+
+        // A parent takes over its children
+        assert_eq!(
+            db.add_scanned_directory("/media").unwrap(),
+            vec!["/media/movies".to_string(), "/media/shows".to_string()]
+        );
+        assert_eq!(paths(&db), vec!["/media", "/music"]);
+
+        // A child of a scanned directory is rejected with a clear message
+        assert_eq!(
+            db.scanned_directory_overlap("/media/movies").unwrap(),
+            Some(DirectoryOverlap::Inside("/media".to_string()))
+        );
+        let err = db.add_scanned_directory("/media/movies").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "/media/movies is already scanned as part of /media"
+        );
+        assert_eq!(paths(&db), vec!["/media", "/music"]);
+
+        // Rescanning the same directory is fine
+        assert!(db.add_scanned_directory("/media").unwrap().is_empty());
+        assert_eq!(paths(&db), vec!["/media", "/music"]);
+    }
+
+    #[test]
+    fn test_import_watch_history() {
+        use chrono::TimeZone;
+
+        let db = create_test_db().unwrap();
+        for (id, title) in [
+            ("tt0111161", "The Shawshank Redemption"),
+            ("tt0068646", "The Godfather"),
+            ("local:1", "Heat"),
+            ("local:2", "Heat"),
+        ] {
+            db.add_to_library(create_test_media_item(id, title))
+                .unwrap();
+        }
+
+        let watched_at = chrono::Utc.with_ymd_and_hms(2023, 5, 1, 20, 0, 0).unwrap();
+        let entry = |title: &str, year: Option<i32>, external_id: Option<&str>| ImportedHistory {
+            title: title.to_string(),
+            year,
+            external_id: external_id.map(str::to_string),
+            media_type: None,
+            watched_at: Some(watched_at),
+            position: None,
+            watched: true,
+        };
+        let entries = vec![
+            // By external id
+            entry("Shawshank", None, Some("tt0111161")),
+            // By title and year, ignoring case
+            entry("the godfather", Some(2024), None),
+            // Not in the library, but has an id to add it under
+            entry("Alien", Some(1979), Some("tt0078748")),
+            // Unmatchable: unknown title, wrong year, ambiguous title
+            entry("Some Obscure Film", Some(2001), None),
+            entry("The Godfather", Some(1972), None),
+            entry("Heat", None, None),
+            ImportedHistory {
+                position: Some(1800),
+                watched: false,
+                ..entry("Heat", Some(2024), Some("local:2"))
+            },
+        ];
+
+        let report = db.import_watch_history(&entries).unwrap();
+        assert_eq!(report.matched, 3);
+        assert_eq!(report.created, 1);
+        assert_eq!(report.unmatched, 3);
+        assert_eq!(
+            report.unmatched_titles,
+            vec!["Some Obscure Film", "The Godfather", "Heat"]
+        );
+
+        let items: std::collections::HashMap<String, MediaItem> = db
+            .get_library_items()
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.id.clone(), item))
+            .collect();
+        assert_eq!(items.len(), 5);
+        assert!(items["tt0111161"].watched);
+        assert!(items["tt0068646"].watched);
+        assert!(items["tt0078748"].watched);
+        assert_eq!(items["tt0078748"].title, "Alien");
+        assert!(!items["local:1"].watched);
+        assert!(!items["local:2"].watched);
+        assert_eq!(items["local:2"].progress, Some(1800));
+
+        let history = db.get_recent_watch_history(10).unwrap();
+        assert_eq!(history.len(), 4);
+        assert!(history.iter().all(|event| event.watched_at == watched_at));
+        let godfather = history
+            .iter()
+            .find(|event| event.media_id == "tt0068646")
+            .unwrap();
+        assert_eq!(godfather.position, 120 * 60);
+
+        // Importing the same entries again adds no watch events
+        let again = db.import_watch_history(&entries).unwrap();
+        assert_eq!(again.matched, 4);
+        assert_eq!(again.created, 0);
+        assert_eq!(again.duplicates, 4);
+        assert_eq!(again.unmatched, 3);
+        assert_eq!(db.get_recent_watch_history(10).unwrap().len(), 4);
+    }
 }
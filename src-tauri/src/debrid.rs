@@ -0,0 +1,308 @@
+/**
+ * Debrid Services
+ *
+ * Resolve magnet links into direct HTTP URLs through debrid providers
+ * (Real-Debrid), so torrent-only addons can be played without P2P
+ */
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::time::Duration;
+
+const REAL_DEBRID_API_URL: &str = "https://api.real-debrid.com/rest/1.0";
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+// A cached torrent is usually "downloaded" right after file selection;
+// anything still downloading after a few polls is treated as not cached
+const STATUS_POLL_ATTEMPTS: u32 = 3;
+const STATUS_POLL_INTERVAL_MS: u64 = 1000;
+
+/// A debrid service that can turn a magnet link into a direct download URL
+pub trait DebridProvider {
+    /// Provider name for logging
+    fn name(&self) -> &'static str;
+
+    /// Resolve a magnet link into an unrestricted HTTP(S) URL
+    async fn resolve_magnet(&self, magnet: &str) -> Result<String>;
+}
+
+#[derive(Debug, Deserialize)]
+struct AddMagnetResponse {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TorrentInfo {
+    status: String,
+    #[serde(default)]
+    files: Vec<TorrentInfoFile>,
+    #[serde(default)]
+    links: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TorrentInfoFile {
+    #[serde(default)]
+    bytes: u64,
+    #[serde(default)]
+    selected: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnrestrictResponse {
+    download: String,
+}
+
+/// Real-Debrid REST API client
+pub struct RealDebridClient {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+}
+
+impl RealDebridClient {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, REAL_DEBRID_API_URL.to_string())
+    }
+
+    /// Create a client against a custom API root (used by tests)
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        Self {
+            client,
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn check(response: reqwest::Response, action: &str) -> Result<reqwest::Response> {
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Real-Debrid {} failed: HTTP {}: {}",
+                action,
+                response.status(),
+                response.text().await.unwrap_or_default()
+            ));
+        }
+        Ok(response)
+    }
+
+    async fn add_magnet(&self, magnet: &str) -> Result<String> {
+        let response = self
+            .client
+            .post(format!("{}/torrents/addMagnet", self.base_url))
+            .bearer_auth(&self.api_key)
+            .form(&[("magnet", magnet)])
+            .send()
+            .await?;
+
+        let added: AddMagnetResponse = Self::check(response, "addMagnet").await?.json().await?;
+        Ok(added.id)
+    }
+
+    async fn select_all_files(&self, torrent_id: &str) -> Result<()> {
+        let response = self
+            .client
+            .post(format!(
+                "{}/torrents/selectFiles/{}",
+                self.base_url, torrent_id
+            ))
+            .bearer_auth(&self.api_key)
+            .form(&[("files", "all")])
+            .send()
+            .await?;
+
+        Self::check(response, "selectFiles").await?;
+        Ok(())
+    }
+
+    async fn torrent_info(&self, torrent_id: &str) -> Result<TorrentInfo> {
+        let response = self
+            .client
+            .get(format!("{}/torrents/info/{}", self.base_url, torrent_id))
+            .bearer_auth(&self.api_key)
+            .send()
+            .await?;
+
+        Ok(Self::check(response, "torrent info").await?.json().await?)
+    }
+
+    async fn unrestrict(&self, link: &str) -> Result<String> {
+        let response = self
+            .client
+            .post(format!("{}/unrestrict/link", self.base_url))
+            .bearer_auth(&self.api_key)
+            .form(&[("link", link)])
+            .send()
+            .await?;
+
+        let unrestricted: UnrestrictResponse =
+            Self::check(response, "unrestrict").await?.json().await?;
+        Ok(unrestricted.download)
+    }
+
+    /// Pick the hoster link of the largest selected file (the main video)
+    fn main_link(info: &TorrentInfo) -> Option<&String> {
+        let largest = info
+            .files
+            .iter()
+            .filter(|f| f.selected == 1)
+            .enumerate()
+            .max_by_key(|(_, f)| f.bytes)
+            .map(|(i, _)| i);
+
+        largest
+            .and_then(|i| info.links.get(i))
+            .or_else(|| info.links.first())
+    }
+}
+
+impl DebridProvider for RealDebridClient {
+    fn name(&self) -> &'static str {
+        "Real-Debrid"
+    }
+
+    async fn resolve_magnet(&self, magnet: &str) -> Result<String> {
+        if !magnet.starts_with("magnet:") {
+            return Err(anyhow!("Not a magnet link"));
+        }
+
+        let torrent_id = self.add_magnet(magnet).await?;
+        self.select_all_files(&torrent_id).await?;
+
+        for attempt in 0..STATUS_POLL_ATTEMPTS {
+            let info = self.torrent_info(&torrent_id).await?;
+            match info.status.as_str() {
+                "downloaded" => {
+                    let link = Self::main_link(&info)
+                        .ok_or_else(|| anyhow!("Real-Debrid returned no links for torrent"))?;
+                    let url = self.unrestrict(link).await?;
+                    tracing::info!(torrent_id = %torrent_id, "Resolved magnet via Real-Debrid");
+                    return Ok(url);
+                }
+                "magnet_error" | "error" | "virus" | "dead" => {
+                    return Err(anyhow!("Real-Debrid rejected torrent: {}", info.status));
+                }
+                status => {
+                    tracing::debug!(attempt = attempt + 1, status = %status, "Waiting for Real-Debrid");
+                    if attempt + 1 < STATUS_POLL_ATTEMPTS {
+                        tokio::time::sleep(Duration::from_millis(STATUS_POLL_INTERVAL_MS)).await;
+                    }
+                }
+            }
+        }
+
+        Err(anyhow!("Torrent is not cached on Real-Debrid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode},
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+
+    fn authorized(headers: &HeaderMap) -> bool {
+        headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v == "Bearer test-key")
+            .unwrap_or(false)
+    }
+
+    async fn spawn_mock_api(status: &'static str) -> String {
+        let app = Router::new()
+            .route(
+                "/torrents/addMagnet",
+                post(|headers: HeaderMap| async move {
+                    if !authorized(&headers) {
+                        return Err(StatusCode::UNAUTHORIZED);
+                    }
+                    Ok(Json(
+                        json!({ "id": "TORRENT1", "uri": "https://api/torrents/info/TORRENT1" }),
+                    ))
+                }),
+            )
+            .route(
+                "/torrents/selectFiles/:id",
+                post(|| async { StatusCode::NO_CONTENT }),
+            )
+            .route(
+                "/torrents/info/:id",
+                get(move |Path(id): Path<String>| async move {
+                    Json(json!({
+                        "id": id,
+                        "status": status,
+                        "files": [
+                            { "id": 1, "path": "/sample.mkv", "bytes": 1000, "selected": 1 },
+                            { "id": 2, "path": "/movie.mkv", "bytes": 900000, "selected": 1 }
+                        ],
+                        "links": [
+                            "https://real-debrid.com/d/SAMPLE",
+                            "https://real-debrid.com/d/MOVIE"
+                        ]
+                    }))
+                }),
+            )
+            .route(
+                "/unrestrict/link",
+                post(|body: String| async move {
+                    let link = if body.contains("MOVIE") {
+                        "movie"
+                    } else {
+                        "sample"
+                    };
+                    Json::<Value>(json!({
+                        "download": format!("https://download.real-debrid.com/d/{}.mkv", link)
+                    }))
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_real_debrid_resolves_cached_magnet() {
+        let base_url = spawn_mock_api("downloaded").await;
+        let client = RealDebridClient::with_base_url("test-key".to_string(), base_url);
+
+        let url = client
+            .resolve_magnet("magnet:?xt=urn:btih:0123456789abcdef")
+            .await
+            .unwrap();
+        assert_eq!(url, "https://download.real-debrid.com/d/movie.mkv");
+    }
+
+    #[tokio::test]
+    async fn test_real_debrid_rejected_torrent() {
+        let base_url = spawn_mock_api("magnet_error").await;
+        let client = RealDebridClient::with_base_url("test-key".to_string(), base_url);
+
+        let result = client
+            .resolve_magnet("magnet:?xt=urn:btih:0123456789abcdef")
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_real_debrid_requires_magnet() {
+        let client = RealDebridClient::new("test-key".to_string());
+        assert!(client
+            .resolve_magnet("https://example.com/movie.mp4")
+            .await
+            .is_err());
+    }
+}
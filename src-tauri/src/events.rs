@@ -0,0 +1,173 @@
+/**
+ * Backend Events
+ *
+ * Typed events pushed to the frontend, so it can react to scans, new
+ * episodes, addon health, cast state, program reminders, recordings and
+ * library imports without polling
+ */
+use crate::casting::CastSession;
+use crate::jellyfin::JellyfinImportProgress;
+use crate::models::ProgramReminder;
+use crate::notifications::NewEpisode;
+use crate::recording::RecordingProgress;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+
+/// Progress of a local media scan
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ScanProgress {
+    pub path: String,
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// An addon's health check result changed
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AddonHealthChanged {
+    pub addon_id: String,
+    pub healthy: bool,
+    pub error: Option<String>,
+}
+
+/// Events the backend emits. The frontend listens for each by `name()`
+/// and receives the variant's fields as the payload.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum AppEvent {
+    ScanProgress(ScanProgress),
+    NewEpisodes(Vec<NewEpisode>),
+    AddonHealthChanged(AddonHealthChanged),
+    CastStateChanged(CastSession),
+    ProgramStartingSoon(ProgramReminder),
+    RecordingProgress(RecordingProgress),
+    JellyfinImportProgress(JellyfinImportProgress),
+}
+
+impl AppEvent {
+    /// Event name used on the frontend
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppEvent::ScanProgress(_) => "scan-progress",
+            AppEvent::NewEpisodes(_) => "new-episodes",
+            AppEvent::AddonHealthChanged(_) => "addon-health-changed",
+            AppEvent::CastStateChanged(_) => "cast-state-changed",
+            AppEvent::ProgramStartingSoon(_) => "program-starting-soon",
+            AppEvent::RecordingProgress(_) => "recording-progress",
+            AppEvent::JellyfinImportProgress(_) => "jellyfin-import-progress",
+        }
+    }
+}
+
+/// Somewhere events can be delivered, normally the Tauri app handle
+pub trait EventSink: Send + Sync {
+    fn emit_json(&self, event: &str, payload: serde_json::Value) -> Result<()>;
+}
+
+impl EventSink for tauri::AppHandle {
+    fn emit_json(&self, event: &str, payload: serde_json::Value) -> Result<()> {
+        tauri::Emitter::emit(self, event, payload)
+            .map_err(|e| anyhow!("Failed to emit {}: {}", event, e))
+    }
+}
+
+/// Emits events from anywhere that holds a clone, including schedulers and
+/// watchers outside of commands. Events emitted before a sink is attached
+/// (i.e. before the app has finished starting) are dropped.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    sink: Arc<RwLock<Option<Arc<dyn EventSink>>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start delivering events to `sink`
+    pub fn attach(&self, sink: Arc<dyn EventSink>) {
+        if let Ok(mut current) = self.sink.write() {
+            *current = Some(sink);
+        }
+    }
+
+    pub fn emit(&self, event: AppEvent) {
+        let sink = match self.sink.read() {
+            Ok(sink) => sink.clone(),
+            Err(_) => return,
+        };
+        let Some(sink) = sink else {
+            tracing::trace!(
+                event = event.name(),
+                "No event sink attached, dropping event"
+            );
+            return;
+        };
+
+        let result = serde_json::to_value(&event)
+            .map_err(|e| anyhow!("Failed to serialize {}: {}", event.name(), e))
+            .and_then(|payload| sink.emit_json(event.name(), payload));
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "Failed to emit event");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        events: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn emit_json(&self, event: &str, payload: serde_json::Value) -> Result<()> {
+            self.events
+                .lock()
+                .unwrap()
+                .push((event.to_string(), payload));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_emit_serializes_payload() {
+        let bus = EventBus::new();
+        let progress = AppEvent::ScanProgress(ScanProgress {
+            path: "/media/movies".to_string(),
+            processed: 3,
+            total: 10,
+        });
+
+        // Nothing is delivered until a sink is attached
+        bus.emit(progress.clone());
+
+        let sink = Arc::new(RecordingSink::default());
+        bus.clone().attach(sink.clone());
+        bus.emit(progress);
+        bus.emit(AppEvent::AddonHealthChanged(AddonHealthChanged {
+            addon_id: "torrentio".to_string(),
+            healthy: false,
+            error: Some("Timeout".to_string()),
+        }));
+
+        let events = sink.events.lock().unwrap();
+        assert_eq!(
+            *events,
+            vec![
+                (
+                    "scan-progress".to_string(),
+                    json!({ "path": "/media/movies", "processed": 3, "total": 10 })
+                ),
+                (
+                    "addon-health-changed".to_string(),
+                    json!({ "addon_id": "torrentio", "healthy": false, "error": "Timeout" })
+                ),
+            ]
+        );
+    }
+}
@@ -14,6 +14,7 @@ use tracing::{debug, error, info, warn};
 
 use crate::database::Database;
 use crate::local_media::{is_video_file, LocalMediaScanner};
+use crate::DEFAULT_PROFILE_ID;
 
 /// Folder watcher event
 #[derive(Debug, Clone)]
@@ -159,8 +160,19 @@ async fn handle_watch_event(
         WatchEvent::FileCreated(path) | WatchEvent::FileModified(path) => {
             info!("Processing new/modified file: {}", path.display());
 
-            // Scan the file
-            let scanner = LocalMediaScanner::new(vec![]);
+            // Scan the file with the user's concurrency and probe timeout
+            let db_prefs = db.clone();
+            let preferences = tokio::task::spawn_blocking(move || {
+                db_prefs
+                    .lock()
+                    .ok()
+                    .and_then(|db| db.get_user_profile(DEFAULT_PROFILE_ID).ok().flatten())
+                    .map(|profile| profile.preferences)
+                    .unwrap_or_default()
+            })
+            .await
+            .unwrap_or_default();
+            let scanner = LocalMediaScanner::new(vec![]).with_preferences(&preferences);
             match scanner.scan_directory(&path.parent().unwrap_or(Path::new("/"))).await {
                 Ok(files) => {
                     // Find the specific file we're interested in
@@ -0,0 +1,397 @@
+/**
+ * Jellyfin Import
+ *
+ * Copy the movies and series of a Jellyfin server into the StreamGo library,
+ * so users migrating from Jellyfin keep their collection
+ */
+use crate::database::Database;
+use crate::events::{AppEvent, EventBus};
+use crate::models::{MediaItem, MediaType};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+const PAGE_SIZE: usize = 200;
+
+// Jellyfin run times are in 100ns ticks
+const TICKS_PER_MINUTE: i64 = 600_000_000;
+
+/// Progress of a Jellyfin library import
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct JellyfinImportProgress {
+    /// Jellyfin items looked at so far
+    pub processed: usize,
+    pub total: usize,
+    /// Movies and series written to the library so far
+    pub imported: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ItemsResponse {
+    #[serde(default)]
+    items: Vec<JellyfinItem>,
+    #[serde(default)]
+    total_record_count: usize,
+}
+
+/// An entry of Jellyfin's `/Items` response (only the fields we import)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JellyfinItem {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "Type")]
+    pub item_type: String,
+    #[serde(default)]
+    pub production_year: Option<i32>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub community_rating: Option<f32>,
+    #[serde(default)]
+    pub run_time_ticks: Option<i64>,
+    #[serde(default)]
+    pub provider_ids: HashMap<String, String>,
+    #[serde(default)]
+    pub image_tags: HashMap<String, String>,
+    #[serde(default)]
+    pub backdrop_image_tags: Vec<String>,
+}
+
+/// Jellyfin REST API client authenticated with an API key
+pub struct JellyfinClient {
+    client: reqwest::Client,
+    server_url: String,
+    api_key: String,
+}
+
+impl JellyfinClient {
+    /// Create a client without contacting the server (see `connect`)
+    pub fn new(server_url: &str, api_key: &str) -> Result<Self> {
+        if !server_url.starts_with("http://") && !server_url.starts_with("https://") {
+            return Err(anyhow!(
+                "Jellyfin server URL must start with http:// or https://"
+            ));
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        Ok(Self {
+            client,
+            server_url: server_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+        })
+    }
+
+    /// Connect to a Jellyfin server, checking that it accepts the API key
+    pub async fn connect(server_url: &str, api_key: &str) -> Result<Self> {
+        let jellyfin = Self::new(server_url, api_key)?;
+        let response = jellyfin.get("/System/Info").send().await?;
+        Self::check(response, "connect").await?;
+        Ok(jellyfin)
+    }
+
+    fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .get(format!("{}{}", self.server_url, path))
+            .header("X-Emby-Token", &self.api_key)
+    }
+
+    async fn check(response: reqwest::Response, action: &str) -> Result<reqwest::Response> {
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Jellyfin {} failed: HTTP {}: {}",
+                action,
+                response.status(),
+                response.text().await.unwrap_or_default()
+            ));
+        }
+        Ok(response)
+    }
+
+    async fn fetch_page(&self, start_index: usize) -> Result<ItemsResponse> {
+        let response = self
+            .get("/Items")
+            .query(&[
+                ("IncludeItemTypes", "Movie,Series"),
+                ("Recursive", "true"),
+                ("Fields", "Genres,Overview,ProviderIds"),
+                ("StartIndex", &start_index.to_string()),
+                ("Limit", &PAGE_SIZE.to_string()),
+            ])
+            .send()
+            .await?;
+        Ok(Self::check(response, "items").await?.json().await?)
+    }
+
+    /// Import every movie and series into the library a page at a time,
+    /// emitting `jellyfin-import-progress` after each page. Items already in
+    /// the library keep their watched state and progress. Returns the number
+    /// of items imported.
+    pub async fn import_library(
+        &self,
+        db: Arc<Mutex<Database>>,
+        events: &EventBus,
+    ) -> Result<usize> {
+        let mut processed = 0;
+        let mut imported = 0;
+
+        loop {
+            let page = self.fetch_page(processed).await?;
+            let fetched = page.items.len();
+            let items: Vec<MediaItem> = page
+                .items
+                .iter()
+                .filter_map(|item| self.to_media_item(item))
+                .collect();
+
+            let db = db.clone();
+            imported += tokio::task::spawn_blocking(move || {
+                let db = db
+                    .lock()
+                    .map_err(|e| anyhow!("Database lock poisoned: {}", e))?;
+                upsert_items(&db, items)
+            })
+            .await??;
+
+            processed += fetched;
+            events.emit(AppEvent::JellyfinImportProgress(JellyfinImportProgress {
+                processed,
+                total: page.total_record_count,
+                imported,
+            }));
+
+            if fetched == 0 || processed >= page.total_record_count {
+                break;
+            }
+        }
+
+        tracing::info!(imported = imported, "Jellyfin library import complete");
+        Ok(imported)
+    }
+
+    /// Map a Jellyfin movie or series to a library item. The id is the IMDb
+    /// id when Jellyfin knows it, else `tmdb:<id>`, so addon streams and
+    /// metadata match; items with neither fall back to `jellyfin:<id>`.
+    /// Library items have a single id, so only that one provider id is kept;
+    /// the others (TMDB when there is an IMDb id, TVDB) are dropped.
+    /// Returns `None` for other item types and items that fail validation.
+    pub fn to_media_item(&self, item: &JellyfinItem) -> Option<MediaItem> {
+        let media_type = match item.item_type.as_str() {
+            "Movie" => MediaType::Movie,
+            "Series" => MediaType::TvShow,
+            _ => return None,
+        };
+
+        let provider_id = |name: &str| {
+            item.provider_ids
+                .iter()
+                .find(|(key, value)| key.eq_ignore_ascii_case(name) && !value.trim().is_empty())
+                .map(|(_, value)| value.trim().to_string())
+        };
+        let id = provider_id("imdb")
+            .or_else(|| provider_id("tmdb").map(|tmdb| format!("tmdb:{}", tmdb)))
+            .unwrap_or_else(|| format!("jellyfin:{}", item.id));
+
+        let mut builder =
+            MediaItem::builder(id, item.name.clone(), media_type).genre(item.genres.clone());
+        if let Some(year) = item.production_year {
+            builder = builder.year(year);
+        }
+        if let Some(overview) = item.overview.as_deref().filter(|o| !o.trim().is_empty()) {
+            builder = builder.description(overview);
+        }
+        if let Some(rating) = item.community_rating {
+            builder = builder.rating(rating);
+        }
+        if let Some(ticks) = item.run_time_ticks.filter(|ticks| *ticks > 0) {
+            builder = builder.duration((ticks / TICKS_PER_MINUTE) as i32);
+        }
+        if let Some(tag) = item.image_tags.get("Primary") {
+            builder = builder.poster_url(format!(
+                "{}/Items/{}/Images/Primary?tag={}",
+                self.server_url, item.id, tag
+            ));
+        }
+        if let Some(tag) = item.backdrop_image_tags.first() {
+            builder = builder.backdrop_url(format!(
+                "{}/Items/{}/Images/Backdrop?tag={}",
+                self.server_url, item.id, tag
+            ));
+        }
+
+        match builder.build() {
+            Ok(media_item) => Some(media_item),
+            Err(e) => {
+                tracing::warn!(item_id = %item.id, error = %e, "Skipping Jellyfin item");
+                None
+            }
+        }
+    }
+}
+
+/// Write items to the library in one transaction, carrying over the
+/// watched state, progress and added date of items already there. Items
+/// are updated in place, so their watch history and playlist entries stay.
+fn upsert_items(db: &Database, mut items: Vec<MediaItem>) -> Result<usize> {
+    let ids: Vec<String> = items.iter().map(|item| item.id.clone()).collect();
+    let existing: HashMap<String, MediaItem> = db
+        .get_media_items_by_ids(&ids)?
+        .into_iter()
+        .map(|item| (item.id.clone(), item))
+        .collect();
+
+    for item in &mut items {
+        if let Some(previous) = existing.get(&item.id) {
+            item.watched = previous.watched;
+            item.progress = previous.progress;
+            item.added_to_library = previous.added_to_library;
+        }
+    }
+    db.add_library_items_batch(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn sample_movie() -> serde_json::Value {
+        json!({
+            "Name": "The Shawshank Redemption",
+            "ServerId": "4b1c",
+            "Id": "a1b2c3",
+            "Type": "Movie",
+            "ProductionYear": 1994,
+            "Genres": ["Drama", "Crime"],
+            "Overview": "Two imprisoned men bond over a number of years.",
+            "CommunityRating": 8.7,
+            "RunTimeTicks": 85_200_000_000_i64,
+            "ProviderIds": { "Imdb": "tt0111161", "Tmdb": "278" },
+            "ImageTags": { "Primary": "f00d" },
+            "BackdropImageTags": ["beef"],
+            "IsFolder": false
+        })
+    }
+
+    #[test]
+    fn test_to_media_item_maps_jellyfin_fields() {
+        let client = JellyfinClient::new("http://jellyfin.local:8096/", "key").unwrap();
+        let item: JellyfinItem = serde_json::from_value(sample_movie()).unwrap();
+        let media = client.to_media_item(&item).unwrap();
+
+        assert_eq!(media.id, "tt0111161");
+        assert_eq!(media.title, "The Shawshank Redemption");
+        assert!(matches!(media.media_type, MediaType::Movie));
+        assert_eq!(media.year, Some(1994));
+        assert_eq!(media.genre, vec!["Drama", "Crime"]);
+        assert_eq!(
+            media.description.as_deref(),
+            Some("Two imprisoned men bond over a number of years.")
+        );
+        assert_eq!(media.rating, Some(8.7));
+        assert_eq!(media.duration, Some(142));
+        assert_eq!(
+            media.poster_url.as_deref(),
+            Some("http://jellyfin.local:8096/Items/a1b2c3/Images/Primary?tag=f00d")
+        );
+        assert_eq!(
+            media.backdrop_url.as_deref(),
+            Some("http://jellyfin.local:8096/Items/a1b2c3/Images/Backdrop?tag=beef")
+        );
+
+        // Without an IMDb id the TMDB id is used, then the Jellyfin id
+        let series: JellyfinItem = serde_json::from_value(json!({
+            "Name": "Breaking Bad",
+            "Id": "d4e5",
+            "Type": "Series",
+            "ProviderIds": { "Tmdb": "1396" }
+        }))
+        .unwrap();
+        let media = client.to_media_item(&series).unwrap();
+        assert_eq!(media.id, "tmdb:1396");
+        assert!(matches!(media.media_type, MediaType::TvShow));
+        assert_eq!(media.poster_url, None);
+
+        let unmatched: JellyfinItem =
+            serde_json::from_value(json!({ "Name": "Home Video", "Id": "f6", "Type": "Movie" }))
+                .unwrap();
+        assert_eq!(client.to_media_item(&unmatched).unwrap().id, "jellyfin:f6");
+
+        let episode: JellyfinItem =
+            serde_json::from_value(json!({ "Name": "Pilot", "Id": "e1", "Type": "Episode" }))
+                .unwrap();
+        assert!(client.to_media_item(&episode).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_import_library_keeps_watched_state() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/System/Info"))
+            .and(header("X-Emby-Token", "key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "Version": "10.9.0" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/Items"))
+            .and(query_param("StartIndex", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "Items": [sample_movie(), { "Name": "Pilot", "Id": "e1", "Type": "Episode" }],
+                "TotalRecordCount": 2
+            })))
+            .mount(&server)
+            .await;
+
+        let db = Arc::new(Mutex::new(Database::new_in_memory().unwrap()));
+        let watched = MediaItem::builder("tt0111161", "Shawshank", MediaType::Movie)
+            .watched(true)
+            .progress(600)
+            .build()
+            .unwrap();
+        {
+            let db = db.lock().unwrap();
+            db.add_to_library(watched).unwrap();
+            db.record_watch_event("tt0111161", 600, Some(8520), chrono::Utc::now())
+                .unwrap();
+            db.create_playlist("p1", "Prison films", None, "default_user")
+                .unwrap();
+            db.add_item_to_playlist("p1", "tt0111161").unwrap();
+        }
+
+        let client = JellyfinClient::connect(&server.uri(), "key").await.unwrap();
+        let imported = client
+            .import_library(db.clone(), &EventBus::new())
+            .await
+            .unwrap();
+        assert_eq!(imported, 1);
+
+        let items = db.lock().unwrap().get_library_items().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "The Shawshank Redemption");
+        assert!(items[0].watched);
+        assert_eq!(items[0].progress, Some(600));
+        {
+            let db = db.lock().unwrap();
+            assert_eq!(db.get_recent_watch_history(10).unwrap().len(), 1);
+            let playlist = db.get_playlist_items("p1").unwrap();
+            assert_eq!(playlist.len(), 1);
+            assert_eq!(playlist[0].id, "tt0111161");
+        }
+
+        assert!(JellyfinClient::connect(&server.uri(), "wrong")
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,138 @@
+/**
+ * Language Codes
+ *
+ * Normalize the mix of ISO 639-1 codes, ISO 639-2 codes, region tags and
+ * English names that addons and subtitle providers use to label languages
+ */
+use std::fmt;
+
+/// ISO 639-1 language code (e.g. "en")
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LangCode(&'static str);
+
+impl LangCode {
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for LangCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+// ISO 639-1 code with its ISO 639-2 (B and T) codes and common names
+const LANGUAGES: &[(&str, &[&str])] = &[
+    ("en", &["eng", "english"]),
+    (
+        "es",
+        &["spa", "spanish", "español", "espanol", "castellano"],
+    ),
+    ("fr", &["fre", "fra", "french", "français", "francais"]),
+    ("de", &["ger", "deu", "german", "deutsch"]),
+    ("it", &["ita", "italian", "italiano"]),
+    (
+        "pt",
+        &[
+            "por",
+            "pob",
+            "portuguese",
+            "português",
+            "portugues",
+            "brazilian",
+        ],
+    ),
+    ("nl", &["dut", "nld", "dutch"]),
+    ("ru", &["rus", "russian"]),
+    ("pl", &["pol", "polish"]),
+    ("tr", &["tur", "turkish"]),
+    ("ar", &["ara", "arabic"]),
+    ("zh", &["chi", "zho", "chinese"]),
+    ("ja", &["jpn", "japanese"]),
+    ("ko", &["kor", "korean"]),
+    ("sv", &["swe", "swedish"]),
+    ("no", &["nor", "nb", "nob", "nn", "nno", "norwegian"]),
+    ("da", &["dan", "danish"]),
+    ("fi", &["fin", "finnish"]),
+    ("el", &["gre", "ell", "greek"]),
+    ("he", &["heb", "hebrew"]),
+    ("hi", &["hin", "hindi"]),
+    ("hu", &["hun", "hungarian"]),
+    ("cs", &["cze", "ces", "czech"]),
+    ("ro", &["rum", "ron", "romanian"]),
+    ("uk", &["ukr", "ukrainian"]),
+    ("vi", &["vie", "vietnamese"]),
+    ("id", &["ind", "indonesian"]),
+    ("th", &["tha", "thai"]),
+];
+
+/// Map a language code or name to its ISO 639-1 code.
+/// Region and script subtags are ignored ("en-US", "pt_BR", "zh-Hans").
+pub fn normalize_lang(code: &str) -> Option<LangCode> {
+    let lower = code.trim().to_lowercase();
+    let primary = lower.split(['-', '_']).next().unwrap_or_default();
+
+    lookup(&lower).or_else(|| lookup(primary))
+}
+
+fn lookup(candidate: &str) -> Option<LangCode> {
+    LANGUAGES
+        .iter()
+        .find(|(code, aliases)| *code == candidate || aliases.contains(&candidate))
+        .map(|(code, _)| LangCode(code))
+}
+
+/// Key for comparing language labels: the ISO 639-1 code when known,
+/// otherwise the lowercased label (kept, but logged as unrecognized)
+pub fn language_key(label: &str) -> String {
+    match normalize_lang(label) {
+        Some(code) => code.to_string(),
+        None => {
+            tracing::debug!(language = %label, "Unrecognized language code");
+            label.trim().to_lowercase()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_lang() {
+        let cases = [
+            ("en", Some("en")),
+            ("eng", Some("en")),
+            ("English", Some("en")),
+            ("en-US", Some("en")),
+            (" EN_gb ", Some("en")),
+            ("fre", Some("fr")),
+            ("fra", Some("fr")),
+            ("Français", Some("fr")),
+            ("ger", Some("de")),
+            ("deu", Some("de")),
+            ("pt-BR", Some("pt")),
+            ("pob", Some("pt")),
+            ("zh-Hans", Some("zh")),
+            ("cze", Some("cs")),
+            ("Klingon", None),
+            ("", None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                normalize_lang(input).map(|code| code.as_str()),
+                expected,
+                "normalize_lang({:?})",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_language_key_keeps_unknown_labels() {
+        assert_eq!(language_key("eng"), "en");
+        assert_eq!(language_key(" Klingon "), "klingon");
+    }
+}
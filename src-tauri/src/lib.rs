@@ -1,5 +1,6 @@
 use std::sync::{Arc, Mutex};
 use tauri::Manager;
+use debrid::DebridProvider;
 use serde::Serialize;
 
 mod addon_protocol;
@@ -9,8 +10,12 @@ mod cache;
 mod calendar;
 mod casting;
 mod database;
+mod debrid;
+mod events;
 mod folder_watcher;
 mod i18n;
+mod jellyfin;
+mod language;
 mod live_tv;
 mod local_media;
 mod logging;
@@ -18,15 +23,30 @@ mod migrations;
 mod models;
 mod notifications;
 mod player;
+mod quality;
+mod rate_limit;
+mod recording;
+mod secrets;
+mod stream_filter;
 mod streaming_server;
 mod subtitle_providers;
+mod version;
 
 // Re-export public items (avoid glob conflicts)
-pub use addon_protocol::{AddonClient, AddonError, Stream, StreamBehaviorHints, Subtitle};
-pub use aggregator::{AggregationResult, ContentAggregator, SourceHealth, StreamAggregationResult};
+pub use addon_protocol::{
+    AddonClient, AddonError, ResponseLimits, Stream, StreamBehaviorHints, Subtitle,
+};
+pub use aggregator::{
+    AggregationResult, ContentAggregator, SourceHealth, StreamAggregationResult,
+    SubtitleAggregationResult,
+};
 pub use cache::{CacheManager, CacheStats};
-pub use casting::{CastDevice, CastManager, CastSession, PlaybackState};
+pub use casting::{
+    CastDevice, CastDeviceTest, CastManager, CastSession, DeviceCapability, PlaybackState,
+};
 pub use database::Database;
+pub use events::{AddonHealthChanged, AppEvent, EventBus, EventSink, ScanProgress};
+pub use language::{language_key, normalize_lang, LangCode};
 pub use logging::{
     init_logging, log_shutdown, log_startup_info, DiagnosticsInfo, PerformanceMetrics,
 };
@@ -34,16 +54,48 @@ pub use migrations::{MigrationRunner, CURRENT_SCHEMA_VERSION};
 pub use models::*;
 pub use local_media::{LocalMediaFile, LocalMediaScanner, VideoMetadata};
 pub use player::{ExternalPlayer, PlayerManager, SubtitleCue, SubtitleManager};
+pub use quality::parse_quality_hint;
+pub use stream_filter::StreamFilter;
 pub use subtitle_providers::{SubtitleProvider, SubtitleResult};
+pub use version::{compare_versions, is_newer_version, Version};
 
 
 // Application state
+#[derive(Clone)]
 pub struct AppState {
     pub db: Arc<Mutex<Database>>,
     pub cache: Arc<Mutex<CacheManager>>,
     pub streaming_server: Option<Arc<streaming_server::StreamingServer>>,
     pub cast_manager: Option<Arc<CastManager>>,
     pub folder_watcher: Option<Arc<tokio::sync::Mutex<folder_watcher::FolderWatcherManager>>>,
+    /// Pushes events to the frontend; attached to the app handle on setup
+    pub events: EventBus,
+    /// Profile whose watchlist, favorites and playlists commands act on
+    pub active_profile: Arc<Mutex<String>>,
+    pub recordings: Arc<recording::RecordingManager>,
+}
+
+/// Profile created on first run; app settings are stored on it
+pub(crate) const DEFAULT_PROFILE_ID: &str = "default_user";
+/// App setting remembering the active profile across restarts
+const ACTIVE_PROFILE_SETTING: &str = "active_profile";
+
+fn active_profile_id(state: &AppState) -> Result<String, String> {
+    state
+        .active_profile
+        .lock()
+        .map(|id| id.clone())
+        .map_err(|e| e.to_string())
+}
+
+/// The profile saved as active, if it still exists, otherwise the default
+fn load_active_profile(db: &Database) -> String {
+    db.get_app_setting(ACTIVE_PROFILE_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|value| serde_json::from_str::<String>(&value).ok())
+        .filter(|id| matches!(db.get_user_profile(id), Ok(Some(_))))
+        .unwrap_or_else(|| DEFAULT_PROFILE_ID.to_string())
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -71,6 +123,27 @@ async fn get_library_items(state: tauri::State<'_, AppState>) -> Result<Vec<Medi
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// `limit` items starting at `offset`, sorted by `sort` (e.g. "title_asc";
+/// newest first by default), with the library's total item count
+#[tauri::command]
+async fn get_library_items_paged(
+    offset: i64,
+    limit: i64,
+    sort: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<LibraryPage, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let (items, total_count) = db
+            .get_library_items_paged(offset, limit, sort.as_deref())
+            .map_err(|e| e.to_string())?;
+        Ok(LibraryPage { items, total_count })
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 #[tauri::command]
 async fn add_to_library(item: MediaItem, state: tauri::State<'_, AppState>) -> Result<(), String> {
     let db = state.inner().db.clone();
@@ -82,6 +155,52 @@ async fn add_to_library(item: MediaItem, state: tauri::State<'_, AppState>) -> R
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// Add many items at once in a single transaction; all or nothing
+#[tauri::command]
+async fn add_to_library_batch(
+    items: Vec<MediaItem>,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.add_library_items_batch(items).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Import the movies and series of a Jellyfin server into the library,
+/// reporting `jellyfin-import-progress` events. Returns the number imported.
+#[tauri::command]
+async fn jellyfin_import(
+    server_url: String,
+    api_key: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, String> {
+    let client = jellyfin::JellyfinClient::connect(&server_url, &api_key)
+        .await
+        .map_err(|e| e.to_string())?;
+    client
+        .import_library(state.db.clone(), &state.events)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn remove_from_library(
+    media_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<bool, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.delete_media_item(&media_id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 #[tauri::command]
 async fn search_content(
     query: String,
@@ -92,7 +211,7 @@ async fn search_content(
         let db = state.inner().db.clone();
         let _ = tokio::task::spawn_blocking(move || {
             let db = db.lock().map_err(|e| e.to_string())?;
-            if let Ok(Some(profile)) = db.get_user_profile("default_user") {
+            if let Ok(Some(profile)) = db.get_user_profile(DEFAULT_PROFILE_ID) {
                 if let Some(key) = profile.preferences.tmdb_api_key {
                     if !key.is_empty() {
                         std::env::set_var("TMDB_API_KEY", key);
@@ -110,6 +229,91 @@ async fn search_content(
         .map_err(|e| e.to_string())
 }
 
+/// Make the TMDB API key from the user's preferences available to `api`
+fn load_tmdb_api_key(db: &Database) {
+    if let Ok(Some(profile)) = db.get_user_profile(DEFAULT_PROFILE_ID) {
+        if let Some(key) = profile.preferences.tmdb_api_key {
+            if !key.is_empty() {
+                std::env::set_var("TMDB_API_KEY", key);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+async fn hydrate_posters(
+    media_ids: Vec<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<MediaItem>, String> {
+    // Load the library items not looked up recently and the TMDB API key
+    // from user preferences
+    let now = chrono::Utc::now();
+    let checked_before = now - chrono::Duration::days(api::ARTWORK_RECHECK_DAYS);
+    let db = state.inner().db.clone();
+    let items = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        load_tmdb_api_key(&db);
+        db.get_media_items_due_artwork(&media_ids, checked_before)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let hydrated = api::hydrate_artwork(items)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        for item in &hydrated.items {
+            db.fill_missing_artwork(&item.id, &item.artwork())
+                .map_err(|e| e.to_string())?;
+        }
+        db.mark_artwork_checked(&hydrated.checked, now)
+            .map_err(|e| e.to_string())?;
+        let ids: Vec<String> = hydrated.items.into_iter().map(|item| item.id).collect();
+        db.get_media_items_by_ids(&ids).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Fill in missing durations from TMDB, which progress percentages and the
+/// continue-watching thresholds depend on. Returns the updated items.
+#[tauri::command]
+async fn backfill_durations(state: tauri::State<'_, AppState>) -> Result<Vec<MediaItem>, String> {
+    let db = state.inner().db.clone();
+    let items = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        load_tmdb_api_key(&db);
+        db.get_items_missing_duration().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let found = api::lookup_durations(items)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let mut ids = Vec::new();
+        for (media_id, minutes) in found {
+            if db
+                .fill_missing_duration(&media_id, minutes)
+                .map_err(|e| e.to_string())?
+            {
+                ids.push(media_id);
+            }
+        }
+        db.get_media_items_by_ids(&ids).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 #[tauri::command]
 async fn search_library_advanced(
     filters: crate::models::SearchFilters,
@@ -125,6 +329,33 @@ async fn search_library_advanced(
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+#[tauri::command]
+async fn search_library_highlighted(
+    query: String,
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<SearchHit>, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.search_library_highlighted(&query, limit.unwrap_or(20))
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn rebuild_search_index(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.rebuild_search_index().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 #[tauri::command]
 async fn list_catalogs(
     media_type: String,
@@ -132,19 +363,10 @@ async fn list_catalogs(
 ) -> Result<Vec<CatalogInfo>, String> {
     let db = state.inner().db.clone();
 
-    // Load addons (initialize built-ins if DB is empty)
+    // Load installed addons
     let addons = tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let mut addons = db.get_addons().map_err(|e| e.to_string())?;
-        if addons.is_empty() {
-            let builtin = tokio::runtime::Handle::current()
-                .block_on(api::get_builtin_addons())
-                .map_err(|e| e.to_string())?;
-            for addon in &builtin {
-                db.save_addon(addon).map_err(|e| e.to_string())?;
-            }
-            addons = builtin;
-        }
+        let addons = db.get_addons().map_err(|e| e.to_string())?;
         Ok::<Vec<Addon>, String>(addons)
     })
     .await
@@ -201,18 +423,7 @@ async fn aggregate_catalogs(
     let catalog_id_clone = catalog_id.clone();
     let addons_res = tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let mut addons = db.get_addons().map_err(|e| e.to_string())?;
-
-        if addons.is_empty() {
-            tracing::info!("No addons found in DB, initializing with built-in addons");
-            let builtin = tokio::runtime::Handle::current()
-                .block_on(api::get_builtin_addons())
-                .map_err(|e| e.to_string())?;
-            for addon in &builtin {
-                db.save_addon(addon).map_err(|e| e.to_string())?;
-            }
-            addons = builtin;
-        }
+        let addons = db.get_addons().map_err(|e| e.to_string())?;
 
         // Filter enabled addons that have catalogs for the requested media type
         let enabled: Vec<Addon> = addons
@@ -257,12 +468,21 @@ async fn aggregate_catalogs(
     );
     
     let cache = state.inner().cache.clone();
-    let aggregator = ContentAggregator::with_cache(cache);
+    let artwork_first = default_preference(state.inner(), |preferences| {
+        preferences.catalog_artwork_first
+    })
+    .await
+    .unwrap_or(false);
+    let aggregator = ContentAggregator::with_cache(cache)
+        .with_strict_validation(strict_addon_validation(state.inner()).await)
+        .with_response_limits(addon_response_limits(state.inner()).await)
+        .with_artwork_first(artwork_first);
     let result = aggregator
         .query_catalogs(&addons, &media_type, &catalog_id, &extra)
         .await;
 
     tracing::info!(
+        request_id = %result.request_id,
         item_count = result.items.len(),
         source_count = result.sources.len(),
         duration_ms = result.total_time_ms,
@@ -272,10 +492,12 @@ async fn aggregate_catalogs(
     // Log each source result
     for source in &result.sources {
         tracing::info!(
+            request_id = %result.request_id,
             addon_id = %source.addon_id,
             addon_name = %source.addon_name,
             success = source.success,
             item_count = source.item_count,
+            invalid_count = source.invalid_count,
             response_time_ms = source.response_time_ms,
             error = ?source.error,
             "Source result"
@@ -283,29 +505,14 @@ async fn aggregate_catalogs(
     }
 
     // Record health metrics for each addon
-    let db_for_health = state.inner().db.clone();
-    let sources_clone = result.sources.clone();
-    tokio::task::spawn_blocking(move || {
-        if let Ok(db) = db_for_health.lock() {
-            for source in sources_clone {
-                let error_msg = source.error.as_deref();
-                let _ = db.record_addon_health(
-                    &source.addon_id,
-                    source.response_time_ms,
-                    source.success,
-                    error_msg,
-                    source.item_count,
-                    "catalog",
-                );
-            }
-        }
-    });
+    record_addon_results(state.inner(), result.sources.clone(), "catalog");
 
     // Convert to JSON for frontend
     Ok(serde_json::json!({
         "items": result.items,
         "sources": result.sources,
-        "total_time_ms": result.total_time_ms
+        "total_time_ms": result.total_time_ms,
+        "request_id": result.request_id
     }))
 }
 
@@ -319,34 +526,148 @@ async fn get_stream_url(
     const FALLBACK_URL: &str =
         "https://commondatastorage.googleapis.com/gtv-videos-bucket/sample/BigBuckBunny.mp4";
 
-    // 1) Load enabled addons from the database (initialize built-ins if DB is empty)
-    let db = state.inner().db.clone();
-    let addons_res = tokio::task::spawn_blocking(move || {
-        let db = db.lock().map_err(|e| e.to_string())?;
-        let mut addons = db.get_addons().map_err(|e| e.to_string())?;
+    if let Some(file) = find_local_copy(&content_id, state.inner()).await? {
+        return serve_local_copy(&file, state.inner()).await;
+    }
 
-        if addons.is_empty() {
-            log::info!("No addons found in DB, initializing with built-in addons");
-            let builtin = tokio::runtime::Handle::current()
-                .block_on(api::get_builtin_addons())
-                .map_err(|e| e.to_string())?;
-            for addon in &builtin {
-                db.save_addon(addon).map_err(|e| e.to_string())?;
-            }
-            addons = builtin;
+    let selected = resolve_stream_url(&content_id, media_type, state.inner()).await?;
+    Ok(selected.unwrap_or_else(|| FALLBACK_URL.to_string()))
+}
+
+/// Pick the best stream for an item (cached per quality preference) and
+/// turn magnet links into direct URLs through debrid
+async fn resolve_stream_url(
+    content_id: &str,
+    media_type: Option<String>,
+    state: &AppState,
+) -> Result<Option<String>, String> {
+    let quality = preferred_quality(&state.db).await;
+    let selected = select_stream_cached(&state.cache, content_id, &quality, || {
+        aggregate_best_stream(content_id, media_type, state)
+    })
+    .await?;
+
+    match selected {
+        Some(url) if url.starts_with("magnet:") => {
+            let (url, _) = resolve_magnet_stream(&url, state).await?;
+            Ok(Some(url))
+        }
+        other => Ok(other),
+    }
+}
+
+/// The user's quality preference, used to key the stream selection cache
+async fn preferred_quality(db: &Arc<Mutex<Database>>) -> String {
+    let db = db.clone();
+    tokio::task::spawn_blocking(move || {
+        db.lock()
+            .ok()
+            .and_then(|db| db.get_user_profile(DEFAULT_PROFILE_ID).ok().flatten())
+            .map(|profile| profile.preferences.quality)
+    })
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| "auto".to_string())
+}
+
+/// Cache size budget in bytes from the `cache_size` preference (MB), or
+/// the default budget if it isn't a positive number
+fn cache_size_budget(preferences: &UserPreferences) -> u64 {
+    preferences
+        .cache_size
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|megabytes| *megabytes > 0)
+        .map(|megabytes| megabytes.saturating_mul(1024 * 1024))
+        .unwrap_or(cache::DEFAULT_MAX_SIZE_BYTES)
+}
+
+/// Whether the user wants direct/cached/debrid streams ranked above P2P ones
+async fn prefer_non_p2p(db: &Arc<Mutex<Database>>) -> bool {
+    let db = db.clone();
+    tokio::task::spawn_blocking(move || {
+        db.lock()
+            .ok()
+            .and_then(|db| db.get_user_profile(DEFAULT_PROFILE_ID).ok().flatten())
+            .map(|profile| profile.preferences.prefer_non_p2p)
+    })
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or(false)
+}
+
+/// Reuse the stream chosen for `(content_id, quality)` while it is fresh,
+/// otherwise run `aggregate` and remember its pick
+async fn select_stream_cached<F, Fut>(
+    cache: &Arc<Mutex<CacheManager>>,
+    content_id: &str,
+    quality: &str,
+    aggregate: F,
+) -> Result<Option<String>, String>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<Option<String>, String>>,
+{
+    if let Ok(cache_guard) = cache.lock() {
+        if let Ok(Some(url)) = cache_guard.get_stream_selection(content_id, quality) {
+            tracing::info!(content_id = %content_id, quality = %quality, "Using cached stream selection");
+            return Ok(Some(url));
+        }
+    }
+
+    let selected = aggregate().await?;
+
+    if let Some(url) = &selected {
+        if let Ok(cache_guard) = cache.lock() {
+            let _ = cache_guard.set_stream_selection(
+                content_id,
+                quality,
+                url,
+                cache::ttl::STREAM_SELECTION,
+            );
         }
+    }
+
+    Ok(selected)
+}
+
+/// Query all enabled stream addons and pick the best stream, if any
+async fn aggregate_best_stream(
+    content_id: &str,
+    media_type: Option<String>,
+    state: &AppState,
+) -> Result<Option<String>, String> {
+    // 1) Load enabled addons from the database
+    let db = state.db.clone();
+    let addons_res = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let addons = db.get_addons().map_err(|e| e.to_string())?;
 
         // Filter enabled addons that provide "stream" resource
         let enabled: Vec<Addon> = addons
             .into_iter()
             .filter(|a| a.enabled && a.manifest.resources.iter().any(|r| r == "stream"))
             .collect();
-        Ok::<Vec<Addon>, String>(enabled)
+
+        // Health scores are only a ranking bonus; missing data just means no bonus
+        let health_scores: std::collections::HashMap<String, f64> = db
+            .get_all_addon_health_summaries()
+            .map(|summaries| {
+                summaries
+                    .into_iter()
+                    .map(|h| (h.addon_id, h.health_score))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok::<_, String>((enabled, health_scores))
     })
     .await;
 
-    let addons = match addons_res {
-        Ok(Ok(v)) if !v.is_empty() => v,
+    let (addons, health_scores) = match addons_res {
+        Ok(Ok((v, scores))) if !v.is_empty() => (v, scores),
         Ok(Ok(_)) => {
             tracing::error!("No enabled addons with stream resource available - StreamGo cannot provide content without streaming addons");
             return Err(
@@ -371,46 +692,67 @@ async fn get_stream_url(
     };
 
     // 2) Query streams via aggregator with cache (default media_type to 'movie' for backward compatibility)
-    let cache = state.inner().cache.clone();
-    let aggregator = ContentAggregator::with_cache(cache);
+    let cache = state.cache.clone();
+    let aggregator = ContentAggregator::with_cache(cache)
+        .with_strict_validation(strict_addon_validation(state).await)
+        .with_response_limits(addon_response_limits(state).await)
+        .with_private_hosts(allow_private_addon_urls(state).await);
     let media_type_effective = media_type.unwrap_or_else(|| "movie".to_string());
     let result = aggregator
-        .query_streams(&addons, &media_type_effective, &content_id)
+        .query_streams_detailed(&addons, &media_type_effective, content_id)
         .await;
 
     // Record health metrics for each addon
-    let db_for_health = state.inner().db.clone();
-    let sources_clone = result.sources.clone();
-    tokio::task::spawn_blocking(move || {
-        if let Ok(db) = db_for_health.lock() {
-            for source in sources_clone {
-                let error_msg = source.error.as_deref();
-                let _ = db.record_addon_health(
-                    &source.addon_id,
-                    source.response_time_ms,
-                    source.success,
-                    error_msg,
-                    source.item_count,
-                    "stream",
-                );
-            }
-        }
-    });
+    record_addon_results(state, result.sources.clone(), "stream");
 
-    if let Some(url) = select_best_stream(&result.streams) {
+    let prefer_non_p2p = prefer_non_p2p(&state.db).await;
+    if let Some(url) = select_best_stream(&result.streams, Some(&health_scores), prefer_non_p2p) {
         tracing::info!(
             stream_count = result.streams.len(),
             duration_ms = result.total_time_ms,
             "Selected best stream via aggregator"
         );
-        return Ok(url);
+        return Ok(Some(url));
     }
 
     tracing::warn!(
         stream_count = result.streams.len(),
         "No valid streams from aggregator; using fallback URL"
     );
-    Ok(FALLBACK_URL.to_string())
+    Ok(None)
+}
+
+/// Forget the cached stream choice for an item after playback failed,
+/// so the next attempt re-runs aggregation
+fn invalidate_stream_selection(
+    cache: &Arc<Mutex<CacheManager>>,
+    content_id: &str,
+) -> Result<usize, String> {
+    let cache = cache.lock().map_err(|e| e.to_string())?;
+    cache
+        .invalidate_stream_selection(content_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn report_stream_failure(
+    content_id: String,
+    stream_url: Option<String>,
+    error: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    tracing::warn!(
+        content_id = %content_id,
+        stream_url = ?stream_url,
+        error = ?error,
+        "Stream playback failed"
+    );
+
+    let cache = state.inner().cache.clone();
+    tokio::task::spawn_blocking(move || invalidate_stream_selection(&cache, &content_id))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+    Ok(())
 }
 
 #[tauri::command]
@@ -419,91 +761,253 @@ async fn get_streams(
     media_type: Option<String>,
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<crate::models::StreamWithSource>, String> {
-    // Load enabled addons (initialize built-ins if needed)
-    let db = state.inner().db.clone();
-    let addons_res = tokio::task::spawn_blocking(move || {
-        let db = db.lock().map_err(|e| e.to_string())?;
-        let mut addons = db.get_addons().map_err(|e| e.to_string())?;
-        if addons.is_empty() {
-            let builtin = tokio::runtime::Handle::current()
-                .block_on(api::get_builtin_addons())
-                .map_err(|e| e.to_string())?;
-            for addon in &builtin {
-                db.save_addon(addon).map_err(|e| e.to_string())?;
-            }
-            addons = builtin;
-        }
-        // Filter enabled addons that provide "stream" resource
-        let enabled: Vec<Addon> = addons
-            .into_iter()
-            .filter(|a| a.enabled && a.manifest.resources.iter().any(|r| r == "stream"))
-            .collect();
-        Ok::<Vec<Addon>, String>(enabled)
-    })
-    .await
-    .map_err(|e| format!("Task join error: {}", e))?;
+    aggregate_streams(&content_id, media_type, state.inner()).await
+}
 
-    let addons = match addons_res {
-        Ok(v) if !v.is_empty() => v,
-        Ok(_) => {
-            tracing::warn!("No enabled addons with stream resource available");
-            return Err(
-                "No streaming addons available. Please install addons that provide streams."
-                    .to_string(),
-            );
-        }
-        Err(e) => return Err(format!("Failed to load addons: {}", e)),
-    };
+/// Whether the user opted in to dropping malformed addon entries
+async fn strict_addon_validation(state: &AppState) -> bool {
+    default_preference(state, |preferences| preferences.strict_addon_validation)
+        .await
+        .unwrap_or(false)
+}
 
-    let cache = state.inner().cache.clone();
-    let aggregator = ContentAggregator::with_cache(cache);
-    let media_type_effective = media_type.unwrap_or_else(|| "movie".to_string());
-    let result = aggregator
-        .query_streams_detailed(&addons, &media_type_effective, &content_id)
-        .await;
+/// Whether the user lets addons link to hosts on the local network
+async fn allow_private_addon_urls(state: &AppState) -> bool {
+    default_preference(state, |preferences| preferences.allow_private_addon_urls)
+        .await
+        .unwrap_or(false)
+}
 
-    // Record health metrics
-    let db_for_health = state.inner().db.clone();
-    let sources_clone = result.sources.clone();
-    tokio::task::spawn_blocking(move || {
-        if let Ok(db) = db_for_health.lock() {
-            for source in sources_clone {
-                let error_msg = source.error.as_deref();
-                let _ = db.record_addon_health(
-                    &source.addon_id,
-                    source.response_time_ms,
-                    source.success,
-                    error_msg,
-                    source.item_count,
-                    "stream",
-                );
+/// Record addon request results in the health table in the background,
+/// emitting `addon-health-changed` for each addon whose request went the
+/// other way from its previous one
+fn record_addon_results(
+    state: &AppState,
+    sources: Vec<SourceHealth>,
+    operation_type: &'static str,
+) {
+    let db = state.db.clone();
+    let events = state.events.clone();
+    tokio::task::spawn_blocking(move || {
+        let Ok(db) = db.lock() else {
+            return;
+        };
+        for source in sources {
+            let previous = db.last_addon_result(&source.addon_id).ok().flatten();
+            let recorded = db.record_addon_health(
+                &source.addon_id,
+                source.response_time_ms,
+                source.success,
+                source.error.as_deref(),
+                source.item_count,
+                source.invalid_count,
+                operation_type,
+            );
+            if recorded.is_ok() && previous != Some(source.success) {
+                events.emit(AppEvent::AddonHealthChanged(AddonHealthChanged {
+                    addon_id: source.addon_id,
+                    healthy: source.success,
+                    error: source.error,
+                }));
             }
         }
     });
+}
+
+async fn addon_response_limits(state: &AppState) -> ResponseLimits {
+    default_preference(state, ResponseLimits::from_preferences)
+        .await
+        .unwrap_or_default()
+}
 
-    Ok(result.streams)
+/// Read one preference of the default profile; `None` if it can't be loaded
+async fn default_preference<T: Send + 'static>(
+    state: &AppState,
+    read: fn(&UserPreferences) -> T,
+) -> Option<T> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().ok()?;
+        let profile = db.get_user_profile(DEFAULT_PROFILE_ID).ok()??;
+        Some(read(&profile.preferences))
+    })
+    .await
+    .ok()
+    .flatten()
 }
 
+/// Like `get_streams`, but sends each stream over `on_stream` as soon as its
+/// addon answers so the UI can render sources incrementally. If the user
+/// prefers non-P2P streams, torrents are held back and sent last. Resolves
+/// with the number of streams sent once every addon has answered.
 #[tauri::command]
-async fn get_subtitles(
+async fn get_streams_incremental(
     content_id: String,
     media_type: Option<String>,
+    on_stream: tauri::ipc::Channel<crate::models::StreamWithSource>,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<Subtitle>, String> {
-    // Load enabled addons
-    let db = state.inner().db.clone();
+) -> Result<usize, String> {
+    let addons = load_stream_addons(state.inner()).await?;
+    let aggregator = ContentAggregator::with_cache(state.inner().cache.clone())
+        .with_strict_validation(strict_addon_validation(state.inner()).await)
+        .with_response_limits(addon_response_limits(state.inner()).await)
+        .with_private_hosts(allow_private_addon_urls(state.inner()).await);
+    let media_type = media_type.unwrap_or_else(|| "movie".to_string());
+    let prefer_non_p2p = prefer_non_p2p(&state.db).await;
+
+    let (mut streams, mut health) =
+        aggregator.query_streams_stream(&addons, &media_type, &content_id);
+    let send = |stream| {
+        on_stream
+            .send(stream)
+            .map_err(|e| format!("Failed to send stream: {}", e))
+    };
+    let mut sent = 0;
+    let mut torrents = Vec::new();
+    let (mut streams_open, mut health_open) = (true, true);
+    while streams_open || health_open {
+        tokio::select! {
+            stream = streams.recv(), if streams_open => match stream {
+                Some(stream)
+                    if prefer_non_p2p
+                        && stream.source_type == crate::models::StreamSourceType::Torrent =>
+                {
+                    torrents.push(stream)
+                }
+                Some(stream) => {
+                    send(stream)?;
+                    sent += 1;
+                }
+                None => streams_open = false,
+            },
+            source = health.recv(), if health_open => match source {
+                Some(source) => record_addon_results(state.inner(), vec![source], "stream"),
+                None => health_open = false,
+            },
+        }
+    }
+    for stream in torrents {
+        send(stream)?;
+        sent += 1;
+    }
+    tracing::info!(content_id = %content_id, sent, "Streamed sources to frontend");
+    Ok(sent)
+}
+
+/// Aggregate streams as in `get_streams`, then drop the ones outside the
+/// user's size/resolution/seeder limits and rank the rest
+#[tauri::command]
+async fn get_streams_filtered(
+    content_id: String,
+    media_type: Option<String>,
+    filter: StreamFilter,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<crate::models::StreamWithSource>, String> {
+    let streams = aggregate_streams(&content_id, media_type, state.inner()).await?;
+    let total = streams.len();
+    let filtered = stream_filter::filter_and_rank(streams, &filter);
+    tracing::info!(
+        content_id = %content_id,
+        total,
+        kept = filtered.len(),
+        "Filtered streams"
+    );
+    Ok(filtered)
+}
+
+/// Load the enabled addons that provide the "stream" resource
+async fn load_stream_addons(state: &AppState) -> Result<Vec<Addon>, String> {
+    let db = state.db.clone();
     let addons_res = tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let mut addons = db.get_addons().map_err(|e| e.to_string())?;
-        if addons.is_empty() {
-            let builtin = tokio::runtime::Handle::current()
-                .block_on(api::get_builtin_addons())
-                .map_err(|e| e.to_string())?;
-            for addon in &builtin {
-                db.save_addon(addon).map_err(|e| e.to_string())?;
-            }
-            addons = builtin;
+        let addons = db.get_addons().map_err(|e| e.to_string())?;
+        // Filter enabled addons that provide "stream" resource
+        let enabled: Vec<Addon> = addons
+            .into_iter()
+            .filter(|a| a.enabled && a.manifest.resources.iter().any(|r| r == "stream"))
+            .collect();
+        Ok::<Vec<Addon>, String>(enabled)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+
+    match addons_res {
+        Ok(v) if !v.is_empty() => Ok(v),
+        Ok(_) => {
+            tracing::warn!("No enabled addons with stream resource available");
+            Err(
+                "No streaming addons available. Please install addons that provide streams."
+                    .to_string(),
+            )
         }
+        Err(e) => Err(format!("Failed to load addons: {}", e)),
+    }
+}
+
+/// Query all enabled stream addons and return every stream with its source,
+/// best quality first (P2P last if the user prefers non-P2P streams)
+async fn aggregate_streams(
+    content_id: &str,
+    media_type: Option<String>,
+    state: &AppState,
+) -> Result<Vec<crate::models::StreamWithSource>, String> {
+    let addons = load_stream_addons(state).await?;
+
+    let cache = state.cache.clone();
+    let aggregator = ContentAggregator::with_cache(cache)
+        .with_quality_sort(true)
+        .with_strict_validation(strict_addon_validation(state).await)
+        .with_response_limits(addon_response_limits(state).await)
+        .with_private_hosts(allow_private_addon_urls(state).await);
+    let media_type_effective = media_type.unwrap_or_else(|| "movie".to_string());
+    let result = aggregator
+        .query_streams_detailed(&addons, &media_type_effective, content_id)
+        .await;
+
+    // Record health metrics
+    record_addon_results(state, result.sources.clone(), "stream");
+
+    let mut streams = result.streams;
+    if prefer_non_p2p(&state.db).await {
+        streams.sort_by_key(|s| s.source_type == crate::models::StreamSourceType::Torrent);
+    }
+    Ok(streams)
+}
+
+#[tauri::command]
+async fn get_subtitles(
+    content_id: String,
+    media_type: Option<String>,
+    stream_subtitles: Option<Vec<Subtitle>>,
+    languages: Option<Vec<String>>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Subtitle>, String> {
+    collect_subtitles(
+        &content_id,
+        media_type,
+        stream_subtitles.unwrap_or_default(),
+        &languages.unwrap_or_default(),
+        state.inner(),
+    )
+    .await
+}
+
+/// Addon subtitles for an item merged with the stream's own, limited to
+/// `languages` (all when empty) and in the user's preferred order
+async fn collect_subtitles(
+    content_id: &str,
+    media_type: Option<String>,
+    stream_subtitles: Vec<Subtitle>,
+    languages: &[String],
+    state: &AppState,
+) -> Result<Vec<Subtitle>, String> {
+    let preferred = preferred_subtitle_languages(&state.db).await;
+
+    // Load enabled addons
+    let db = state.db.clone();
+    let addons_res = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let addons = db.get_addons().map_err(|e| e.to_string())?;
         // Filter enabled addons that provide "subtitles" resource
         let enabled: Vec<Addon> = addons
             .into_iter()
@@ -518,80 +1022,303 @@ async fn get_subtitles(
         Ok(v) if !v.is_empty() => v,
         Ok(_) => {
             tracing::debug!("No enabled addons with subtitles resource available");
-            // Return empty list instead of error - subtitles are optional
-            return Ok(Vec::new());
+            // Return the stream's own subtitles instead of error - subtitles are optional
+            let subs = merge_subtitles(stream_subtitles, Vec::new());
+            return Ok(order_subtitles(subs, languages, &preferred));
         }
         Err(e) => return Err(format!("Failed to load addons: {}", e)),
     };
 
+    let cache = state.cache.clone();
+    let aggregator = ContentAggregator::with_cache(cache)
+        .with_response_limits(addon_response_limits(state).await)
+        .with_private_hosts(allow_private_addon_urls(state).await);
     let media_type_effective = media_type.unwrap_or_else(|| "movie".to_string());
-    let mut subs: Vec<Subtitle> = Vec::new();
-    let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let result = aggregator
+        .query_subtitles_detailed(&addons, &media_type_effective, content_id)
+        .await;
 
-    for addon in addons {
-        let base = if addon.url.ends_with("/manifest.json") {
-            addon.url.replace("/manifest.json", "")
-        } else if addon.url.ends_with("manifest.json") {
-            addon.url.replace("manifest.json", "")
-        } else {
-            addon.url.clone()
-        };
-        let start = std::time::Instant::now();
-        let mut success = false;
-        let mut err_msg: Option<String> = None;
-        let mut item_count: usize = 0;
+    // Record health metrics
+    record_addon_results(state, result.sources.clone(), "subtitles");
 
-        match AddonClient::new(base) {
-            Ok(client) => match client
-                .get_subtitles(&media_type_effective, &content_id)
-                .await
-            {
-                Ok(response) => {
-                    for s in response.subtitles.into_iter() {
-                        if seen.insert(s.url.clone()) {
-                            subs.push(s);
-                            item_count += 1;
-                        }
-                    }
-                    success = item_count > 0;
-                }
-                Err(e) => {
-                    err_msg = Some(e.to_string());
-                }
-            },
-            Err(e) => {
-                err_msg = Some(e.to_string());
-            }
+    let subs = merge_subtitles(stream_subtitles, result.subtitles);
+    Ok(order_subtitles(subs, languages, &preferred))
+}
+
+/// The user's subtitle languages in order of preference
+/// (`subtitle_language` may hold a comma-separated list)
+async fn preferred_subtitle_languages(db: &Arc<Mutex<Database>>) -> Vec<String> {
+    let db = db.clone();
+    let setting = tokio::task::spawn_blocking(move || {
+        db.lock()
+            .ok()
+            .and_then(|db| db.get_user_profile(DEFAULT_PROFILE_ID).ok().flatten())
+            .map(|profile| profile.preferences.subtitle_language)
+    })
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| UserPreferences::default().subtitle_language);
+
+    setting
+        .split(',')
+        .map(|lang| lang.trim().to_string())
+        .filter(|lang| !lang.is_empty())
+        .collect()
+}
+
+/// Combine subtitles embedded in the selected stream with addon results,
+/// dropping duplicates by url and language. Stream subtitles come first
+/// since they are timed for that exact release.
+fn merge_subtitles(
+    stream_subtitles: Vec<Subtitle>,
+    provider_subtitles: Vec<Subtitle>,
+) -> Vec<Subtitle> {
+    let mut seen = std::collections::HashSet::new();
+    stream_subtitles
+        .into_iter()
+        .chain(provider_subtitles)
+        .filter(|s| seen.insert((s.url.clone(), language::language_key(&s.lang))))
+        .collect()
+}
+
+/// Keep only the requested languages (all when empty) and float the
+/// preferred languages to the top in preference order. Everything else
+/// keeps its original order below them.
+fn order_subtitles(
+    subtitles: Vec<Subtitle>,
+    languages: &[String],
+    preferred: &[String],
+) -> Vec<Subtitle> {
+    let languages: Vec<String> = languages
+        .iter()
+        .map(|l| language::language_key(l))
+        .collect();
+    let preferred: Vec<String> = preferred
+        .iter()
+        .map(|l| language::language_key(l))
+        .collect();
+
+    let mut subtitles: Vec<Subtitle> = subtitles
+        .into_iter()
+        .filter(|s| {
+            languages.is_empty() || languages.contains(&language::language_key(&s.lang))
+        })
+        .collect();
+    subtitles.sort_by_key(|s| {
+        let lang = language::language_key(&s.lang);
+        preferred
+            .iter()
+            .position(|p| *p == lang)
+            .unwrap_or(preferred.len())
+    });
+    subtitles
+}
+
+/// Everything the player needs to start an item in one call: a scanned
+/// local copy if there is one (and `prefer_local` is on), otherwise the best addon stream for
+/// `quality_pref` (the user's quality setting when omitted) with magnet
+/// links resolved through debrid or the torrent server. Subtitles from the
+/// stream and subtitle addons are attached either way.
+#[tauri::command]
+async fn resolve_playable(
+    content_id: String,
+    media_type: Option<String>,
+    quality_pref: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<PlayableResult, String> {
+    resolve_playable_for(&content_id, media_type, quality_pref, state.inner()).await
+}
+
+async fn resolve_playable_for(
+    content_id: &str,
+    media_type: Option<String>,
+    quality_pref: Option<String>,
+    state: &AppState,
+) -> Result<PlayableResult, String> {
+    let (url, stream, resolved_via) = match find_local_copy(content_id, state).await? {
+        Some(file) => (
+            serve_local_copy(&file, state).await?,
+            None,
+            ResolvedVia::LocalFile,
+        ),
+        None => {
+            let streams = aggregate_streams(content_id, media_type.clone(), state).await?;
+            let quality = match quality_pref {
+                Some(quality) => quality,
+                None => preferred_quality(&state.db).await,
+            };
+            let health_scores = addon_health_scores(state).await;
+            let prefer_non_p2p = prefer_non_p2p(&state.db).await;
+            let stream = pick_playable_stream(streams, &quality, &health_scores, prefer_non_p2p)
+                .ok_or_else(|| format!("No playable streams found for {}", content_id))?;
+
+            let (url, resolved_via) = if stream.url.starts_with("magnet:") {
+                resolve_magnet_stream(&stream.url, state).await?
+            } else {
+                (stream.url.clone(), ResolvedVia::Addon)
+            };
+            (url, Some(stream), resolved_via)
         }
+    };
 
-        let elapsed = start.elapsed().as_millis();
-        let addon_id = addon.id.clone();
-        let db_for_health = state.inner().db.clone();
-        let err_msg_clone = err_msg.clone();
-        tokio::task::spawn_blocking(move || {
-            if let Ok(db) = db_for_health.lock() {
-                let _ = db.record_addon_health(
-                    &addon_id,
-                    elapsed,
-                    success,
-                    err_msg_clone.as_deref(),
-                    item_count,
-                    "subtitles",
-                );
-            }
+    // Missing subtitles shouldn't keep the item from playing
+    let stream_subtitles = stream
+        .as_ref()
+        .map(|s| s.subtitles.clone())
+        .unwrap_or_default();
+    let subtitles = collect_subtitles(content_id, media_type, stream_subtitles, &[], state)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!(content_id = %content_id, error = %e, "Subtitle lookup failed");
+            Vec::new()
         });
+
+    tracing::info!(
+        content_id = %content_id,
+        resolved_via = ?resolved_via,
+        subtitles = subtitles.len(),
+        "Resolved playable source"
+    );
+    Ok(PlayableResult {
+        url,
+        stream,
+        subtitles,
+        is_local: resolved_via == ResolvedVia::LocalFile,
+        resolved_via,
+    })
+}
+
+/// Scanned local copy of an item, unless the user turned `prefer_local` off
+async fn find_local_copy(
+    content_id: &str,
+    state: &AppState,
+) -> Result<Option<LocalMediaFile>, String> {
+    let db = state.db.clone();
+    let content_id = content_id.to_string();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let prefer_local = db
+            .get_user_profile(DEFAULT_PROFILE_ID)
+            .map_err(|e| e.to_string())?
+            .map_or(true, |profile| profile.preferences.prefer_local);
+        if !prefer_local {
+            return Ok(None);
+        }
+
+        let files = db.get_local_media_files().map_err(|e| e.to_string())?;
+        let item = db
+            .get_media_items_by_ids(std::slice::from_ref(&content_id))
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .next();
+        Ok(find_local_file(&files, &content_id, item.as_ref()).cloned())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Streaming server URL for a scanned local copy; the webview can't load
+/// raw filesystem paths
+async fn serve_local_copy(file: &LocalMediaFile, state: &AppState) -> Result<String, String> {
+    let server = state
+        .streaming_server
+        .as_ref()
+        .ok_or_else(|| "Streaming server not available".to_string())?;
+    Ok(server
+        .register_local_file(std::path::PathBuf::from(&file.file_path))
+        .await)
+}
+
+/// Scanned file that still exists on disk for an IMDb id. Episode ids
+/// ("tt0903747:1:2") match the file of that season and episode; plain ids
+/// only match files that aren't episodes. Movies without a file tagged
+/// with their id fall back to the title and year of `item`.
+fn find_local_file<'a>(
+    files: &'a [LocalMediaFile],
+    content_id: &str,
+    item: Option<&MediaItem>,
+) -> Option<&'a LocalMediaFile> {
+    let on_disk = |file: &LocalMediaFile| std::path::Path::new(&file.file_path).is_file();
+    let (imdb_id, episode) = match addon_protocol::episode_id::parse(content_id) {
+        Some((series_id, season, episode)) => (series_id, Some((season, episode))),
+        None => (content_id.to_string(), None),
+    };
+    let by_id = files.iter().find(|file| {
+        let same_episode = match episode {
+            Some((season, episode)) => file.season == Some(season) && file.episode == Some(episode),
+            None => file.season.is_none(),
+        };
+        file.imdb_id.as_deref() == Some(imdb_id.as_str()) && same_episode && on_disk(file)
+    });
+    if by_id.is_some() || episode.is_some() {
+        return by_id;
     }
 
-    Ok(subs)
+    // Files tagged with another id are known to be something else
+    let item = item?;
+    let title = local_media::title_key(&item.title);
+    files.iter().find(|file| {
+        let same_year = match (item.year, file.year) {
+            (Some(year), Some(file_year)) => i64::from(year) == i64::from(file_year),
+            _ => true,
+        };
+        file.imdb_id.is_none()
+            && file.season.is_none()
+            && same_year
+            && local_media::title_key(&file.title) == title
+            && on_disk(file)
+    })
+}
+
+/// Best stream at or below the preferred resolution ("auto" allows any).
+/// Falls back to all streams when none fit the preference.
+fn pick_playable_stream(
+    streams: Vec<crate::models::StreamWithSource>,
+    quality: &str,
+    health_scores: &std::collections::HashMap<String, f64>,
+    prefer_non_p2p: bool,
+) -> Option<crate::models::StreamWithSource> {
+    let max_resolution = parse_quality_hint(quality);
+    let filter = StreamFilter {
+        max_resolution: (max_resolution > 0).then_some(max_resolution),
+        ..StreamFilter::default()
+    };
+    let (fitting, others): (Vec<_>, Vec<_>) = streams.into_iter().partition(|s| filter.matches(s));
+    let candidates = if fitting.is_empty() { others } else { fitting };
+
+    let url = select_best_stream(&candidates, Some(health_scores), prefer_non_p2p)?;
+    candidates.into_iter().find(|s| s.url == url)
+}
+
+/// Recorded health score per addon id; empty when none is available
+async fn addon_health_scores(state: &AppState) -> std::collections::HashMap<String, f64> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().ok()?;
+        let summaries = db.get_all_addon_health_summaries().ok()?;
+        Some(
+            summaries
+                .into_iter()
+                .map(|h| (h.addon_id, h.health_score))
+                .collect(),
+        )
+    })
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or_default()
 }
 
 // Ratings and skip segments commands
 #[tauri::command]
 async fn rate_addon(addon_id: String, rating: u8, state: tauri::State<'_, AppState>) -> Result<AddonRatingSummary, String> {
     let db = state.inner().db.clone();
+    let user_id = active_profile_id(state.inner())?;
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        db.upsert_addon_rating("default_user", &addon_id, rating as i32).map_err(|e| e.to_string())
+        db.rate_addon(&addon_id, &user_id, rating as i32)
+            .map_err(|e| e.to_string())
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?
@@ -602,7 +1329,7 @@ async fn get_addon_rating(addon_id: String, state: tauri::State<'_, AppState>) -
     let db = state.inner().db.clone();
     let db_result = tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        db.get_addon_rating_summary(&addon_id).map_err(|e| e.to_string())
+        db.get_addon_rating(&addon_id).map_err(|e| e.to_string())
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?;
@@ -610,12 +1337,26 @@ async fn get_addon_rating(addon_id: String, state: tauri::State<'_, AppState>) -
     db_result.and_then(|summary_opt| summary_opt.ok_or_else(|| "No rating available".to_string()))
 }
 
+/// Rating summaries for all rated addons, best weighted rating first
+#[tauri::command]
+async fn get_addon_ratings(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<AddonRatingSummary>, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_addon_ratings().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 #[tauri::command]
-async fn save_skip_segments(media_id: String, segments: SkipSegments, state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn set_skip_segments(media_id: String, segments: SkipSegments, state: tauri::State<'_, AppState>) -> Result<(), String> {
     let db = state.inner().db.clone();
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        db.upsert_skip_segments(&media_id, &segments).map_err(|e| e.to_string())
+        db.set_skip_segments(&media_id, &segments).map_err(|e| e.to_string())
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?
@@ -632,26 +1373,63 @@ async fn get_skip_segments(media_id: String, state: tauri::State<'_, AppState>)
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+#[tauri::command]
+async fn get_episodes(
+    series_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Episode>, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_episodes(&series_id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn get_next_unwatched_episode(
+    series_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<Episode>, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_next_unwatched_episode(&series_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn update_episode_progress(
+    episode_id: String,
+    progress: i32,
+    watched: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.update_episode_progress(&episode_id, progress, watched)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 #[tauri::command]
 async fn get_addon_meta(
     content_id: String,
     media_type: Option<String>,
     state: tauri::State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<crate::addon_protocol::MetaItem, String> {
     // Load enabled addons
     let db = state.inner().db.clone();
     let addons_res = tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let mut addons = db.get_addons().map_err(|e| e.to_string())?;
-        if addons.is_empty() {
-            let builtin = tokio::runtime::Handle::current()
-                .block_on(api::get_builtin_addons())
-                .map_err(|e| e.to_string())?;
-            for addon in &builtin {
-                db.save_addon(addon).map_err(|e| e.to_string())?;
-            }
-            addons = builtin;
-        }
+        let addons = db.get_addons().map_err(|e| e.to_string())?;
         // Filter enabled addons that provide "meta" resource
         let enabled: Vec<Addon> = addons
             .into_iter()
@@ -672,77 +1450,121 @@ async fn get_addon_meta(
     };
 
     let media_type_effective = media_type.unwrap_or_else(|| "movie".to_string());
-    let mut aggregated_meta: Option<serde_json::Value> = None;
+    let mut aggregated_meta: Option<crate::addon_protocol::MetaItem> = None;
 
     // Query each addon for meta and merge results (first successful wins)
     for addon in addons {
-        let base = if addon.url.ends_with("/manifest.json") {
-            addon.url.replace("/manifest.json", "")
-        } else if addon.url.ends_with("manifest.json") {
-            addon.url.replace("manifest.json", "")
-        } else {
-            addon.url.clone()
-        };
+        let base = addon.base_url();
 
         let start = std::time::Instant::now();
-        let mut err_msg: Option<String> = None;
 
-        match AddonClient::new(base) {
+        let outcome = match AddonClient::new(base) {
             Ok(client) => match client.get_meta(&media_type_effective, &content_id).await {
                 Ok(response) => {
-                    // Convert to JSON and use first successful response
-                    if let Ok(json) = serde_json::to_value(&response.meta) {
-                        aggregated_meta = Some(json);
-
-                        // Record health and return immediately on success
-                        let elapsed = start.elapsed().as_millis();
-                        let addon_id = addon.id.clone();
-                        let db_for_health = state.inner().db.clone();
-                        tokio::task::spawn_blocking(move || {
-                            if let Ok(db) = db_for_health.lock() {
-                                let _ = db
-                                    .record_addon_health(&addon_id, elapsed, true, None, 1, "meta");
-                            }
-                        });
-
-                        break; // Stop at first successful meta response
-                    }
-                }
-                Err(e) => {
-                    err_msg = Some(e.to_string());
+                    // Use first successful response
+                    aggregated_meta = Some(response.meta);
+                    Ok(())
                 }
+                Err(e) => Err(e.to_string()),
             },
-            Err(e) => {
-                err_msg = Some(e.to_string());
-            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        let success = outcome.is_ok();
+        let health = SourceHealth {
+            addon_id: addon.id.clone(),
+            addon_name: addon.name.clone(),
+            response_time_ms: start.elapsed().as_millis(),
+            success,
+            error: outcome.err(),
+            item_count: usize::from(success),
+            priority: addon.priority,
+            invalid_count: 0,
+            request_id: None,
+        };
+        record_addon_results(state.inner(), vec![health], "meta");
+        if success {
+            break; // Stop at first successful meta response
         }
+    }
 
-        // Record health for failed attempts (only if no meta was aggregated)
-        if aggregated_meta.is_none() {
-            let elapsed = start.elapsed().as_millis();
-            let addon_id = addon.id.clone();
-            let db_for_health = state.inner().db.clone();
-            let err_msg_clone = err_msg.clone();
-            tokio::task::spawn_blocking(move || {
-                if let Ok(db) = db_for_health.lock() {
-                    let _ = db.record_addon_health(
-                        &addon_id,
-                        elapsed,
-                        false,
-                        err_msg_clone.as_deref(),
-                        0,
-                        "meta",
+    // A library item picks up any artwork it is missing from the meta
+    if let Some(meta) = &aggregated_meta {
+        let artwork = Artwork {
+            poster_url: meta.poster.clone(),
+            backdrop_url: meta.background.clone(),
+            logo_url: meta.logo.clone(),
+            thumb_url: None,
+        };
+        let db = state.inner().db.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Ok(db) = db.lock() {
+                if let Err(e) = db.fill_missing_artwork(&content_id, &artwork) {
+                    tracing::debug!(
+                        content_id = %content_id,
+                        error = %e,
+                        "Failed to store meta artwork"
                     );
                 }
-            });
-        }
+            }
+        });
     }
 
     aggregated_meta.ok_or_else(|| "No metadata found from any addon".to_string())
 }
 
-fn select_best_stream(streams: &[crate::addon_protocol::Stream]) -> Option<String> {
-    let mut best_score = i32::MIN;
+/// Like `get_addon_meta`, but returns the first addon's meta JSON untouched (for debugging)
+#[tauri::command]
+async fn get_addon_meta_raw(
+    content_id: String,
+    media_type: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let db = state.inner().db.clone();
+    let addons = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let addons = db.get_addons().map_err(|e| e.to_string())?;
+        Ok::<Vec<Addon>, String>(
+            addons
+                .into_iter()
+                .filter(|a| a.enabled && a.manifest.resources.iter().any(|r| r == "meta"))
+                .collect(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let media_type_effective = media_type.unwrap_or_else(|| "movie".to_string());
+    let mut last_error = "No addons with metadata support available".to_string();
+
+    for addon in addons {
+        let result = match AddonClient::new(addon.base_url()) {
+            Ok(client) => {
+                client
+                    .get_meta_raw(&media_type_effective, &content_id)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(json) => return Ok(json),
+            Err(e) => last_error = format!("{}: {}", addon.id, e),
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Pick the highest scoring stream. `health_scores` maps addon id to its
+/// recorded health score (0-100), worth up to a 10 point bonus. With
+/// `prefer_non_p2p` any direct, cached or debrid stream beats every P2P one.
+fn select_best_stream(
+    streams: &[crate::models::StreamWithSource],
+    health_scores: Option<&std::collections::HashMap<String, f64>>,
+    prefer_non_p2p: bool,
+) -> Option<String> {
+    let mut best_score = (false, i32::MIN);
     let mut best_url: Option<String> = None;
 
     for s in streams {
@@ -758,20 +1580,8 @@ fn select_best_stream(streams: &[crate::addon_protocol::Stream]) -> Option<Strin
             score += 100;
         }
 
-        // Quality parsing from name/title/description
-        let mut q = 0;
-        if let Some(name) = &s.name {
-            q = q.max(parse_quality_hint(name));
-        }
-        if let Some(title) = &s.title {
-            q = q.max(parse_quality_hint(title));
-        }
-        if let Some(desc) = &s.description {
-            q = q.max(parse_quality_hint(desc));
-        }
-
-        // Weight higher quality
-        score += match q {
+        // Weight higher quality, as labelled in name/title/description
+        score += match quality::labels_quality([&s.name, &s.title, &s.description]) {
             2160 => 50,
             1440 => 40,
             1080 => 30,
@@ -782,65 +1592,69 @@ fn select_best_stream(streams: &[crate::addon_protocol::Stream]) -> Option<Strin
         };
 
         // Penalize not web ready
-        if s.behaviorHints.notWebReady {
+        if s.not_web_ready {
             score -= 25;
         }
 
-        if score > best_score {
-            best_score = score;
-            best_url = Some(s.url.clone());
+        // Prefer addons that have been reliable
+        if let Some(health) = health_scores.and_then(|scores| scores.get(&s.addon_id)) {
+            score += (health / 10.0) as i32;
+        }
+
+        let non_p2p = prefer_non_p2p && s.source_type != crate::models::StreamSourceType::Torrent;
+        if (non_p2p, score) > best_score {
+            best_score = (non_p2p, score);
+            best_url = Some(s.url.clone());
         }
     }
 
     best_url
 }
 
-fn parse_quality_hint(s: &str) -> i32 {
-    let l = s.to_lowercase();
-    if l.contains("2160p") || l.contains("4k") {
-        return 2160;
-    }
-    if l.contains("1440p") {
-        return 1440;
-    }
-    if l.contains("1080p") || l.contains("full hd") {
-        return 1080;
-    }
-    if l.contains("720p") || l.contains(" hd") {
-        return 720;
-    }
-    if l.contains("480p") {
-        return 480;
-    }
-    if l.contains("360p") {
-        return 360;
-    }
-    0
-}
-
 #[tauri::command]
 async fn install_addon(
     addon_url: String,
+    replace_existing: Option<bool>,
     state: tauri::State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<AddonInstallResult, String> {
     // Download and validate addon
     let addon = api::install_addon(&addon_url)
         .await
         .map_err(|e| e.to_string())?;
 
-    let addon_id = addon.id.clone();
     let db = state.inner().db.clone();
 
-    // Save to database
+    // Save to database unless another URL already provides this addon
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        db.save_addon(&addon).map_err(|e| e.to_string())?;
-        Ok::<(), String>(())
+        db.install_addon(&addon, replace_existing.unwrap_or(false))
+            .map_err(|e| e.to_string())
     })
     .await
-    .map_err(|e| format!("Task join error: {}", e))??;
+    .map_err(|e| format!("Task join error: {}", e))?
+}
 
-    Ok(addon_id)
+/// Fetch and validate an addon's manifest and probe each resource it
+/// declares, without installing it
+#[tauri::command]
+async fn test_addon(addon_url: String) -> Result<api::AddonTestReport, String> {
+    api::test_addon(&addon_url).await.map_err(|e| e.to_string())
+}
+
+/// Dry-run a catalog/stream/meta/subtitles query against any addon URL and
+/// return the raw response, without installing the addon or writing to the
+/// database or cache
+#[tauri::command]
+async fn sandbox_query(
+    addon_url: String,
+    resource: String,
+    media_type: String,
+    id: String,
+    extra: Option<std::collections::HashMap<String, String>>,
+) -> Result<serde_json::Value, String> {
+    api::sandbox_query(&addon_url, &resource, &media_type, &id, extra.as_ref())
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -849,64 +1663,141 @@ async fn get_addons(state: tauri::State<'_, AppState>) -> Result<Vec<Addon>, Str
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let mut addons = db.get_addons().map_err(|e| e.to_string())?;
+        db.get_addons().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
 
-        // If no addons in DB, initialize with built-in ones
-        if addons.is_empty() {
-            log::info!("No addons found in DB, initializing with built-in addons");
-            let builtin = tokio::runtime::Handle::current()
-                .block_on(api::get_builtin_addons())
-                .map_err(|e| e.to_string())?;
+#[tauri::command]
+async fn enable_addon(addon_id: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let db = state.inner().db.clone();
 
-            for addon in &builtin {
-                db.save_addon(addon).map_err(|e| e.to_string())?;
-            }
-            addons = builtin;
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let updated = db
+            .set_addons_enabled(std::slice::from_ref(&addon_id), true)
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err(format!("Addon not found: {}", addon_id));
         }
-
-        Ok(addons)
+        Ok(())
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
 #[tauri::command]
-async fn enable_addon(addon_id: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn disable_addon(addon_id: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
     let db = state.inner().db.clone();
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let addons = db.get_addons().map_err(|e| e.to_string())?;
+        let updated = db
+            .set_addons_enabled(std::slice::from_ref(&addon_id), false)
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err(format!("Addon not found: {}", addon_id));
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
 
-        let mut addon = addons
-            .into_iter()
-            .find(|a| a.id == addon_id)
-            .ok_or_else(|| format!("Addon not found: {}", addon_id))?;
+#[tauri::command]
+async fn set_addon_priority(
+    addon_id: String,
+    priority: i32,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let db = state.inner().db.clone();
 
-        addon.enabled = true;
-        db.save_addon(&addon).map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let updated = db
+            .update_addon_priority(&addon_id, priority)
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err(format!("Addon not found: {}", addon_id));
+        }
         Ok(())
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// Give addons descending priorities in the order listed, highest first.
+/// The list must hold every installed addon exactly once.
 #[tauri::command]
-async fn disable_addon(addon_id: String, state: tauri::State<'_, AppState>) -> Result<(), String> {
+async fn reorder_addons(
+    ordered_ids: Vec<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Addon>, String> {
     let db = state.inner().db.clone();
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let addons = db.get_addons().map_err(|e| e.to_string())?;
+        db.reorder_addons(&ordered_ids).map_err(|e| e.to_string())?;
+        db.get_addons().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
 
-        let mut addon = addons
-            .into_iter()
-            .find(|a| a.id == addon_id)
-            .ok_or_else(|| format!("Addon not found: {}", addon_id))?;
+#[tauri::command]
+async fn set_addons_enabled(
+    ids: Vec<String>,
+    enabled: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Addon>, String> {
+    let db = state.inner().db.clone();
 
-        addon.enabled = false;
-        db.save_addon(&addon).map_err(|e| e.to_string())?;
-        Ok(())
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.set_addons_enabled(&ids, enabled)
+            .map_err(|e| e.to_string())?;
+        db.get_addons().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn enable_all_addons(state: tauri::State<'_, AppState>) -> Result<Vec<Addon>, String> {
+    set_all_addons_enabled(true, state.inner()).await
+}
+
+#[tauri::command]
+async fn disable_all_addons(state: tauri::State<'_, AppState>) -> Result<Vec<Addon>, String> {
+    set_all_addons_enabled(false, state.inner()).await
+}
+
+async fn set_all_addons_enabled(enabled: bool, state: &AppState) -> Result<Vec<Addon>, String> {
+    let db = state.db.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.set_all_addons_enabled(enabled)
+            .map_err(|e| e.to_string())?;
+        db.get_addons().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Addons that haven't contributed results in `days` days (default 30), as cleanup suggestions
+#[tauri::command]
+async fn get_unused_addons(
+    days: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Addon>, String> {
+    let db = state.inner().db.clone();
+    let days = days.unwrap_or(30);
+
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_unused_addons(days).map_err(|e| e.to_string())
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?
@@ -928,6 +1819,166 @@ async fn uninstall_addon(
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// Store one configuration value for an addon. An empty value removes the key.
+/// Cached responses from the old configuration are dropped.
+#[tauri::command]
+async fn set_addon_config(
+    addon_id: String,
+    key: String,
+    value: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let db = state.inner().db.clone();
+    let cache = state.inner().cache.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        if value.is_empty() {
+            db.delete_addon_config(&addon_id, &key)
+        } else {
+            db.set_addon_config(&addon_id, &key, &value)
+        }
+        .map_err(|e| e.to_string())?;
+
+        if let Ok(cache) = cache.lock() {
+            let _ = cache.clear_addon_cache(&addon_id);
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn get_addon_config(
+    addon_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<std::collections::HashMap<String, String>, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_all_addon_config(&addon_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn get_app_setting(
+    key: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_app_setting(&key).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn set_app_setting(
+    key: String,
+    value: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.set_app_setting(&key, &value).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn get_all_app_settings(
+    state: tauri::State<'_, AppState>,
+) -> Result<std::collections::HashMap<String, String>, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_all_app_settings().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn check_addon_updates(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<AddonUpdate>, String> {
+    let db = state.inner().db.clone();
+    let addons = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_addons().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let checks = addons.iter().map(api::check_addon_update);
+    let results = futures::future::join_all(checks).await;
+
+    let mut updates = Vec::new();
+    for (addon, result) in addons.iter().zip(results) {
+        match result {
+            Ok(update) => updates.push(update),
+            Err(e) => {
+                tracing::warn!(addon_id = %addon.id, error = %e, "Failed to check addon for updates")
+            }
+        }
+    }
+
+    Ok(updates)
+}
+
+#[tauri::command]
+async fn update_addon(
+    addon_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<AddonUpdateResult, String> {
+    let db = state.inner().db.clone();
+    let current = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_addons()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|a| a.id == addon_id)
+            .ok_or_else(|| format!("Addon not found: {}", addon_id))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let updated = api::fetch_updated_addon(&current)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let changes = api::diff_manifests(&current.manifest, &updated.manifest);
+    tracing::info!(
+        addon_id = %updated.id,
+        from = %current.version,
+        to = %updated.version,
+        unchanged = changes.is_empty(),
+        "Updated addon manifest"
+    );
+
+    let db = state.inner().db.clone();
+    let saved = updated.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.save_addon(&saved).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    Ok(AddonUpdateResult {
+        addon: updated,
+        changes,
+    })
+}
+
 #[tauri::command]
 async fn get_media_details(
     content_id: String,
@@ -943,7 +1994,7 @@ async fn get_media_details(
 #[tauri::command]
 async fn get_settings(state: tauri::State<'_, AppState>) -> Result<UserPreferences, String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = DEFAULT_PROFILE_ID.to_string();
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -977,7 +2028,9 @@ async fn save_settings(
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = DEFAULT_PROFILE_ID.to_string();
+    let max_active_torrents = settings.max_active_torrents as usize;
+    let cache_budget = cache_size_budget(&settings);
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -995,93 +2048,190 @@ async fn save_settings(
             },
         };
 
+        // The seeding flag and OpenSubtitles login state are internal and
+        // not round-tripped by the settings UI
+        let builtins_seeded = profile.preferences.builtins_seeded;
+        let opensubtitles_logged_in = profile.preferences.opensubtitles_logged_in;
+        let legacy_token = profile.preferences.legacy_opensubtitles_token.take();
         profile.preferences = settings;
+        profile.preferences.builtins_seeded |= builtins_seeded;
+        profile.preferences.opensubtitles_logged_in = opensubtitles_logged_in;
+        profile.preferences.legacy_opensubtitles_token = legacy_token;
         db.save_user_profile(&profile).map_err(|e| e.to_string())
     })
     .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    if let Some(server) = state.inner().streaming_server.as_ref() {
+        server.set_max_active_torrents(max_active_torrents);
+    }
+    if let Ok(mut cache) = state.inner().cache.lock() {
+        cache.set_max_size_bytes(cache_budget);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn check_new_episodes(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<notifications::NewEpisode>, String> {
+    notifications::check_and_record(state.inner().db.clone())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_calendar(
+    days_ahead: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<calendar::CalendarEntry>, String> {
+    let db = state.inner().db.clone();
+    let days = days_ahead.unwrap_or(7); // Default to 7 days
+
+    // Get library items and addons
+    let (library_items, addons) = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let items = db.get_library_items().map_err(|e| e.to_string())?;
+        let addons = db.get_addons().map_err(|e| e.to_string())?;
+        Ok::<(Vec<MediaItem>, Vec<Addon>), String>((items, addons))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    // Generate calendar
+    let calendar_entries = calendar::get_calendar(library_items, days, addons)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(calendar_entries)
+}
+
+#[tauri::command]
+async fn export_calendar_ics(
+    days_ahead: Option<u32>,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let entries = get_calendar(days_ahead, state).await?;
+
+    let output_path = dirs::data_local_dir()
+        .ok_or_else(|| "Could not find data directory".to_string())?
+        .join("StreamGo")
+        .join("calendar.ics");
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&output_path, calendar::to_ics(&entries)).map_err(|e| e.to_string())?;
+
+    tracing::info!(
+        entries = entries.len(),
+        path = %output_path.display(),
+        "Exported calendar"
+    );
+    Ok(output_path.display().to_string())
+}
+
+// Profile commands
+#[tauri::command]
+async fn create_profile(
+    username: String,
+    email: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<UserProfile, String> {
+    let username = username.trim().to_string();
+    if username.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    let db = state.inner().db.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let profile = UserProfile {
+            id: uuid::Uuid::new_v4().to_string(),
+            username,
+            email,
+            preferences: UserPreferences::default(),
+            library_items: Vec::new(),
+            watchlist: Vec::new(),
+            favorites: Vec::new(),
+        };
+        db.save_user_profile(&profile).map_err(|e| e.to_string())?;
+        Ok(profile)
+    })
+    .await
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
 #[tauri::command]
-async fn check_new_episodes(
-    state: tauri::State<'_, AppState>,
-) -> Result<Vec<notifications::NewEpisode>, String> {
+async fn list_profiles(state: tauri::State<'_, AppState>) -> Result<Vec<UserProfile>, String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
 
-    // Get library items, addons, and last check timestamp
-    let user_id_clone = user_id.clone();
-    let (library_items, addons, last_check) = tokio::task::spawn_blocking(move || {
+    tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let items = db.get_library_items().map_err(|e| e.to_string())?;
-        let addons = db.get_addons().map_err(|e| e.to_string())?;
-        
-        let profile = db.get_user_profile(&user_id_clone).map_err(|e| e.to_string())?;
-        let last_check = profile
-            .and_then(|p| p.preferences.last_notification_check)
-            .and_then(|ts| chrono::DateTime::parse_from_rfc3339(&ts).ok())
-            .map(|dt| dt.with_timezone(&chrono::Utc));
-        
-        Ok::<(Vec<MediaItem>, Vec<Addon>, Option<chrono::DateTime<chrono::Utc>>), String>((items, addons, last_check))
+        db.list_user_profiles().map_err(|e| e.to_string())
     })
     .await
-    .map_err(|e| format!("Task join error: {}", e))??;
+    .map_err(|e| format!("Task join error: {}", e))?
+}
 
-    // Check for new episodes
-    let new_episodes = notifications::check_new_episodes(library_items, last_check, addons)
-        .await
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+async fn get_active_profile(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    active_profile_id(state.inner())
+}
 
-    // Update last_check timestamp
+/// Make `profile_id` the profile library commands act on, remembered across restarts
+#[tauri::command]
+async fn switch_active_profile(
+    profile_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<UserProfile, String> {
     let db = state.inner().db.clone();
-    let now = chrono::Utc::now().to_rfc3339();
-    tokio::task::spawn_blocking(move || {
+    let id = profile_id.clone();
+
+    let profile = tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let mut profile = match db.get_user_profile(&user_id).map_err(|e| e.to_string())? {
-            Some(p) => p,
-            None => UserProfile {
-                id: user_id.clone(),
-                username: "User".to_string(),
-                email: None,
-                preferences: UserPreferences::default(),
-                library_items: Vec::new(),
-                watchlist: Vec::new(),
-                favorites: Vec::new(),
-            },
-        };
-        profile.preferences.last_notification_check = Some(now);
-        db.save_user_profile(&profile).map_err(|e| e.to_string())
+        let profile = db
+            .get_user_profile(&id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Profile not found: {}", id))?;
+        let value = serde_json::to_string(&id).map_err(|e| e.to_string())?;
+        db.set_app_setting(ACTIVE_PROFILE_SETTING, &value)
+            .map_err(|e| e.to_string())?;
+        Ok::<UserProfile, String>(profile)
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))??;
 
-    Ok(new_episodes)
+    *state.active_profile.lock().map_err(|e| e.to_string())? = profile_id;
+    Ok(profile)
 }
 
+/// Delete a profile and its library lists. The active and default profiles
+/// can't be deleted.
 #[tauri::command]
-async fn get_calendar(
-    days_ahead: Option<u32>,
+async fn delete_profile(
+    profile_id: String,
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<calendar::CalendarEntry>, String> {
+) -> Result<(), String> {
+    if profile_id == DEFAULT_PROFILE_ID {
+        return Err("The default profile cannot be deleted".to_string());
+    }
+    if profile_id == active_profile_id(state.inner())? {
+        return Err("Switch to another profile before deleting this one".to_string());
+    }
     let db = state.inner().db.clone();
-    let days = days_ahead.unwrap_or(7); // Default to 7 days
 
-    // Get library items and addons
-    let (library_items, addons) = tokio::task::spawn_blocking(move || {
+    tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        let items = db.get_library_items().map_err(|e| e.to_string())?;
-        let addons = db.get_addons().map_err(|e| e.to_string())?;
-        Ok::<(Vec<MediaItem>, Vec<Addon>), String>((items, addons))
+        if !db
+            .delete_user_profile(&profile_id)
+            .map_err(|e| e.to_string())?
+        {
+            return Err(format!("Profile not found: {}", profile_id));
+        }
+        Ok(())
     })
     .await
-    .map_err(|e| format!("Task join error: {}", e))??;
-
-    // Generate calendar
-    let calendar_entries = calendar::get_calendar(library_items, days, addons)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(calendar_entries)
+    .map_err(|e| format!("Task join error: {}", e))?
 }
 
 // Watchlist commands
@@ -1091,7 +2241,7 @@ async fn add_to_watchlist(
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -1108,7 +2258,7 @@ async fn remove_from_watchlist(
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -1122,7 +2272,7 @@ async fn remove_from_watchlist(
 #[tauri::command]
 async fn get_watchlist(state: tauri::State<'_, AppState>) -> Result<Vec<MediaItem>, String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -1139,7 +2289,7 @@ async fn add_to_favorites(
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -1156,7 +2306,7 @@ async fn remove_from_favorites(
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -1170,7 +2320,7 @@ async fn remove_from_favorites(
 #[tauri::command]
 async fn get_favorites(state: tauri::State<'_, AppState>) -> Result<Vec<MediaItem>, String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -1181,11 +2331,14 @@ async fn get_favorites(state: tauri::State<'_, AppState>) -> Result<Vec<MediaIte
 }
 
 // Watch progress commands
+/// Save the playback position and log it to the watch history. `duration` is
+/// the item's total length in seconds, if the player knows it.
 #[tauri::command]
 async fn update_watch_progress(
     media_id: String,
     progress: i32,
     watched: bool,
+    duration: Option<i32>,
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
     let db = state.inner().db.clone();
@@ -1193,6 +2346,51 @@ async fn update_watch_progress(
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
         db.update_watch_progress(&media_id, progress, watched)
+            .map_err(|e| e.to_string())?;
+        db.record_watch_event(&media_id, progress, duration, chrono::Utc::now())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Import watched titles from another app and report how many matched
+#[tauri::command]
+async fn import_watch_history(
+    entries: Vec<ImportedHistory>,
+    state: tauri::State<'_, AppState>,
+) -> Result<WatchHistoryImport, String> {
+    let db = state.inner().db.clone();
+
+    let report = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.import_watch_history(&entries).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    tracing::info!(
+        matched = report.matched,
+        created = report.created,
+        duplicates = report.duplicates,
+        unmatched = report.unmatched,
+        "Imported watch history"
+    );
+    Ok(report)
+}
+
+/// Recent playback sessions, newest first (default 50)
+#[tauri::command]
+async fn get_watch_history(
+    limit: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<WatchHistoryEntry>, String> {
+    let db = state.inner().db.clone();
+    let limit = limit.unwrap_or(50);
+
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_recent_watch_history(limit)
             .map_err(|e| e.to_string())
     })
     .await
@@ -1204,7 +2402,7 @@ async fn get_continue_watching(
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<MediaItem>, String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -1223,7 +2421,7 @@ async fn create_playlist(
     state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
     let playlist_id = uuid::Uuid::new_v4().to_string();
     let playlist_id_clone = playlist_id.clone();
 
@@ -1242,7 +2440,7 @@ async fn get_playlists(
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<crate::models::Playlist>, String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
@@ -1402,19 +2600,45 @@ async fn clear_expired_cache(state: tauri::State<'_, AppState>) -> Result<usize,
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// Database maintenance; `vacuum` also rebuilds the file to reclaim space
+#[tauri::command]
+async fn optimize_database(
+    vacuum: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<OptimizeReport, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let report = db
+            .optimize(vacuum.unwrap_or(true))
+            .map_err(|e| e.to_string())?;
+        tracing::info!(
+            reclaimed_bytes = report.reclaimed_bytes,
+            health_records_removed = report.health_records_removed,
+            "Optimized database"
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 // Data export/import commands
 #[tauri::command]
 async fn export_user_data(state: tauri::State<'_, AppState>) -> Result<String, String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
 
-        let profile = db
-            .get_user_profile(&user_id)
+        // Settings live on the default profile; lists on the active one
+        let mut profile = db
+            .get_user_profile(DEFAULT_PROFILE_ID)
             .map_err(|e| e.to_string())?
             .ok_or_else(|| "User profile not found".to_string())?;
+        // The keyring entry stays on this machine
+        profile.preferences.opensubtitles_logged_in = false;
 
         let playlists = db.get_playlists(&user_id).map_err(|e| e.to_string())?;
         let mut playlists_with_items = Vec::new();
@@ -1451,17 +2675,18 @@ async fn import_user_data(
     state: tauri::State<'_, AppState>,
 ) -> Result<(), String> {
     let db = state.inner().db.clone();
-    let user_id = "default_user".to_string();
+    let user_id = active_profile_id(state.inner())?;
 
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
 
-        // Import user profile preferences (merge, not replace)
+        // Import preferences into the profile settings are read from
+        // (merge, not replace); lists go to the active profile
         let mut current_profile = db
-            .get_user_profile(&user_id)
+            .get_user_profile(DEFAULT_PROFILE_ID)
             .map_err(|e| e.to_string())?
             .unwrap_or_else(|| UserProfile {
-                id: user_id.clone(),
+                id: DEFAULT_PROFILE_ID.to_string(),
                 username: data.profile.username.clone(),
                 email: data.profile.email.clone(),
                 preferences: data.profile.preferences.clone(),
@@ -1471,7 +2696,16 @@ async fn import_user_data(
             });
 
         // Merge preferences (imported data takes precedence)
+        let builtins_seeded = current_profile.preferences.builtins_seeded;
+        let opensubtitles_logged_in = current_profile.preferences.opensubtitles_logged_in;
+        let legacy_token = current_profile
+            .preferences
+            .legacy_opensubtitles_token
+            .take();
         current_profile.preferences = data.profile.preferences.clone();
+        current_profile.preferences.builtins_seeded |= builtins_seeded;
+        current_profile.preferences.opensubtitles_logged_in = opensubtitles_logged_in;
+        current_profile.preferences.legacy_opensubtitles_token = legacy_token;
         current_profile.username = data.profile.username.clone();
         current_profile.email = data.profile.email.clone();
 
@@ -1480,11 +2714,16 @@ async fn import_user_data(
 
         tracing::info!("Imported user profile and preferences");
 
-        // Import library items (merge, avoiding duplicates)
+        // Import library items (merge, avoiding duplicates). One transaction
+        // for the lot; if that fails, import item by item so one bad entry
+        // doesn't drop the rest.
         let library_count = data.library.len();
-        for item in data.library {
-            if let Err(e) = db.add_to_library(item.clone()) {
-                tracing::warn!("Failed to import library item {}: {}", item.id, e);
+        if let Err(e) = db.add_library_items_batch(data.library.clone()) {
+            tracing::warn!("Batch library import failed, retrying per item: {}", e);
+            for item in data.library {
+                if let Err(e) = db.add_to_library(item.clone()) {
+                    tracing::warn!("Failed to import library item {}: {}", item.id, e);
+                }
             }
         }
         tracing::info!("Imported {} library items", library_count);
@@ -1598,6 +2837,39 @@ async fn launch_external_player(
         .map_err(|e| e.to_string())
 }
 
+/// Playback position in seconds of the MPV instance launched by StreamGo
+#[tauri::command]
+async fn mpv_get_position() -> Result<f64, String> {
+    tokio::task::spawn_blocking(|| ExternalPlayer::MPV.mpv_get_position())
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mpv_seek(seconds: f64) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || ExternalPlayer::MPV.mpv_seek(seconds))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mpv_pause(paused: bool) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || ExternalPlayer::MPV.mpv_pause(paused))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn mpv_set_subtitle(subtitle_path: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || ExternalPlayer::MPV.mpv_set_subtitle(&subtitle_path))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn download_subtitle(url: String) -> Result<String, String> {
     SubtitleManager::download_subtitle(&url)
@@ -1610,11 +2882,27 @@ async fn convert_srt_to_vtt(srt_content: String) -> Result<String, String> {
     SubtitleManager::srt_to_vtt(&srt_content).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn convert_ass_to_vtt(ass_content: String) -> Result<String, String> {
+    SubtitleManager::ass_to_vtt(&ass_content).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn parse_vtt_subtitle(vtt_content: String) -> Result<Vec<SubtitleCue>, String> {
     SubtitleManager::parse_vtt(&vtt_content).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn parse_srt_subtitle(srt_content: String) -> Result<Vec<SubtitleCue>, String> {
+    SubtitleManager::parse_srt(&srt_content).map_err(|e| e.to_string())
+}
+
+/// Shift every cue of a WebVTT subtitle by `offset_ms` to fix out-of-sync subtitles
+#[tauri::command]
+async fn resync_subtitle(vtt_content: String, offset_ms: i64) -> Result<String, String> {
+    SubtitleManager::shift_vtt(&vtt_content, offset_ms).map_err(|e| e.to_string())
+}
+
 // Diagnostics and metrics commands
 #[tauri::command]
 async fn get_performance_metrics() -> Result<logging::PerformanceMetrics, String> {
@@ -1676,6 +2964,20 @@ async fn get_addon_health(
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+#[tauri::command]
+async fn export_addon_health_csv(
+    since: Option<i64>,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.export_addon_health_csv(since).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 // Torrent streaming commands
 #[tauri::command]
 async fn start_torrent_stream(
@@ -1690,8 +2992,31 @@ async fn start_torrent_stream(
         .ok_or_else(|| "Streaming server not available".to_string())?
         .clone();
 
+    stream_torrent_locally(&server, &magnet_or_url, file_index).await
+}
+
+#[tauri::command]
+async fn get_torrent_queue_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<streaming_server::TorrentQueueStatus, String> {
+    let server = state
+        .inner()
+        .streaming_server
+        .as_ref()
+        .ok_or_else(|| "Streaming server not available".to_string())?;
+    Ok(server.queue_status())
+}
+
+/// Add a torrent to the local streaming server and return the URL of its video file
+async fn stream_torrent_locally(
+    server: &streaming_server::StreamingServer,
+    magnet_or_url: &str,
+    file_index: Option<usize>,
+) -> Result<String, String> {
+    // This torrent is about to be watched, so it goes first if it has to queue
+    server.set_watching(magnet_or_url);
     let info = server
-        .add_torrent(&magnet_or_url, file_index)
+        .add_torrent(magnet_or_url, file_index)
         .await
         .map_err(|e| e.to_string())?;
 
@@ -1718,43 +3043,343 @@ async fn start_torrent_stream(
         format!("{}/file/{}", base, selected_index)
     };
 
-    Ok(file_url)
-}
+    Ok(file_url)
+}
+
+/// Turn a magnet link into a playable URL: through Real-Debrid when an API key
+/// is configured, otherwise (or if debrid fails) through the local streaming server
+async fn resolve_magnet_stream(
+    magnet: &str,
+    state: &AppState,
+) -> Result<(String, ResolvedVia), String> {
+    let db = state.db.clone();
+    let api_key = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let profile = db
+            .get_user_profile(DEFAULT_PROFILE_ID)
+            .map_err(|e| e.to_string())?;
+        Ok::<Option<String>, String>(
+            profile
+                .and_then(|p| p.preferences.real_debrid_api_key)
+                .filter(|key| !key.trim().is_empty()),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    if let Some(api_key) = api_key {
+        let provider = debrid::RealDebridClient::new(api_key);
+        match provider.resolve_magnet(magnet).await {
+            Ok(url) => return Ok((url, ResolvedVia::Debrid)),
+            Err(e) => tracing::warn!(
+                provider = provider.name(),
+                error = %e,
+                "Debrid resolution failed, falling back to local streaming"
+            ),
+        }
+    }
+
+    let server = state
+        .streaming_server
+        .as_ref()
+        .ok_or_else(|| "Streaming server not available".to_string())?
+        .clone();
+
+    let url = stream_torrent_locally(&server, magnet, None).await?;
+    Ok((url, ResolvedVia::Torrent))
+}
+
+/// Serve a scanned local file through the streaming server so cast devices,
+/// which can't open file paths, can play it. Only files inside scanned
+/// directories are served; the URL uses this machine's LAN address.
+#[tauri::command]
+async fn serve_local_file(
+    path: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let server = state
+        .inner()
+        .streaming_server
+        .as_ref()
+        .ok_or_else(|| "Streaming server not available".to_string())?
+        .clone();
+
+    let db = state.inner().db.clone();
+    let scanned_dirs = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let dirs = db.get_scanned_directories().map_err(|e| e.to_string())?;
+        Ok::<Vec<std::path::PathBuf>, String>(
+            dirs.into_iter()
+                .filter(|(_, _, enabled)| *enabled)
+                .map(|(path, _, _)| std::path::PathBuf::from(path))
+                .collect(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let file = streaming_server::servable_local_path(std::path::Path::new(&path), &scanned_dirs)
+        .map_err(|e| e.to_string())?;
+    let url = server.register_local_file(file).await;
+    Ok(match &state.inner().cast_manager {
+        Some(cast_manager) => cast_manager.make_url_accessible(&url),
+        None => url,
+    })
+}
+
+// Local media commands - removed duplicates (DB-integrated versions are defined later)
+
+// Subtitle auto-fetch commands
+
+/// Search providers for subtitles. Languages already downloaded for this
+/// content (IMDB id, else file path) are answered from the subtitle cache
+/// unless `force_refresh` is set.
+#[tauri::command]
+async fn auto_fetch_subtitles(
+    file_path: Option<String>,
+    imdb_id: Option<String>,
+    languages: Vec<String>,
+    force_refresh: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<SubtitleResult>, String> {
+    let manager = subtitle_manager(state.inner()).await?;
+
+    let mut cached = Vec::new();
+    let mut missing: Vec<&str> = Vec::new();
+    let content_id = imdb_id.as_deref().or(file_path.as_deref());
+    for language in &languages {
+        let hit = match content_id {
+            Some(id) if !force_refresh.unwrap_or(false) => {
+                let cache = state.inner().cache.lock().map_err(|e| e.to_string())?;
+                cache
+                    .get_subtitle(id, &language::language_key(language))
+                    .map_err(|e| e.to_string())?
+            }
+            _ => None,
+        };
+        match hit {
+            Some((_, result)) => cached.push(result),
+            None => missing.push(language),
+        }
+    }
+    if missing.is_empty() && !cached.is_empty() {
+        tracing::debug!(content_id = ?content_id, "Subtitles served from cache");
+        return Ok(cached);
+    }
+
+    let mut results = manager
+        .auto_fetch(file_path.as_deref(), imdb_id.as_deref(), &missing)
+        .await
+        .map_err(|e| e.to_string())?;
+    cached.append(&mut results);
+    Ok(cached)
+}
+
+/// Download the best match. With `content_id`, the download is cached per
+/// content and language and reused unless `force_refresh` is set. With
+/// `convert_to_vtt`, an SRT download is converted to WebVTT for the built-in
+/// player; otherwise the raw subtitle is returned (e.g. for external players).
+#[tauri::command]
+async fn download_best_subtitle(
+    results: Vec<SubtitleResult>,
+    convert_to_vtt: Option<bool>,
+    content_id: Option<String>,
+    force_refresh: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<(String, SubtitleResult), String> {
+    let manager = subtitle_manager(state.inner()).await?;
+
+    let (content, result) = match (content_id, results.first()) {
+        (Some(content_id), Some(best)) => {
+            subtitle_providers::download_cached(
+                &state.inner().cache,
+                &content_id,
+                &best.language_code,
+                force_refresh.unwrap_or(false),
+                || manager.download_best(&results),
+            )
+            .await
+        }
+        _ => manager.download_best(&results).await,
+    }
+    .map_err(|e| e.to_string())?;
+    if convert_to_vtt.unwrap_or(false) {
+        subtitle_providers::srt_download_to_vtt(content, result).map_err(|e| e.to_string())
+    } else {
+        Ok((content, result))
+    }
+}
+
+/// Subtitle manager using the OpenSubtitles API key from the environment and
+/// the user's login token, if they logged in
+async fn subtitle_manager(state: &AppState) -> Result<subtitle_providers::SubtitleManager, String> {
+    let api_key = std::env::var("OPENSUBTITLES_API_KEY").ok();
+    let token = opensubtitles_token(state).await?;
+    Ok(subtitle_providers::SubtitleManager::new(api_key).with_opensubtitles_token(token))
+}
+
+/// The OpenSubtitles login token from the OS keyring. A plaintext token left
+/// in the profile by an older version is moved to the keyring first. When
+/// the keyring can't be used (e.g. no Secret Service on a headless Linux
+/// box) requests fall back to anonymous, API key only ones.
+async fn opensubtitles_token(state: &AppState) -> Result<Option<String>, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let Some(mut profile) = db
+            .get_user_profile(DEFAULT_PROFILE_ID)
+            .map_err(|e| e.to_string())?
+        else {
+            return Ok(None);
+        };
+        if let Some(token) = profile.preferences.legacy_opensubtitles_token.clone() {
+            match secrets::set(secrets::OPENSUBTITLES_TOKEN, &token) {
+                Ok(()) => {
+                    profile.preferences.legacy_opensubtitles_token = None;
+                    profile.preferences.opensubtitles_logged_in = true;
+                    db.save_user_profile(&profile).map_err(|e| e.to_string())?;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Keeping the old OpenSubtitles token until the keyring works");
+                }
+            }
+            return Ok(Some(token));
+        }
+        if !profile.preferences.opensubtitles_logged_in {
+            return Ok(None);
+        }
+        match secrets::get(secrets::OPENSUBTITLES_TOKEN) {
+            Ok(token) => Ok(token),
+            Err(e) => {
+                tracing::warn!(error = %e, "OpenSubtitles token unavailable, searching anonymously");
+                Ok(None)
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Store or forget the OpenSubtitles login token in the OS keyring, keeping
+/// only whether one is present in the profile
+async fn set_opensubtitles_token(state: &AppState, token: Option<String>) -> Result<(), String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        match &token {
+            Some(token) => secrets::set(secrets::OPENSUBTITLES_TOKEN, token),
+            None => secrets::delete(secrets::OPENSUBTITLES_TOKEN),
+        }
+        .map_err(|e| e.to_string())?;
+
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let mut profile = db
+            .get_user_profile(DEFAULT_PROFILE_ID)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "User profile not found".to_string())?;
+        profile.preferences.opensubtitles_logged_in = token.is_some();
+        profile.preferences.legacy_opensubtitles_token = None;
+        db.save_user_profile(&profile).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
 
-// Local media commands - removed duplicates (DB-integrated versions are defined later)
+    // An exhausted quota belonged to the previous account
+    subtitle_providers::reset_subtitle_quota();
+    Ok(())
+}
 
-// Subtitle auto-fetch commands
+/// Log in to OpenSubtitles; later subtitle requests use the account's quota
 #[tauri::command]
-async fn auto_fetch_subtitles(
-    file_path: Option<String>,
-    imdb_id: Option<String>,
-    languages: Vec<String>,
-) -> Result<Vec<SubtitleResult>, String> {
+async fn opensubtitles_login(
+    username: String,
+    password: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
     let api_key = std::env::var("OPENSUBTITLES_API_KEY").ok();
-    let manager = subtitle_providers::SubtitleManager::new(api_key);
-
-    let lang_refs: Vec<&str> = languages.iter().map(|s| s.as_str()).collect();
-    manager
-        .auto_fetch(
-            file_path.as_deref(),
-            imdb_id.as_deref(),
-            &lang_refs,
-        )
+    let token = subtitle_providers::OpenSubtitlesClient::new(api_key)
+        .login(&username, &password)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    set_opensubtitles_token(state.inner(), Some(token)).await
 }
 
+/// Forget the OpenSubtitles login, ending the session when possible
 #[tauri::command]
-async fn download_best_subtitle(
-    results: Vec<SubtitleResult>,
-) -> Result<(String, SubtitleResult), String> {
+async fn opensubtitles_logout(state: tauri::State<'_, AppState>) -> Result<(), String> {
     let api_key = std::env::var("OPENSUBTITLES_API_KEY").ok();
-    let manager = subtitle_providers::SubtitleManager::new(api_key);
-
-    manager
-        .download_best(&results)
+    let token = opensubtitles_token(state.inner()).await?;
+    if let Err(e) = subtitle_providers::OpenSubtitlesClient::new(api_key)
+        .with_token(token)
+        .logout()
         .await
-        .map_err(|e| e.to_string())
+    {
+        tracing::warn!(error = %e, "OpenSubtitles logout failed, forgetting token anyway");
+    }
+    set_opensubtitles_token(state.inner(), None).await
+}
+
+/// OpenSubtitles download quota as reported by the last download, if any
+#[tauri::command]
+async fn get_subtitle_quota() -> Result<Option<subtitle_providers::QuotaInfo>, String> {
+    Ok(subtitle_providers::subtitle_quota())
+}
+
+/// Search and download the best subtitle per language for each media item
+/// ahead of a binge session. Downloads are cached for
+/// `get_prefetched_subtitle`; an item that fails is reported in its own result.
+#[tauri::command]
+async fn prefetch_subtitles(
+    media_ids: Vec<String>,
+    languages: Vec<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<std::collections::HashMap<String, subtitle_providers::PrefetchResult>, String> {
+    let manager = subtitle_manager(state.inner()).await?;
+    let cache = state.inner().cache.clone();
+    let lang_refs: Vec<&str> = languages.iter().map(|s| s.as_str()).collect();
+
+    let results = subtitle_providers::prefetch(
+        media_ids,
+        subtitle_providers::PREFETCH_CONCURRENCY,
+        subtitle_providers::PREFETCH_REQUEST_INTERVAL,
+        |media_id| {
+            let (manager, cache, lang_refs) = (&manager, &cache, &lang_refs);
+            async move {
+                let downloaded = manager
+                    .fetch_best_per_language(&media_id, lang_refs)
+                    .await?;
+                let cache = cache
+                    .lock()
+                    .map_err(|e| anyhow::anyhow!("Cache lock poisoned: {}", e))?;
+                for entry in &downloaded {
+                    cache.set_subtitle(&media_id, &entry.1.language_code, entry)?;
+                }
+                Ok(downloaded.into_iter().map(|(_, result)| result).collect())
+            }
+        },
+    )
+    .await;
+
+    let failed = results.values().filter(|r| r.error.is_some()).count();
+    tracing::info!(items = results.len(), failed, "Prefetched subtitles");
+    Ok(results)
+}
+
+/// Subtitle downloaded by `prefetch_subtitles`, if it is still cached
+#[tauri::command]
+async fn get_prefetched_subtitle(
+    media_id: String,
+    language: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<(String, SubtitleResult)>, String> {
+    let cache = state.inner().cache.clone();
+    tokio::task::spawn_blocking(move || {
+        let cache = cache.lock().map_err(|e| e.to_string())?;
+        cache
+            .get_subtitle(&media_id, &language::language_key(&language))
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
 }
 
 #[tauri::command]
@@ -1772,8 +3397,31 @@ async fn scan_local_folder(
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<local_media::LocalMediaFile>, String> {
     use std::path::PathBuf;
-    
-    let scanner = local_media::LocalMediaScanner::new(vec![PathBuf::from(&path)]);
+
+    let db = state.db.clone();
+    let scan_path = path.clone();
+    let preferences = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        // Don't scan files a recursively scanned directory already covers
+        if let Some(DirectoryOverlap::Inside(parent)) = db
+            .scanned_directory_overlap(&scan_path)
+            .map_err(|e| e.to_string())?
+        {
+            return Err(format!(
+                "{} is already scanned as part of {}",
+                scan_path, parent
+            ));
+        }
+        db.get_user_profile(DEFAULT_PROFILE_ID)
+            .map(|profile| profile.map(|p| p.preferences).unwrap_or_default())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let scanner = local_media::LocalMediaScanner::new(vec![PathBuf::from(&path)])
+        .with_preferences(&preferences)
+        .with_events(state.events.clone());
     let files = scanner.scan_all().await.map_err(|e| e.to_string())?;
     
     // Save to database
@@ -1889,6 +3537,23 @@ async fn live_tv_import_m3u(url: String, state: tauri::State<'_, AppState>) -> R
         .await
         .map_err(|e| e.to_string())?;
     let channels = crate::live_tv::LiveTvManager::parse_m3u(&content);
+    save_live_tv_channels(state.inner(), channels).await
+}
+
+/// Import channels from M3U playlist text, e.g. a file the user opened
+#[tauri::command]
+async fn import_m3u_playlist(
+    content: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<usize, String> {
+    let channels = crate::live_tv::LiveTvManager::parse_m3u(&content);
+    save_live_tv_channels(state.inner(), channels).await
+}
+
+async fn save_live_tv_channels(
+    state: &AppState,
+    channels: Vec<LiveTvChannel>,
+) -> Result<usize, String> {
     let count = channels.len();
     let db = state.db.clone();
     tokio::task::spawn_blocking(move || {
@@ -1902,46 +3567,324 @@ async fn live_tv_import_m3u(url: String, state: tauri::State<'_, AppState>) -> R
 
 #[tauri::command]
 async fn live_tv_get_channels(state: tauri::State<'_, AppState>) -> Result<Vec<LiveTvChannel>, String> {
+    get_live_channels(None, state).await
+}
+
+/// Channels sorted by name, optionally only those in one group
+#[tauri::command]
+async fn get_live_channels(
+    group: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<LiveTvChannel>, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_live_tv_channels(group.as_deref())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn delete_live_channel(
+    channel_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<bool, String> {
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.delete_live_tv_channel(&channel_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn live_tv_import_xmltv(url: String, state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let xml = crate::live_tv::LiveTvManager::fetch_text(&url)
+        .await
+        .map_err(|e| e.to_string())?;
+    import_xmltv(xml, state).await
+}
+
+/// Import EPG programmes from XMLTV text. Programmes are matched to stored
+/// channels by tvg-id; ones for unknown channels are skipped.
+#[tauri::command]
+async fn import_xmltv(content: String, state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let programs =
+        crate::live_tv::LiveTvManager::parse_xmltv(&content).map_err(|e| e.to_string())?;
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let channels = db.get_live_tv_channels(None).map_err(|e| e.to_string())?;
+        let programs = crate::live_tv::map_programs_to_channels(programs, &channels);
+        db.upsert_epg_programs(&programs)
+            .map_err(|e| e.to_string())?;
+        Ok(programs.len())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The programme airing now on a channel and the one after it
+#[tauri::command]
+async fn get_now_next(
+    channel_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(Option<EpgProgram>, Option<EpgProgram>), String> {
+    let now = chrono::Utc::now().timestamp();
+    let db = state.db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_now_next(&channel_id, now).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn live_tv_get_epg(
+    channel_id: String,
+    since: Option<i64>,
+    until: Option<i64>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<EpgProgram>, String> {
     let db = state.db.clone();
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        db.get_live_tv_channels().map_err(|e| e.to_string())
+        db.get_epg_for_channel(&channel_id, since, until).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn add_favorite_channel(
+    channel_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let db = state.inner().db.clone();
+    let user_id = active_profile_id(state.inner())?;
+
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.add_favorite_channel(&user_id, &channel_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn remove_favorite_channel(
+    channel_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<bool, String> {
+    let db = state.inner().db.clone();
+    let user_id = active_profile_id(state.inner())?;
+
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.remove_favorite_channel(&user_id, &channel_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+#[tauri::command]
+async fn get_favorite_channels(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<LiveTvChannel>, String> {
+    let db = state.inner().db.clone();
+    let user_id = active_profile_id(state.inner())?;
+
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_favorite_channels(&user_id)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Check every channel's stream and store which ones are reachable
+#[tauri::command]
+async fn probe_channels(state: tauri::State<'_, AppState>) -> Result<Vec<ChannelStatus>, String> {
+    let db = state.inner().db.clone();
+    let channels = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_live_tv_channels(None).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let client = reqwest::Client::builder()
+        .timeout(crate::live_tv::CHANNEL_PROBE_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let client = &client;
+    let statuses = crate::live_tv::probe_channels(
+        channels,
+        crate::live_tv::CHANNEL_PROBE_CONCURRENCY,
+        crate::live_tv::CHANNEL_PROBE_INTERVAL,
+        |url| async move { crate::live_tv::check_stream_url(client, &url).await },
+    )
+    .await;
+
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.update_channel_statuses(&statuses)
+            .map_err(|e| e.to_string())?;
+        Ok(statuses)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Last stream check result for each channel that has been checked
+#[tauri::command]
+async fn get_channel_statuses(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ChannelStatus>, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_channel_statuses().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Channel groups with counts, for the live TV sidebar
+#[tauri::command]
+async fn get_channel_groups(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ChannelGroup>, String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.get_channel_groups().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Record a program from its channel's stream to the downloads folder and
+/// add it to local media, reporting `recording-progress` events. Returns
+/// false without recording when ffmpeg isn't installed.
+#[tauri::command]
+async fn record_program(
+    channel_id: String,
+    program_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> Result<bool, String> {
+    if !recording::ffmpeg_available().await {
+        tracing::warn!("ffmpeg not found, skipping live TV recording");
+        return Ok(false);
+    }
+
+    let db = state.inner().db.clone();
+    let (channel, program) = tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let channel = db
+            .get_live_tv_channel(&channel_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Channel not found: {}", channel_id))?;
+        let program = db
+            .get_epg_program(program_id)
+            .map_err(|e| e.to_string())?
+            .filter(|program| program.channel_id == channel.id)
+            .ok_or_else(|| format!("Program {} not found on channel {}", program_id, channel_id))?;
+        Ok::<(LiveTvChannel, EpgProgram), String>((channel, program))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let window = recording::recording_window(
+        program.start,
+        program.end,
+        chrono::Utc::now().timestamp(),
+        recording::RECORDING_PADDING_BEFORE,
+        recording::RECORDING_PADDING_AFTER,
+    )
+    .ok_or_else(|| format!("{} has already ended", program.title))?;
+    let file_name = recording::recording_file_name(&channel.name, &program.title, program.start);
+    let job = recording::RecordingJob {
+        program_id,
+        channel_id: channel.id,
+        title: program.title,
+        stream_url: channel.stream_url,
+        output: recording::recordings_dir().join(file_name),
+        window,
+    };
+
+    if !state
+        .recordings
+        .start(job, state.db.clone(), state.events.clone())
+    {
+        return Err(format!("Program {} is already being recorded", program_id));
+    }
+    Ok(true)
+}
+
+/// Stop a scheduled or running recording; returns false if there was none
+#[tauri::command]
+async fn cancel_recording(
+    program_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> Result<bool, String> {
+    Ok(state.recordings.cancel(program_id))
+}
+
+/// Notify the user shortly before a program starts
+#[tauri::command]
+async fn set_program_reminder(
+    channel_id: String,
+    program_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> Result<(), String> {
+    let db = state.inner().db.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.set_program_reminder(&channel_id, program_id)
+            .map_err(|e| e.to_string())
     })
     .await
-    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("Task join error: {}", e))?
 }
 
 #[tauri::command]
-async fn live_tv_import_xmltv(url: String, state: tauri::State<'_, AppState>) -> Result<usize, String> {
-    let xml = crate::live_tv::LiveTvManager::fetch_text(&url)
-        .await
-        .map_err(|e| e.to_string())?;
-    let programs = crate::live_tv::LiveTvManager::parse_xmltv(&xml).map_err(|e| e.to_string())?;
-    let count = programs.len();
-    let db = state.db.clone();
+async fn remove_program_reminder(
+    program_id: i64,
+    state: tauri::State<'_, AppState>,
+) -> Result<bool, String> {
+    let db = state.inner().db.clone();
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        db.upsert_epg_programs(&programs).map_err(|e| e.to_string())
+        db.remove_program_reminder(program_id)
+            .map_err(|e| e.to_string())
     })
     .await
-    .map_err(|e| e.to_string())??;
-    Ok(count)
+    .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// Reminders for programs that haven't ended yet, soonest first
 #[tauri::command]
-async fn live_tv_get_epg(
-    channel_id: String,
-    since: Option<i64>,
-    until: Option<i64>,
+async fn get_upcoming_reminders(
     state: tauri::State<'_, AppState>,
-) -> Result<Vec<EpgProgram>, String> {
-    let db = state.db.clone();
+) -> Result<Vec<ProgramReminder>, String> {
+    let db = state.inner().db.clone();
     tokio::task::spawn_blocking(move || {
         let db = db.lock().map_err(|e| e.to_string())?;
-        db.get_epg_for_channel(&channel_id, since, until).map_err(|e| e.to_string())
+        db.get_upcoming_reminders(chrono::Utc::now().timestamp())
+            .map_err(|e| e.to_string())
     })
     .await
-    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("Task join error: {}", e))?
 }
 
 // Casting commands
@@ -2030,6 +3973,181 @@ async fn get_cast_session_status(
     Ok(cast_manager.get_session_status(&session_id).await)
 }
 
+#[tauri::command]
+async fn cast_queue(
+    device_id: String,
+    media_ids: Vec<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<CastSession, String> {
+    let cast_manager = state
+        .cast_manager
+        .as_ref()
+        .ok_or_else(|| "Cast manager not available".to_string())?;
+
+    // Streams are resolved as each queued item comes up
+    let app_state = state.inner().clone();
+    let resolver: casting::QueueResolver = Arc::new(move |media_id: String| {
+        let state = app_state.clone();
+        Box::pin(async move {
+            let db = state.db.clone();
+            let id = media_id.clone();
+            let item = tokio::task::spawn_blocking(move || {
+                let db = db.lock().map_err(|e| e.to_string())?;
+                db.get_media_items_by_ids(&[id]).map_err(|e| e.to_string())
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?
+            .map_err(|e| anyhow::anyhow!(e))?
+            .pop();
+
+            let media_type = item.as_ref().map(|item| match item.media_type {
+                MediaType::TvShow | MediaType::Episode => "series".to_string(),
+                _ => "movie".to_string(),
+            });
+            let url = resolve_stream_url(&media_id, media_type, &state)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?
+                .ok_or_else(|| anyhow::anyhow!("No stream found for {}", media_id))?;
+            Ok((url, item.map(|item| item.title)))
+        })
+    });
+
+    cast_manager
+        .cast_queue(&device_id, media_ids, resolver)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn cast_next(
+    session_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<CastSession, String> {
+    let cast_manager = state
+        .cast_manager
+        .as_ref()
+        .ok_or_else(|| "Cast manager not available".to_string())?;
+
+    cast_manager
+        .cast_next(&session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn cast_previous(
+    session_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<CastSession, String> {
+    let cast_manager = state
+        .cast_manager
+        .as_ref()
+        .ok_or_else(|| "Cast manager not available".to_string())?;
+
+    cast_manager
+        .cast_previous(&session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn test_cast_device(
+    device_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<CastDeviceTest, String> {
+    let cast_manager = state
+        .cast_manager
+        .as_ref()
+        .ok_or_else(|| "Cast manager not available".to_string())?;
+
+    Ok(cast_manager
+        .test_device(&device_id, std::time::Duration::from_secs(5))
+        .await)
+}
+
+#[tauri::command]
+async fn pause_casting(
+    session_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<CastSession, String> {
+    let cast_manager = state
+        .cast_manager
+        .as_ref()
+        .ok_or_else(|| "Cast manager not available".to_string())?;
+
+    cast_manager
+        .pause_cast(&session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn resume_casting(
+    session_id: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<CastSession, String> {
+    let cast_manager = state
+        .cast_manager
+        .as_ref()
+        .ok_or_else(|| "Cast manager not available".to_string())?;
+
+    cast_manager
+        .resume_cast(&session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn seek_casting(
+    session_id: String,
+    position_secs: f64,
+    state: tauri::State<'_, AppState>,
+) -> Result<CastSession, String> {
+    let cast_manager = state
+        .cast_manager
+        .as_ref()
+        .ok_or_else(|| "Cast manager not available".to_string())?;
+
+    cast_manager
+        .seek_cast(&session_id, position_secs)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_casting_volume(
+    session_id: String,
+    level: f64,
+    state: tauri::State<'_, AppState>,
+) -> Result<CastSession, String> {
+    let cast_manager = state
+        .cast_manager
+        .as_ref()
+        .ok_or_else(|| "Cast manager not available".to_string())?;
+
+    cast_manager
+        .set_cast_volume(&session_id, level)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_casting_mute(
+    session_id: String,
+    muted: bool,
+    state: tauri::State<'_, AppState>,
+) -> Result<CastSession, String> {
+    let cast_manager = state
+        .cast_manager
+        .as_ref()
+        .ok_or_else(|| "Cast manager not available".to_string())?;
+
+    cast_manager
+        .set_cast_mute(&session_id, muted)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn auto_disable_unhealthy_addons(
     threshold: f64,
@@ -2128,6 +4246,8 @@ pub fn run() {
         }
     };
 
+    let active_profile = load_active_profile(&database);
+
     // Initialize cache
     let cache_path = dirs::data_local_dir()
         .map(|dir| dir.join("StreamGo").join("cache.db"))
@@ -2150,6 +4270,13 @@ pub fn run() {
             }
         }
     };
+    let cache_budget = database
+        .get_user_profile(DEFAULT_PROFILE_ID)
+        .ok()
+        .flatten()
+        .map(|profile| cache_size_budget(&profile.preferences))
+        .unwrap_or(cache::DEFAULT_MAX_SIZE_BYTES);
+    let cache = cache.with_max_size_bytes(cache_budget);
 
     // Initialize i18n manager as global
     let locales_dir = dirs::data_local_dir()
@@ -2174,6 +4301,9 @@ pub fn run() {
         .block_on(streaming_server::StreamingServer::new(downloads_dir, 8765))
     {
         Ok(server) => {
+            if let Ok(Some(profile)) = database.get_user_profile(DEFAULT_PROFILE_ID) {
+                server.set_max_active_torrents(profile.preferences.max_active_torrents as usize);
+            }
             tracing::info!("Streaming server initialized successfully on port 8765");
             Some(Arc::new(server))
         }
@@ -2184,10 +4314,11 @@ pub fn run() {
     };
 
     // Initialize cast manager (optional - can fail gracefully)
+    let events = EventBus::new();
     let cast_manager = match CastManager::new(8765) {
         Ok(manager) => {
             tracing::info!("Cast manager initialized successfully");
-            Some(Arc::new(manager))
+            Some(Arc::new(manager.with_events(events.clone())))
         }
         Err(e) => {
             tracing::warn!(error = %e, "Failed to initialize cast manager, casting will not be available");
@@ -2201,11 +4332,15 @@ pub fn run() {
         streaming_server,
         cast_manager,
         folder_watcher: Some(Arc::new(tokio::sync::Mutex::new(folder_watcher::FolderWatcherManager::new()))),
+        events,
+        active_profile: Arc::new(Mutex::new(active_profile)),
+        recordings: Arc::new(recording::RecordingManager::new()),
     };
 
     tauri::Builder::default()
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(app_state)
         .setup(|app| {
             // Initialize application data directories
@@ -2220,9 +4355,40 @@ pub fn run() {
 
             // Start folder watcher for previously-scanned directories
             let state = app.state::<AppState>();
+
+            // Let background tasks push events to the frontend
+            state.events.attach(Arc::new(app.app_handle().clone()));
             let db_arc = state.db.clone();
             let watcher_opt = state.folder_watcher.clone();
 
+            // Seed built-in addons on first run (respects the user's opt-in)
+            let seed_db = db_arc.clone();
+            tauri::async_runtime::spawn(async move {
+                match api::ensure_builtin_addons(seed_db).await {
+                    Ok(seeded) if !seeded.is_empty() => {
+                        tracing::info!(count = seeded.len(), "Seeded built-in addons on first run");
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(error = %e, "Failed to seed built-in addons"),
+                }
+            });
+
+            // Keep the cache under its size budget
+            tauri::async_runtime::spawn(cache::run_cache_eviction(state.cache.clone()));
+
+            // Notify about reminded live TV programs
+            tauri::async_runtime::spawn(live_tv::run_program_reminders(
+                db_arc.clone(),
+                state.events.clone(),
+            ));
+
+            // Check for new episodes in the background and notify the desktop
+            tauri::async_runtime::spawn(notifications::run_new_episode_checks(
+                db_arc.clone(),
+                state.events.clone(),
+                app.app_handle().clone(),
+            ));
+
             // Start streaming server in background
             if let Some(server) = state.streaming_server.clone() {
                 let server_clone = server.clone();
@@ -2275,25 +4441,59 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             get_library_items,
+            get_library_items_paged,
             add_to_library,
+            add_to_library_batch,
+            jellyfin_import,
+            remove_from_library,
             search_content,
+            hydrate_posters,
+            backfill_durations,
             search_library_advanced,
+            search_library_highlighted,
+            rebuild_search_index,
             get_stream_url,
+            report_stream_failure,
             get_streams,
+            get_streams_filtered,
+            resolve_playable,
+            get_streams_incremental,
             get_subtitles,
             get_addon_meta,
+            get_addon_meta_raw,
             list_catalogs,
             aggregate_catalogs,
             install_addon,
+            test_addon,
+            sandbox_query,
             get_addons,
             enable_addon,
             disable_addon,
+            set_addon_priority,
+            reorder_addons,
+            set_addons_enabled,
+            enable_all_addons,
+            disable_all_addons,
+            get_unused_addons,
             uninstall_addon,
+            set_addon_config,
+            get_addon_config,
+            get_app_setting,
+            set_app_setting,
+            get_all_app_settings,
+            check_addon_updates,
+            update_addon,
             get_media_details,
             get_settings,
             save_settings,
             check_new_episodes,
             get_calendar,
+            export_calendar_ics,
+            create_profile,
+            list_profiles,
+            get_active_profile,
+            switch_active_profile,
+            delete_profile,
             add_to_watchlist,
             remove_from_watchlist,
             get_watchlist,
@@ -2301,6 +4501,8 @@ pub fn run() {
             remove_from_favorites,
             get_favorites,
             update_watch_progress,
+            get_watch_history,
+            import_watch_history,
             get_continue_watching,
             create_playlist,
             get_playlists,
@@ -2314,26 +4516,42 @@ pub fn run() {
             get_cache_stats,
             clear_cache,
             clear_expired_cache,
+            optimize_database,
             get_available_players,
             launch_external_player,
+            mpv_get_position,
+            mpv_seek,
+            mpv_pause,
+            mpv_set_subtitle,
             export_user_data,
             import_user_data,
             get_log_directory_path,
             download_subtitle,
             convert_srt_to_vtt,
+            convert_ass_to_vtt,
             parse_vtt_subtitle,
+            parse_srt_subtitle,
+            resync_subtitle,
             get_performance_metrics,
             export_diagnostics,
             export_diagnostics_file,
             reset_performance_metrics,
             get_addon_health_summaries,
             get_addon_health,
+            export_addon_health_csv,
             start_torrent_stream,
+            get_torrent_queue_status,
+            serve_local_file,
             // Ratings & skip segments
             rate_addon,
             get_addon_rating,
-            save_skip_segments,
+            get_addon_ratings,
+            set_skip_segments,
             get_skip_segments,
+            // Episodes
+            get_episodes,
+            get_next_unwatched_episode,
+            update_episode_progress,
             auto_disable_unhealthy_addons,
             // Local media scanning
             scan_local_folder,
@@ -2346,11 +4564,32 @@ pub fn run() {
             // Live TV
             live_tv_import_m3u,
             live_tv_get_channels,
+            import_m3u_playlist,
+            get_live_channels,
+            delete_live_channel,
             live_tv_import_xmltv,
             live_tv_get_epg,
+            import_xmltv,
+            get_now_next,
+            add_favorite_channel,
+            remove_favorite_channel,
+            get_favorite_channels,
+            get_channel_groups,
+            probe_channels,
+            get_channel_statuses,
+            set_program_reminder,
+            remove_program_reminder,
+            get_upcoming_reminders,
+            record_program,
+            cancel_recording,
             // Subtitles
             auto_fetch_subtitles,
+            prefetch_subtitles,
+            get_prefetched_subtitle,
             download_best_subtitle,
+            get_subtitle_quota,
+            opensubtitles_login,
+            opensubtitles_logout,
             calculate_video_hash,
             discover_cast_devices,
             get_cast_devices,
@@ -2358,6 +4597,15 @@ pub fn run() {
             stop_casting,
             get_cast_sessions,
             get_cast_session_status,
+            test_cast_device,
+            cast_queue,
+            cast_next,
+            cast_previous,
+            pause_casting,
+            resume_casting,
+            seek_casting,
+            set_casting_volume,
+            set_casting_mute,
             i18n::i18n_get_supported_locales,
             i18n::i18n_set_locale,
             i18n::i18n_get_current_locale,
@@ -2369,3 +4617,562 @@ pub fn run() {
             std::process::exit(1);
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_stream_selection_cached_until_failure_reported() {
+        let cache = Arc::new(Mutex::new(CacheManager::new(None).unwrap()));
+        let aggregations = AtomicUsize::new(0);
+        let aggregate = || async {
+            aggregations.fetch_add(1, Ordering::SeqCst);
+            Ok(Some("https://cdn.example.com/movie.m3u8".to_string()))
+        };
+
+        let first = select_stream_cached(&cache, "tt0111161", "1080p", aggregate)
+            .await
+            .unwrap();
+        let second = select_stream_cached(&cache, "tt0111161", "1080p", aggregate)
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(aggregations.load(Ordering::SeqCst), 1);
+
+        // A different quality preference is cached separately
+        select_stream_cached(&cache, "tt0111161", "720p", aggregate)
+            .await
+            .unwrap();
+        assert_eq!(aggregations.load(Ordering::SeqCst), 2);
+
+        // A reported failure forces the next open to aggregate again
+        assert_eq!(invalidate_stream_selection(&cache, "tt0111161").unwrap(), 2);
+        select_stream_cached(&cache, "tt0111161", "1080p", aggregate)
+            .await
+            .unwrap();
+        assert_eq!(aggregations.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stream_selection_not_cached_without_result() {
+        let cache = Arc::new(Mutex::new(CacheManager::new(None).unwrap()));
+        let aggregations = AtomicUsize::new(0);
+        let aggregate = || async {
+            aggregations.fetch_add(1, Ordering::SeqCst);
+            Ok(None)
+        };
+
+        for _ in 0..2 {
+            let selected = select_stream_cached(&cache, "tt0111161", "auto", aggregate)
+                .await
+                .unwrap();
+            assert_eq!(selected, None);
+        }
+        assert_eq!(aggregations.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_select_best_stream_prefers_non_p2p() {
+        let stream = |url: &str, title: &str, source_type| crate::models::StreamWithSource {
+            url: url.to_string(),
+            title: Some(title.to_string()),
+            name: None,
+            description: None,
+            addon_id: "addon".to_string(),
+            addon_name: "addon".to_string(),
+            source_type,
+            size_bytes: None,
+            seeders: None,
+            not_web_ready: false,
+            subtitles: Vec::new(),
+        };
+        let streams = vec![
+            stream(
+                "https://tracker.example.com/movie.mp4",
+                "Movie 2160p",
+                crate::models::StreamSourceType::Torrent,
+            ),
+            stream(
+                "https://cdn.example.com/movie.mp4",
+                "Movie 720p",
+                crate::models::StreamSourceType::Direct,
+            ),
+        ];
+
+        // Higher resolution wins by default
+        assert_eq!(
+            select_best_stream(&streams, None, false).as_deref(),
+            Some("https://tracker.example.com/movie.mp4")
+        );
+        assert_eq!(
+            select_best_stream(&streams, None, true).as_deref(),
+            Some("https://cdn.example.com/movie.mp4")
+        );
+
+        // P2P is still picked when it's the only option
+        assert_eq!(
+            select_best_stream(&streams[..1], None, true).as_deref(),
+            Some("https://tracker.example.com/movie.mp4")
+        );
+    }
+
+    #[test]
+    fn test_active_profile_restored_only_if_it_exists() {
+        let db = Database::new_in_memory().unwrap();
+        assert_eq!(load_active_profile(&db), DEFAULT_PROFILE_ID);
+
+        let set_active = |id: &str| {
+            db.set_app_setting(ACTIVE_PROFILE_SETTING, &serde_json::to_string(id).unwrap())
+                .unwrap()
+        };
+        set_active("kids");
+        assert_eq!(load_active_profile(&db), DEFAULT_PROFILE_ID);
+
+        db.save_user_profile(&UserProfile {
+            id: "kids".to_string(),
+            username: "Kids".to_string(),
+            email: None,
+            preferences: UserPreferences::default(),
+            library_items: Vec::new(),
+            watchlist: Vec::new(),
+            favorites: Vec::new(),
+        })
+        .unwrap();
+        assert_eq!(load_active_profile(&db), "kids");
+    }
+
+    #[test]
+    fn test_stream_embedded_subtitles_merged() {
+        let stream: Stream = serde_json::from_value(serde_json::json!({
+            "url": "https://cdn.example.com/movie.mkv",
+            "subtitles": [
+                { "id": "emb-en", "url": "https://cdn.example.com/movie.en.srt", "lang": "eng" }
+            ]
+        }))
+        .unwrap();
+        let subtitle = |id: &str, url: &str, lang: &str| Subtitle {
+            id: id.to_string(),
+            url: url.to_string(),
+            lang: lang.to_string(),
+        };
+        let provider = vec![
+            subtitle("os-en", "https://subs.example.com/en.srt", "eng"),
+            subtitle("dup", "https://cdn.example.com/movie.en.srt", "ENG"),
+            subtitle("os-fr", "https://subs.example.com/en.srt", "fre"),
+        ];
+
+        let merged = merge_subtitles(stream.subtitles, provider);
+        let ids: Vec<&str> = merged.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["emb-en", "os-en", "os-fr"]);
+    }
+
+    #[test]
+    fn test_subtitles_filtered_and_ordered_by_language() {
+        let subtitle = |id: &str, lang: &str| Subtitle {
+            id: id.to_string(),
+            url: format!("https://subs.example.com/{}.srt", id),
+            lang: lang.to_string(),
+        };
+        let subtitles = vec![
+            subtitle("1", "fre"),
+            subtitle("2", "eng"),
+            subtitle("3", "spa"),
+            subtitle("4", "English"),
+            subtitle("5", "es"),
+            subtitle("6", "ger"),
+        ];
+        let ids = |subs: &[Subtitle]| subs.iter().map(|s| s.id.clone()).collect::<Vec<_>>();
+
+        let preferred = vec!["es".to_string(), "en".to_string()];
+        let ordered = order_subtitles(subtitles.clone(), &[], &preferred);
+        assert_eq!(ids(&ordered), vec!["3", "5", "2", "4", "1", "6"]);
+
+        let languages = vec!["english".to_string(), "de".to_string()];
+        let filtered = order_subtitles(subtitles, &languages, &preferred);
+        assert_eq!(ids(&filtered), vec!["2", "4", "6"]);
+    }
+
+    #[test]
+    fn test_select_best_stream_weighted_by_addon_health() {
+        let stream = |url: &str, addon_id: &str| crate::models::StreamWithSource {
+            url: url.to_string(),
+            title: Some("Movie 1080p".to_string()),
+            name: None,
+            description: None,
+            addon_id: addon_id.to_string(),
+            addon_name: addon_id.to_string(),
+            source_type: crate::models::StreamSourceType::Direct,
+            size_bytes: None,
+            seeders: None,
+            not_web_ready: false,
+            subtitles: Vec::new(),
+        };
+        let streams = vec![
+            stream("https://flaky.example.com/movie.mp4", "flaky"),
+            stream("https://reliable.example.com/movie.mp4", "reliable"),
+        ];
+
+        // Without health data the first of equal streams wins
+        assert_eq!(
+            select_best_stream(&streams, None, false).as_deref(),
+            Some("https://flaky.example.com/movie.mp4")
+        );
+        let empty = std::collections::HashMap::new();
+        assert_eq!(
+            select_best_stream(&streams, Some(&empty), false).as_deref(),
+            Some("https://flaky.example.com/movie.mp4")
+        );
+
+        let scores = std::collections::HashMap::from([
+            ("flaky".to_string(), 35.0),
+            ("reliable".to_string(), 97.5),
+        ]);
+        assert_eq!(
+            select_best_stream(&streams, Some(&scores), false).as_deref(),
+            Some("https://reliable.example.com/movie.mp4")
+        );
+    }
+
+    #[test]
+    fn test_cache_size_budget_from_preference() {
+        let budget = |cache_size: &str| {
+            cache_size_budget(&UserPreferences {
+                cache_size: cache_size.to_string(),
+                ..UserPreferences::default()
+            })
+        };
+
+        assert_eq!(budget("512"), 512 * 1024 * 1024);
+        assert_eq!(budget(" 64 "), 64 * 1024 * 1024);
+        // Unset, zero or nonsense values keep the default budget
+        assert_eq!(budget(""), cache::DEFAULT_MAX_SIZE_BYTES);
+        assert_eq!(budget("0"), cache::DEFAULT_MAX_SIZE_BYTES);
+        assert_eq!(budget("lots"), cache::DEFAULT_MAX_SIZE_BYTES);
+    }
+
+    fn test_state() -> AppState {
+        AppState {
+            db: Arc::new(Mutex::new(Database::new_in_memory().unwrap())),
+            cache: Arc::new(Mutex::new(CacheManager::new(None).unwrap())),
+            streaming_server: None,
+            cast_manager: None,
+            folder_watcher: None,
+            events: EventBus::new(),
+            active_profile: Arc::new(Mutex::new(DEFAULT_PROFILE_ID.to_string())),
+            recordings: Arc::new(recording::RecordingManager::new()),
+        }
+    }
+
+    /// Temp directory removed when dropped
+    struct TempDir(std::path::PathBuf);
+
+    impl TempDir {
+        fn new(prefix: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("{}_{}", prefix, uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// `test_state` with an offline streaming server to serve local copies
+    /// through. Keep the returned dir alive for as long as the state.
+    async fn test_state_with_server() -> (AppState, TempDir) {
+        let dir = TempDir::new("streamgo_server");
+        let server = streaming_server::StreamingServer::new_offline(dir.0.clone(), 8765)
+            .await
+            .unwrap();
+        let state = AppState {
+            streaming_server: Some(Arc::new(server)),
+            ..test_state()
+        };
+        (state, dir)
+    }
+
+    /// Assert `url` is where the streaming server serves `path`
+    async fn assert_served_local_file(state: &AppState, url: &str, path: &std::path::Path) {
+        assert!(url.starts_with("http://127.0.0.1:8765/local/"), "{}", url);
+        let server = state.streaming_server.as_ref().unwrap();
+        assert_eq!(server.register_local_file(path.to_path_buf()).await, url);
+    }
+
+    fn local_file(path: &std::path::Path, imdb_id: &str) -> LocalMediaFile {
+        LocalMediaFile {
+            id: format!("local-{}", imdb_id),
+            file_path: path.to_string_lossy().to_string(),
+            file_name: "The.Shawshank.Redemption.1994.1080p.mkv".to_string(),
+            file_size: 4,
+            title: "The Shawshank Redemption".to_string(),
+            year: Some(1994),
+            season: None,
+            episode: None,
+            duration: None,
+            resolution: Some("1080p".to_string()),
+            video_codec: None,
+            audio_codec: None,
+            tmdb_id: None,
+            imdb_id: Some(imdb_id.to_string()),
+            poster_url: None,
+            plot: None,
+            added_at: chrono::Utc::now(),
+            last_modified: chrono::Utc::now(),
+        }
+    }
+
+    /// Stream and subtitles addon served from `base_url`
+    fn mock_addon(base_url: &str) -> Addon {
+        Addon {
+            id: "org.mock.streams".to_string(),
+            name: "Mock streams".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Mock addon".to_string(),
+            author: "Tests".to_string(),
+            url: format!("{}/manifest.json", base_url),
+            enabled: true,
+            addon_type: AddonType::ContentProvider,
+            manifest: AddonManifest {
+                id: "org.mock.streams".to_string(),
+                name: "Mock streams".to_string(),
+                version: "1.0.0".to_string(),
+                description: "Mock addon".to_string(),
+                resources: vec!["stream".to_string(), "subtitles".to_string()],
+                types: vec!["movie".to_string()],
+                catalogs: vec![],
+            },
+            priority: 0,
+            config: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_playable_prefers_local_file() {
+        let path = std::env::temp_dir().join(format!(
+            "streamgo_resolve_playable_local_{}.mkv",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::write(&path, b"mkv!").unwrap();
+        let (state, _dir) = test_state_with_server().await;
+        state
+            .db
+            .lock()
+            .unwrap()
+            .upsert_local_media_file(&local_file(&path, "tt0111161"))
+            .unwrap();
+
+        // No addons installed: the local copy needs none
+        let playable = resolve_playable_for("tt0111161", None, None, &state)
+            .await
+            .unwrap();
+        assert!(playable.is_local);
+        assert_eq!(playable.resolved_via, ResolvedVia::LocalFile);
+        assert_served_local_file(&state, &playable.url, &path).await;
+        assert!(playable.stream.is_none());
+
+        // Episodes and deleted files fall through to addon streams
+        assert!(resolve_playable_for("tt0111161:1:1", None, None, &state)
+            .await
+            .is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(resolve_playable_for("tt0111161", None, None, &state)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_playable_matches_local_title_without_addon_queries() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/stream/movie/tt10872600.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "streams": [{ "url": "https://cdn.example.com/movie.mp4", "name": "1080p" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let file_path = std::env::temp_dir().join(format!(
+            "streamgo_resolve_playable_title_{}.mkv",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::write(&file_path, b"mkv!").unwrap();
+        let (state, _dir) = test_state_with_server().await;
+        {
+            let db = state.db.lock().unwrap();
+            let mut addon = mock_addon(&server.uri());
+            addon.manifest.resources = vec!["stream".to_string()];
+            db.save_addon(&addon).unwrap();
+            db.add_to_library(
+                MediaItem::builder("tt10872600", "Spider-Man: No Way Home", MediaType::Movie)
+                    .year(2021)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+            // Scanned from the filename only, so there is no IMDb id to match on
+            let mut file = local_file(&file_path, "unused");
+            file.imdb_id = None;
+            file.title = "Spider Man No Way Home".to_string();
+            file.year = Some(2021);
+            db.upsert_local_media_file(&file).unwrap();
+        }
+
+        let playable = resolve_playable_for("tt10872600", None, None, &state)
+            .await
+            .unwrap();
+        assert_eq!(playable.resolved_via, ResolvedVia::LocalFile);
+        assert_served_local_file(&state, &playable.url, &file_path).await;
+        assert!(server.received_requests().await.unwrap().is_empty());
+
+        // With prefer_local off the addons are asked instead
+        let mut preferences = UserPreferences::default();
+        preferences.prefer_local = false;
+        state
+            .db
+            .lock()
+            .unwrap()
+            .save_user_profile(&UserProfile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                username: "Default".to_string(),
+                email: None,
+                preferences,
+                library_items: Vec::new(),
+                watchlist: Vec::new(),
+                favorites: Vec::new(),
+            })
+            .unwrap();
+        let playable = resolve_playable_for("tt10872600", None, None, &state)
+            .await
+            .unwrap();
+        assert_eq!(playable.resolved_via, ResolvedVia::Addon);
+        assert_eq!(playable.url, "https://cdn.example.com/movie.mp4");
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+        std::fs::remove_file(&file_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_playable_picks_addon_stream() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/stream/movie/tt0111161.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "streams": [
+                    { "url": "https://cdn.example.com/movie.2160p.mp4", "name": "4K" },
+                    {
+                        "url": "https://cdn.example.com/movie.1080p.mp4",
+                        "name": "1080p",
+                        "subtitles": [
+                            { "id": "emb-en", "url": "https://cdn.example.com/en.srt", "lang": "eng" }
+                        ]
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/subtitles/movie/tt0111161.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "subtitles": [
+                    { "id": "os-fr", "url": "https://subs.example.com/fr.srt", "lang": "fre" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let state = test_state();
+        let mut addon = mock_addon(&server.uri());
+        state.db.lock().unwrap().save_addon(&addon).unwrap();
+
+        let playable = resolve_playable_for("tt0111161", None, Some("1080p".to_string()), &state)
+            .await
+            .unwrap();
+        assert!(!playable.is_local);
+        assert_eq!(playable.resolved_via, ResolvedVia::Addon);
+        assert_eq!(playable.url, "https://cdn.example.com/movie.1080p.mp4");
+        assert_eq!(playable.stream.unwrap().addon_id, "org.mock.streams");
+        let subtitle_ids: Vec<&str> = playable.subtitles.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(subtitle_ids, vec!["emb-en", "os-fr"]);
+
+        // Without a cap the best stream wins; subtitle addons are optional
+        addon.manifest.resources = vec!["stream".to_string()];
+        state.db.lock().unwrap().save_addon(&addon).unwrap();
+        let playable = resolve_playable_for("tt0111161", None, Some("auto".to_string()), &state)
+            .await
+            .unwrap();
+        assert_eq!(playable.url, "https://cdn.example.com/movie.2160p.mp4");
+        assert!(playable.subtitles.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_query_leaves_state_untouched() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/stream/movie/tt0111161.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "streams": [{ "url": "https://cdn.example.com/movie.mp4", "name": "1080p" }]
+            })))
+            .mount(&server)
+            .await;
+        let state = test_state();
+        // Rows of the raw addon_health table, minus the CSV header
+        let health_rows = |state: &AppState| {
+            let csv = state
+                .db
+                .lock()
+                .unwrap()
+                .export_addon_health_csv(None)
+                .unwrap();
+            csv.lines().count() - 1
+        };
+
+        let streams = sandbox_query(
+            server.uri(),
+            "stream".to_string(),
+            "movie".to_string(),
+            "tt0111161".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            streams["streams"][0]["url"],
+            "https://cdn.example.com/movie.mp4"
+        );
+        assert_eq!(health_rows(&state), 0);
+        assert!(state.db.lock().unwrap().get_addons().unwrap().is_empty());
+        assert_eq!(
+            state.cache.lock().unwrap().get_stats().unwrap().addon_total,
+            0
+        );
+
+        // The same query through an installed addon is recorded in that DB
+        state
+            .db
+            .lock()
+            .unwrap()
+            .save_addon(&mock_addon(&server.uri()))
+            .unwrap();
+        aggregate_streams("tt0111161", None, &state).await.unwrap();
+        for _ in 0..50 {
+            if health_rows(&state) > 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(health_rows(&state), 1);
+    }
+}
@@ -1,9 +1,32 @@
 use chrono::{DateTime, TimeZone, Utc};
+use futures::stream::{self, StreamExt};
 use quick_xml::de::from_str as xml_from_str;
 use regex::Regex;
 use serde::Deserialize;
 
-use crate::models::{EpgProgram, LiveTvChannel};
+use crate::database::Database;
+use crate::events::{AppEvent, EventBus};
+use crate::models::{ChannelStatus, EpgProgram, LiveTvChannel, ProgramReminder};
+use crate::rate_limit::RateLimiter;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long before a reminded program starts the user is notified
+pub const REMINDER_LEAD: Duration = Duration::from_secs(5 * 60);
+
+/// How often due program reminders are checked
+const REMINDER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Channel streams checked at the same time
+pub const CHANNEL_PROBE_CONCURRENCY: usize = 8;
+
+/// Minimum gap between starting two channel stream checks
+pub const CHANNEL_PROBE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a channel's stream may take to answer a check
+pub const CHANNEL_PROBE_TIMEOUT: Duration = Duration::from_secs(8);
 
 #[derive(Debug, thiserror::Error)]
 pub enum LiveTvError {
@@ -74,13 +97,10 @@ impl LiveTvManager {
                         _ => {}
                     }
                 }
-                // Fallback: name is text after comma
-                if let Some(idx) = line.rfind(',') {
-                    if let Some(name) = line.get(idx + 1..) {
-                        if !name.trim().is_empty() {
-                            current_name = Some(name.trim().to_string());
-                        }
-                    }
+                // Display name is the text after the first comma outside the
+                // quoted attributes (group titles and names may contain commas)
+                if let Some(name) = extinf_title(line) {
+                    current_name = Some(name.to_string());
                 }
             } else if !line.starts_with('#') {
                 // URL line
@@ -111,14 +131,28 @@ impl LiveTvManager {
         let tv: XmlTv = xml_from_str(xml).map_err(|e| LiveTvError::Parse(e.to_string()))?;
         let mut programs = Vec::new();
 
-        for p in tv.programmes.unwrap_or_default() {
-            let start = parse_xmltv_datetime(&p.start).unwrap_or_else(|| Utc::now());
-            let end = parse_xmltv_datetime(&p.stop).unwrap_or_else(|| start + chrono::Duration::minutes(30));
+        let all = tv
+            .programme
+            .unwrap_or_default()
+            .into_iter()
+            .chain(tv.programmes.unwrap_or_default());
+        for p in all {
+            let Some(start) = parse_xmltv_datetime(&p.start) else {
+                tracing::debug!(
+                    channel = %p.channel,
+                    start = %p.start,
+                    "Skipping programme with bad start time"
+                );
+                continue;
+            };
+            let end = parse_xmltv_datetime(&p.stop)
+                .unwrap_or_else(|| start + chrono::Duration::minutes(30));
             let title = p.title.map(|t| t.value).unwrap_or_else(|| "".to_string());
             let description = p.desc.map(|d| d.value);
             let category = p.category.map(|c| c.value);
 
             programs.push(EpgProgram {
+                id: None,
                 channel_id: p.channel,
                 start: start.timestamp(),
                 end: end.timestamp(),
@@ -134,6 +168,126 @@ impl LiveTvManager {
     }
 }
 
+/// Whether a stream URL answers: a HEAD request, falling back to a one-byte
+/// GET for servers that reject HEAD
+pub async fn check_stream_url(client: &reqwest::Client, url: &str) -> Result<(), LiveTvError> {
+    if let Ok(resp) = client.head(url).send().await {
+        if resp.status().is_success() {
+            return Ok(());
+        }
+    }
+
+    let resp = client
+        .get(url)
+        .header(reqwest::header::RANGE, "bytes=0-0")
+        .send()
+        .await
+        .map_err(|e| LiveTvError::Network(e.to_string()))?;
+    if !resp.status().is_success() {
+        return Err(LiveTvError::Network(format!("HTTP {}", resp.status())));
+    }
+    Ok(())
+}
+
+/// Check each channel's stream with `probe`, at most `max_concurrency` at a
+/// time and starting no more often than every `min_interval`. Streams that
+/// aren't HTTP(S), such as rtmp:// or udp://, can't be checked and are skipped.
+pub async fn probe_channels<F, Fut>(
+    channels: Vec<LiveTvChannel>,
+    max_concurrency: usize,
+    min_interval: Duration,
+    probe: F,
+) -> Vec<ChannelStatus>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<(), LiveTvError>>,
+{
+    let limiter = RateLimiter::new(min_interval);
+    let probe = &probe;
+    let limiter = &limiter;
+
+    let checkable = channels.into_iter().filter(|channel| {
+        let url = channel.stream_url.to_lowercase();
+        url.starts_with("http://") || url.starts_with("https://")
+    });
+
+    stream::iter(checkable)
+        .map(|channel| async move {
+            limiter.wait().await;
+            let result = probe(channel.stream_url).await;
+            if let Err(e) = &result {
+                tracing::debug!(channel_id = %channel.id, error = %e, "Channel stream unreachable");
+            }
+            ChannelStatus {
+                channel_id: channel.id,
+                online: result.is_ok(),
+                checked_at: Utc::now().timestamp(),
+                error: result.err().map(|e| e.to_string()),
+            }
+        })
+        .buffer_unordered(max_concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Emit `program-starting-soon` for each reminded program about to start,
+/// once per reminder, and drop reminders for programs that have ended.
+/// Runs for the lifetime of the app.
+pub async fn run_program_reminders(db: Arc<Mutex<Database>>, events: EventBus) {
+    let mut interval = tokio::time::interval(REMINDER_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let db = db.clone();
+        let due = tokio::task::spawn_blocking(move || {
+            let db = db
+                .lock()
+                .map_err(|e| anyhow::anyhow!("Database lock poisoned: {}", e))?;
+            let now = Utc::now().timestamp();
+            let removed = db.remove_past_reminders(now)?;
+            if removed > 0 {
+                tracing::debug!(removed, "Removed reminders for finished programs");
+            }
+            let due = db.get_due_reminders(now, REMINDER_LEAD.as_secs() as i64)?;
+            for reminder in &due {
+                db.mark_reminder_notified(reminder.program_id)?;
+            }
+            Ok::<Vec<ProgramReminder>, anyhow::Error>(due)
+        })
+        .await;
+
+        match due {
+            Ok(Ok(due)) => {
+                for reminder in due {
+                    tracing::info!(
+                        channel_id = %reminder.channel_id,
+                        title = %reminder.title,
+                        "Program starting soon"
+                    );
+                    events.emit(AppEvent::ProgramStartingSoon(reminder));
+                }
+            }
+            Ok(Err(e)) => tracing::warn!(error = %e, "Failed to check program reminders"),
+            Err(e) => tracing::warn!(error = %e, "Program reminder task failed"),
+        }
+    }
+}
+
+/// Title of an `#EXTINF` line, if non-empty
+fn extinf_title(line: &str) -> Option<&str> {
+    let mut in_quotes = false;
+    let idx = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ',' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let name = line[idx + 1..].trim();
+    (!name.is_empty()).then_some(name)
+}
+
 fn sanitize_channel_id(name: &str) -> String {
     let mut s = name.to_lowercase();
     s.retain(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
@@ -145,37 +299,61 @@ fn sanitize_channel_id(name: &str) -> String {
     }
 }
 
-fn parse_xmltv_datetime(s: &str) -> Option<DateTime<Utc>> {
-    // Common formats: YYYYMMDDHHMMSS Z, or without space, with offset
-    // Try to parse using chrono
-    // Example: 20250101120000 +0000
-    if s.len() >= 14 {
-        let y: i32 = s.get(0..4)?.parse().ok()?;
-        let mo: u32 = s.get(4..6)?.parse().ok()?;
-        let d: u32 = s.get(6..8)?.parse().ok()?;
-        let h: u32 = s.get(8..10)?.parse().ok()?;
-        let mi: u32 = s.get(10..12)?.parse().ok()?;
-        let se: u32 = s.get(12..14)?.parse().ok()?;
-        let naive = chrono::NaiveDate::from_ymd_opt(y, mo, d)?.and_hms_opt(h, mi, se)?;
-        if s.len() >= 19 {
-            // has offset
-            if let Some(offset_str) = s.get(15..) {
-                // offset like +0000 or -0500
-                let sign = &offset_str[0..1];
-                if let (Ok(oh), Ok(om)) = (
-                    offset_str.get(1..3).unwrap_or("00").parse::<i32>(),
-                    offset_str.get(3..5).unwrap_or("00").parse::<i32>(),
-                ) {
-                    let minutes = oh * 60 + om;
-                    let offset = if sign == "-" { -minutes } else { minutes };
-                    let fixed = chrono::FixedOffset::east_opt(offset * 60)?;
-                    return Some(fixed.from_local_datetime(&naive).single()?.with_timezone(&Utc));
-                }
+/// Point programs at stored channels: the XMLTV `channel` attribute is
+/// matched against each channel's tvg-id, falling back to its id. A program
+/// is copied to every channel sharing the tvg-id (e.g. SD and HD variants);
+/// programs for unknown channels are dropped.
+pub fn map_programs_to_channels(
+    programs: Vec<EpgProgram>,
+    channels: &[LiveTvChannel],
+) -> Vec<EpgProgram> {
+    let mut by_tvg_id: HashMap<&str, Vec<&str>> = HashMap::new();
+    for channel in channels {
+        if let Some(tvg_id) = channel.tvg_id.as_deref().filter(|t| !t.is_empty()) {
+            by_tvg_id.entry(tvg_id).or_default().push(&channel.id);
+        }
+    }
+
+    let mut mapped = Vec::new();
+    for program in programs {
+        let targets = match by_tvg_id.get(program.channel_id.as_str()) {
+            Some(ids) => ids.clone(),
+            None if channels.iter().any(|c| c.id == program.channel_id) => {
+                vec![program.channel_id.as_str()]
             }
+            None => continue,
+        };
+        for channel_id in targets {
+            mapped.push(EpgProgram {
+                channel_id: channel_id.to_string(),
+                ..program.clone()
+            });
         }
-        return Some(Utc.from_local_datetime(&naive).single()?);
     }
-    None
+    mapped
+}
+
+/// XMLTV time: `YYYYMMDDHHMMSS`, optionally followed by a UTC offset such
+/// as ` +0100` or `-0500` (the space is optional). No offset means UTC.
+fn parse_xmltv_datetime(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    let naive = chrono::NaiveDateTime::parse_from_str(s.get(0..14)?, "%Y%m%d%H%M%S").ok()?;
+    let offset = s.get(14..)?.trim();
+    if offset.is_empty() {
+        return Some(Utc.from_utc_datetime(&naive));
+    }
+
+    let sign = match offset.get(0..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits = offset[1..].replace(':', "");
+    let hours: i32 = digits.get(0..2)?.parse().ok()?;
+    let minutes: i32 = digits.get(2..4).unwrap_or("00").parse().ok()?;
+    let fixed = chrono::FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))?;
+    let local = fixed.from_local_datetime(&naive).single()?;
+    Some(local.with_timezone(&Utc))
 }
 
 // Minimal XMLTV structs
@@ -189,10 +367,11 @@ struct XmlTv {
 
 #[derive(Debug, Deserialize)]
 struct XmlProgramme {
-    #[serde(rename = "channel")]
+    #[serde(rename = "@channel")]
     channel: String,
+    #[serde(rename = "@start")]
     start: String,
-    #[serde(default)]
+    #[serde(rename = "@stop", default)]
     stop: String,
     #[serde(default)]
     title: Option<XmlText>,
@@ -207,3 +386,148 @@ struct XmlText {
     #[serde(rename = "$text")]
     value: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(id: &str, stream_url: &str) -> LiveTvChannel {
+        LiveTvChannel {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            logo: None,
+            group: None,
+            tvg_id: None,
+            stream_url: stream_url.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_xmltv_two_programmes() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tv generator-info-name="test">
+  <channel id="bbc1.uk"><display-name>BBC One</display-name></channel>
+  <programme start="20250101120000 +0100" stop="20250101130000 +0100" channel="bbc1.uk">
+    <title lang="en">News at Noon</title>
+    <desc lang="en">The latest headlines.</desc>
+    <category lang="en">News</category>
+  </programme>
+  <programme start="20250101130000+0000" stop="20250101143000 -0130" channel="bbc1.uk">
+    <title>Film: Two &amp; a Half</title>
+  </programme>
+</tv>"#;
+        let programs = LiveTvManager::parse_xmltv(xml).unwrap();
+        assert_eq!(programs.len(), 2);
+
+        // 12:00 at +01:00 is 11:00 UTC
+        let news = &programs[0];
+        assert_eq!(news.channel_id, "bbc1.uk");
+        let utc = |h, m| Utc.with_ymd_and_hms(2025, 1, 1, h, m, 0).unwrap();
+        assert_eq!(news.start, utc(11, 0).timestamp());
+        assert_eq!(news.end, news.start + 3600);
+        assert_eq!(news.title, "News at Noon");
+        assert_eq!(news.description.as_deref(), Some("The latest headlines."));
+        assert_eq!(news.category.as_deref(), Some("News"));
+
+        // Offset without a space; 14:30 at -01:30 is 16:00 UTC
+        let film = &programs[1];
+        assert_eq!(film.start, utc(13, 0).timestamp());
+        assert_eq!(film.end, utc(16, 0).timestamp());
+        assert_eq!(film.title, "Film: Two & a Half");
+        assert!(film.description.is_none());
+
+        let mut bbc_hd = channel("bbc-one-hd", "https://live.example.com/bbc1hd.m3u8");
+        bbc_hd.tvg_id = Some("bbc1.uk".to_string());
+        let mut bbc_sd = channel("bbc-one", "https://live.example.com/bbc1.m3u8");
+        bbc_sd.tvg_id = Some("bbc1.uk".to_string());
+        let mapped = map_programs_to_channels(programs.clone(), &[bbc_hd, bbc_sd]);
+        assert_eq!(mapped.len(), 4);
+        assert_eq!(mapped[0].channel_id, "bbc-one-hd");
+        assert_eq!(mapped[1].channel_id, "bbc-one");
+        assert!(map_programs_to_channels(programs, &[channel("itv", "https://x/itv")]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_m3u_groups_and_logos() {
+        let playlist = r#"#EXTM3U x-tvg-url="https://epg.example.com/guide.xml"
+#EXTINF:-1 tvg-id="bbc1.uk" tvg-name="BBC One HD" tvg-logo="https://logos.example.com/bbc1.png" group-title="UK, General",BBC One
+https://live.example.com/bbc1.m3u8
+
+#EXTINF:-1 tvg-logo="https://logos.example.com/espn.png" group-title="Sports",ESPN, US
+#EXTVLCOPT:http-user-agent=StreamGo
+https://live.example.com/espn.m3u8
+#EXTINF:-1 tvg-id="" tvg-name="Local News",
+http://10.0.0.5:8000/local
+#EXTINF:-1,Radio
+"#;
+        let channels = LiveTvManager::parse_m3u(playlist);
+        assert_eq!(channels.len(), 3, "entries without a URL are dropped");
+
+        assert_eq!(channels[0].id, "bbc1.uk");
+        assert_eq!(channels[0].name, "BBC One");
+        assert_eq!(channels[0].tvg_id.as_deref(), Some("bbc1.uk"));
+        assert_eq!(
+            channels[0].logo.as_deref(),
+            Some("https://logos.example.com/bbc1.png")
+        );
+        assert_eq!(channels[0].group.as_deref(), Some("UK, General"));
+        assert_eq!(channels[0].stream_url, "https://live.example.com/bbc1.m3u8");
+
+        assert_eq!(channels[1].name, "ESPN, US");
+        assert_eq!(channels[1].group.as_deref(), Some("Sports"));
+        assert!(channels[1].tvg_id.is_none());
+        assert_eq!(channels[1].stream_url, "https://live.example.com/espn.m3u8");
+
+        // No title after the comma: tvg-name is used, and the id is derived from it
+        assert_eq!(channels[2].name, "Local News");
+        assert!(channels[2].logo.is_none());
+        assert!(channels[2].group.is_none());
+        assert_eq!(channels[2].id, "localnews");
+    }
+
+    #[tokio::test]
+    async fn test_probe_channels_updates_statuses() {
+        let db = Database::new_in_memory().unwrap();
+        let channels = vec![
+            channel("bbc-one", "https://live.example.com/bbc-one.m3u8"),
+            channel("dead", "https://gone.example.com/dead.m3u8"),
+            channel("multicast", "udp://239.0.0.1:1234"),
+        ];
+        db.upsert_live_tv_channels(&channels).unwrap();
+
+        // Mocked reachability: only the live.example.com stream answers
+        let mut statuses = probe_channels(channels, 2, Duration::ZERO, |url| async move {
+            if url.contains("live.example.com") {
+                Ok(())
+            } else {
+                Err(LiveTvError::Network("HTTP 404 Not Found".to_string()))
+            }
+        })
+        .await;
+        statuses.sort_by(|a, b| a.channel_id.cmp(&b.channel_id));
+        assert_eq!(statuses.len(), 2, "non-HTTP streams are skipped");
+        assert_eq!(db.update_channel_statuses(&statuses).unwrap(), 2);
+
+        let stored = db.get_channel_statuses().unwrap();
+        assert_eq!(stored, vec![statuses[0].clone(), statuses[1].clone()]);
+        assert_eq!(stored[0].channel_id, "bbc-one");
+        assert!(stored[0].online);
+        assert!(stored[0].error.is_none());
+        assert_eq!(stored[1].channel_id, "dead");
+        assert!(!stored[1].online);
+        assert_eq!(
+            stored[1].error.as_deref(),
+            Some("Network error: HTTP 404 Not Found")
+        );
+
+        // A later check replaces the earlier result
+        let recovered = ChannelStatus {
+            channel_id: "dead".to_string(),
+            online: true,
+            checked_at: stored[1].checked_at + 60,
+            error: None,
+        };
+        db.update_channel_statuses(&[recovered.clone()]).unwrap();
+        assert_eq!(db.get_channel_statuses().unwrap()[1], recovered);
+    }
+}
@@ -3,13 +3,20 @@
  *
  * Scans local filesystem for video files and integrates them with StreamGo library
  */
+use crate::events::{AppEvent, EventBus, ScanProgress};
 use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
 const TMDB_BASE_URL: &str = "https://api.themoviedb.org/3";
 
+const DEFAULT_SCAN_CONCURRENCY: usize = 4;
+const DEFAULT_PROBE_TIMEOUT_SECS: u64 = 30;
+
 /// Supported video file extensions
 const VIDEO_EXTENSIONS: &[&str] = &[
     "mp4", "mkv", "avi", "mov", "wmv", "flv", "webm", "m4v", "mpg", "mpeg", "3gp", "ogv", "ts",
@@ -34,6 +41,7 @@ pub struct LocalMediaFile {
     pub tmdb_id: Option<String>,
     pub imdb_id: Option<String>,
     pub poster_url: Option<String>,
+    pub plot: Option<String>,
     pub added_at: chrono::DateTime<chrono::Utc>,
     pub last_modified: chrono::DateTime<chrono::Utc>,
 }
@@ -46,6 +54,20 @@ pub struct ParsedFilename {
     pub season: Option<u32>,
     pub episode: Option<u32>,
     pub quality: Option<String>,
+    /// Last episode of a multi-episode file such as `S01E01-E02`
+    pub episode_end: Option<u32>,
+    /// Release group from a leading `[Group]` or a trailing `-GROUP`
+    pub release_group: Option<String>,
+}
+
+/// Metadata read from a Kodi-style `.nfo` sidecar file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NfoMetadata {
+    pub title: Option<String>,
+    pub year: Option<u32>,
+    pub imdb_id: Option<String>,
+    pub plot: Option<String>,
+    pub thumb: Option<String>,
 }
 
 /// FFmpeg probe result
@@ -63,12 +85,47 @@ pub struct VideoMetadata {
 /// Local media scanner
 pub struct LocalMediaScanner {
     scan_paths: Vec<PathBuf>,
+    concurrency: usize,
+    /// `None` lets ffprobe run as long as it needs
+    probe_timeout: Option<Duration>,
+    /// Receives `scan-progress` events while a directory is scanned
+    events: Option<EventBus>,
 }
 
 impl LocalMediaScanner {
     /// Create new scanner with scan paths
     pub fn new(scan_paths: Vec<PathBuf>) -> Self {
-        Self { scan_paths }
+        Self {
+            scan_paths,
+            concurrency: DEFAULT_SCAN_CONCURRENCY,
+            probe_timeout: Some(Duration::from_secs(DEFAULT_PROBE_TIMEOUT_SECS)),
+            events: None,
+        }
+    }
+
+    /// Apply the user's scan concurrency and probe timeout
+    pub fn with_preferences(self, preferences: &crate::models::UserPreferences) -> Self {
+        self.with_concurrency(preferences.scan_concurrency as usize)
+            .with_probe_timeout(Duration::from_secs(preferences.probe_timeout_secs))
+    }
+
+    /// Number of files processed at the same time (at least 1)
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// How long ffprobe may take per file before its metadata is skipped
+    /// (zero disables the timeout)
+    pub fn with_probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = (!timeout.is_zero()).then_some(timeout);
+        self
+    }
+
+    /// Report scan progress on `events`
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
     }
 
     /// Scan all configured paths
@@ -94,8 +151,6 @@ impl LocalMediaScanner {
 
     /// Scan a single directory recursively
     pub async fn scan_directory(&self, path: &Path) -> Result<Vec<LocalMediaFile>> {
-        let mut files = Vec::new();
-
         if !path.exists() {
             return Err(anyhow!("Path does not exist: {}", path.display()));
         }
@@ -105,31 +160,40 @@ impl LocalMediaScanner {
         }
 
         // Walk directory recursively
-        for entry in walkdir::WalkDir::new(path)
+        let video_paths: Vec<PathBuf> = walkdir::WalkDir::new(path)
             .follow_links(false)
             .into_iter()
             .filter_map(|e| e.ok())
-        {
-            let entry_path = entry.path();
-
-            // Skip directories
-            if entry_path.is_dir() {
-                continue;
-            }
-
-            // Check if it's a video file
-            if is_video_file(entry_path) {
+            .map(|entry| entry.into_path())
+            .filter(|entry_path| !entry_path.is_dir() && is_video_file(entry_path))
+            .collect();
+
+        // Probe and match several files at once, keeping walk order
+        let total = video_paths.len();
+        self.report_progress(path, 0, total);
+        let mut processing = stream::iter(video_paths)
+            .map(|entry_path| async move {
                 debug!("Found video file: {}", entry_path.display());
+                let result = self.process_video_file(&entry_path).await;
+                (entry_path, result)
+            })
+            .buffered(self.concurrency);
+        let mut results: Vec<(PathBuf, Result<LocalMediaFile>)> = Vec::with_capacity(total);
+        while let Some(result) = processing.next().await {
+            results.push(result);
+            self.report_progress(path, results.len(), total);
+        }
 
-                match self.process_video_file(entry_path).await {
-                    Ok(file) => files.push(file),
-                    Err(e) => {
-                        warn!(
-                            error = %e,
-                            path = %entry_path.display(),
-                            "Failed to process video file"
-                        );
-                    }
+        let mut files = Vec::new();
+        for (entry_path, result) in results {
+            match result {
+                Ok(file) => files.push(file),
+                Err(e) => {
+                    warn!(
+                        error = %e,
+                        path = %entry_path.display(),
+                        "Failed to process video file"
+                    );
                 }
             }
         }
@@ -137,6 +201,21 @@ impl LocalMediaScanner {
         Ok(files)
     }
 
+    fn report_progress(&self, path: &Path, processed: usize, total: usize) {
+        if let Some(events) = &self.events {
+            events.emit(AppEvent::ScanProgress(ScanProgress {
+                path: path.display().to_string(),
+                processed,
+                total,
+            }));
+        }
+    }
+
+    /// Read one video file's metadata without looking it up on TMDB
+    pub async fn scan_file(&self, path: &Path) -> Result<LocalMediaFile> {
+        self.process_video_file_with_tmdb(path, false).await
+    }
+
     /// Process a single video file with optional TMDB matching
     async fn process_video_file(&self, path: &Path) -> Result<LocalMediaFile> {
         self.process_video_file_with_tmdb(path, true).await
@@ -144,6 +223,18 @@ impl LocalMediaScanner {
 
     /// Process a single video file with optional TMDB matching control
     async fn process_video_file_with_tmdb(&self, path: &Path, enable_tmdb: bool) -> Result<LocalMediaFile> {
+        self.process_video_file_with_probe(path, enable_tmdb, probe_video_metadata(path))
+            .await
+    }
+
+    /// Process a video file using the given metadata probe, which is
+    /// abandoned after the scanner's probe timeout
+    async fn process_video_file_with_probe(
+        &self,
+        path: &Path,
+        enable_tmdb: bool,
+        probe: impl Future<Output = Result<VideoMetadata>>,
+    ) -> Result<LocalMediaFile> {
         let file_name = path
             .file_name()
             .and_then(|n| n.to_str())
@@ -171,35 +262,66 @@ impl LocalMediaScanner {
         );
 
         // Probe video metadata with FFmpeg
-        let video_meta = probe_video_metadata(path).await.ok();
+        let probed = match self.probe_timeout {
+            Some(probe_timeout) => tokio::time::timeout(probe_timeout, probe).await,
+            None => Ok(probe.await),
+        };
+        let video_meta = match probed {
+            Ok(Ok(meta)) => Some(meta),
+            Ok(Err(e)) => {
+                debug!(error = %e, path = %path.display(), "Failed to probe video file");
+                None
+            }
+            Err(_) => {
+                warn!(
+                    path = %path.display(),
+                    timeout_secs = self.probe_timeout.map_or(0, |t| t.as_secs()),
+                    "Video probe timed out; adding file without metadata"
+                );
+                None
+            }
+        };
 
         // Generate unique ID from file path hash
         let digest = md5::compute(path.to_string_lossy().as_bytes());
         let id = format!("local:{:x}", digest);
 
-        // Try to match with TMDB if enabled
-        let (tmdb_id, imdb_id, poster_url, enriched_title) = if enable_tmdb {
-            match match_tmdb_metadata(&parsed.title, parsed.year, parsed.season).await {
+        // Prefer whatever the user wrote in a .nfo sidecar over the filename
+        let nfo = find_nfo(path).unwrap_or_default();
+        let has_nfo_title = nfo.title.is_some();
+        let title = nfo.title.unwrap_or_else(|| parsed.title.clone());
+        let year = nfo.year.or(parsed.year);
+
+        // An IMDB id from the .nfo already identifies the file, so skip TMDB
+        let (tmdb_id, imdb_id, poster_url, enriched_title) = if nfo.imdb_id.is_some() {
+            debug!(imdb_id = ?nfo.imdb_id, path = %path.display(), "Using .nfo metadata");
+            (None, nfo.imdb_id, nfo.thumb, title)
+        } else if enable_tmdb {
+            match match_tmdb_metadata(&title, year, parsed.season).await {
                 Ok(tmdb_match) => {
                     debug!(
-                        original_title = %parsed.title,
+                        original_title = %title,
                         tmdb_title = %tmdb_match.title,
                         "TMDB match found"
                     );
                     (
                         Some(tmdb_match.tmdb_id),
                         tmdb_match.imdb_id,
-                        tmdb_match.poster_url,
-                        tmdb_match.title,
+                        nfo.thumb.or(tmdb_match.poster_url),
+                        if has_nfo_title {
+                            title
+                        } else {
+                            tmdb_match.title
+                        },
                     )
                 }
                 Err(e) => {
-                    debug!(error = %e, title = %parsed.title, "No TMDB match found");
-                    (None, None, None, parsed.title.clone())
+                    debug!(error = %e, title = %title, "No TMDB match found");
+                    (None, None, nfo.thumb, title)
                 }
             }
         } else {
-            (None, None, None, parsed.title.clone())
+            (None, None, nfo.thumb, title)
         };
 
         Ok(LocalMediaFile {
@@ -208,7 +330,7 @@ impl LocalMediaScanner {
             file_name,
             file_size,
             title: enriched_title,
-            year: parsed.year,
+            year,
             season: parsed.season,
             episode: parsed.episode,
             duration: video_meta.as_ref().and_then(|m| m.duration),
@@ -222,12 +344,109 @@ impl LocalMediaScanner {
             tmdb_id,
             imdb_id,
             poster_url,
+            plot: nfo.plot,
             added_at: chrono::Utc::now(),
             last_modified: last_modified.unwrap_or_else(chrono::Utc::now),
         })
     }
 }
 
+/// Find the `.nfo` describing a video: `<basename>.nfo` next to it, then a
+/// `movie.nfo` or `tvshow.nfo` in its folder, then a `tvshow.nfo` one level
+/// up for shows organised into season folders
+fn find_nfo(video_path: &Path) -> Option<NfoMetadata> {
+    let dir = video_path.parent()?;
+    let candidates = [
+        Some(video_path.with_extension("nfo")),
+        Some(dir.join("movie.nfo")),
+        Some(dir.join("tvshow.nfo")),
+        dir.parent().map(|parent| parent.join("tvshow.nfo")),
+    ];
+
+    candidates
+        .into_iter()
+        .flatten()
+        .filter(|candidate| candidate.is_file())
+        .find_map(|candidate| parse_nfo(&candidate))
+}
+
+/// Parse a Kodi `.nfo` file. Only direct children of the root element are
+/// read, so actor thumbnails and other nested fields are ignored. Episode
+/// files use `<showtitle>` as the title when present.
+pub fn parse_nfo(path: &Path) -> Option<NfoMetadata> {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    let xml = std::fs::read_to_string(path).ok()?;
+    let mut reader = Reader::from_str(&xml);
+    reader.config_mut().trim_text(true);
+
+    let mut nfo = NfoMetadata::default();
+    let mut show_title = None;
+    let mut depth = 0usize;
+    let mut field = String::new();
+    let mut imdb_uniqueid = false;
+
+    loop {
+        let text = match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                depth += 1;
+                if depth == 2 {
+                    field = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                    imdb_uniqueid = field == "uniqueid"
+                        && e.attributes()
+                            .flatten()
+                            .any(|a| a.key.as_ref() == b"type" && a.value.as_ref() == b"imdb");
+                }
+                continue;
+            }
+            Ok(Event::End(_)) => {
+                depth = depth.saturating_sub(1);
+                field.clear();
+                continue;
+            }
+            Ok(Event::Text(e)) => e.unescape().map(|t| t.into_owned()).unwrap_or_default(),
+            Ok(Event::CData(e)) => String::from_utf8_lossy(&e).into_owned(),
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                debug!(error = %e, path = %path.display(), "Failed to parse .nfo file");
+                return None;
+            }
+            Ok(_) => continue,
+        };
+
+        let text = text.trim().to_string();
+        if depth != 2 || text.is_empty() {
+            continue;
+        }
+
+        match field.as_str() {
+            "title" => nfo.title = Some(text),
+            "showtitle" => show_title = Some(text),
+            "year" => nfo.year = text.parse().ok().or(nfo.year),
+            "premiered" | "aired" if nfo.year.is_none() => {
+                nfo.year = text.get(..4).and_then(|y| y.parse().ok());
+            }
+            "imdbid" => nfo.imdb_id = Some(text),
+            "uniqueid" if imdb_uniqueid => nfo.imdb_id = Some(text),
+            "plot" => nfo.plot = Some(text),
+            "thumb" if nfo.thumb.is_none() => nfo.thumb = Some(text),
+            _ => {}
+        }
+    }
+
+    if show_title.is_some() {
+        nfo.title = show_title;
+    }
+    nfo.imdb_id = nfo.imdb_id.filter(|id| id.starts_with("tt"));
+
+    if nfo == NfoMetadata::default() {
+        None
+    } else {
+        Some(nfo)
+    }
+}
+
 /// TMDB match result
 #[derive(Debug, Clone)]
 pub struct TmdbMatch {
@@ -379,100 +598,86 @@ pub fn parse_filename(filename: &str) -> ParsedFilename {
     use regex::Regex;
 
     // Remove file extension
-    let name = filename
-        .rsplit('.')
-        .skip(1)
-        .collect::<Vec<_>>()
-        .into_iter()
-        .rev()
-        .collect::<Vec<_>>()
-        .join(".");
-
-    // Clean up common patterns
-    let cleaned = name
-        .replace('.', " ")
-        .replace('_', " ")
-        .replace('-', " ");
-
-    // Try to extract year (1900-2099)
-    let year_re = Regex::new(r"\b(19\d{2}|20\d{2})\b").unwrap();
-    let year = year_re
-        .find(&cleaned)
-        .and_then(|m| m.as_str().parse::<u32>().ok());
-
-    // Try to extract season/episode patterns
-    // Patterns: S01E01, s01e01, 1x01, Season 1 Episode 1
-    let se_re = Regex::new(r"(?i)[Ss](\d{1,2})[Ee](\d{1,2})").unwrap();
-    let x_re = Regex::new(r"(?i)(\d{1,2})x(\d{1,2})").unwrap();
-
-    let (season, episode) = if let Some(caps) = se_re.captures(&cleaned) {
-        (
-            caps.get(1).and_then(|m| m.as_str().parse().ok()),
-            caps.get(2).and_then(|m| m.as_str().parse().ok()),
-        )
-    } else if let Some(caps) = x_re.captures(&cleaned) {
-        (
-            caps.get(1).and_then(|m| m.as_str().parse().ok()),
-            caps.get(2).and_then(|m| m.as_str().parse().ok()),
-        )
+    let name = if is_video_file(filename) {
+        Path::new(filename)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(filename)
+            .to_string()
     } else {
-        (None, None)
+        filename.to_string()
     };
 
+    // Dots and underscores are separators. Hyphens are kept until episode
+    // ranges and release groups have been read.
+    let spaced = name.replace(['.', '_'], " ");
+
     // Extract quality/resolution hints
-    let quality_re = Regex::new(r"(?i)(2160p|1080p|720p|480p|4K|UHD)").unwrap();
+    let quality_re = Regex::new(r"(?i)\b(2160p|1080p|720p|480p|4K|UHD)\b").unwrap();
     let quality = quality_re
-        .find(&cleaned)
+        .find(&spaced)
         .map(|m| m.as_str().to_uppercase());
 
-    // Extract title (everything before year or quality indicators)
-    let mut title = cleaned.clone();
-
-    // Remove year
-    if let Some(y) = year {
-        title = title.replace(&y.to_string(), "");
-    }
-
-    // Remove season/episode
-    if let Some(caps) = se_re.captures(&title) {
-        title = title.replace(caps.get(0).unwrap().as_str(), "");
-    }
-    if let Some(caps) = x_re.captures(&title) {
-        title = title.replace(caps.get(0).unwrap().as_str(), "");
-    }
-
-    // Remove quality
-    if let Some(q) = &quality {
-        title = title.replace(q, "");
-    }
-
-    // Remove common tags/patterns
-    let tags = [
-        "BluRay",
-        "BRRip",
-        "WEBRip",
-        "HDRip",
-        "DVDRip",
-        "HDTV",
-        "WEB-DL",
-        "x264",
-        "x265",
-        "HEVC",
-        "AAC",
-        "AC3",
-        "DTS",
-        "YIFY",
-        "RARBG",
-        "Sample",
-    ];
-    for tag in &tags {
-        title = title.replace(tag, "");
-        title = title.replace(&tag.to_lowercase(), "");
+    // Fansub releases: [Group] Show - 12 [1080p], numbered from the first
+    // episode of the show rather than per season
+    let anime_re = Regex::new(
+        r"^\[([^\]]+)\]\s*(.+?)\s+-\s+(\d{1,4})(?:v\d)?(?:\s*-\s*(\d{1,4}))?(?:\s|\[|\(|$)",
+    )
+    .unwrap();
+    if let Some(caps) = anime_re.captures(&spaced) {
+        let (title, year) = clean_title(&caps[2]);
+        return ParsedFilename {
+            title: if title.is_empty() { name } else { title },
+            year,
+            season: None,
+            episode: caps[3].parse().ok(),
+            quality,
+            episode_end: caps.get(4).and_then(|m| m.as_str().parse().ok()),
+            release_group: Some(caps[1].trim().to_string()),
+        };
     }
 
-    // Clean up whitespace
-    title = title.split_whitespace().collect::<Vec<_>>().join(" ");
-    title = title.trim().to_string();
+    // Season/episode patterns, with optional ranges:
+    // S01E01, S01E01-E02, S01E01E02, S01E01-02, 1x01, 1x01-02, 1x01-1x02
+    let se_re = Regex::new(r"(?i)\bS(\d{1,2}) ?E(\d{1,3})(?:-?E(\d{1,3})|-(\d{1,3})\b)?").unwrap();
+    let x_re = Regex::new(r"(?i)\b(\d{1,2})x(\d{1,3})(?:-(?:\d{1,2}x)?(\d{1,3}))?\b").unwrap();
+
+    // Ranges of `spaced` that are not part of the title
+    let mut strip = Vec::new();
+
+    let (season, episode, episode_end) =
+        match se_re.captures(&spaced).or_else(|| x_re.captures(&spaced)) {
+            Some(caps) => {
+                let number = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<u32>().ok());
+                strip.push(caps.get(0).unwrap().range());
+                (number(1), number(2), number(3).or_else(|| number(4)))
+            }
+            None => (None, None, None),
+        };
+
+    // Release group from a leading [Group] or a scene-style trailing -GROUP
+    let leading_group_re = Regex::new(r"^\[([^\]]+)\]").unwrap();
+    let trailing_group_re = Regex::new(
+        r"(?i)(?:x26[45]|h 26[45]|hevc|xvid|web[ -]?dl|webrip|bluray|hdtv|aac|ac3|dts|\d{3,4}p)(-([a-z0-9]+))\s*$",
+    )
+    .unwrap();
+    let release_group = if let Some(caps) = leading_group_re.captures(&spaced) {
+        strip.push(caps.get(0).unwrap().range());
+        Some(caps[1].trim().to_string())
+    } else if let Some(caps) = trailing_group_re.captures(&spaced) {
+        strip.push(caps.get(1).unwrap().range());
+        Some(caps[2].to_string())
+    } else {
+        None
+    };
+
+    let mut title = spaced.clone();
+    for range in strip {
+        let blank = " ".repeat(range.len());
+        title.replace_range(range, &blank);
+    }
+    let title = quality_re.replace_all(&title, " ");
+    let (mut title, year) = clean_title(&title);
 
     // If title is empty, use original filename
     if title.is_empty() {
@@ -485,114 +690,161 @@ pub fn parse_filename(filename: &str) -> ParsedFilename {
         season,
         episode,
         quality,
+        episode_end,
+        release_group,
     }
 }
 
-/// Probe video file with FFmpeg
+/// Strip release tags, brackets and the release year from a filename title.
+/// The year is the last one in the name, so titles that start with a number
+/// like "2001 A Space Odyssey 1968" keep it.
+fn clean_title(raw: &str) -> (String, Option<u32>) {
+    use regex::Regex;
+
+    let year_re = Regex::new(r"\b(19\d{2}|20\d{2})\b").unwrap();
+    let tag_re = Regex::new(
+        r"(?i)\b(?:blu ?ray|brrip|bdrip|webrip|web[ -]?dl|hdrip|dvdrip|hdtv|x26[45]|h 26[45]|hevc|avc|xvid|divx|10 ?bit|hdr|aac(?: 2 0)?|e?ac3|ddp?5 1|dts|atmos|remux|proper|repack|yify|rarbg|sample)\b",
+    )
+    .unwrap();
+    let bracket_re = Regex::new(r"\[[^\]]*\]|\{[^}]*\}").unwrap();
+    let empty_parens_re = Regex::new(r"\(\s*\)").unwrap();
+
+    let mut title = bracket_re.replace_all(raw, " ").into_owned();
+
+    let mut year = None;
+    if let Some(m) = year_re.find_iter(&title).last() {
+        year = m.as_str().parse::<u32>().ok();
+        title.replace_range(m.range(), "");
+    }
+
+    let title = tag_re.replace_all(&title, " ");
+    let title = empty_parens_re.replace_all(&title, " ").replace('-', " ");
+
+    // Clean up whitespace
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (title, year)
+}
+
+/// Lowercase alphanumeric words of a title, so titles from filenames and
+/// metadata compare equal ("Spider-Man: Homecoming" vs "Spider Man Homecoming")
+pub fn title_key(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Probe video file with FFmpeg.
+/// ffprobe is killed if the returned future is dropped (e.g. on timeout).
 pub async fn probe_video_metadata<P: AsRef<Path>>(path: P) -> Result<VideoMetadata> {
     let path_str = path.as_ref().to_string_lossy().to_string();
 
-    tokio::task::spawn_blocking(move || {
-        use std::process::Command;
-
-        // Run ffprobe
-        let output = Command::new("ffprobe")
-            .args([
-                "-v",
-                "quiet",
-                "-print_format",
-                "json",
-                "-show_format",
-                "-show_streams",
-                &path_str,
-            ])
-            .output()
-            .map_err(|e| anyhow!("Failed to run ffprobe: {}. Is FFmpeg installed?", e))?;
-
-        if !output.status.success() {
-            return Err(anyhow!("ffprobe failed"));
-        }
+    // Run ffprobe
+    let output = tokio::process::Command::new("ffprobe")
+        .args([
+            "-v",
+            "quiet",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_streams",
+            &path_str,
+        ])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run ffprobe: {}. Is FFmpeg installed?", e))?;
+
+    if !output.status.success() {
+        return Err(anyhow!("ffprobe failed"));
+    }
 
-        let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
-
-        // Extract format info
-        let format = json.get("format");
-        let duration = format
-            .and_then(|f| f.get("duration"))
-            .and_then(|d| d.as_str())
-            .and_then(|s| s.parse::<f64>().ok());
-
-        let bitrate = format
-            .and_then(|f| f.get("bit_rate"))
-            .and_then(|b| b.as_str())
-            .and_then(|s| s.parse::<u64>().ok());
-
-        // Extract video stream info
-        let streams = json.get("streams").and_then(|s| s.as_array());
-        let video_stream = streams
-            .and_then(|arr| {
-                arr.iter()
-                    .find(|s| s.get("codec_type").and_then(|t| t.as_str()) == Some("video"))
-            });
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
 
-        let width = video_stream
-            .and_then(|s| s.get("width"))
-            .and_then(|w| w.as_u64())
-            .map(|w| w as u32);
-
-        let height = video_stream
-            .and_then(|s| s.get("height"))
-            .and_then(|h| h.as_u64())
-            .map(|h| h as u32);
-
-        let video_codec = video_stream
-            .and_then(|s| s.get("codec_name"))
-            .and_then(|c| c.as_str())
-            .map(String::from);
-
-        let fps = video_stream
-            .and_then(|s| s.get("r_frame_rate"))
-            .and_then(|f| f.as_str())
-            .and_then(|s| {
-                let parts: Vec<&str> = s.split('/').collect();
-                if parts.len() == 2 {
-                    let num = parts[0].parse::<f64>().ok()?;
-                    let den = parts[1].parse::<f64>().ok()?;
-                    Some(num / den)
-                } else {
-                    None
-                }
-            });
+    // Extract format info
+    let format = json.get("format");
+    let duration = format
+        .and_then(|f| f.get("duration"))
+        .and_then(|d| d.as_str())
+        .and_then(|s| s.parse::<f64>().ok());
 
-        // Extract audio stream info
-        let audio_stream = streams.and_then(|arr| {
+    let bitrate = format
+        .and_then(|f| f.get("bit_rate"))
+        .and_then(|b| b.as_str())
+        .and_then(|s| s.parse::<u64>().ok());
+
+    // Extract video stream info
+    let streams = json.get("streams").and_then(|s| s.as_array());
+    let video_stream = streams
+        .and_then(|arr| {
             arr.iter()
-                .find(|s| s.get("codec_type").and_then(|t| t.as_str()) == Some("audio"))
+                .find(|s| s.get("codec_type").and_then(|t| t.as_str()) == Some("video"))
         });
 
-        let audio_codec = audio_stream
-            .and_then(|s| s.get("codec_name"))
-            .and_then(|c| c.as_str())
-            .map(String::from);
-
-        Ok(VideoMetadata {
-            duration,
-            width,
-            height,
-            video_codec,
-            audio_codec,
-            bitrate,
-            fps,
-        })
+    let width = video_stream
+        .and_then(|s| s.get("width"))
+        .and_then(|w| w.as_u64())
+        .map(|w| w as u32);
+
+    let height = video_stream
+        .and_then(|s| s.get("height"))
+        .and_then(|h| h.as_u64())
+        .map(|h| h as u32);
+
+    let video_codec = video_stream
+        .and_then(|s| s.get("codec_name"))
+        .and_then(|c| c.as_str())
+        .map(String::from);
+
+    let fps = video_stream
+        .and_then(|s| s.get("r_frame_rate"))
+        .and_then(|f| f.as_str())
+        .and_then(|s| {
+            let parts: Vec<&str> = s.split('/').collect();
+            if parts.len() == 2 {
+                let num = parts[0].parse::<f64>().ok()?;
+                let den = parts[1].parse::<f64>().ok()?;
+                Some(num / den)
+            } else {
+                None
+            }
+        });
+
+    // Extract audio stream info
+    let audio_stream = streams.and_then(|arr| {
+        arr.iter()
+            .find(|s| s.get("codec_type").and_then(|t| t.as_str()) == Some("audio"))
+    });
+
+    let audio_codec = audio_stream
+        .and_then(|s| s.get("codec_name"))
+        .and_then(|c| c.as_str())
+        .map(String::from);
+
+    Ok(VideoMetadata {
+        duration,
+        width,
+        height,
+        video_codec,
+        audio_codec,
+        bitrate,
+        fps,
     })
-    .await
-    .map_err(|e| anyhow!("Task join error: {}", e))?
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_title_key() {
+        assert_eq!(title_key("Spider-Man: Homecoming"), "spider man homecoming");
+        assert_eq!(title_key("Spider Man  Homecoming"), "spider man homecoming");
+        assert_eq!(title_key("Amélie"), "amélie");
+    }
+
     #[test]
     fn test_is_video_file() {
         assert!(is_video_file("movie.mp4"));
@@ -626,4 +878,296 @@ mod tests {
         assert_eq!(parsed.season, Some(1));
         assert_eq!(parsed.episode, Some(1));
     }
+
+    #[test]
+    fn test_parse_filename_release_names() {
+        // (filename, title, year, season, episode, episode_end, release group)
+        let cases: &[(
+            &str,
+            &str,
+            Option<u32>,
+            Option<u32>,
+            Option<u32>,
+            Option<u32>,
+            Option<&str>,
+        )] = &[
+            (
+                "The.Office.US.S02E01-E02.720p.WEB-DL.mkv",
+                "The Office US",
+                None,
+                Some(2),
+                Some(1),
+                Some(2),
+                None,
+            ),
+            (
+                "Friends.S01E16E17.DVDRip.XviD.avi",
+                "Friends",
+                None,
+                Some(1),
+                Some(16),
+                Some(17),
+                None,
+            ),
+            (
+                "Doctor.Who.2005.S04E12-13.HDTV.x264-FoV.mkv",
+                "Doctor Who",
+                Some(2005),
+                Some(4),
+                Some(12),
+                Some(13),
+                Some("FoV"),
+            ),
+            (
+                "Lost.1x05.White.Rabbit.mkv",
+                "Lost White Rabbit",
+                None,
+                Some(1),
+                Some(5),
+                None,
+                None,
+            ),
+            (
+                "Futurama_3x01-3x02_DVDRip.avi",
+                "Futurama",
+                None,
+                Some(3),
+                Some(1),
+                Some(2),
+                None,
+            ),
+            (
+                "[SubsPlease] Jujutsu Kaisen - 24 (1080p) [ABCD1234].mkv",
+                "Jujutsu Kaisen",
+                None,
+                None,
+                Some(24),
+                None,
+                Some("SubsPlease"),
+            ),
+            (
+                "[Erai-raws] Dr. Stone - 05v2 [720p][Multiple Subtitle].mkv",
+                "Dr Stone",
+                None,
+                None,
+                Some(5),
+                None,
+                Some("Erai-raws"),
+            ),
+            (
+                "[Judas] Cowboy Bebop - 01-26 [BD 1080p][HEVC x265 10bit].mkv",
+                "Cowboy Bebop",
+                None,
+                None,
+                Some(1),
+                Some(26),
+                Some("Judas"),
+            ),
+            (
+                "[HorribleSubs] One Punch Man S2 - 03 [1080p].mkv",
+                "One Punch Man S2",
+                None,
+                None,
+                Some(3),
+                None,
+                Some("HorribleSubs"),
+            ),
+            (
+                "2001.A.Space.Odyssey.1968.2160p.UHD.BluRay.REMUX.HDR.HEVC.Atmos.mkv",
+                "2001 A Space Odyssey",
+                Some(1968),
+                None,
+                None,
+                None,
+                None,
+            ),
+            (
+                "Inception (2010) [1080p] [YTS.MX].mp4",
+                "Inception",
+                Some(2010),
+                None,
+                None,
+                None,
+                None,
+            ),
+            (
+                "Parasite.2019.KOREAN.1080p.BluRay.H.264.AAC-RARBG.mp4",
+                "Parasite KOREAN",
+                Some(2019),
+                None,
+                None,
+                None,
+                Some("RARBG"),
+            ),
+        ];
+
+        for &(filename, title, year, season, episode, episode_end, group) in cases {
+            let parsed = parse_filename(filename);
+            assert_eq!(parsed.title, title, "title of {}", filename);
+            assert_eq!(parsed.year, year, "year of {}", filename);
+            assert_eq!(parsed.season, season, "season of {}", filename);
+            assert_eq!(parsed.episode, episode, "episode of {}", filename);
+            assert_eq!(
+                parsed.episode_end, episode_end,
+                "episode_end of {}",
+                filename
+            );
+            assert_eq!(
+                parsed.release_group.as_deref(),
+                group,
+                "group of {}",
+                filename
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_timeout_keeps_file_without_metadata() {
+        let path = std::env::temp_dir().join("streamgo_probe_timeout.The.Matrix.1999.1080p.mkv");
+        std::fs::write(&path, b"not really a video").unwrap();
+
+        let scanner = LocalMediaScanner::new(vec![]).with_probe_timeout(Duration::from_millis(100));
+        let hung_probe = std::future::pending::<Result<VideoMetadata>>();
+
+        let file = tokio::time::timeout(
+            Duration::from_secs(5),
+            scanner.process_video_file_with_probe(&path, false, hung_probe),
+        )
+        .await
+        .expect("scan should not hang on a stuck probe")
+        .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(file.year, Some(1999));
+        assert_eq!(file.duration, None);
+        assert_eq!(file.resolution, None);
+    }
+
+    #[tokio::test]
+    async fn test_zero_probe_timeout_disables_timeout() {
+        let path = std::env::temp_dir().join("streamgo_probe_no_timeout.The.Matrix.1999.1080p.mkv");
+        std::fs::write(&path, b"not really a video").unwrap();
+
+        let scanner = LocalMediaScanner::new(vec![]).with_probe_timeout(Duration::ZERO);
+        let slow_probe = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(VideoMetadata {
+                duration: Some(8160.0),
+                width: Some(1920),
+                height: Some(1080),
+                video_codec: Some("h264".to_string()),
+                audio_codec: None,
+                bitrate: None,
+                fps: None,
+            })
+        };
+
+        let file = scanner
+            .process_video_file_with_probe(&path, false, slow_probe)
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(file.duration, Some(8160.0));
+        assert_eq!(file.resolution.as_deref(), Some("1920x1080"));
+    }
+
+    fn write_temp_nfo(name: &str, xml: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, xml).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_nfo_movie() {
+        let path = write_temp_nfo(
+            "streamgo_movie.nfo",
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes" ?>
+<movie>
+    <title>The Matrix</title>
+    <year>1999</year>
+    <plot><![CDATA[A hacker learns the truth about his reality.]]></plot>
+    <thumb aspect="poster">https://example.com/matrix-poster.jpg</thumb>
+    <thumb aspect="landscape">https://example.com/matrix-fanart.jpg</thumb>
+    <uniqueid type="tmdb">603</uniqueid>
+    <uniqueid type="imdb" default="true">tt0133093</uniqueid>
+    <actor>
+        <name>Keanu Reeves</name>
+        <thumb>https://example.com/keanu.jpg</thumb>
+    </actor>
+</movie>"#,
+        );
+
+        let nfo = parse_nfo(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(
+            nfo,
+            NfoMetadata {
+                title: Some("The Matrix".to_string()),
+                year: Some(1999),
+                imdb_id: Some("tt0133093".to_string()),
+                plot: Some("A hacker learns the truth about his reality.".to_string()),
+                thumb: Some("https://example.com/matrix-poster.jpg".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_nfo_episode() {
+        let path = write_temp_nfo(
+            "streamgo_episode.nfo",
+            r#"<episodedetails>
+    <title>Pilot</title>
+    <showtitle>Breaking Bad</showtitle>
+    <season>1</season>
+    <episode>1</episode>
+    <aired>2008-01-20</aired>
+    <plot>A chemistry teacher turns to crime.</plot>
+    <imdbid>tt0959621</imdbid>
+</episodedetails>"#,
+        );
+
+        let nfo = parse_nfo(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(nfo.title.as_deref(), Some("Breaking Bad"));
+        assert_eq!(nfo.year, Some(2008));
+        assert_eq!(nfo.imdb_id.as_deref(), Some("tt0959621"));
+        assert_eq!(
+            nfo.plot.as_deref(),
+            Some("A chemistry teacher turns to crime.")
+        );
+        assert_eq!(nfo.thumb, None);
+
+        let missing = std::env::temp_dir().join("streamgo_missing.nfo");
+        assert_eq!(parse_nfo(&missing), None);
+    }
+
+    #[tokio::test]
+    async fn test_sibling_nfo_overrides_filename() {
+        let dir = std::env::temp_dir().join("streamgo_nfo_scan");
+        std::fs::create_dir_all(&dir).unwrap();
+        let video = dir.join("matrix.1080p.mkv");
+        std::fs::write(&video, b"not really a video").unwrap();
+        std::fs::write(
+            dir.join("matrix.1080p.nfo"),
+            "<movie><title>The Matrix</title><year>1999</year><imdbid>tt0133093</imdbid></movie>",
+        )
+        .unwrap();
+
+        // TMDB is enabled, but the .nfo's IMDB id means it is never called
+        let scanner = LocalMediaScanner::new(vec![]);
+        let no_probe = async { Err(anyhow!("no ffprobe in tests")) };
+        let file = scanner
+            .process_video_file_with_probe(&video, true, no_probe)
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(file.title, "The Matrix");
+        assert_eq!(file.year, Some(1999));
+        assert_eq!(file.imdb_id.as_deref(), Some("tt0133093"));
+        assert_eq!(file.tmdb_id, None);
+    }
 }
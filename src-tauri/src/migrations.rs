@@ -7,7 +7,7 @@ use anyhow::{anyhow, Result};
 use rusqlite::Connection;
 
 /// Current schema version
-pub const CURRENT_SCHEMA_VERSION: u32 = 10;
+pub const CURRENT_SCHEMA_VERSION: u32 = 22;
 
 /// Migration trait for implementing version upgrades
 pub trait Migration {
@@ -809,6 +809,335 @@ impl Migration for Migration010RatingsAndSkips {
     }
 }
 
+/// Migration v11: Track when each addon last contributed results
+struct Migration011AddonUsage;
+
+impl Migration for Migration011AddonUsage {
+    fn version(&self) -> u32 {
+        11
+    }
+
+    fn description(&self) -> &str {
+        "Add addon usage table for last-used tracking"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS addon_usage (
+                addon_id TEXT PRIMARY KEY,
+                last_used INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Migration v12: Keep the plot read from local .nfo files
+struct Migration012LocalMediaPlot;
+
+impl Migration for Migration012LocalMediaPlot {
+    fn version(&self) -> u32 {
+        12
+    }
+
+    fn description(&self) -> &str {
+        "Add plot column to local media files"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute("ALTER TABLE local_media_files ADD COLUMN plot TEXT", [])?;
+
+        Ok(())
+    }
+}
+
+/// Migration v13: Key-value store for app state that isn't a preference
+struct Migration013AppSettings;
+
+impl Migration for Migration013AppSettings {
+    fn version(&self) -> u32 {
+        13
+    }
+
+    fn description(&self) -> &str {
+        "Add app settings key-value table"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS app_settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Migration v14: Keep playlist item counts in sync when items are removed
+/// by a cascade (e.g. the media item was deleted), not just explicitly
+struct Migration014PlaylistItemCount;
+
+impl Migration for Migration014PlaylistItemCount {
+    fn version(&self) -> u32 {
+        14
+    }
+
+    fn description(&self) -> &str {
+        "Recompute playlist item counts on playlist item deletion"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TRIGGER IF NOT EXISTS playlist_items_count_delete AFTER DELETE ON playlist_items BEGIN
+                UPDATE playlists
+                SET item_count = (SELECT COUNT(*) FROM playlist_items WHERE playlist_id = OLD.playlist_id)
+                WHERE id = OLD.playlist_id;
+             END",
+            [],
+        )?;
+
+        // Fix counts left stale by earlier media deletions
+        conn.execute(
+            "UPDATE playlists
+             SET item_count = (SELECT COUNT(*) FROM playlist_items WHERE playlist_id = playlists.id)",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Migration v15: Reminders for upcoming live TV programs
+struct Migration015ProgramReminders;
+
+impl Migration for Migration015ProgramReminders {
+    fn version(&self) -> u32 {
+        15
+    }
+
+    fn description(&self) -> &str {
+        "Add live TV program reminders table"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS program_reminders (
+                program_id INTEGER PRIMARY KEY,
+                channel_id TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                notified INTEGER NOT NULL DEFAULT 0,
+                FOREIGN KEY (program_id) REFERENCES epg_programs(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Migration v16: Favorite live TV channels
+struct Migration016FavoriteChannels;
+
+impl Migration for Migration016FavoriteChannels {
+    fn version(&self) -> u32 {
+        16
+    }
+
+    fn description(&self) -> &str {
+        "Add favorite live TV channels table"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        // No foreign key: playlist imports replace channel rows, and
+        // favorites should survive a refresh of the same playlist
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS favorite_channels (
+                user_id TEXT NOT NULL,
+                channel_id TEXT NOT NULL,
+                added_at TEXT NOT NULL,
+                PRIMARY KEY (user_id, channel_id)
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Migration v17: Watch history events
+struct Migration017WatchHistory;
+
+impl Migration for Migration017WatchHistory {
+    fn version(&self) -> u32 {
+        17
+    }
+
+    fn description(&self) -> &str {
+        "Add watch history table"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS watch_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                media_id TEXT NOT NULL,
+                watched_at INTEGER NOT NULL,
+                position INTEGER NOT NULL,
+                duration INTEGER,
+                FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_watch_history_media ON watch_history(media_id, watched_at)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_watch_history_watched_at ON watch_history(watched_at DESC)",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Migration v18: Last stream check result per live TV channel
+struct Migration018ChannelStatus;
+
+impl Migration for Migration018ChannelStatus {
+    fn version(&self) -> u32 {
+        18
+    }
+
+    fn description(&self) -> &str {
+        "Add stream status columns to live TV channels"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "ALTER TABLE live_tv_channels ADD COLUMN stream_online INTEGER",
+            [],
+        )?;
+        conn.execute(
+            "ALTER TABLE live_tv_channels ADD COLUMN stream_checked_at INTEGER",
+            [],
+        )?;
+        conn.execute(
+            "ALTER TABLE live_tv_channels ADD COLUMN stream_error TEXT",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Migration v19: One EPG entry per channel and start time, so re-imports
+/// update programs in place
+struct Migration019UniqueEpgPrograms;
+
+impl Migration for Migration019UniqueEpgPrograms {
+    fn version(&self) -> u32 {
+        19
+    }
+
+    fn description(&self) -> &str {
+        "Deduplicate EPG programs and make channel/start unique"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        // Keep the oldest row of each duplicate so existing reminders stay valid
+        conn.execute(
+            "DELETE FROM epg_programs WHERE id NOT IN (
+                SELECT MIN(id) FROM epg_programs GROUP BY channel_id, start
+            )",
+            [],
+        )?;
+        conn.execute("DROP INDEX IF EXISTS idx_epg_channel_time", [])?;
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_epg_channel_start
+             ON epg_programs(channel_id, start)",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Migration v20: Logo and thumbnail artwork for library items
+struct Migration020MediaArtwork;
+
+impl Migration for Migration020MediaArtwork {
+    fn version(&self) -> u32 {
+        20
+    }
+
+    fn description(&self) -> &str {
+        "Add logo and thumbnail columns to media items"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute("ALTER TABLE media_items ADD COLUMN logo_url TEXT", [])?;
+        conn.execute("ALTER TABLE media_items ADD COLUMN thumb_url TEXT", [])?;
+
+        Ok(())
+    }
+}
+
+/// Migration v21: Entries strict validation dropped from each addon response
+struct Migration021AddonInvalidCount;
+
+impl Migration for Migration021AddonInvalidCount {
+    fn version(&self) -> u32 {
+        21
+    }
+
+    fn description(&self) -> &str {
+        "Track invalid entries in addon health"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "ALTER TABLE addon_health ADD COLUMN invalid_count INTEGER NOT NULL DEFAULT 0",
+            [],
+        )?;
+        conn.execute(
+            "ALTER TABLE addon_health_summary ADD COLUMN invalid_count INTEGER NOT NULL DEFAULT 0",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Migration v22: When TMDB was last asked for a media item's artwork
+struct Migration022ArtworkCheckedAt;
+
+impl Migration for Migration022ArtworkCheckedAt {
+    fn version(&self) -> u32 {
+        22
+    }
+
+    fn description(&self) -> &str {
+        "Track artwork lookups on media items"
+    }
+
+    fn up(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            "ALTER TABLE media_items ADD COLUMN artwork_checked_at INTEGER",
+            [],
+        )?;
+
+        Ok(())
+    }
+}
+
 /// Migration runner
 pub struct MigrationRunner {
     migrations: Vec<Box<dyn Migration>>,
@@ -827,6 +1156,18 @@ impl MigrationRunner {
             Box::new(Migration008LocalMedia),
             Box::new(Migration009LiveTv),
             Box::new(Migration010RatingsAndSkips),
+            Box::new(Migration011AddonUsage),
+            Box::new(Migration012LocalMediaPlot),
+            Box::new(Migration013AppSettings),
+            Box::new(Migration014PlaylistItemCount),
+            Box::new(Migration015ProgramReminders),
+            Box::new(Migration016FavoriteChannels),
+            Box::new(Migration017WatchHistory),
+            Box::new(Migration018ChannelStatus),
+            Box::new(Migration019UniqueEpgPrograms),
+            Box::new(Migration020MediaArtwork),
+            Box::new(Migration021AddonInvalidCount),
+            Box::new(Migration022ArtworkCheckedAt),
         ];
         Self { migrations }
     }
@@ -1,3 +1,4 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,11 +11,20 @@ pub struct MediaItem {
     pub description: Option<String>,
     pub poster_url: Option<String>,
     pub backdrop_url: Option<String>,
+    /// Transparent title logo, shown over the backdrop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_url: Option<String>,
+    /// Small landscape still for rows and cards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumb_url: Option<String>,
     pub rating: Option<f32>,
     pub duration: Option<i32>, // in minutes
     pub added_to_library: Option<chrono::DateTime<chrono::Utc>>,
     pub watched: bool,
     pub progress: Option<i32>, // in seconds
+    /// Watched share of `duration` (0-100), filled in when loaded from the library
+    #[serde(default)]
+    pub progress_percent: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +37,226 @@ pub enum MediaType {
     Podcast,
 }
 
+// Sane bounds for library item validation
+const MIN_MEDIA_YEAR: i32 = 1870;
+const MAX_MEDIA_YEAR: i32 = 2100;
+const MAX_RATING: f32 = 10.0;
+
+impl MediaItem {
+    /// Start building an item with the required fields; everything else defaults to empty
+    pub fn builder(
+        id: impl Into<String>,
+        title: impl Into<String>,
+        media_type: MediaType,
+    ) -> MediaItemBuilder {
+        MediaItemBuilder::new(id, title, media_type)
+    }
+
+    /// Check the item before it is stored in the library
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.id.trim().is_empty() {
+            anyhow::bail!("Media item id is required");
+        }
+        if self.title.trim().is_empty() {
+            anyhow::bail!("Media item title is required");
+        }
+        if let Some(year) = self.year {
+            if !(MIN_MEDIA_YEAR..=MAX_MEDIA_YEAR).contains(&year) {
+                anyhow::bail!(
+                    "Year {} is out of range ({}-{})",
+                    year,
+                    MIN_MEDIA_YEAR,
+                    MAX_MEDIA_YEAR
+                );
+            }
+        }
+        if let Some(rating) = self.rating {
+            if !(0.0..=MAX_RATING).contains(&rating) {
+                anyhow::bail!("Rating {} is out of range (0-{})", rating, MAX_RATING);
+            }
+        }
+        if let Some(progress) = self.progress {
+            if progress < 0 {
+                anyhow::bail!("Progress cannot be negative: {}", progress);
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill in `progress_percent` from `progress` (seconds) and `duration` (minutes)
+    pub fn with_progress_percent(mut self) -> Self {
+        self.progress_percent = match (self.progress, self.duration) {
+            (Some(progress), Some(duration)) if duration > 0 => {
+                let percent = progress.max(0) as f32 / (duration as f32 * 60.0) * 100.0;
+                Some(percent.min(100.0))
+            }
+            _ => None,
+        };
+        self
+    }
+
+    /// The item's current artwork URLs
+    pub fn artwork(&self) -> Artwork {
+        Artwork {
+            poster_url: self.poster_url.clone(),
+            backdrop_url: self.backdrop_url.clone(),
+            logo_url: self.logo_url.clone(),
+            thumb_url: self.thumb_url.clone(),
+        }
+    }
+
+    /// Take artwork from `found` for every slot that is still empty.
+    /// Returns whether anything changed.
+    pub fn fill_missing_artwork(&mut self, found: Artwork) -> bool {
+        fn fill(slot: &mut Option<String>, value: Option<String>) -> bool {
+            let empty = slot.as_deref().map_or(true, str::is_empty);
+            match value {
+                Some(value) if empty && !value.is_empty() => {
+                    *slot = Some(value);
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        // Not short-circuiting: every slot gets a chance to fill
+        fill(&mut self.poster_url, found.poster_url)
+            | fill(&mut self.backdrop_url, found.backdrop_url)
+            | fill(&mut self.logo_url, found.logo_url)
+            | fill(&mut self.thumb_url, found.thumb_url)
+    }
+}
+
+/// Artwork URLs for a media item, e.g. as found on TMDB or in addon metadata
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Artwork {
+    pub poster_url: Option<String>,
+    pub backdrop_url: Option<String>,
+    pub logo_url: Option<String>,
+    pub thumb_url: Option<String>,
+}
+
+/// Builder for [`MediaItem`] so callers don't have to spell out every optional field
+#[derive(Debug, Clone)]
+pub struct MediaItemBuilder {
+    item: MediaItem,
+}
+
+impl MediaItemBuilder {
+    pub fn new(id: impl Into<String>, title: impl Into<String>, media_type: MediaType) -> Self {
+        Self {
+            item: MediaItem {
+                id: id.into(),
+                title: title.into(),
+                media_type,
+                year: None,
+                genre: Vec::new(),
+                description: None,
+                poster_url: None,
+                backdrop_url: None,
+                logo_url: None,
+                thumb_url: None,
+                rating: None,
+                duration: None,
+                added_to_library: None,
+                watched: false,
+                progress: None,
+                progress_percent: None,
+            },
+        }
+    }
+
+    pub fn year(mut self, year: i32) -> Self {
+        self.item.year = Some(year);
+        self
+    }
+
+    pub fn genre(mut self, genre: Vec<String>) -> Self {
+        self.item.genre = genre;
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.item.description = Some(description.into());
+        self
+    }
+
+    pub fn poster_url(mut self, poster_url: impl Into<String>) -> Self {
+        self.item.poster_url = Some(poster_url.into());
+        self
+    }
+
+    pub fn backdrop_url(mut self, backdrop_url: impl Into<String>) -> Self {
+        self.item.backdrop_url = Some(backdrop_url.into());
+        self
+    }
+
+    pub fn logo_url(mut self, logo_url: impl Into<String>) -> Self {
+        self.item.logo_url = Some(logo_url.into());
+        self
+    }
+
+    pub fn thumb_url(mut self, thumb_url: impl Into<String>) -> Self {
+        self.item.thumb_url = Some(thumb_url.into());
+        self
+    }
+
+    pub fn rating(mut self, rating: f32) -> Self {
+        self.item.rating = Some(rating);
+        self
+    }
+
+    /// Duration in minutes
+    pub fn duration(mut self, duration: i32) -> Self {
+        self.item.duration = Some(duration);
+        self
+    }
+
+    pub fn added_to_library(mut self, added: chrono::DateTime<chrono::Utc>) -> Self {
+        self.item.added_to_library = Some(added);
+        self
+    }
+
+    pub fn watched(mut self, watched: bool) -> Self {
+        self.item.watched = watched;
+        self
+    }
+
+    /// Playback progress in seconds
+    pub fn progress(mut self, progress: i32) -> Self {
+        self.item.progress = Some(progress);
+        self
+    }
+
+    /// Validate and return the item
+    pub fn build(self) -> anyhow::Result<MediaItem> {
+        self.item.validate()?;
+        Ok(self.item)
+    }
+}
+
+/// A single episode of a series in the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Episode {
+    pub id: String,
+    pub series_id: String,
+    pub season: i32,
+    pub episode: i32,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overview: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub released: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<String>,
+    #[serde(default)]
+    pub watched: bool,
+    #[serde(default)]
+    pub progress: i32, // in seconds
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamSource {
     pub url: String,
@@ -48,6 +278,52 @@ pub struct Addon {
     pub manifest: AddonManifest,
     #[serde(default = "default_priority")]
     pub priority: i32, // Higher number = higher priority
+    /// User configuration from the addon_config table (not written by `save_addon`).
+    /// Never serialized, since it may hold API keys or debrid tokens; the
+    /// frontend reads it through `get_addon_config` instead.
+    #[serde(default, skip_serializing)]
+    pub config: std::collections::HashMap<String, String>,
+}
+
+/// Path-segment percent-encode set, plus the `=` and `|` config separators
+const CONFIG_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'=')
+    .add(b'|');
+
+impl Addon {
+    /// Root URL for addon requests (without `/manifest.json`). Any user
+    /// configuration is inserted as a Stremio-style `key=value|key=value`
+    /// path segment, sorted by key so the URL is stable.
+    pub fn base_url(&self) -> String {
+        let base = self
+            .url
+            .trim_end_matches("manifest.json")
+            .trim_end_matches('/');
+        if self.config.is_empty() {
+            return base.to_string();
+        }
+
+        let encode = |s: &str| utf8_percent_encode(s, CONFIG_SEGMENT).to_string();
+        let mut entries: Vec<_> = self.config.iter().collect();
+        entries.sort();
+        let segment = entries
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+            .collect::<Vec<_>>()
+            .join("|");
+        format!("{}/{}", base, segment)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +334,24 @@ pub enum AddonType {
     Player,
 }
 
+/// Outcome of installing an addon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddonInstallResult {
+    pub addon_id: String,
+    pub installed: bool,
+    /// Set when the addon id is already installed from another URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<AddonConflict>,
+}
+
+/// The same manifest id served from two different URLs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddonConflict {
+    pub addon_id: String,
+    pub existing_url: String,
+    pub new_url: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddonManifest {
     pub id: String,
@@ -99,6 +393,107 @@ pub struct StreamWithSource {
     pub description: Option<String>,
     pub addon_id: String,
     pub addon_name: String,
+    #[serde(default)]
+    pub source_type: StreamSourceType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seeders: Option<u32>,
+    /// Addon flagged the stream as needing transcoding or an external player
+    #[serde(default)]
+    pub not_web_ready: bool,
+    /// Subtitles the addon shipped with this particular stream
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subtitles: Vec<crate::addon_protocol::Subtitle>,
+}
+
+/// Where a stream is actually served from, so the UI can badge streams
+/// and warn before starting a torrent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamSourceType {
+    /// Plain HTTP(S) file
+    Direct,
+    /// HLS playlist (.m3u8)
+    Hls,
+    /// Magnet link, .torrent file or peer-to-peer addon stream
+    Torrent,
+    /// Link unrestricted through a debrid service
+    Debrid,
+    #[default]
+    Unknown,
+}
+
+// Hosts that serve unrestricted debrid downloads
+const DEBRID_HOSTS: &[&str] = &[
+    "real-debrid.com",
+    "alldebrid.com",
+    "premiumize.me",
+    "debrid-link.com",
+    "debrid-link.fr",
+    "torbox.app",
+];
+
+impl StreamSourceType {
+    /// Classify a stream from its URL and the addon's p2p behavior hint
+    pub fn classify(url: &str, p2p: bool) -> Self {
+        let url = url.trim();
+        if url.starts_with("magnet:") {
+            return StreamSourceType::Torrent;
+        }
+
+        let parsed = match url::Url::parse(url) {
+            Ok(parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => parsed,
+            _ => return StreamSourceType::Unknown,
+        };
+
+        let path = parsed.path().to_lowercase();
+        if p2p || path.ends_with(".torrent") {
+            return StreamSourceType::Torrent;
+        }
+
+        let host = parsed.host_str().unwrap_or_default().to_lowercase();
+        if DEBRID_HOSTS
+            .iter()
+            .any(|d| host == *d || host.ends_with(&format!(".{}", d)))
+        {
+            return StreamSourceType::Debrid;
+        }
+
+        if path.ends_with(".m3u8") {
+            return StreamSourceType::Hls;
+        }
+
+        StreamSourceType::Direct
+    }
+}
+
+/// A ready-to-play source for a media id, from `resolve_playable`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayableResult {
+    /// URL to hand to the player; local copies are served by the streaming server
+    pub url: String,
+    /// Addon stream the URL came from; `None` for local files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<StreamWithSource>,
+    #[serde(default)]
+    pub subtitles: Vec<crate::addon_protocol::Subtitle>,
+    pub is_local: bool,
+    pub resolved_via: ResolvedVia,
+}
+
+/// How a playable URL was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolvedVia {
+    /// Scanned file in the local library
+    LocalFile,
+    /// Addon stream URL used as is
+    Addon,
+    /// Magnet link unrestricted through a debrid service
+    Debrid,
+    /// Magnet link served by the local torrent streaming server
+    Torrent,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,6 +510,14 @@ pub struct UserPreferences {
     // Integrations / API keys
     #[serde(default)]
     pub tmdb_api_key: Option<String>,
+    #[serde(default)]
+    pub real_debrid_api_key: Option<String>,
+    /// Whether an OpenSubtitles login token is stored in the OS keyring
+    #[serde(default)]
+    pub opensubtitles_logged_in: bool,
+    /// Plaintext token saved by older versions; moved to the keyring on first use
+    #[serde(default, rename = "opensubtitles_token", skip_serializing)]
+    pub legacy_opensubtitles_token: Option<String>,
 
     // Video Settings
     #[serde(default = "default_quality")]
@@ -165,23 +568,66 @@ pub struct UserPreferences {
     pub preload_next: bool,
     #[serde(default = "default_torrent_connections")]
     pub torrent_connections: String,
+    /// Size budget in MB for cached metadata and addon responses
     #[serde(default = "default_cache_size")]
     pub cache_size: String,
+    /// Rank direct, cached and debrid streams above peer-to-peer ones
+    #[serde(default = "default_bool_false")]
+    pub prefer_non_p2p: bool,
+    /// Torrents downloading at once; more wait in line for a free slot
+    #[serde(default = "default_max_active_torrents")]
+    pub max_active_torrents: u32,
+    /// Catalog entries kept per addon response (10 to 10,000)
+    #[serde(default = "default_max_catalog_items")]
+    pub max_catalog_items: u32,
+    /// Largest addon response accepted, in MB (1 to 100)
+    #[serde(default = "default_max_addon_response_mb")]
+    pub max_addon_response_mb: u32,
+
+    // Local media
+    /// Files probed and matched against TMDB at the same time while scanning
+    #[serde(default = "default_scan_concurrency")]
+    pub scan_concurrency: u32,
+    /// Give up on ffprobe for a file after this long; the file is still added.
+    /// Zero means no timeout.
+    #[serde(default = "default_probe_timeout_secs")]
+    pub probe_timeout_secs: u64,
+    /// Play a scanned local copy instead of addon streams when one matches
+    #[serde(default = "default_bool_true")]
+    pub prefer_local: bool,
 
     // Advanced
     #[serde(default = "default_player_engine")]
     pub player_engine: String,
     #[serde(default = "default_bool_false")]
     pub debug_logging: bool,
+    /// Drop addon catalog items and streams that are missing required fields
+    #[serde(default = "default_bool_false")]
+    pub strict_addon_validation: bool,
+    /// Accept addon stream and subtitle URLs that point at the local network
+    #[serde(default = "default_bool_false")]
+    pub allow_private_addon_urls: bool,
+    /// List catalog items that have a poster or background before posterless ones
+    #[serde(default = "default_bool_false")]
+    pub catalog_artwork_first: bool,
     #[serde(default = "default_bool_false")]
     pub analytics: bool,
 
     // General
     #[serde(default = "default_true")]
     pub notifications_enabled: bool,
+    /// Minutes between background checks for new episodes
+    #[serde(default = "default_episode_check_interval_minutes")]
+    pub episode_check_interval_minutes: u32,
     #[serde(default = "default_true")]
     pub auto_update: bool,
-    
+    /// Install the built-in addons on first run (user opt-in)
+    #[serde(default = "default_true")]
+    pub install_builtin_addons: bool,
+    /// Set once built-in seeding has happened; never reseed after that
+    #[serde(default)]
+    pub builtins_seeded: bool,
+
     // Notification tracking
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_notification_check: Option<String>, // RFC3339 timestamp
@@ -252,6 +698,24 @@ fn default_cache_size() -> String {
 fn default_player_engine() -> String {
     "auto".to_string()
 }
+fn default_episode_check_interval_minutes() -> u32 {
+    60
+}
+fn default_max_active_torrents() -> u32 {
+    3
+}
+fn default_max_catalog_items() -> u32 {
+    1000
+}
+fn default_max_addon_response_mb() -> u32 {
+    10
+}
+fn default_scan_concurrency() -> u32 {
+    4
+}
+fn default_probe_timeout_secs() -> u64 {
+    30
+}
 
 impl Default for UserPreferences {
     fn default() -> Self {
@@ -261,6 +725,9 @@ impl Default for UserPreferences {
             theme: default_theme(),
             language: default_language(),
             tmdb_api_key: None,
+            real_debrid_api_key: None,
+            opensubtitles_logged_in: false,
+            legacy_opensubtitles_token: None,
             // Video
             quality: default_quality(),
             default_quality: default_quality(),
@@ -287,13 +754,27 @@ impl Default for UserPreferences {
             preload_next: default_bool_true(),
             torrent_connections: default_torrent_connections(),
             cache_size: default_cache_size(),
+            prefer_non_p2p: default_bool_false(),
+            max_active_torrents: default_max_active_torrents(),
+            max_catalog_items: default_max_catalog_items(),
+            max_addon_response_mb: default_max_addon_response_mb(),
+            // Local media
+            scan_concurrency: default_scan_concurrency(),
+            probe_timeout_secs: default_probe_timeout_secs(),
+            prefer_local: default_bool_true(),
             // Advanced
             player_engine: default_player_engine(),
             debug_logging: default_bool_false(),
+            strict_addon_validation: default_bool_false(),
+            allow_private_addon_urls: default_bool_false(),
+            catalog_artwork_first: default_bool_false(),
             analytics: default_bool_false(),
             // General
             notifications_enabled: default_true(),
+            episode_check_interval_minutes: default_episode_check_interval_minutes(),
             auto_update: default_true(),
+            install_builtin_addons: default_true(),
+            builtins_seeded: false,
             last_notification_check: None,
             // Telemetry
             telemetry_enabled: false,
@@ -323,6 +804,65 @@ pub struct Playlist {
     pub item_count: i32,
 }
 
+/// A page of library items and how many items the library holds in total
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryPage {
+    pub items: Vec<MediaItem>,
+    pub total_count: i64,
+}
+
+/// One playback session reported for a media item
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchHistoryEntry {
+    pub id: i64,
+    pub media_id: String,
+    pub title: String,
+    pub poster_url: Option<String>,
+    pub watched_at: chrono::DateTime<chrono::Utc>,
+    /// Playback position in seconds
+    pub position: i32,
+    /// Total length in seconds, when the player knew it
+    pub duration: Option<i32>,
+}
+
+/// A watched title exported from another app, e.g. one row of a CSV
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedHistory {
+    pub title: String,
+    #[serde(default)]
+    pub year: Option<i32>,
+    /// IMDb id, or another id library items are stored under
+    #[serde(default)]
+    pub external_id: Option<String>,
+    /// Type of the item created when the library doesn't have it (default movie)
+    #[serde(default)]
+    pub media_type: Option<MediaType>,
+    /// When it was watched; the time of import if missing
+    #[serde(default)]
+    pub watched_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Playback position in seconds, for titles not finished
+    #[serde(default)]
+    pub position: Option<i32>,
+    #[serde(default = "default_true")]
+    pub watched: bool,
+}
+
+/// Outcome of `import_watch_history`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WatchHistoryImport {
+    /// Entries matched to an item already in the library
+    pub matched: usize,
+    /// Entries whose id wasn't in the library, added as new items
+    pub created: usize,
+    /// Entries whose watch event was already in the history, e.g. from an
+    /// earlier import of the same file
+    #[serde(default)]
+    pub duplicates: usize,
+    /// Entries matching no library item and without an id to add one under
+    pub unmatched: usize,
+    pub unmatched_titles: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaylistItem {
     pub playlist_id: String,
@@ -349,6 +889,17 @@ pub struct SearchFilters {
     pub sort_by: Option<String>, // "title_asc", "title_desc", "year_asc", "year_desc", "rating_desc", "added_desc"
 }
 
+/// A library search match with the matched terms wrapped in
+/// `<mark>`/`</mark>`. The surrounding text is HTML-escaped, so the
+/// snippets are safe to render as markup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub item: MediaItem,
+    pub title_snippet: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_snippet: Option<String>,
+}
+
 /// Addon health summary statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddonHealthSummary {
@@ -362,10 +913,54 @@ pub struct AddonHealthSummary {
     pub failed_requests: i64,
     pub last_error: Option<String>,
     pub health_score: f64,
+    /// Entries strict validation dropped over the same recent requests
+    #[serde(default)]
+    pub invalid_count: i64,
+}
+
+/// Result of checking an installed addon against its remote manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddonUpdate {
+    pub addon_id: String,
+    pub current_version: String,
+    pub latest_version: String,
+    pub has_update: bool,
 }
 
-// New: Skip segments for media items
+/// What changed between two versions of an addon manifest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestDiff {
+    pub old_version: String,
+    pub new_version: String,
+    pub added_catalogs: Vec<Catalog>,
+    pub removed_catalogs: Vec<Catalog>,
+    pub added_resources: Vec<String>,
+    pub removed_resources: Vec<String>,
+    pub added_types: Vec<String>,
+    pub removed_types: Vec<String>,
+}
+
+impl ManifestDiff {
+    /// True when catalogs, resources and types are all unchanged
+    pub fn is_empty(&self) -> bool {
+        self.added_catalogs.is_empty()
+            && self.removed_catalogs.is_empty()
+            && self.added_resources.is_empty()
+            && self.removed_resources.is_empty()
+            && self.added_types.is_empty()
+            && self.removed_types.is_empty()
+    }
+}
+
+/// An applied addon update together with what changed
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddonUpdateResult {
+    pub addon: Addon,
+    pub changes: ManifestDiff,
+}
+
+/// Intro/outro ranges of a media item in seconds, used by the player to auto-skip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SkipSegments {
     #[serde(skip_serializing_if = "Option::is_none")] pub intro_start: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")] pub intro_end: Option<f64>,
@@ -373,6 +968,45 @@ pub struct SkipSegments {
     #[serde(skip_serializing_if = "Option::is_none")] pub outro_end: Option<f64>,
 }
 
+impl SkipSegments {
+    /// Each segment must be given in full, start at or after zero and end after it starts
+    pub fn validate(&self) -> anyhow::Result<()> {
+        Self::validate_range("Intro", self.intro_start, self.intro_end)?;
+        Self::validate_range("Outro", self.outro_start, self.outro_end)
+    }
+
+    fn validate_range(label: &str, start: Option<f64>, end: Option<f64>) -> anyhow::Result<()> {
+        match (start, end) {
+            (None, None) => Ok(()),
+            (Some(start), Some(end)) => {
+                if !start.is_finite() || !end.is_finite() || start < 0.0 || end < 0.0 {
+                    anyhow::bail!("{} segment times must be non-negative", label);
+                }
+                if start >= end {
+                    anyhow::bail!(
+                        "{} segment start ({}) must be before its end ({})",
+                        label,
+                        start,
+                        end
+                    );
+                }
+                Ok(())
+            }
+            _ => anyhow::bail!("{} segment needs both a start and an end", label),
+        }
+    }
+}
+
+/// How a directory relates to the directories already scanned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "paths")]
+pub enum DirectoryOverlap {
+    /// Inside this recursively scanned directory, so already covered by it
+    Inside(String),
+    /// Contains these scanned directories, which it covers from now on
+    Contains(Vec<String>),
+}
+
 // New: Addon rating summary
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AddonRatingSummary {
@@ -398,6 +1032,9 @@ pub struct LiveTvChannel {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EpgProgram {
+    /// Row id once stored, used to refer to the program (e.g. reminders)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
     pub channel_id: String,
     pub start: i64, // unix timestamp (UTC)
     pub end: i64,   // unix timestamp (UTC)
@@ -411,3 +1048,162 @@ pub struct EpgProgram {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub episode: Option<u32>,
 }
+
+/// Result of the last reachability check of a channel's stream
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChannelStatus {
+    pub channel_id: String,
+    pub online: bool,
+    pub checked_at: i64, // unix timestamp (UTC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A live TV channel group with how many channels it holds
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChannelGroup {
+    /// None for channels without a group
+    pub name: Option<String>,
+    pub channel_count: usize,
+}
+
+/// A program the user asked to be reminded about before it starts
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProgramReminder {
+    pub program_id: i64,
+    pub channel_id: String,
+    pub channel_name: String,
+    pub title: String,
+    pub start: i64, // unix timestamp (UTC)
+    pub end: i64,   // unix timestamp (UTC)
+    /// The "starting soon" notification was already sent
+    pub notified: bool,
+}
+
+/// Outcome of a database maintenance run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OptimizeReport {
+    pub health_records_removed: usize,
+    pub vacuumed: bool,
+    pub size_before: u64,
+    pub size_after: u64,
+    pub reclaimed_bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_item_builder_defaults() {
+        let item = MediaItem::builder("tt0111161", "The Shawshank Redemption", MediaType::Movie)
+            .build()
+            .unwrap();
+
+        assert_eq!(item.id, "tt0111161");
+        assert_eq!(item.title, "The Shawshank Redemption");
+        assert!(matches!(item.media_type, MediaType::Movie));
+        assert!(item.genre.is_empty());
+        assert!(item.year.is_none());
+        assert!(item.rating.is_none());
+        assert!(item.progress.is_none());
+        assert!(item.added_to_library.is_none());
+        assert!(!item.watched);
+
+        let item = MediaItem::builder("tt0903747", "Breaking Bad", MediaType::TvShow)
+            .year(2008)
+            .rating(9.5)
+            .progress(0)
+            .watched(true)
+            .build()
+            .unwrap();
+        assert_eq!(item.year, Some(2008));
+        assert_eq!(item.rating, Some(9.5));
+        assert!(item.watched);
+    }
+
+    #[test]
+    fn test_media_item_validation_rejects_out_of_range() {
+        let builder = || MediaItem::builder("tt0111161", "Movie", MediaType::Movie);
+        let untitled = MediaItem::builder("tt0111161", "  ", MediaType::Movie);
+
+        assert!(MediaItem::builder("", "Movie", MediaType::Movie)
+            .build()
+            .is_err());
+        assert!(untitled.build().is_err());
+        assert!(builder().year(1500).build().is_err());
+        assert!(builder().year(3000).build().is_err());
+        assert!(builder().rating(-1.0).build().is_err());
+        assert!(builder().rating(10.5).build().is_err());
+        assert!(builder().rating(f32::NAN).build().is_err());
+        assert!(builder().progress(-5).build().is_err());
+
+        assert!(builder().year(1895).rating(10.0).progress(0).build().is_ok());
+    }
+
+    #[test]
+    fn test_progress_percent_guards() {
+        let builder = || MediaItem::builder("tt0111161", "Movie", MediaType::Movie);
+
+        let zero_duration = builder().duration(0).progress(60).build().unwrap();
+        assert_eq!(zero_duration.with_progress_percent().progress_percent, None);
+
+        let no_progress = builder().duration(90).build().unwrap();
+        assert_eq!(no_progress.with_progress_percent().progress_percent, None);
+
+        let overrun = builder().duration(1).progress(600).build().unwrap();
+        assert_eq!(
+            overrun.with_progress_percent().progress_percent,
+            Some(100.0)
+        );
+    }
+
+    #[test]
+    fn test_classify_direct_streams() {
+        assert_eq!(
+            StreamSourceType::classify("https://cdn.example.com/movie.mp4", false),
+            StreamSourceType::Direct
+        );
+        assert_eq!(
+            StreamSourceType::classify("http://cdn.example.com/movie.mkv", false),
+            StreamSourceType::Direct
+        );
+    }
+
+    #[test]
+    fn test_classify_hls_and_debrid() {
+        assert_eq!(
+            StreamSourceType::classify("https://cdn.example.com/live/index.M3U8", false),
+            StreamSourceType::Hls
+        );
+        assert_eq!(
+            StreamSourceType::classify("https://abc.download.real-debrid.com/d/XYZ/movie.mkv", false),
+            StreamSourceType::Debrid
+        );
+    }
+
+    #[test]
+    fn test_classify_torrents() {
+        assert_eq!(
+            StreamSourceType::classify("magnet:?xt=urn:btih:abcdef", false),
+            StreamSourceType::Torrent
+        );
+        assert_eq!(
+            StreamSourceType::classify("https://example.com/file.torrent", false),
+            StreamSourceType::Torrent
+        );
+        assert_eq!(
+            StreamSourceType::classify("https://example.com/stream/1", true),
+            StreamSourceType::Torrent
+        );
+    }
+
+    #[test]
+    fn test_classify_unknown_schemes() {
+        assert_eq!(
+            StreamSourceType::classify("data:video/mp4;base64,AAAA", false),
+            StreamSourceType::Unknown
+        );
+        assert_eq!(StreamSourceType::classify("not a url", false), StreamSourceType::Unknown);
+    }
+}
@@ -1,8 +1,21 @@
 use crate::addon_protocol::AddonClient;
-use crate::models::{Addon, MediaItem, MediaType};
+use crate::database::Database;
+use crate::events::{AppEvent, EventBus};
+use crate::models::{Addon, MediaItem, MediaType, UserPreferences, UserProfile};
+use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tauri_plugin_notification::NotificationExt;
+
+/// Wait before the first background check so startup isn't slowed down
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+/// Shortest interval between background checks, whatever the preference says
+const MIN_CHECK_INTERVAL_MINUTES: u32 = 15;
+/// More new episodes than this are summed up in a single notification
+const MAX_INDIVIDUAL_NOTIFICATIONS: usize = 3;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewEpisode {
@@ -16,11 +29,12 @@ pub struct NewEpisode {
     pub poster_url: Option<String>,
 }
 
-/// Check library for new episodes since last check
-/// Note: This requires addon integration and will return empty if addons parameter is added
+/// Check library for new episodes that aired after `last_check` and up to
+/// `now`. Store `now` as the next `last_check` so no episode is reported twice.
 pub async fn check_new_episodes(
     library_items: Vec<MediaItem>,
     last_check: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
     addons: Vec<Addon>,
 ) -> Result<Vec<NewEpisode>, anyhow::Error> {
     let mut new_episodes = Vec::new();
@@ -48,7 +62,6 @@ pub async fn check_new_episodes(
         return Ok(new_episodes);
     }
 
-    let now = Utc::now();
     let cutoff = last_check.unwrap_or_else(|| now - chrono::Duration::days(7));
 
     tracing::info!(
@@ -81,13 +94,7 @@ async fn check_show_for_new_episodes(
 
     // Try each addon until we get episode data
     for addon in addons {
-        let base_url = if addon.url.ends_with("/manifest.json") {
-            addon.url.replace("/manifest.json", "")
-        } else if addon.url.ends_with("manifest.json") {
-            addon.url.replace("manifest.json", "")
-        } else {
-            addon.url.clone()
-        };
+        let base_url = addon.base_url();
 
         let client = match AddonClient::new(base_url) {
             Ok(c) => c,
@@ -134,7 +141,7 @@ async fn check_show_for_new_episodes(
             };
 
             // Filter: only episodes that aired since last check and before now
-            if air_date >= cutoff && air_date <= now {
+            if aired_between(air_date, cutoff, now) {
                 new_episodes.push(NewEpisode {
                     series_id: show.id.clone(),
                     series_name: show.title.clone(),
@@ -164,6 +171,142 @@ async fn check_show_for_new_episodes(
     new_episodes
 }
 
+/// Whether an episode aired after `cutoff` and no later than `now`. The
+/// windows of consecutive checks don't overlap, so each episode falls in
+/// exactly one of them.
+fn aired_between(air_date: DateTime<Utc>, cutoff: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    air_date > cutoff && air_date <= now
+}
+
+/// Check the library for episodes that aired since the last check and
+/// record this check in the user's preferences
+pub async fn check_and_record(db: Arc<Mutex<Database>>) -> anyhow::Result<Vec<NewEpisode>> {
+    let user_id = crate::DEFAULT_PROFILE_ID;
+
+    let lookup_db = db.clone();
+    let (library_items, addons, last_check) = tokio::task::spawn_blocking(move || {
+        let db = lookup_db
+            .lock()
+            .map_err(|e| anyhow!("Database lock poisoned: {}", e))?;
+        let items = db.get_library_items()?;
+        let addons = db.get_addons()?;
+        let last_check = db
+            .get_user_profile(user_id)?
+            .and_then(|p| p.preferences.last_notification_check)
+            .and_then(|ts| DateTime::parse_from_rfc3339(&ts).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+        Ok::<_, anyhow::Error>((items, addons, last_check))
+    })
+    .await??;
+
+    let now = Utc::now();
+    let new_episodes = check_new_episodes(library_items, last_check, now, addons).await?;
+
+    tokio::task::spawn_blocking(move || {
+        let db = db
+            .lock()
+            .map_err(|e| anyhow!("Database lock poisoned: {}", e))?;
+        let mut profile = db
+            .get_user_profile(user_id)?
+            .unwrap_or_else(|| UserProfile {
+                id: user_id.to_string(),
+                username: "User".to_string(),
+                email: None,
+                preferences: UserPreferences::default(),
+                library_items: Vec::new(),
+                watchlist: Vec::new(),
+                favorites: Vec::new(),
+            });
+        profile.preferences.last_notification_check = Some(now.to_rfc3339());
+        db.save_user_profile(&profile)
+    })
+    .await??;
+
+    Ok(new_episodes)
+}
+
+/// Show OS notifications for new episodes: one per episode, or a single
+/// summary when there are many
+pub fn notify_desktop(app: &tauri::AppHandle, episodes: &[NewEpisode]) {
+    for (title, body) in notification_texts(episodes) {
+        if let Err(e) = app.notification().builder().title(title).body(body).show() {
+            tracing::warn!(error = %e, "Failed to show new episode notification");
+        }
+    }
+}
+
+/// Title and body of each notification to show for `episodes`
+fn notification_texts(episodes: &[NewEpisode]) -> Vec<(String, String)> {
+    if episodes.len() > MAX_INDIVIDUAL_NOTIFICATIONS {
+        let mut seen = HashSet::new();
+        let series: Vec<&str> = episodes
+            .iter()
+            .map(|e| e.series_name.as_str())
+            .filter(|name| seen.insert(*name))
+            .collect();
+        return vec![(
+            format!("{} new episodes", episodes.len()),
+            series.join(", "),
+        )];
+    }
+
+    episodes
+        .iter()
+        .map(|e| {
+            (
+                format!("New episode of {}", e.series_name),
+                format!("S{:02}E{:02}: {}", e.season, e.episode, e.title),
+            )
+        })
+        .collect()
+}
+
+/// Check for new episodes on the interval set in the user's preferences,
+/// showing desktop notifications and emitting `new-episodes` for what's
+/// found. Does nothing while notifications are turned off.
+pub async fn run_new_episode_checks(
+    db: Arc<Mutex<Database>>,
+    events: EventBus,
+    app: tauri::AppHandle,
+) {
+    tokio::time::sleep(FIRST_CHECK_DELAY).await;
+    loop {
+        let prefs_db = db.clone();
+        let (enabled, interval_minutes) = tokio::task::spawn_blocking(move || {
+            let db = prefs_db.lock().ok()?;
+            let profile = db.get_user_profile(crate::DEFAULT_PROFILE_ID).ok()??;
+            Some((
+                profile.preferences.notifications_enabled,
+                profile.preferences.episode_check_interval_minutes,
+            ))
+        })
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| {
+            let defaults = UserPreferences::default();
+            (
+                defaults.notifications_enabled,
+                defaults.episode_check_interval_minutes,
+            )
+        });
+
+        if enabled {
+            match check_and_record(db.clone()).await {
+                Ok(episodes) if !episodes.is_empty() => {
+                    notify_desktop(&app, &episodes);
+                    events.emit(AppEvent::NewEpisodes(episodes));
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "Background new episode check failed"),
+            }
+        }
+
+        let interval_minutes = interval_minutes.max(MIN_CHECK_INTERVAL_MINUTES);
+        tokio::time::sleep(Duration::from_secs(u64::from(interval_minutes) * 60)).await;
+    }
+}
+
 /// Parse episode air date from various formats
 fn parse_episode_air_date(released: &Option<String>) -> Option<DateTime<Utc>> {
     let date_str = released.as_ref()?;
@@ -185,3 +328,83 @@ fn parse_episode_air_date(released: &Option<String>) -> Option<DateTime<Utc>> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn episode(series_name: &str, episode: u32) -> NewEpisode {
+        NewEpisode {
+            series_id: format!("tt-{}", series_name),
+            series_name: series_name.to_string(),
+            episode_id: format!("tt-{}:1:{}", series_name, episode),
+            season: 1,
+            episode,
+            title: format!("Episode {}", episode),
+            air_date: None,
+            poster_url: None,
+        }
+    }
+
+    #[test]
+    fn test_episode_is_new_in_only_one_check() {
+        let first = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let second = first + chrono::Duration::hours(6);
+        let third = second + chrono::Duration::hours(6);
+        // Each check covers the time since the previous one
+        let checks = [
+            (first - chrono::Duration::days(7), first),
+            (first, second),
+            (second, third),
+        ];
+
+        for air_date in [first, first + chrono::Duration::hours(1), second, third] {
+            let notified = checks
+                .iter()
+                .filter(|(cutoff, now)| aired_between(air_date, *cutoff, *now))
+                .count();
+            assert_eq!(
+                notified, 1,
+                "episode airing at {} notified {} times",
+                air_date, notified
+            );
+        }
+
+        // Not yet aired at the last check
+        let future = third + chrono::Duration::minutes(1);
+        assert!(!checks
+            .iter()
+            .any(|(cutoff, now)| aired_between(future, *cutoff, *now)));
+    }
+
+    #[test]
+    fn test_notification_texts() {
+        let texts = notification_texts(&[episode("Severance", 3)]);
+        assert_eq!(
+            texts,
+            vec![(
+                "New episode of Severance".to_string(),
+                "S01E03: Episode 3".to_string()
+            )]
+        );
+
+        let many: Vec<NewEpisode> = [
+            ("Severance", 1),
+            ("Severance", 2),
+            ("Andor", 1),
+            ("Shogun", 4),
+        ]
+        .into_iter()
+        .map(|(name, number)| episode(name, number))
+        .collect();
+        assert_eq!(
+            notification_texts(&many),
+            vec![(
+                "4 new episodes".to_string(),
+                "Severance, Andor, Shogun".to_string()
+            )]
+        );
+        assert!(notification_texts(&[]).is_empty());
+    }
+}
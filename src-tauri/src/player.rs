@@ -5,7 +5,12 @@
  */
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 /// Video quality options
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -164,6 +169,10 @@ impl ExternalPlayer {
                     cmd.arg(format!("--sub-file={}", sub_path));
                 }
                 cmd.arg("--force-window=yes");
+                cmd.arg(format!(
+                    "--input-ipc-server={}",
+                    Self::mpv_ipc_path().display()
+                ));
             }
             ExternalPlayer::IINA => {
                 cmd.arg(url);
@@ -186,6 +195,138 @@ impl ExternalPlayer {
 
         Ok(())
     }
+
+    /// IPC endpoint MPV is launched with: a Unix socket in the user's runtime
+    /// dir (the temp dir when there is none), or a named pipe on Windows. The
+    /// name includes our pid so other users and instances can't collide.
+    pub fn mpv_ipc_path() -> PathBuf {
+        let name = format!("streamgo-mpv-{}", std::process::id());
+        if cfg!(target_os = "windows") {
+            PathBuf::from(format!(r"\\.\pipe\{}", name))
+        } else {
+            dirs::runtime_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join(format!("{}.sock", name))
+        }
+    }
+
+    /// Current playback position of MPV in seconds
+    pub fn mpv_get_position(&self) -> Result<f64> {
+        let position = self.mpv_command(&[Value::from("get_property"), Value::from("time-pos")])?;
+        position
+            .as_f64()
+            .ok_or_else(|| anyhow!("MPV returned no playback position"))
+    }
+
+    /// Seek MPV to an absolute position in seconds
+    pub fn mpv_seek(&self, seconds: f64) -> Result<()> {
+        self.mpv_command(&[
+            Value::from("seek"),
+            Value::from(seconds),
+            Value::from("absolute"),
+        ])?;
+        Ok(())
+    }
+
+    /// Pause or resume MPV
+    pub fn mpv_pause(&self, paused: bool) -> Result<()> {
+        self.mpv_command(&[
+            Value::from("set_property"),
+            Value::from("pause"),
+            Value::from(paused),
+        ])?;
+        Ok(())
+    }
+
+    /// Load a subtitle file or URL into MPV and select it
+    pub fn mpv_set_subtitle(&self, subtitle_path: &str) -> Result<()> {
+        self.mpv_command(&[
+            Value::from("sub-add"),
+            Value::from(subtitle_path),
+            Value::from("select"),
+        ])?;
+        Ok(())
+    }
+
+    /// Send a command over MPV's JSON IPC and wait for its reply, skipping
+    /// any events MPV sends in between
+    fn mpv_command(&self, command: &[Value]) -> Result<Value> {
+        if !matches!(self, ExternalPlayer::MPV) {
+            return Err(anyhow!("IPC control is only supported for MPV"));
+        }
+
+        mpv_command_at(Self::mpv_ipc_path(), command, MPV_IPC_TIMEOUT)
+    }
+}
+
+static NEXT_MPV_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// How long to wait for MPV to answer an IPC command
+const MPV_IPC_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Send `command` to the MPV listening at `path` and wait up to `timeout`
+/// for the reply. The exchange runs on a worker thread because named pipes
+/// opened through `std::fs` can't have a read timeout; a worker stuck on an
+/// unresponsive MPV is left to finish when the pipe closes.
+fn mpv_command_at(path: PathBuf, command: &[Value], timeout: Duration) -> Result<Value> {
+    let request_id = NEXT_MPV_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+    let request = mpv_request(command, request_id);
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(mpv_exchange(&path, &request, request_id));
+    });
+    rx.recv_timeout(timeout)
+        .map_err(|_| anyhow!("MPV did not reply within {:?}", timeout))?
+}
+
+fn mpv_exchange(path: &std::path::Path, request: &str, request_id: u64) -> Result<Value> {
+    let mut stream =
+        connect_mpv_ipc(path).map_err(|e| anyhow!("Failed to connect to MPV IPC: {}", e))?;
+    stream.write_all(request.as_bytes())?;
+    stream.flush()?;
+
+    for line in BufReader::new(stream).lines() {
+        if let Some(data) = parse_mpv_response(&line?, request_id)? {
+            return Ok(data);
+        }
+    }
+    Err(anyhow!("MPV closed the IPC connection without replying"))
+}
+
+#[cfg(unix)]
+fn connect_mpv_ipc(path: &std::path::Path) -> std::io::Result<std::os::unix::net::UnixStream> {
+    let stream = std::os::unix::net::UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(MPV_IPC_TIMEOUT))?;
+    Ok(stream)
+}
+
+#[cfg(windows)]
+fn connect_mpv_ipc(path: &std::path::Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+}
+
+/// Serialize an MPV IPC command as a newline-terminated JSON line
+fn mpv_request(command: &[Value], request_id: u64) -> String {
+    let mut line = serde_json::json!({ "command": command, "request_id": request_id }).to_string();
+    line.push('\n');
+    line
+}
+
+/// Parse one line from MPV's IPC. Returns `None` for events and replies to
+/// other requests, and the `data` field (null when absent) for our reply.
+fn parse_mpv_response(line: &str, request_id: u64) -> Result<Option<Value>> {
+    let response: Value = serde_json::from_str(line)?;
+    if response.get("request_id").and_then(Value::as_u64) != Some(request_id) {
+        return Ok(None);
+    }
+    match response.get("error").and_then(Value::as_str) {
+        Some("success") => Ok(Some(response.get("data").cloned().unwrap_or(Value::Null))),
+        Some(error) => Err(anyhow!("MPV error: {}", error)),
+        None => Err(anyhow!("Malformed MPV response: {}", line)),
+    }
 }
 
 /// Player manager for handling playback
@@ -278,7 +419,7 @@ impl Default for PlayerManager {
 pub struct SubtitleManager;
 
 impl SubtitleManager {
-    /// Download subtitle from URL
+    /// Download subtitle from URL, decoded to UTF-8
     pub async fn download_subtitle(url: &str) -> Result<String> {
         let client = reqwest::Client::new();
         let response = client.get(url).send().await?;
@@ -290,21 +431,169 @@ impl SubtitleManager {
             ));
         }
 
-        let content = response.text().await?;
-        Ok(content)
+        let bytes = response.bytes().await?;
+        Ok(Self::detect_and_decode(&bytes))
+    }
+
+    /// Decode subtitle bytes to UTF-8 text without a BOM. A BOM decides the
+    /// encoding when present; otherwise valid UTF-8 is kept as is and other
+    /// bytes are sniffed, since many SRT files use legacy encodings such as
+    /// Windows-1252.
+    pub fn detect_and_decode(bytes: &[u8]) -> String {
+        if let Some((encoding, bom_len)) = encoding_rs::Encoding::for_bom(bytes) {
+            let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+            return text.into_owned();
+        }
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return text.to_string();
+        }
+
+        let mut detector = chardetng::EncodingDetector::new();
+        detector.feed(bytes, true);
+        let encoding = detector.guess(None, true);
+        let (text, _) = encoding.decode_without_bom_handling(bytes);
+        text.into_owned()
     }
 
     /// Convert SRT to WebVTT format
     pub fn srt_to_vtt(srt_content: &str) -> Result<String> {
         let mut vtt = String::from("WEBVTT\n\n");
 
-        // Replace SRT time format (00:00:00,000) with WebVTT format (00:00:00.000)
-        let converted = srt_content.replace(',', ".");
-        vtt.push_str(&converted);
+        for line in srt_content.trim_start_matches('\u{feff}').lines() {
+            // Replace SRT time format (00:00:00,000) with WebVTT format (00:00:00.000),
+            // leaving commas in the cue text alone
+            if line.contains("-->") {
+                vtt.push_str(&line.replace(',', "."));
+            } else {
+                vtt.push_str(line);
+            }
+            vtt.push('\n');
+        }
 
         Ok(vtt)
     }
 
+    /// Parse SRT subtitle into cues ordered by start time. Timestamps are
+    /// returned in WebVTT form (00:00:01.000) so they match `parse_vtt`.
+    /// Blocks without a valid timing line are skipped.
+    pub fn parse_srt(srt_content: &str) -> Result<Vec<SubtitleCue>> {
+        let mut timed_cues = Vec::new();
+        let mut lines = srt_content
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .peekable();
+
+        while lines.peek().is_some() {
+            // Each block runs up to the next blank line
+            let mut block = Vec::new();
+            for line in lines.by_ref() {
+                if line.trim().is_empty() {
+                    break;
+                }
+                block.push(line.trim());
+            }
+
+            // The index line is ignored; cues are ordered by their timing instead
+            let Some(timing) = block.iter().position(|line| line.contains("-->")) else {
+                continue;
+            };
+            let Some((start, end)) = block[timing].split_once("-->") else {
+                continue;
+            };
+            let end = end.split_whitespace().next().unwrap_or("");
+            let (Some(start_ms), Some(end_ms)) = (cue_timestamp_ms(start), cue_timestamp_ms(end))
+            else {
+                continue;
+            };
+
+            timed_cues.push((
+                start_ms,
+                SubtitleCue {
+                    start: vtt_timestamp(start_ms),
+                    end: vtt_timestamp(end_ms),
+                    text: block[timing + 1..].join("\n"),
+                },
+            ));
+        }
+
+        timed_cues.sort_by_key(|(start_ms, _)| *start_ms);
+        Ok(timed_cues.into_iter().map(|(_, cue)| cue).collect())
+    }
+
+    /// Convert ASS/SSA subtitles to WebVTT. Only `Dialogue:` lines of the
+    /// `[Events]` section are used; styles and positioning are dropped, and
+    /// italic/bold override tags become `<i>`/`<b>`.
+    pub fn ass_to_vtt(ass_content: &str) -> Result<String> {
+        let mut in_events = false;
+        let mut found_events = false;
+        let mut format: Vec<String> = ASS_DEFAULT_FORMAT
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+        let mut timed_cues = Vec::new();
+
+        for line in ass_content.trim_start_matches('\u{feff}').lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                in_events = line.eq_ignore_ascii_case("[events]");
+                found_events |= in_events;
+                continue;
+            }
+            if !in_events {
+                continue;
+            }
+
+            if let Some(fields) = line.strip_prefix("Format:") {
+                format = fields
+                    .split(',')
+                    .map(|field| field.trim().to_lowercase())
+                    .collect();
+            } else if let Some(values) = line.strip_prefix("Dialogue:") {
+                // Text is always the last field and may itself contain commas
+                let values: Vec<&str> = values.splitn(format.len(), ',').collect();
+                let field = |name: &str| {
+                    format
+                        .iter()
+                        .position(|field| field == name)
+                        .and_then(|index| values.get(index))
+                        .map(|value| value.trim())
+                };
+                let (Some(start), Some(end), Some(text)) =
+                    (field("start"), field("end"), field("text"))
+                else {
+                    continue;
+                };
+                let (Some(start_ms), Some(end_ms)) =
+                    (ass_timestamp_ms(start), ass_timestamp_ms(end))
+                else {
+                    continue;
+                };
+
+                let text = ass_text_to_vtt(text);
+                if text.trim().is_empty() {
+                    continue;
+                }
+                timed_cues.push((start_ms, end_ms, text));
+            }
+        }
+
+        if !found_events {
+            return Err(anyhow!("No [Events] section in ASS subtitle"));
+        }
+
+        timed_cues.sort_by_key(|(start_ms, _, _)| *start_ms);
+        let mut vtt = String::from("WEBVTT\n");
+        for (start_ms, end_ms, text) in timed_cues {
+            vtt.push_str(&format!(
+                "\n{} --> {}\n{}\n",
+                vtt_timestamp(start_ms),
+                vtt_timestamp(end_ms),
+                text
+            ));
+        }
+        Ok(vtt)
+    }
+
     /// Parse WebVTT subtitle
     pub fn parse_vtt(vtt_content: &str) -> Result<Vec<SubtitleCue>> {
         let mut cues = Vec::new();
@@ -354,6 +643,159 @@ impl SubtitleManager {
 
         Ok(cues)
     }
+
+    /// Move every cue by `offset_ms` (negative shows subtitles earlier),
+    /// clamping at zero. Cues with unparseable timestamps are left as they are.
+    pub fn shift_cues(cues: &[SubtitleCue], offset_ms: i64) -> Vec<SubtitleCue> {
+        let shift = |timestamp: &str| {
+            cue_timestamp_ms(timestamp)
+                .map(|ms| vtt_timestamp(ms.saturating_add_signed(offset_ms)))
+                .unwrap_or_else(|| timestamp.to_string())
+        };
+        cues.iter()
+            .map(|cue| SubtitleCue {
+                start: shift(&cue.start),
+                end: shift(&cue.end),
+                text: cue.text.clone(),
+            })
+            .collect()
+    }
+
+    /// Shift a WebVTT file by `offset_ms`. The result is re-serialized from
+    /// the parsed cues, so cue identifiers and settings are not kept.
+    pub fn shift_vtt(vtt_content: &str, offset_ms: i64) -> Result<String> {
+        let cues = Self::parse_vtt(vtt_content)?;
+        let mut vtt = String::from("WEBVTT\n");
+        for cue in Self::shift_cues(&cues, offset_ms) {
+            vtt.push_str(&format!("\n{} --> {}\n{}\n", cue.start, cue.end, cue.text));
+        }
+        Ok(vtt)
+    }
+}
+
+/// Milliseconds of an SRT (00:00:01,000) or WebVTT (00:00:01.000, or
+/// 00:01.000 without hours) timestamp
+fn cue_timestamp_ms(timestamp: &str) -> Option<u64> {
+    let (clock, millis) = timestamp.trim().split_once([',', '.'])?;
+    let parts: Vec<&str> = clock.split(':').collect();
+    let (hours, minutes, seconds): (u64, u64, u64) = match parts.as_slice() {
+        [hours, minutes, seconds] => (
+            hours.parse().ok()?,
+            minutes.parse().ok()?,
+            seconds.parse().ok()?,
+        ),
+        [minutes, seconds] => (0, minutes.parse().ok()?, seconds.parse().ok()?),
+        _ => return None,
+    };
+    if minutes >= 60 || seconds >= 60 || millis.len() != 3 {
+        return None;
+    }
+    let millis: u64 = millis.parse().ok()?;
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis)
+}
+
+/// Event fields used when an `[Events]` section has no `Format:` line
+const ASS_DEFAULT_FORMAT: [&str; 10] = [
+    "layer", "start", "end", "style", "name", "marginl", "marginr", "marginv", "effect", "text",
+];
+
+/// Milliseconds of an ASS timestamp (H:MM:SS.cs)
+fn ass_timestamp_ms(timestamp: &str) -> Option<u64> {
+    let (clock, centis) = timestamp.trim().split_once('.')?;
+    let parts: Vec<&str> = clock.split(':').collect();
+    let [hours, minutes, seconds] = parts.as_slice() else {
+        return None;
+    };
+    let (hours, minutes, seconds): (u64, u64, u64) = (
+        hours.parse().ok()?,
+        minutes.parse().ok()?,
+        seconds.parse().ok()?,
+    );
+    if minutes >= 60 || seconds >= 60 || centis.len() != 2 {
+        return None;
+    }
+    let centis: u64 = centis.parse().ok()?;
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + centis * 10)
+}
+
+/// Turn ASS dialogue text into WebVTT cue text: `\N` line breaks become
+/// newlines, `{\i1}`/`{\b1}` become `<i>`/`<b>` and every other override
+/// tag is dropped.
+fn ass_text_to_vtt(text: &str) -> String {
+    let mut out = String::new();
+    // Open <i>/<b> tags, innermost last, so they can be closed in order
+    let mut open: Vec<char> = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        if let Some(block) = rest.strip_prefix('{') {
+            let Some(close) = block.find('}') else {
+                break;
+            };
+            for tag in block[..close].split('\\') {
+                // Only \i and \b with a numeric value; \iclip, \blur etc. are dropped
+                let Some(kind) = tag.chars().next().filter(|c| matches!(c, 'i' | 'b')) else {
+                    continue;
+                };
+                let Ok(value) = tag[1..].parse::<u32>() else {
+                    continue;
+                };
+                let is_open = open.contains(&kind);
+                if value != 0 && !is_open {
+                    out.push_str(&format!("<{}>", kind));
+                    open.push(kind);
+                } else if value == 0 && is_open {
+                    // Close tags opened after this one too, then reopen them
+                    let index = open
+                        .iter()
+                        .position(|open_kind| *open_kind == kind)
+                        .unwrap();
+                    let reopen = open.split_off(index + 1);
+                    for inner in reopen.iter().rev() {
+                        out.push_str(&format!("</{}>", inner));
+                    }
+                    out.push_str(&format!("</{}>", kind));
+                    open.pop();
+                    for inner in reopen {
+                        out.push_str(&format!("<{}>", inner));
+                        open.push(inner);
+                    }
+                }
+            }
+            rest = &block[close + 1..];
+        } else if let Some(escaped) = rest.strip_prefix('\\') {
+            match escaped.chars().next() {
+                Some('N') | Some('n') => out.push('\n'),
+                Some('h') => out.push(' '),
+                _ => {
+                    out.push('\\');
+                    rest = escaped;
+                    continue;
+                }
+            }
+            rest = &escaped[1..];
+        } else {
+            let next = rest.find(['{', '\\']).unwrap_or(rest.len());
+            let plain = &rest[..next];
+            out.push_str(&plain.replace('&', "&amp;").replace('<', "&lt;"));
+            rest = &rest[next..];
+        }
+    }
+
+    for kind in open.iter().rev() {
+        out.push_str(&format!("</{}>", kind));
+    }
+    out
+}
+
+fn vtt_timestamp(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
 }
 
 /// Subtitle cue (single subtitle entry)
@@ -408,6 +850,99 @@ mod tests {
         assert_eq!(auto.unwrap().bitrate, Some(8000));
     }
 
+    #[test]
+    fn test_mpv_request_serialization() {
+        let request = mpv_request(
+            &[
+                Value::from("seek"),
+                Value::from(12.5),
+                Value::from("absolute"),
+            ],
+            7,
+        );
+        assert!(request.ends_with('\n'));
+        let parsed: Value = serde_json::from_str(request.trim_end()).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!({ "command": ["seek", 12.5, "absolute"], "request_id": 7 })
+        );
+    }
+
+    #[test]
+    fn test_parse_mpv_response() {
+        let reply = r#"{"data":42.5,"error":"success","request_id":3}"#;
+        assert_eq!(
+            parse_mpv_response(reply, 3).unwrap(),
+            Some(Value::from(42.5))
+        );
+
+        // Events and replies to other requests are skipped
+        assert_eq!(parse_mpv_response(r#"{"event":"pause"}"#, 3).unwrap(), None);
+        assert_eq!(parse_mpv_response(reply, 4).unwrap(), None);
+
+        let no_data = r#"{"error":"success","request_id":5}"#;
+        assert_eq!(parse_mpv_response(no_data, 5).unwrap(), Some(Value::Null));
+        let failed = r#"{"error":"property unavailable","request_id":6}"#;
+        assert!(parse_mpv_response(failed, 6).is_err());
+        assert!(parse_mpv_response("not json", 6).is_err());
+
+        assert!(ExternalPlayer::VLC.mpv_get_position().is_err());
+    }
+
+    #[test]
+    fn test_mpv_ipc_path_is_per_process() {
+        let path = ExternalPlayer::mpv_ipc_path();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.contains(&std::process::id().to_string()), "{}", name);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mpv_command_times_out_without_reply() {
+        use std::os::unix::net::UnixListener;
+
+        let dir = std::env::temp_dir().join(format!("streamgo_mpv_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mpv.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        // A live MPV replies after any events it sends first
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            let request: Value = serde_json::from_str(&request).unwrap();
+            let id = request["request_id"].as_u64().unwrap();
+            let mut stream = stream;
+            writeln!(stream, r#"{{"event":"pause"}}"#).unwrap();
+            writeln!(
+                stream,
+                r#"{{"data":42.5,"error":"success","request_id":{}}}"#,
+                id
+            )
+            .unwrap();
+            // Stays connected without answering the next request
+            let (_hung, _) = listener.accept().unwrap();
+            std::thread::sleep(Duration::from_secs(1));
+        });
+
+        let command = [Value::from("get_property"), Value::from("time-pos")];
+        let timeout = Duration::from_millis(200);
+        assert_eq!(
+            mpv_command_at(path.clone(), &command, timeout).unwrap(),
+            Value::from(42.5)
+        );
+
+        let started = std::time::Instant::now();
+        let err = mpv_command_at(path, &command, timeout).unwrap_err();
+        assert!(err.to_string().contains("did not reply"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        server.join().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_srt_to_vtt() {
         let srt = "1\n00:00:01,000 --> 00:00:04,000\nHello World";
@@ -415,4 +950,154 @@ mod tests {
         assert!(vtt.starts_with("WEBVTT"));
         assert!(vtt.contains("00:00:01.000"));
     }
+
+    #[test]
+    fn test_srt_to_vtt_keeps_cue_text() {
+        let srt = "\u{feff}1\r\n00:00:01,000 --> 00:00:04,000\r\nWell, hello\r\n";
+        let vtt = SubtitleManager::srt_to_vtt(srt).unwrap();
+        assert_eq!(
+            vtt,
+            "WEBVTT\n\n1\n00:00:01.000 --> 00:00:04.000\nWell, hello\n"
+        );
+    }
+
+    #[test]
+    fn test_detect_and_decode() {
+        // "Café déjà vu" in Windows-1252
+        let windows_1252 = b"1\r\n00:00:01,000 --> 00:00:02,000\r\nCaf\xe9 d\xe9j\xe0 vu\r\n";
+        let text = SubtitleManager::detect_and_decode(windows_1252);
+        assert!(text.contains("Café déjà vu"), "{}", text);
+        assert_eq!(
+            SubtitleManager::parse_srt(&text).unwrap()[0].text,
+            "Café déjà vu"
+        );
+
+        let utf8 = "Ça va, Zoë?";
+        assert_eq!(SubtitleManager::detect_and_decode(utf8.as_bytes()), utf8);
+
+        let mut with_bom = vec![0xef, 0xbb, 0xbf];
+        with_bom.extend_from_slice(utf8.as_bytes());
+        assert_eq!(SubtitleManager::detect_and_decode(&with_bom), utf8);
+
+        let utf16le: Vec<u8> = [0xff, 0xfe]
+            .into_iter()
+            .chain(utf8.encode_utf16().flat_map(|unit| unit.to_le_bytes()))
+            .collect();
+        assert_eq!(SubtitleManager::detect_and_decode(&utf16le), utf8);
+    }
+
+    #[test]
+    fn test_parse_srt() {
+        let srt = "\u{feff}2\r\n\
+                   00:01:02,500 --> 00:01:05,250\r\n\
+                   <i>Well, hello</i>\r\n\
+                   there\r\n\
+                   \r\n\
+                   1\r\n\
+                   00:00:01,000 --> 00:00:04,000\r\n\
+                   First line\r\n\
+                   \r\n\
+                   3\r\n\
+                   not a timestamp\r\n\
+                   Dropped\r\n";
+        let cues = SubtitleManager::parse_srt(srt).unwrap();
+        assert_eq!(cues.len(), 2);
+
+        // Ordered by timestamp, not by the out-of-order index
+        assert_eq!(cues[0].start, "00:00:01.000");
+        assert_eq!(cues[0].end, "00:00:04.000");
+        assert_eq!(cues[0].text, "First line");
+        assert_eq!(cues[1].start, "00:01:02.500");
+        assert_eq!(cues[1].end, "00:01:05.250");
+        assert_eq!(cues[1].text, "<i>Well, hello</i>\nthere");
+    }
+
+    #[test]
+    fn test_cue_timestamp_ms() {
+        assert_eq!(cue_timestamp_ms("01:02:03,004"), Some(3_723_004));
+        assert_eq!(cue_timestamp_ms(" 00:00:01.500 "), Some(1_500));
+        assert_eq!(cue_timestamp_ms("02:01.250"), Some(121_250));
+        assert_eq!(cue_timestamp_ms("00:61:00,000"), None);
+        assert_eq!(cue_timestamp_ms("00:00:01"), None);
+        assert_eq!(vtt_timestamp(3_723_004), "01:02:03.004");
+    }
+
+    #[test]
+    fn test_shift_cues() {
+        let cue = |start: &str, end: &str| SubtitleCue {
+            start: start.to_string(),
+            end: end.to_string(),
+            text: "Hello".to_string(),
+        };
+        let cues = vec![
+            cue("00:00:01.000", "00:00:04.000"),
+            cue("00:59:59.500", "01:00:01.000"),
+        ];
+
+        let later = SubtitleManager::shift_cues(&cues, 2_500);
+        assert_eq!(later[0].start, "00:00:03.500");
+        assert_eq!(later[0].end, "00:00:06.500");
+        assert_eq!(later[1].start, "01:00:02.000");
+        assert_eq!(later[1].end, "01:00:03.500");
+        assert_eq!(later[0].text, "Hello");
+
+        // Times that would go negative stop at zero
+        let earlier = SubtitleManager::shift_cues(&cues, -2_000);
+        assert_eq!(earlier[0].start, "00:00:00.000");
+        assert_eq!(earlier[0].end, "00:00:02.000");
+        assert_eq!(earlier[1].start, "00:59:57.500");
+
+        let way_earlier = SubtitleManager::shift_cues(&cues, -10_000);
+        assert_eq!(way_earlier[0].start, "00:00:00.000");
+        assert_eq!(way_earlier[0].end, "00:00:00.000");
+    }
+
+    #[test]
+    fn test_shift_vtt() {
+        let vtt = "WEBVTT\n\n00:01.000 --> 00:04.000\nWell, hello\nthere\n\n\
+                   00:00:05.000 --> 00:00:06.000 align:start\nBye\n";
+        let shifted = SubtitleManager::shift_vtt(vtt, -1_500).unwrap();
+        assert_eq!(
+            shifted,
+            "WEBVTT\n\n00:00:00.000 --> 00:00:02.500\nWell, hello\nthere\n\n\
+             00:00:03.500 --> 00:00:04.500\nBye\n"
+        );
+        assert_eq!(SubtitleManager::parse_vtt(&shifted).unwrap().len(), 2);
+    }
+
+    const ASS_HEADER: &str = "[Script Info]\nTitle: Test\n\n[V4+ Styles]\n\
+                              Format: Name, Fontname, Fontsize\nStyle: Default,Arial,20\n\n\
+                              [Events]\nFormat: Layer, Start, End, Style, Name, MarginL, \
+                              MarginR, MarginV, Effect, Text\n";
+
+    #[test]
+    fn test_ass_to_vtt_converts_override_tags() {
+        let ass = format!(
+            "{}Dialogue: 0,0:00:01.50,0:00:03.00,Default,,0,0,0,,{{\\an8}}{{\\i1}}Top{{\\i0}} line\\N{{\\b1}}bold{{\\fs20}} & <more>\n\
+             Comment: 0,0:00:02.00,0:00:04.00,Default,,0,0,0,,Translator note\n",
+            ASS_HEADER
+        );
+        let vtt = SubtitleManager::ass_to_vtt(&ass).unwrap();
+        assert_eq!(
+            vtt,
+            "WEBVTT\n\n00:00:01.500 --> 00:00:03.000\n<i>Top</i> line\n<b>bold &amp; &lt;more></b>\n"
+        );
+    }
+
+    #[test]
+    fn test_ass_to_vtt_keeps_commas_in_text() {
+        let ass = format!(
+            "{}Dialogue: 0,1:02:03.04,1:02:05.00,Default,Bob,0,0,0,,Well, well, well.\n\
+             Dialogue: 0,0:00:00.00,0:00:01.00,Default,,0,0,0,,First\n",
+            ASS_HEADER
+        );
+        let cues = SubtitleManager::parse_vtt(&SubtitleManager::ass_to_vtt(&ass).unwrap()).unwrap();
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].text, "First");
+        assert_eq!(cues[1].start, "01:02:03.040");
+        assert_eq!(cues[1].end, "01:02:05.000");
+        assert_eq!(cues[1].text, "Well, well, well.");
+
+        assert!(SubtitleManager::ass_to_vtt("1\n00:00:01,000 --> 00:00:02,000\nHi\n").is_err());
+    }
 }
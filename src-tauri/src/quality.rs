@@ -0,0 +1,142 @@
+/**
+ * Stream Quality
+ *
+ * Parse resolution hints out of addon stream labels and order streams
+ * best quality first
+ */
+use crate::addon_protocol::Stream;
+use crate::models::StreamWithSource;
+use std::cmp::Reverse;
+
+/// Resolution hint from a stream label such as "1080p" or "4K", 0 if none
+pub fn parse_quality_hint(s: &str) -> i32 {
+    let l = s.to_lowercase();
+    if l.contains("2160p") || l.contains("4k") {
+        return 2160;
+    }
+    if l.contains("1440p") {
+        return 1440;
+    }
+    if l.contains("1080p") || l.contains("full hd") {
+        return 1080;
+    }
+    if l.contains("720p") || l.contains(" hd") {
+        return 720;
+    }
+    if l.contains("480p") {
+        return 480;
+    }
+    if l.contains("360p") {
+        return 360;
+    }
+    0
+}
+
+/// Highest resolution mentioned in any of the labels, 0 if unknown
+pub fn labels_quality<'a>(labels: impl IntoIterator<Item = &'a Option<String>>) -> i32 {
+    labels
+        .into_iter()
+        .flatten()
+        .map(|text| parse_quality_hint(text))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Sort key ordering streams by resolution, then HLS, then web-ready
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct QualityRank {
+    resolution: i32,
+    hls: bool,
+    web_ready: bool,
+}
+
+impl QualityRank {
+    fn new(resolution: i32, url: &str, not_web_ready: bool) -> Self {
+        Self {
+            resolution,
+            hls: url.to_lowercase().contains(".m3u8"),
+            web_ready: !not_web_ready,
+        }
+    }
+}
+
+/// Sort streams best quality first. The sort is stable, so streams of equal
+/// quality keep their addon priority order.
+pub fn sort_streams(streams: &mut [Stream]) {
+    streams.sort_by_key(|s| {
+        let resolution = labels_quality([&s.name, &s.title, &s.description]);
+        Reverse(QualityRank::new(
+            resolution,
+            &s.url,
+            s.behaviorHints.notWebReady,
+        ))
+    });
+}
+
+/// `sort_streams` for streams that already carry their source addon
+pub fn sort_streams_with_source(streams: &mut [StreamWithSource]) {
+    streams.sort_by_key(|s| {
+        let resolution = labels_quality([&s.name, &s.title, &s.description]);
+        Reverse(QualityRank::new(resolution, &s.url, s.not_web_ready))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addon_protocol::StreamBehaviorHints;
+
+    fn stream(url: &str, name: &str, not_web_ready: bool) -> Stream {
+        Stream {
+            url: url.to_string(),
+            title: None,
+            name: Some(name.to_string()),
+            description: None,
+            behaviorHints: StreamBehaviorHints {
+                notWebReady: not_web_ready,
+                ..Default::default()
+            },
+            subtitles: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sort_streams_by_quality() {
+        let mut streams = vec![
+            stream("https://a.example.com/480.mp4", "480p", false),
+            stream("https://b.example.com/1080.mp4", "1080p", true),
+            stream("https://c.example.com/unknown.mp4", "Direct", false),
+            stream("https://d.example.com/4k.mkv", "4K HDR", false),
+            stream("https://e.example.com/1080.mp4", "1080p", false),
+            stream("https://f.example.com/1080.m3u8", "1080p", true),
+            stream("https://g.example.com/1080.mp4", "Full HD", false),
+        ];
+        sort_streams(&mut streams);
+
+        let urls: Vec<_> = streams.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://d.example.com/4k.mkv",
+                // HLS beats web-ready, web-ready beats not, ties keep their order
+                "https://f.example.com/1080.m3u8",
+                "https://e.example.com/1080.mp4",
+                "https://g.example.com/1080.mp4",
+                "https://b.example.com/1080.mp4",
+                "https://a.example.com/480.mp4",
+                "https://c.example.com/unknown.mp4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_labels_quality_takes_highest_hint() {
+        let labels = [
+            Some("Torrentio\n720p".to_string()),
+            None,
+            Some("Remux 2160p".to_string()),
+        ];
+        assert_eq!(labels_quality(&labels), 2160);
+        assert_eq!(labels_quality(&[None, Some("CAM".to_string())]), 0);
+    }
+}
@@ -0,0 +1,49 @@
+/**
+ * Rate Limit
+ *
+ * Space out requests to services that limit how often they may be called
+ */
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Lets tasks start no more often than every `min_interval`, in the order
+/// they ask. Share it by reference between the tasks being limited.
+pub struct RateLimiter {
+    min_interval: Duration,
+    next_start: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            next_start: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait until the caller's turn to start
+    pub async fn wait(&self) {
+        let mut next = self.next_start.lock().await;
+        tokio::time::sleep_until(*next).await;
+        *next = Instant::now() + self.min_interval;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_starts_are_spaced_out() {
+        let limiter = RateLimiter::new(Duration::from_millis(20));
+        let started = Instant::now();
+
+        // The first start is immediate
+        limiter.wait().await;
+        assert!(started.elapsed() < Duration::from_millis(20));
+
+        futures::future::join3(limiter.wait(), limiter.wait(), limiter.wait()).await;
+        assert!(started.elapsed() >= Duration::from_millis(60));
+    }
+}
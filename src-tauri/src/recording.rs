@@ -0,0 +1,440 @@
+/**
+ * Live TV Recording
+ *
+ * Capture a live channel to disk with ffmpeg for the length of an EPG
+ * program, then add the file to the local media library
+ */
+use crate::database::Database;
+use crate::events::{AppEvent, EventBus};
+use crate::local_media::LocalMediaScanner;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::sync::Notify;
+
+/// Recording starts this long before the guide says, in case it runs early
+pub const RECORDING_PADDING_BEFORE: Duration = Duration::from_secs(60);
+
+/// Recording continues this long after the guide says the program ends
+pub const RECORDING_PADDING_AFTER: Duration = Duration::from_secs(2 * 60);
+
+/// Minimum recorded time between two progress events
+const PROGRESS_INTERVAL_SECS: u64 = 5;
+
+/// When a recording starts and how long it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordingWindow {
+    /// Seconds until capture should start
+    pub wait_secs: u64,
+    pub duration_secs: u64,
+}
+
+/// Window for recording a program airing `start..end` (unix seconds), padded
+/// on both sides. A program already on air is recorded from `now`; None once
+/// it (and its padding) is over.
+pub fn recording_window(
+    start: i64,
+    end: i64,
+    now: i64,
+    padding_before: Duration,
+    padding_after: Duration,
+) -> Option<RecordingWindow> {
+    let from = (start - padding_before.as_secs() as i64).max(now);
+    let until = end + padding_after.as_secs() as i64;
+    if until <= from {
+        return None;
+    }
+
+    Some(RecordingWindow {
+        wait_secs: (from - now) as u64,
+        duration_secs: (until - from) as u64,
+    })
+}
+
+/// ffmpeg arguments copying `stream_url` into an MPEG-TS file at `output`
+/// for `duration_secs`, with machine-readable progress on stdout
+pub fn ffmpeg_record_args(stream_url: &str, duration_secs: u64, output: &Path) -> Vec<String> {
+    [
+        "-hide_banner",
+        "-nostdin",
+        "-loglevel",
+        "error",
+        "-nostats",
+        "-progress",
+        "pipe:1",
+        "-y",
+        "-i",
+        stream_url,
+        "-t",
+        &duration_secs.to_string(),
+        "-c",
+        "copy",
+        "-f",
+        "mpegts",
+        &output.to_string_lossy(),
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect()
+}
+
+/// Seconds recorded so far, from one line of ffmpeg's `-progress` output
+fn parse_progress_secs(line: &str) -> Option<u64> {
+    let (key, value) = line.trim().split_once('=')?;
+    match key {
+        // Both are in microseconds; out_time_ms is the older, misnamed key
+        "out_time_us" | "out_time_ms" => {
+            let micros = value.parse::<i64>().ok()?;
+            Some((micros.max(0) / 1_000_000) as u64)
+        }
+        _ => None,
+    }
+}
+
+/// File name for a recording, e.g. "News - BBC One - 2026-10-16 1800.ts"
+pub fn recording_file_name(channel_name: &str, title: &str, start: i64) -> String {
+    let date = chrono::DateTime::from_timestamp(start, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H%M").to_string())
+        .unwrap_or_default();
+    let name = format!("{} - {} - {}", title.trim(), channel_name.trim(), date);
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    format!("{}.ts", name)
+}
+
+/// Folder recordings are saved to, next to torrent downloads
+pub fn recordings_dir() -> PathBuf {
+    dirs::download_dir()
+        .unwrap_or_else(|| PathBuf::from("downloads"))
+        .join("StreamGo")
+        .join("Recordings")
+}
+
+/// Whether an `ffmpeg` binary can be run
+pub async fn ffmpeg_available() -> bool {
+    tokio::process::Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingState {
+    Scheduled,
+    Recording,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Progress of a program recording, sent as `recording-progress`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RecordingProgress {
+    pub program_id: i64,
+    pub channel_id: String,
+    pub title: String,
+    pub state: RecordingState,
+    pub recorded_secs: u64,
+    pub duration_secs: u64,
+    pub file_path: String,
+    pub error: Option<String>,
+}
+
+/// A program to record from a channel's stream
+#[derive(Debug, Clone)]
+pub struct RecordingJob {
+    pub program_id: i64,
+    pub channel_id: String,
+    pub title: String,
+    pub stream_url: String,
+    pub output: PathBuf,
+    pub window: RecordingWindow,
+}
+
+/// Tracks scheduled and running recordings so they can be cancelled
+#[derive(Default)]
+pub struct RecordingManager {
+    active: Mutex<HashMap<i64, Arc<Notify>>>,
+}
+
+impl RecordingManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedule `job` in the background. Returns false if the program is
+    /// already being recorded.
+    pub fn start(
+        self: &Arc<Self>,
+        job: RecordingJob,
+        db: Arc<Mutex<Database>>,
+        events: EventBus,
+    ) -> bool {
+        let cancel = Arc::new(Notify::new());
+        {
+            let Ok(mut active) = self.active.lock() else {
+                return false;
+            };
+            if active.contains_key(&job.program_id) {
+                return false;
+            }
+            active.insert(job.program_id, cancel.clone());
+        }
+
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let program_id = job.program_id;
+            run_recording(job, &cancel, db, &events).await;
+            if let Ok(mut active) = manager.active.lock() {
+                active.remove(&program_id);
+            }
+        });
+        true
+    }
+
+    /// Stop a scheduled or running recording, discarding what was captured.
+    /// Returns false if the program isn't being recorded.
+    pub fn cancel(&self, program_id: i64) -> bool {
+        match self.active.lock() {
+            Ok(active) => match active.get(&program_id) {
+                Some(cancel) => {
+                    cancel.notify_one();
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        }
+    }
+}
+
+async fn run_recording(
+    job: RecordingJob,
+    cancel: &Notify,
+    db: Arc<Mutex<Database>>,
+    events: &EventBus,
+) {
+    let mut progress = RecordingProgress {
+        program_id: job.program_id,
+        channel_id: job.channel_id.clone(),
+        title: job.title.clone(),
+        state: RecordingState::Scheduled,
+        recorded_secs: 0,
+        duration_secs: job.window.duration_secs,
+        file_path: job.output.to_string_lossy().to_string(),
+        error: None,
+    };
+    events.emit(AppEvent::RecordingProgress(progress.clone()));
+
+    tokio::select! {
+        _ = tokio::time::sleep(Duration::from_secs(job.window.wait_secs)) => {}
+        _ = cancel.notified() => {
+            progress.state = RecordingState::Cancelled;
+            events.emit(AppEvent::RecordingProgress(progress));
+            return;
+        }
+    }
+
+    tracing::info!(
+        channel_id = %job.channel_id,
+        title = %job.title,
+        duration_secs = job.window.duration_secs,
+        "Recording started"
+    );
+    progress.state = RecordingState::Recording;
+    events.emit(AppEvent::RecordingProgress(progress.clone()));
+
+    let result = match capture(&job, cancel, events, &mut progress).await {
+        Ok(true) => {
+            let _ = tokio::fs::remove_file(&job.output).await;
+            Ok(RecordingState::Cancelled)
+        }
+        Ok(false) => add_to_library(&job, db)
+            .await
+            .map(|_| RecordingState::Completed),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(state) => {
+            tracing::info!(title = %job.title, state = ?state, "Recording finished");
+            progress.state = state;
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, title = %job.title, "Recording failed");
+            progress.state = RecordingState::Failed;
+            progress.error = Some(e.to_string());
+        }
+    }
+    events.emit(AppEvent::RecordingProgress(progress));
+}
+
+/// Run ffmpeg for the job, emitting progress. Returns true if cancelled.
+async fn capture(
+    job: &RecordingJob,
+    cancel: &Notify,
+    events: &EventBus,
+    progress: &mut RecordingProgress,
+) -> Result<bool> {
+    if let Some(dir) = job.output.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+
+    let mut child = tokio::process::Command::new("ffmpeg")
+        .args(ffmpeg_record_args(
+            &job.stream_url,
+            job.window.duration_secs,
+            &job.output,
+        ))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow!("Failed to run ffmpeg: {}. Is FFmpeg installed?", e))?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow!("ffmpeg stdout unavailable"))?;
+    // Drain stderr as we go so ffmpeg never blocks on a full pipe
+    let stderr = child.stderr.take();
+    let stderr_task = tokio::spawn(async move {
+        let mut text = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut text).await;
+        }
+        text
+    });
+
+    let mut lines = BufReader::new(stdout).lines();
+    let mut reported_secs = 0;
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line? {
+                Some(line) => {
+                    let Some(secs) = parse_progress_secs(&line) else {
+                        continue;
+                    };
+                    if secs >= reported_secs + PROGRESS_INTERVAL_SECS {
+                        reported_secs = secs;
+                        progress.recorded_secs = secs;
+                        events.emit(AppEvent::RecordingProgress(progress.clone()));
+                    }
+                }
+                None => break,
+            },
+            _ = cancel.notified() => {
+                child.kill().await?;
+                return Ok(true);
+            }
+        }
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        let stderr = stderr_task.await.unwrap_or_default();
+        return Err(anyhow!("ffmpeg exited with {}: {}", status, stderr.trim()));
+    }
+    progress.recorded_secs = progress.duration_secs;
+    Ok(false)
+}
+
+async fn add_to_library(job: &RecordingJob, db: Arc<Mutex<Database>>) -> Result<()> {
+    let mut file = LocalMediaScanner::new(vec![])
+        .scan_file(&job.output)
+        .await?;
+    file.title = job.title.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let db = db
+            .lock()
+            .map_err(|e| anyhow!("Database lock poisoned: {}", e))?;
+        db.upsert_local_media_file(&file)
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_window() {
+        let before = Duration::from_secs(60);
+        let after = Duration::from_secs(120);
+
+        // Upcoming program: wait until just before it starts
+        assert_eq!(
+            recording_window(10_000, 13_600, 7_000, before, after),
+            Some(RecordingWindow {
+                wait_secs: 2_940,
+                duration_secs: 3_780,
+            })
+        );
+        // Already on air: start now and record the rest
+        assert_eq!(
+            recording_window(10_000, 13_600, 12_000, before, after),
+            Some(RecordingWindow {
+                wait_secs: 0,
+                duration_secs: 1_720,
+            })
+        );
+        // Over, padding included
+        assert_eq!(
+            recording_window(10_000, 13_600, 13_720, before, after),
+            None
+        );
+        assert_eq!(recording_window(10_000, 9_000, 5_000, before, after), None);
+    }
+
+    #[test]
+    fn test_ffmpeg_record_args() {
+        let args = ffmpeg_record_args(
+            "https://live.example.com/bbc-one.m3u8",
+            3_780,
+            Path::new("/recordings/News.ts"),
+        );
+        let position = |flag: &str| args.iter().position(|a| a == flag).unwrap();
+
+        assert_eq!(
+            args[position("-i") + 1],
+            "https://live.example.com/bbc-one.m3u8"
+        );
+        assert_eq!(args[position("-t") + 1], "3780");
+        assert_eq!(args[position("-c") + 1], "copy");
+        assert_eq!(args[position("-progress") + 1], "pipe:1");
+        // Duration applies to the output, so it comes after the input
+        assert!(position("-t") > position("-i"));
+        assert_eq!(args.last().unwrap(), "/recordings/News.ts");
+    }
+
+    #[test]
+    fn test_parse_progress_and_file_name() {
+        assert_eq!(parse_progress_secs("out_time_us=12500000"), Some(12));
+        assert_eq!(parse_progress_secs("out_time_ms=61000000\n"), Some(61));
+        assert_eq!(parse_progress_secs("out_time_us=N/A"), None);
+        assert_eq!(parse_progress_secs("progress=continue"), None);
+
+        assert_eq!(
+            recording_file_name("BBC One", "News: Live/Late", 1_760_637_600),
+            "News_ Live_Late - BBC One - 2025-10-16 1800.ts"
+        );
+    }
+}
@@ -0,0 +1,41 @@
+/**
+ * Secrets
+ *
+ * Credentials kept in the OS keyring (Keychain, Credential Manager or the
+ * Secret Service) instead of the database. All calls are blocking.
+ */
+use anyhow::{anyhow, Result};
+
+const KEYRING_SERVICE: &str = "StreamGo";
+
+/// Keyring entry holding the OpenSubtitles session token
+pub const OPENSUBTITLES_TOKEN: &str = "opensubtitles_token";
+
+fn entry(name: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, name)
+        .map_err(|e| anyhow!("Failed to open keyring entry {}: {}", name, e))
+}
+
+/// The secret stored under `name`, if any
+pub fn get(name: &str) -> Result<Option<String>> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(anyhow!("Failed to read {} from the keyring: {}", name, e)),
+    }
+}
+
+/// Store `secret` under `name`, replacing any earlier one
+pub fn set(name: &str, secret: &str) -> Result<()> {
+    entry(name)?
+        .set_password(secret)
+        .map_err(|e| anyhow!("Failed to store {} in the keyring: {}", name, e))
+}
+
+/// Remove the secret stored under `name`; removing a missing one is fine
+pub fn delete(name: &str) -> Result<()> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(anyhow!("Failed to remove {} from the keyring: {}", name, e)),
+    }
+}
@@ -0,0 +1,330 @@
+/**
+ * Stream Filtering
+ *
+ * Parse size, seeder and resolution hints out of addon stream labels and
+ * filter/rank aggregated streams against user limits
+ */
+use crate::addon_protocol::Stream;
+use crate::models::{StreamSourceType, StreamWithSource};
+use crate::quality::labels_quality;
+use serde::{Deserialize, Serialize};
+
+/// Limits applied to aggregated streams.
+/// Streams whose size, seeders or resolution can't be determined are kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamFilter {
+    #[serde(default)]
+    pub max_size_bytes: Option<u64>,
+    /// Highest vertical resolution allowed (e.g. 1080)
+    #[serde(default)]
+    pub max_resolution: Option<i32>,
+    #[serde(default)]
+    pub min_seeders: Option<u32>,
+    #[serde(default = "default_allow_p2p")]
+    pub allow_p2p: bool,
+}
+
+fn default_allow_p2p() -> bool {
+    true
+}
+
+impl Default for StreamFilter {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: None,
+            max_resolution: None,
+            min_seeders: None,
+            allow_p2p: true,
+        }
+    }
+}
+
+impl StreamFilter {
+    pub fn matches(&self, stream: &StreamWithSource) -> bool {
+        if !self.allow_p2p && stream.source_type == StreamSourceType::Torrent {
+            return false;
+        }
+        if let (Some(max), Some(size)) = (self.max_size_bytes, stream.size_bytes) {
+            if size > max {
+                return false;
+            }
+        }
+        if let (Some(min), Some(seeders)) = (self.min_seeders, stream.seeders) {
+            if seeders < min {
+                return false;
+            }
+        }
+        if let Some(max) = self.max_resolution {
+            let resolution = stream_resolution(stream);
+            if resolution > 0 && resolution > max {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Drop streams outside the filter and order the rest by resolution,
+/// then seeders. The sort is stable, so addon priority order breaks ties.
+pub fn filter_and_rank(
+    streams: Vec<StreamWithSource>,
+    filter: &StreamFilter,
+) -> Vec<StreamWithSource> {
+    let mut streams: Vec<StreamWithSource> =
+        streams.into_iter().filter(|s| filter.matches(s)).collect();
+    streams.sort_by(|a, b| {
+        stream_resolution(b)
+            .cmp(&stream_resolution(a))
+            .then_with(|| b.seeders.unwrap_or(0).cmp(&a.seeders.unwrap_or(0)))
+    });
+    streams
+}
+
+/// Highest resolution mentioned in the stream's labels, 0 if unknown
+pub fn stream_resolution(stream: &StreamWithSource) -> i32 {
+    labels_quality([&stream.name, &stream.title, &stream.description])
+}
+
+/// Video size from the `videoSize` hint, falling back to the stream labels
+pub fn stream_size_bytes(stream: &Stream) -> Option<u64> {
+    stream.behaviorHints.videoSize.or_else(|| {
+        [&stream.title, &stream.description, &stream.name]
+            .into_iter()
+            .flatten()
+            .find_map(|text| parse_size_bytes(text))
+    })
+}
+
+/// Seeder count from the stream labels
+pub fn stream_seeders(stream: &Stream) -> Option<u32> {
+    [&stream.title, &stream.description, &stream.name]
+        .into_iter()
+        .flatten()
+        .find_map(|text| parse_seeders(text))
+}
+
+/// Key identifying the same stream across addons. Torrents are keyed by
+/// infohash, since addons list the same magnet with different trackers;
+/// everything else by its normalized URL.
+pub fn stream_dedup_key(stream: &Stream) -> String {
+    match magnet_infohash(&stream.url) {
+        Some(hash) => format!("btih:{}", hash),
+        None => stream.url.trim().to_lowercase(),
+    }
+}
+
+/// Lowercase hex infohash from a `btih:` URN, accepting both the 40-char
+/// hex and the 32-char base32 forms
+fn magnet_infohash(url: &str) -> Option<String> {
+    let lower = url.to_ascii_lowercase();
+    let start = lower.find("btih:")? + "btih:".len();
+    let hash: String = lower[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect();
+
+    match hash.len() {
+        40 if hash.chars().all(|c| c.is_ascii_hexdigit()) => Some(hash),
+        32 => base32_to_hex(&hash),
+        _ => None,
+    }
+}
+
+/// Decode RFC 4648 base32 (case-insensitive, no padding) to lowercase hex
+fn base32_to_hex(encoded: &str) -> Option<String> {
+    let mut bits: u64 = 0;
+    let mut bit_count = 0;
+    let mut hex = String::new();
+
+    for c in encoded.chars() {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u64 - 'A' as u64,
+            c @ '2'..='7' => c as u64 - '2' as u64 + 26,
+            _ => return None,
+        };
+        bits = (bits << 5) | value;
+        bit_count += 5;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            hex.push_str(&format!("{:02x}", (bits >> bit_count) & 0xff));
+        }
+    }
+
+    Some(hex)
+}
+
+/// First human readable size in a label, e.g. "💾 1.4 GB" -> bytes
+pub fn parse_size_bytes(text: &str) -> Option<u64> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == ',') {
+            i += 1;
+        }
+        let number: String = chars[start..i]
+            .iter()
+            .map(|c| if *c == ',' { '.' } else { *c })
+            .collect();
+
+        let mut j = i;
+        while j < chars.len() && chars[j] == ' ' {
+            j += 1;
+        }
+        let unit_start = j;
+        while j < chars.len() && chars[j].is_ascii_alphabetic() {
+            j += 1;
+        }
+        let unit = chars[unit_start..j]
+            .iter()
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        let multiplier: u64 = match unit.as_str() {
+            "tb" | "tib" => 1 << 40,
+            "gb" | "gib" => 1 << 30,
+            "mb" | "mib" => 1 << 20,
+            "kb" | "kib" => 1 << 10,
+            _ => continue,
+        };
+        if let Ok(value) = number.parse::<f64>() {
+            return Some((value * multiplier as f64) as u64);
+        }
+    }
+
+    None
+}
+
+/// Seeder count from a label, e.g. "👤 42" (Torrentio) or "Seeders: 42"
+pub fn parse_seeders(text: &str) -> Option<u32> {
+    let lower = text.to_lowercase();
+    ["👤", "seeders", "seeds"].iter().find_map(|marker| {
+        let pos = lower.find(marker)?;
+        let digits: String = lower[pos + marker.len()..]
+            .trim_start_matches(|c: char| c == ':' || c.is_whitespace())
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        digits.parse().ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(title: &str, source_type: StreamSourceType) -> StreamWithSource {
+        StreamWithSource {
+            url: format!("https://example.com/{}", title.len()),
+            title: Some(title.to_string()),
+            name: None,
+            description: None,
+            addon_id: "test".to_string(),
+            addon_name: "Test".to_string(),
+            source_type,
+            size_bytes: parse_size_bytes(title),
+            seeders: parse_seeders(title),
+            not_web_ready: false,
+            subtitles: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_stream_dedup_key() {
+        let stream = |url: &str| Stream {
+            url: url.to_string(),
+            title: None,
+            name: None,
+            description: None,
+            behaviorHints: Default::default(),
+            subtitles: Vec::new(),
+        };
+        let hash = "dd8255ecdc7ca55fb0bbf81323d87062db1f6d1c";
+
+        let first = stream(&format!(
+            "magnet:?xt=urn:btih:{}&dn=Movie&tr=udp%3A%2F%2Ftracker.one%3A1337",
+            hash
+        ));
+        let second = stream(&format!(
+            "magnet:?xt=urn:btih:{}&tr=udp%3A%2F%2Ftracker.two%3A6969&tr=udp%3A%2F%2Fthree%3A80",
+            hash.to_uppercase()
+        ));
+        assert_eq!(stream_dedup_key(&first), format!("btih:{}", hash));
+        assert_eq!(stream_dedup_key(&first), stream_dedup_key(&second));
+
+        // The base32 form of the same infohash
+        let base32 = stream("magnet:?xt=urn:btih:3WBFL3G4PSSV7MF37AJSHWDQMLNR63I4&dn=Movie");
+        assert_eq!(stream_dedup_key(&base32), stream_dedup_key(&first));
+
+        let other = stream("magnet:?xt=urn:btih:08ada5a7a6183aae1e09d831df6748d566095a10");
+        assert_ne!(stream_dedup_key(&other), stream_dedup_key(&first));
+
+        // Direct links keep URL-based dedup
+        let direct = stream("  https://CDN.example.com/movie.mp4 ");
+        assert_eq!(
+            stream_dedup_key(&direct),
+            "https://cdn.example.com/movie.mp4"
+        );
+    }
+
+    #[test]
+    fn test_parse_size_and_seeders() {
+        assert_eq!(
+            parse_size_bytes("Movie 1080p\n👤 12 💾 1.5 GB ⚙️ ThePirateBay"),
+            Some(1_610_612_736)
+        );
+        assert_eq!(parse_size_bytes("700MB x264"), Some(734_003_200));
+        assert_eq!(parse_size_bytes("Movie 2020 1080p x265 10bit"), None);
+
+        assert_eq!(parse_seeders("Movie\n👤 12 💾 1.5 GB"), Some(12));
+        assert_eq!(parse_seeders("Seeders: 340"), Some(340));
+        assert_eq!(parse_seeders("Direct link"), None);
+    }
+
+    #[test]
+    fn test_filter_excludes_large_and_poorly_seeded() {
+        let streams = vec![
+            stream("Remux 2160p 👤 500 💾 60 GB", StreamSourceType::Torrent),
+            stream("WEB 1080p 👤 3 💾 2 GB", StreamSourceType::Torrent),
+            stream("WEB 1080p 👤 80 💾 2.5 GB", StreamSourceType::Torrent),
+            stream("HDTV 720p 💾 900 MB", StreamSourceType::Direct),
+        ];
+        let filter = StreamFilter {
+            max_size_bytes: Some(10 << 30),
+            min_seeders: Some(10),
+            ..Default::default()
+        };
+
+        let filtered = filter_and_rank(streams, &filter);
+        let titles: Vec<_> = filtered.iter().filter_map(|s| s.title.as_deref()).collect();
+        assert_eq!(
+            titles,
+            vec!["WEB 1080p 👤 80 💾 2.5 GB", "HDTV 720p 💾 900 MB"]
+        );
+    }
+
+    #[test]
+    fn test_filter_resolution_and_p2p() {
+        let streams = vec![
+            stream("Movie 2160p", StreamSourceType::Direct),
+            stream("Movie 1080p 👤 50", StreamSourceType::Torrent),
+            stream("Movie 720p", StreamSourceType::Debrid),
+            stream("Movie", StreamSourceType::Direct),
+        ];
+        let filter = StreamFilter {
+            max_resolution: Some(1080),
+            allow_p2p: false,
+            ..Default::default()
+        };
+
+        let filtered = filter_and_rank(streams, &filter);
+        let titles: Vec<_> = filtered.iter().filter_map(|s| s.title.as_deref()).collect();
+        assert_eq!(titles, vec!["Movie 720p", "Movie"]);
+    }
+}
@@ -1,24 +1,27 @@
 use anyhow::{Context, Result};
 use axum::{
-    extract::{Path, State},
+    extract::{ConnectInfo, Path, Request, State},
     http::{header, HeaderMap, StatusCode},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::{delete, get, post},
     Json, Router,
 };
 use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
 use librqbit::{
     api::TorrentIdOrHash, SessionOptions, SessionPersistenceConfig, AddTorrentOptions, Session as RqbitSession,
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     net::SocketAddr,
     path::PathBuf,
-    sync::Arc,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
 };
 use tokio::{
-    sync::RwLock,
+    sync::{Notify, RwLock},
 };
 use tower_http::cors::CorsLayer;
 use tracing::{error, info, warn};
@@ -60,12 +63,118 @@ pub struct StreamResponse {
     pub play_url: String,
 }
 
+/// Torrents allowed to download at once unless the user changes it
+pub const DEFAULT_MAX_ACTIVE_TORRENTS: usize = 3;
+
+/// Active and queued torrent counts, see `TorrentQueue`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TorrentQueueStatus {
+    pub active: usize,
+    pub queued: usize,
+    pub max_active: usize,
+}
+
+/// Download slots for torrents, keyed by `queue_key`. Torrents past
+/// `max_active` wait in line until a slot frees; the one being watched
+/// skips ahead of the rest.
+#[derive(Debug)]
+pub struct TorrentQueue {
+    max_active: usize,
+    active: Vec<String>,
+    queued: VecDeque<String>,
+    watching: Option<String>,
+}
+
+impl TorrentQueue {
+    pub fn new(max_active: usize) -> Self {
+        Self {
+            max_active: max_active.max(1),
+            active: Vec::new(),
+            queued: VecDeque::new(),
+            watching: None,
+        }
+    }
+
+    /// Give `key` a slot if one is free, otherwise queue it. Returns
+    /// whether it holds a slot.
+    pub fn request(&mut self, key: &str) -> bool {
+        if self.is_active(key) {
+            return true;
+        }
+        if self.active.len() < self.max_active {
+            self.queued.retain(|queued| queued != key);
+            self.active.push(key.to_string());
+            return true;
+        }
+        if !self.queued.iter().any(|queued| queued == key) {
+            self.queued.push_back(key.to_string());
+        }
+        false
+    }
+
+    pub fn is_active(&self, key: &str) -> bool {
+        self.active.iter().any(|active| active == key)
+    }
+
+    /// Release `key`'s slot, or drop it from the queue, and return the
+    /// queued torrents that start in the freed slots
+    pub fn finish(&mut self, key: &str) -> Vec<String> {
+        self.active.retain(|active| active != key);
+        self.queued.retain(|queued| queued != key);
+        if self.watching.as_deref() == Some(key) {
+            self.watching = None;
+        }
+        self.promote()
+    }
+
+    /// Mark the torrent being watched so it gets the next free slot
+    pub fn set_watching(&mut self, key: &str) {
+        self.watching = Some(key.to_string());
+    }
+
+    /// Change the limit and return the queued torrents that start because
+    /// of it. Lowering it doesn't stop running downloads.
+    pub fn set_max_active(&mut self, max_active: usize) -> Vec<String> {
+        self.max_active = max_active.max(1);
+        self.promote()
+    }
+
+    pub fn status(&self) -> TorrentQueueStatus {
+        TorrentQueueStatus {
+            active: self.active.len(),
+            queued: self.queued.len(),
+            max_active: self.max_active,
+        }
+    }
+
+    fn promote(&mut self) -> Vec<String> {
+        let mut started = Vec::new();
+        while self.active.len() < self.max_active {
+            let watched = self
+                .watching
+                .as_ref()
+                .and_then(|watching| self.queued.iter().position(|queued| queued == watching));
+            let Some(next) = self.queued.remove(watched.unwrap_or(0)) else {
+                break;
+            };
+            self.active.push(next.clone());
+            started.push(next);
+        }
+        started
+    }
+}
+
 pub struct StreamingServer {
     session: std::sync::Arc<RqbitSession>,
     port: u16,
     base_url: String,
     active_streams: Arc<RwLock<HashMap<String, StreamInfo>>>,
+    /// Local files served at `/local/{id}`, e.g. for casting
+    local_files: Arc<RwLock<HashMap<String, LocalFile>>>,
     download_dir: PathBuf,
+    queue: Arc<Mutex<TorrentQueue>>,
+    /// Wakes `add_torrent` calls waiting in the queue when slots change hands
+    slot_freed: Arc<Notify>,
 }
 
 impl StreamingServer {
@@ -81,7 +190,29 @@ impl StreamingServer {
             listen_port_range: Some(6881..6891),
             ..Default::default()
         };
+        Self::with_session_options(download_dir, port, opts).await
+    }
+
+    /// Server whose torrent session has no DHT, listening port or
+    /// persistence, so tests don't touch the network or leave files behind
+    #[cfg(test)]
+    pub(crate) async fn new_offline(download_dir: PathBuf, port: u16) -> Result<Self> {
+        let opts = SessionOptions {
+            disable_dht: true,
+            disable_dht_persistence: true,
+            persistence: None,
+            fastresume: false,
+            listen_port_range: None,
+            ..Default::default()
+        };
+        Self::with_session_options(download_dir, port, opts).await
+    }
 
+    async fn with_session_options(
+        download_dir: PathBuf,
+        port: u16,
+        opts: SessionOptions,
+    ) -> Result<Self> {
         let session = RqbitSession::new_with_opts(download_dir.clone(), opts)
             .await
             .context("Failed to create torrent session")?;
@@ -93,12 +224,27 @@ impl StreamingServer {
             port,
             base_url,
             active_streams: Arc::new(RwLock::new(HashMap::new())),
+            local_files: Arc::new(RwLock::new(HashMap::new())),
             download_dir,
+            queue: Arc::new(Mutex::new(TorrentQueue::new(DEFAULT_MAX_ACTIVE_TORRENTS))),
+            slot_freed: Arc::new(Notify::new()),
         })
     }
 
+    /// Listen on all interfaces so cast devices can fetch `/local` files;
+    /// every other route only answers this machine
     pub async fn start(&self) -> Result<()> {
-        let app = Router::new()
+        let addr = SocketAddr::from(([0, 0, 0, 0], self.port));
+        info!("Streaming server listening on {}", addr);
+
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .context("Failed to bind streaming server")?;
+        self.serve(listener).await
+    }
+
+    pub(crate) async fn serve(&self, listener: tokio::net::TcpListener) -> Result<()> {
+        let loopback = Router::new()
             .route("/streams", post(add_stream))
             .route("/streams", get(list_streams))
             .route("/streams/:id", get(get_stream_info))
@@ -106,24 +252,40 @@ impl StreamingServer {
             .route("/streams/:id/play", get(play_stream))
             .route("/streams/:id/file/:file_index", get(stream_file))
             .route("/health", get(health_check))
+            .route_layer(middleware::from_fn(loopback_only));
+        let app = Router::new()
+            .route("/local/:id", get(stream_local_file))
+            .merge(loopback)
             .layer(CorsLayer::permissive())
             .with_state(Arc::new(self.clone()));
 
-        let addr = SocketAddr::from(([127, 0, 0, 1], self.port));
-        info!("Streaming server listening on {}", addr);
-
-        let listener = tokio::net::TcpListener::bind(addr)
-            .await
-            .context("Failed to bind streaming server")?;
-
-        axum::serve(listener, app)
-            .await
-            .context("Streaming server error")?;
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .context("Streaming server error")?;
 
         Ok(())
     }
 
+    /// Add a torrent and start downloading it. When `max_active_torrents`
+    /// are already downloading this waits in the queue for a slot.
     pub async fn add_torrent(&self, magnet_or_url: &str, file_index: Option<usize>) -> Result<StreamInfo> {
+        let key = queue_key(magnet_or_url);
+        self.acquire_slot(&key).await;
+        let result = self.start_torrent(magnet_or_url, file_index).await;
+        if result.is_err() {
+            self.release_slot(&key);
+        }
+        result
+    }
+
+    async fn start_torrent(
+        &self,
+        magnet_or_url: &str,
+        file_index: Option<usize>,
+    ) -> Result<StreamInfo> {
         info!("Adding torrent: {}", magnet_or_url);
 
         let opts = AddTorrentOptions {
@@ -148,6 +310,17 @@ impl StreamingServer {
 
         let info_hash = handle.info_hash().as_string();
         let stats = handle.stats();
+
+        // The slot goes to the next queued torrent once this one is downloaded
+        let server = self.clone();
+        let key = queue_key(magnet_or_url);
+        let completed = handle.clone();
+        tokio::spawn(async move {
+            if completed.wait_until_completed().await.is_ok() {
+                info!("Torrent finished downloading: {}", key);
+                server.release_slot(&key);
+            }
+        });
         
         let (name, files) = handle.with_metadata(|metadata| {
             let name = metadata.name.clone().unwrap_or_else(|| "Unknown".to_string());
@@ -213,9 +386,181 @@ impl StreamingServer {
             }
         }
 
-        self.active_streams.write().await.remove(id);
+        if let Some(removed) = self.active_streams.write().await.remove(id) {
+            self.release_slot(&queue_key(removed.magnet.as_deref().unwrap_or(id)));
+        }
         Ok(())
     }
+
+    /// Limit how many torrents download at once. Raising it starts queued
+    /// torrents right away.
+    pub fn set_max_active_torrents(&self, max_active: usize) {
+        let started = self.queue().set_max_active(max_active);
+        if !started.is_empty() {
+            self.slot_freed.notify_waiters();
+        }
+    }
+
+    /// Let the torrent being watched skip ahead of the rest of the queue
+    pub fn set_watching(&self, magnet_or_url: &str) {
+        self.queue().set_watching(&queue_key(magnet_or_url));
+    }
+
+    pub fn queue_status(&self) -> TorrentQueueStatus {
+        self.queue().status()
+    }
+
+    fn queue(&self) -> MutexGuard<'_, TorrentQueue> {
+        self.queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    async fn acquire_slot(&self, key: &str) {
+        let mut ticket = QueueTicket {
+            server: self,
+            key,
+            waiting: true,
+        };
+        let mut logged = false;
+        loop {
+            let notified = self.slot_freed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.queue().request(key) {
+                ticket.waiting = false;
+                return;
+            }
+            if !logged {
+                info!("Torrent queued until a download slot frees: {}", key);
+                logged = true;
+            }
+            notified.await;
+        }
+    }
+
+    fn release_slot(&self, key: &str) {
+        let started = self.queue().finish(key);
+        for next in &started {
+            info!("Starting queued torrent: {}", next);
+        }
+        if !started.is_empty() {
+            self.slot_freed.notify_waiters();
+        }
+    }
+
+    /// Serve a local file at `/local/{id}` and return its URL. Registering
+    /// the same file again returns the same URL. Files nobody fetched for
+    /// `LOCAL_FILE_IDLE_TTL` are dropped. Callers must validate the path
+    /// first, see `servable_local_path`.
+    pub async fn register_local_file(&self, path: PathBuf) -> String {
+        let mut files = self.local_files.write().await;
+        files.retain(|_, file| !file.is_expired());
+        let existing = files
+            .iter_mut()
+            .find(|(_, registered)| registered.path == path)
+            .map(|(id, registered)| {
+                registered.last_used = Instant::now();
+                id.clone()
+            });
+        let id = existing.unwrap_or_else(|| {
+            let id = uuid::Uuid::new_v4().to_string();
+            info!("Serving local file {:?} as {}", path, id);
+            let last_used = Instant::now();
+            files.insert(id.clone(), LocalFile { path, last_used });
+            id
+        });
+        local_file_url(&self.base_url, &id)
+    }
+
+    /// Path registered as `id`, unless it expired; counts as a use
+    async fn local_file(&self, id: &str) -> Option<PathBuf> {
+        let mut files = self.local_files.write().await;
+        let file = files.get_mut(id)?;
+        if file.is_expired() {
+            files.remove(id);
+            return None;
+        }
+        file.last_used = Instant::now();
+        Some(file.path.clone())
+    }
+}
+
+/// How long a registered local file stays servable without being fetched
+const LOCAL_FILE_IDLE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+struct LocalFile {
+    path: PathBuf,
+    last_used: Instant,
+}
+
+impl LocalFile {
+    fn is_expired(&self) -> bool {
+        self.last_used.elapsed() > LOCAL_FILE_IDLE_TTL
+    }
+}
+
+/// Rejects requests from other machines
+async fn loopback_only(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if peer.ip().is_loopback() {
+        next.run(request).await
+    } else {
+        StatusCode::FORBIDDEN.into_response()
+    }
+}
+
+/// Takes a torrent back out of the queue if its `add_torrent` call is
+/// dropped while waiting, e.g. when an HTTP client disconnects
+struct QueueTicket<'a> {
+    server: &'a StreamingServer,
+    key: &'a str,
+    waiting: bool,
+}
+
+impl Drop for QueueTicket<'_> {
+    fn drop(&mut self) {
+        if self.waiting {
+            self.server.release_slot(self.key);
+        }
+    }
+}
+
+/// Key of a torrent in the `TorrentQueue`: the info hash of a magnet link,
+/// so the same torrent with other trackers or a base32 hash shares a slot.
+/// Other URLs, such as `.torrent` files, are keyed as given.
+fn queue_key(magnet_or_url: &str) -> String {
+    librqbit::Magnet::parse(magnet_or_url)
+        .ok()
+        .and_then(|magnet| magnet.as_id20())
+        .map(|info_hash| info_hash.as_string())
+        .unwrap_or_else(|| magnet_or_url.to_string())
+}
+
+fn local_file_url(base_url: &str, id: &str) -> String {
+    format!("{}/local/{}", base_url, id)
+}
+
+/// Canonical path of `path` if it is a file inside one of `allowed_dirs`.
+/// Symlinks and `..` are resolved first so a path can't escape the
+/// allowed directories.
+pub fn servable_local_path(path: &std::path::Path, allowed_dirs: &[PathBuf]) -> Result<PathBuf> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("File not found: {}", path.display()))?;
+    if !canonical.is_file() {
+        anyhow::bail!("Not a file: {}", path.display());
+    }
+
+    let allowed = allowed_dirs
+        .iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .any(|dir| canonical.starts_with(dir));
+    if !allowed {
+        anyhow::bail!("{} is outside the scanned directories", path.display());
+    }
+    Ok(canonical)
 }
 
 impl Clone for StreamingServer {
@@ -225,7 +570,10 @@ impl Clone for StreamingServer {
             port: self.port,
             base_url: self.base_url.clone(),
             active_streams: Arc::clone(&self.active_streams),
+            local_files: Arc::clone(&self.local_files),
             download_dir: self.download_dir.clone(),
+            queue: Arc::clone(&self.queue),
+            slot_freed: Arc::clone(&self.slot_freed),
         }
     }
 }
@@ -300,7 +648,26 @@ async fn stream_file(
         .ok_or_else(|| AppError::NotFound("File not found in torrent".into()))?;
 
     let file_path = download_dir.join(&file_info.path);
+    serve_file(&file_path, &headers).await
+}
 
+async fn stream_local_file(
+    State(server): State<Arc<StreamingServer>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let file_path = server
+        .local_file(&id)
+        .await
+        .ok_or_else(|| AppError::NotFound("Local file not found".into()))?;
+    serve_file(&file_path, &headers).await
+}
+
+/// Respond with a file, or the single byte range asked for in its Range header
+async fn serve_file(
+    file_path: &std::path::Path,
+    headers: &HeaderMap,
+) -> Result<Response, AppError> {
     if !file_path.exists() {
         return Err(AppError::NotFound(format!(
             "File not found on disk: {:?}",
@@ -308,74 +675,98 @@ async fn stream_file(
         )));
     }
 
-    let mut file = tokio::fs::File::open(&file_path).await.map_err(|e| {
+    let mut file = tokio::fs::File::open(file_path).await.map_err(|e| {
         AppError::Internal(anyhow::anyhow!("Failed to open file: {:?}, error: {}", file_path, e))
     })?;
 
     let file_size = file.metadata().await?.len();
-    let mime_type = file_path_to_mime_str(&file_path);
+    let mime_type = file_path_to_mime_str(file_path);
 
-    let range_header = headers
+    let range = match headers
         .get(header::RANGE)
-        .and_then(|value| value.to_str().ok());
-
-    let (start, end) = if let Some(range_str) = range_header {
-        let (start, end) = parse_range_header(range_str, file_size)?;
-        (start, end)
-    } else {
-        (0, file_size - 1)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(range_str) => parse_range_header(range_str, file_size)?,
+        None => None,
+    };
+    let (start, len) = match range {
+        Some((start, end)) => (start, end - start + 1),
+        None => (0, file_size),
     };
 
-    let len = end - start + 1;
-
+    // Stream the bytes instead of buffering them; files can be several GB
     file.seek(std::io::SeekFrom::Start(start)).await?;
-    let mut buffer = vec![0; len as usize];
-    file.read_exact(&mut buffer).await?;
+    let body = axum::body::Body::from_stream(ReaderStream::new(file.take(len)));
 
-    let content_range = format!("bytes {}-{}/{}", start, end, file_size);
-
-    let response = Response::builder()
-        .status(StatusCode::PARTIAL_CONTENT)
-        .header(header::CONTENT_TYPE, mime_type)
-        .header(header::CONTENT_LENGTH, len)
-        .header(header::CONTENT_RANGE, content_range)
-        .header(header::ACCEPT_RANGES, "bytes")
-        .body(axum::body::Body::from(buffer))
+    let response = file_response(range, file_size, mime_type)
+        .body(body)
         .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to build response: {}", e)))?;
 
     Ok(response)
 }
 
-fn parse_range_header(range_str: &str, file_size: u64) -> Result<(u64, u64), AppError> {
-    let range = range_str.strip_prefix("bytes=").ok_or_else(|| {
-        AppError::BadRequest("Invalid range header format".into())
-    })?;
+/// Status and headers for sending the inclusive byte `range` of a file,
+/// or all of it (200) when `range` is `None`
+fn file_response(
+    range: Option<(u64, u64)>,
+    file_size: u64,
+    mime_type: &str,
+) -> axum::http::response::Builder {
+    let builder = Response::builder()
+        .header(header::CONTENT_TYPE, mime_type)
+        .header(header::ACCEPT_RANGES, "bytes");
+    match range {
+        Some((start, end)) => builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_LENGTH, end - start + 1)
+            .header(
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, end, file_size),
+            ),
+        None => builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_LENGTH, file_size),
+    }
+}
 
-    let parts: Vec<&str> = range.split('-').collect();
-    if parts.len() != 2 {
-        return Err(AppError::BadRequest("Invalid range header format".into()));
+/// Inclusive byte range from a Range header: `bytes=0-499`, `bytes=500-`
+/// or `bytes=-500` (the last 500 bytes). `None` means the whole file is
+/// sent instead, for multiple ranges or a unit other than bytes.
+fn parse_range_header(range_str: &str, file_size: u64) -> Result<Option<(u64, u64)>, AppError> {
+    let Some(range) = range_str.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if range.contains(',') {
+        return Ok(None);
     }
 
-    let start = parts[0].parse::<u64>().map_err(|_| {
-        AppError::BadRequest("Invalid start of range".into())
-    })?;
+    let (start, end) = range
+        .split_once('-')
+        .ok_or_else(|| AppError::BadRequest("Invalid range header format".into()))?;
+    let parse = |value: &str, part: &str| {
+        value
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| AppError::BadRequest(format!("Invalid {} of range", part)))
+    };
+    let last_byte = file_size.saturating_sub(1);
 
-    let end = if parts[1].is_empty() {
-        file_size - 1
+    let (start, end) = if start.trim().is_empty() {
+        let suffix = parse(end, "suffix")?;
+        if suffix == 0 {
+            return Err(AppError::RangeNotSatisfiable(file_size));
+        }
+        (file_size.saturating_sub(suffix), last_byte)
+    } else if end.trim().is_empty() {
+        (parse(start, "start")?, last_byte)
     } else {
-        parts[1].parse::<u64>().map_err(|_| {
-            AppError::BadRequest("Invalid end of range".into())
-        })?.min(file_size - 1)
+        (parse(start, "start")?, parse(end, "end")?.min(last_byte))
     };
 
-    if start > end {
-        return Err(AppError::RangeNotSatisfiable(format!(
-            "Invalid range: start > end ({} > {})",
-            start, end
-        )));
+    if file_size == 0 || start > end {
+        return Err(AppError::RangeNotSatisfiable(file_size));
     }
-
-    Ok((start, end))
+    Ok(Some((start, end)))
 }
 
 fn file_path_to_mime_str(path: &std::path::Path) -> &'static str {
@@ -409,11 +800,16 @@ enum AppError {
     Internal(anyhow::Error),
     NotFound(String),
     BadRequest(String),
-    RangeNotSatisfiable(String),
+    /// Requested range lies outside a file of this many bytes
+    RangeNotSatisfiable(u64),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let content_range = match &self {
+            AppError::RangeNotSatisfiable(file_size) => Some(format!("bytes */{}", file_size)),
+            _ => None,
+        };
         let (status, error_message) = match self {
             AppError::Internal(e) => {
                 error!("Internal server error: {:?}", e);
@@ -424,14 +820,20 @@ impl IntoResponse for AppError {
             }
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            AppError::RangeNotSatisfiable(msg) => (StatusCode::RANGE_NOT_SATISFIABLE, msg),
+            AppError::RangeNotSatisfiable(file_size) => (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                format!("Range not satisfiable for a file of {} bytes", file_size),
+            ),
         };
 
-        (
-            status,
-            Json(serde_json::json!({ "error": error_message })),
-        )
-            .into_response()
+        let mut response =
+            (status, Json(serde_json::json!({ "error": error_message }))).into_response();
+        if let Some(content_range) = content_range.and_then(|v| v.parse().ok()) {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_RANGE, content_range);
+        }
+        response
     }
 }
 
@@ -443,3 +845,210 @@ where
         AppError::Internal(err.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_servable_local_path_stays_in_allowed_dirs() {
+        let root = std::env::temp_dir().join(format!(
+            "streamgo_servable_local_path_{}",
+            uuid::Uuid::new_v4()
+        ));
+        let library = root.join("library");
+        std::fs::create_dir_all(&library).unwrap();
+        let movie = library.join("Movie.2020.mkv");
+        let outside = root.join("secret.txt");
+        std::fs::write(&movie, b"mkv").unwrap();
+        std::fs::write(&outside, b"secret").unwrap();
+        let allowed = vec![library.clone()];
+
+        assert_eq!(
+            servable_local_path(&movie, &allowed).unwrap(),
+            movie.canonicalize().unwrap()
+        );
+        // Traversal out of the scanned directory, directories and missing files
+        assert!(servable_local_path(&library.join("../secret.txt"), &allowed).is_err());
+        assert!(servable_local_path(&outside, &allowed).is_err());
+        assert!(servable_local_path(&library, &allowed).is_err());
+        assert!(servable_local_path(&library.join("missing.mkv"), &allowed).is_err());
+        assert!(servable_local_path(&movie, &[]).is_err());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_range_header() {
+        let parse = |range: &str| parse_range_header(range, 1000);
+        assert_eq!(parse("bytes=0-499").ok(), Some(Some((0, 499))));
+        assert_eq!(parse("bytes=500-").ok(), Some(Some((500, 999))));
+        assert_eq!(parse("bytes=-100").ok(), Some(Some((900, 999))));
+        assert_eq!(parse("bytes=900-5000").ok(), Some(Some((900, 999))));
+        assert_eq!(parse("bytes=-5000").ok(), Some(Some((0, 999))));
+
+        // Whole file for multiple ranges and other units
+        assert_eq!(parse("bytes=0-99,200-299").ok(), Some(None));
+        assert_eq!(parse("items=0-1").ok(), Some(None));
+
+        assert!(matches!(parse("bytes=abc-"), Err(AppError::BadRequest(_))));
+        assert!(matches!(parse("bytes=0-xyz"), Err(AppError::BadRequest(_))));
+        assert!(matches!(parse("bytes=100"), Err(AppError::BadRequest(_))));
+        assert!(matches!(
+            parse("bytes=1000-"),
+            Err(AppError::RangeNotSatisfiable(1000))
+        ));
+        assert!(matches!(
+            parse("bytes=500-100"),
+            Err(AppError::RangeNotSatisfiable(_))
+        ));
+        assert!(matches!(
+            parse_range_header("bytes=0-", 0),
+            Err(AppError::RangeNotSatisfiable(_))
+        ));
+    }
+
+    #[test]
+    fn test_file_response_headers() {
+        let partial = file_response(Some((100, 199)), 1000, "video/mp4")
+            .body(())
+            .unwrap();
+        assert_eq!(partial.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(partial.headers()[header::CONTENT_LENGTH], "100");
+        assert_eq!(
+            partial.headers()[header::CONTENT_RANGE],
+            "bytes 100-199/1000"
+        );
+        assert_eq!(partial.headers()[header::ACCEPT_RANGES], "bytes");
+        assert_eq!(partial.headers()[header::CONTENT_TYPE], "video/mp4");
+
+        let full = file_response(None, 1000, "video/mp4").body(()).unwrap();
+        assert_eq!(full.status(), StatusCode::OK);
+        assert_eq!(full.headers()[header::CONTENT_LENGTH], "1000");
+        assert!(full.headers().get(header::CONTENT_RANGE).is_none());
+        assert_eq!(full.headers()[header::ACCEPT_RANGES], "bytes");
+
+        let unsatisfiable = AppError::RangeNotSatisfiable(1000).into_response();
+        assert_eq!(unsatisfiable.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            unsatisfiable.headers()[header::CONTENT_RANGE],
+            "bytes */1000"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serve_file_sends_requested_range() {
+        let path = std::env::temp_dir().join(format!(
+            "streamgo_serve_file_range_{}.mp4",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::write(&path, b"0123456789").unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RANGE, "bytes=2-5".parse().unwrap());
+        let response = serve_file(&path, &headers).await.ok().unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"2345");
+
+        let response = serve_file(&path, &HeaderMap::new()).await.ok().unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"0123456789");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_idle_local_files_expire() {
+        let dir =
+            std::env::temp_dir().join(format!("streamgo_local_files_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let server = StreamingServer::new_offline(dir.clone(), 8765)
+            .await
+            .unwrap();
+        let movie = dir.join("movie.mp4");
+
+        let url = server.register_local_file(movie.clone()).await;
+        let id = url.rsplit('/').next().unwrap().to_string();
+        assert_eq!(server.register_local_file(movie.clone()).await, url);
+        assert_eq!(server.local_file(&id).await, Some(movie.clone()));
+
+        let idle_since = Instant::now() - LOCAL_FILE_IDLE_TTL - Duration::from_secs(1);
+        let mut files = server.local_files.write().await;
+        files.get_mut(&id).unwrap().last_used = idle_since;
+        drop(files);
+        assert_eq!(server.local_file(&id).await, None);
+        assert!(server.local_files.read().await.is_empty());
+        assert_ne!(server.register_local_file(movie).await, url);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_torrent_queue_limits_active_torrents() {
+        let mut queue = TorrentQueue::new(2);
+        assert!(queue.request("magnet:a"));
+        assert!(queue.request("magnet:b"));
+        assert!(!queue.request("magnet:c"));
+        assert!(!queue.request("magnet:d"));
+        // Asking again doesn't queue twice
+        assert!(!queue.request("magnet:c"));
+        assert_eq!(
+            queue.status(),
+            TorrentQueueStatus {
+                active: 2,
+                queued: 2,
+                max_active: 2
+            }
+        );
+
+        // Finishing one starts the first queued torrent
+        assert_eq!(queue.finish("magnet:a"), vec!["magnet:c".to_string()]);
+        assert!(queue.is_active("magnet:c"));
+        assert!(!queue.is_active("magnet:d"));
+
+        // The watched torrent skips the line
+        assert!(!queue.request("magnet:e"));
+        queue.set_watching("magnet:e");
+        assert_eq!(queue.finish("magnet:b"), vec!["magnet:e".to_string()]);
+
+        // Dropping a queued torrent doesn't start anything
+        assert!(queue.finish("magnet:d").is_empty());
+        assert_eq!(queue.status().queued, 0);
+
+        // Raising the limit starts queued torrents right away
+        assert!(!queue.request("magnet:f"));
+        assert_eq!(queue.set_max_active(3), vec!["magnet:f".to_string()]);
+        assert_eq!(queue.status().active, 3);
+    }
+
+    #[test]
+    fn test_queue_key_is_the_info_hash() {
+        let hash = "c9e15763f722f23e98a29decdfae341b98d53056";
+        let magnets = [
+            format!("magnet:?xt=urn:btih:{}&dn=Movie", hash),
+            format!(
+                "magnet:?xt=urn:btih:{}&tr=udp%3A%2F%2Ftracker.example.com%3A1337",
+                hash.to_uppercase()
+            ),
+            "magnet:?xt=urn:btih:ZHQVOY7XELZD5GFCTXWN7LRUDOMNKMCW".to_string(),
+        ];
+        for magnet in &magnets {
+            assert_eq!(queue_key(magnet), hash, "{}", magnet);
+        }
+
+        let torrent_url = "https://example.com/movie.torrent";
+        assert_eq!(queue_key(torrent_url), torrent_url);
+    }
+
+    #[test]
+    fn test_local_file_url() {
+        assert_eq!(
+            local_file_url("http://127.0.0.1:8765", "abc-123"),
+            "http://127.0.0.1:8765/local/abc-123"
+        );
+    }
+}
@@ -3,12 +3,28 @@
  *
  * Automatic subtitle fetching from OpenSubtitles and SubDB
  */
+use crate::cache::CacheManager;
+use crate::language::language_key;
+use crate::rate_limit::RateLimiter;
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::future::Future;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
+/// Items prefetched at the same time
+pub const PREFETCH_CONCURRENCY: usize = 3;
+
+/// Minimum spacing between prefetch lookups, to stay under provider rate limits
+pub const PREFETCH_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+
 /// Subtitle search result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubtitleResult {
@@ -25,6 +41,86 @@ pub struct SubtitleResult {
     pub rating: Option<f32>,
 }
 
+/// Download allowance OpenSubtitles reported with the last download
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuotaInfo {
+    pub remaining: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SubtitleError {
+    /// The message ends with the RFC 3339 reset time when known, so it
+    /// survives commands turning errors into strings
+    #[error("Subtitle download quota exhausted{}", quota_reset_suffix(.reset_at))]
+    QuotaExhausted { reset_at: Option<DateTime<Utc>> },
+}
+
+fn quota_reset_suffix(reset_at: &Option<DateTime<Utc>>) -> String {
+    reset_at
+        .map(|reset_at| format!(" until {}", reset_at.to_rfc3339()))
+        .unwrap_or_default()
+}
+
+/// Last quota seen from OpenSubtitles, shared by all clients
+static OPENSUBTITLES_QUOTA: Lazy<Mutex<Option<QuotaInfo>>> = Lazy::new(|| Mutex::new(None));
+
+/// The OpenSubtitles download quota as of the last download, if any was made
+pub fn subtitle_quota() -> Option<QuotaInfo> {
+    OPENSUBTITLES_QUOTA
+        .lock()
+        .ok()
+        .and_then(|quota| quota.clone())
+}
+
+/// Forget the last quota seen, e.g. after logging in or out, since the new
+/// account (or anonymous use) has a quota of its own
+pub fn reset_subtitle_quota() {
+    if let Ok(mut quota) = OPENSUBTITLES_QUOTA.lock() {
+        *quota = None;
+    }
+}
+
+/// Quota fields of an OpenSubtitles `/download` response body
+fn parse_quota(json: &serde_json::Value) -> Option<QuotaInfo> {
+    let remaining = json.get("remaining").and_then(|v| v.as_i64())?;
+    let reset_at = json
+        .get("reset_time_utc")
+        .and_then(|v| v.as_str())
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&Utc));
+    Some(QuotaInfo {
+        remaining,
+        reset_at,
+    })
+}
+
+/// The file link from an OpenSubtitles `/download` response. An exhausted
+/// quota (HTTP 406, or no link with nothing remaining) becomes
+/// `SubtitleError::QuotaExhausted`.
+fn parse_download_response(
+    status: reqwest::StatusCode,
+    json: &serde_json::Value,
+) -> Result<String> {
+    let quota = parse_quota(json);
+    let link = json.get("link").and_then(|v| v.as_str());
+
+    let exhausted = quota.as_ref().is_some_and(|q| q.remaining <= 0) && link.is_none();
+    if status == reqwest::StatusCode::NOT_ACCEPTABLE || exhausted {
+        return Err(SubtitleError::QuotaExhausted {
+            reset_at: quota.and_then(|q| q.reset_at),
+        }
+        .into());
+    }
+    if !status.is_success() {
+        return Err(anyhow!("Failed to download subtitle: {}", status));
+    }
+
+    let link = link.ok_or_else(|| anyhow!("No download link in response"))?;
+    Ok(link.to_string())
+}
+
 /// Subtitle provider
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -36,6 +132,8 @@ pub enum SubtitleProvider {
 /// OpenSubtitles API client
 pub struct OpenSubtitlesClient {
     api_key: Option<String>,
+    /// User session token from `login`; logged-in users get a higher download quota
+    token: Option<String>,
     user_agent: String,
     base_url: String,
 }
@@ -45,11 +143,90 @@ impl OpenSubtitlesClient {
     pub fn new(api_key: Option<String>) -> Self {
         Self {
             api_key,
+            token: None,
             user_agent: format!("StreamGo v{}", env!("CARGO_PKG_VERSION")),
             base_url: "https://api.opensubtitles.com/api/v1".to_string(),
         }
     }
 
+    /// Send requests on behalf of a logged-in user
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    #[cfg(test)]
+    fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// Add the API key, user agent and, when logged in, the user token
+    fn authorize(
+        &self,
+        request: reqwest::RequestBuilder,
+        api_key: &str,
+    ) -> reqwest::RequestBuilder {
+        let request = request
+            .header("Api-Key", api_key)
+            .header("User-Agent", &self.user_agent);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Log in with an OpenSubtitles account and return the session token
+    pub async fn login(&self, username: &str, password: &str) -> Result<String> {
+        let api_key = self
+            .api_key
+            .as_ref()
+            .ok_or_else(|| anyhow!("OpenSubtitles API key not configured"))?;
+
+        let response = reqwest::Client::new()
+            .post(format!("{}/login", self.base_url))
+            .header("Api-Key", api_key)
+            .header("User-Agent", &self.user_agent)
+            .json(&serde_json::json!({ "username": username, "password": password }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            warn!(status = %status, "OpenSubtitles login failed");
+            return Err(anyhow!("OpenSubtitles login failed: {}", status));
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        let token = json
+            .get("token")
+            .and_then(|v| v.as_str())
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| anyhow!("No token in OpenSubtitles login response"))?;
+        info!("Logged in to OpenSubtitles");
+        Ok(token.to_string())
+    }
+
+    /// End the current user session; a no-op when not logged in
+    pub async fn logout(&self) -> Result<()> {
+        let (Some(api_key), Some(_)) = (&self.api_key, &self.token) else {
+            return Ok(());
+        };
+
+        let response = self
+            .authorize(
+                reqwest::Client::new().delete(format!("{}/logout", self.base_url)),
+                api_key,
+            )
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow!("OpenSubtitles logout failed: {}", status));
+        }
+        Ok(())
+    }
+
     /// Search subtitles by IMDB ID
     pub async fn search_by_imdb(
         &self,
@@ -63,20 +240,26 @@ impl OpenSubtitlesClient {
         debug!(imdb_id = %imdb_id, "Searching OpenSubtitles by IMDB ID");
 
         let languages_str = languages.join(",");
-        let url = format!(
-            "{}/subtitles?imdb_id={}&languages={}",
-            self.base_url,
-            imdb_id.trim_start_matches("tt"),
-            languages_str
-        );
+        // Episodes use the Stremio "tt0944947:1:2" form
+        let url = match imdb_id.split(':').collect::<Vec<_>>().as_slice() {
+            [show, season, episode] => format!(
+                "{}/subtitles?parent_imdb_id={}&season_number={}&episode_number={}&languages={}",
+                self.base_url,
+                show.trim_start_matches("tt"),
+                season,
+                episode,
+                languages_str
+            ),
+            _ => format!(
+                "{}/subtitles?imdb_id={}&languages={}",
+                self.base_url,
+                imdb_id.trim_start_matches("tt"),
+                languages_str
+            ),
+        };
 
         let client = reqwest::Client::new();
-        let response = client
-            .get(&url)
-            .header("Api-Key", api_key)
-            .header("User-Agent", &self.user_agent)
-            .send()
-            .await?;
+        let response = self.authorize(client.get(&url), api_key).send().await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -122,12 +305,7 @@ impl OpenSubtitlesClient {
         );
 
         let client = reqwest::Client::new();
-        let response = client
-            .get(&url)
-            .header("Api-Key", api_key)
-            .header("User-Agent", &self.user_agent)
-            .send()
-            .await?;
+        let response = self.authorize(client.get(&url), api_key).send().await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -166,8 +344,8 @@ impl OpenSubtitlesClient {
         let language_code = attributes
             .get("language")
             .and_then(|v| v.as_str())
-            .unwrap_or("en")
-            .to_string();
+            .map(language_key)
+            .unwrap_or_else(|| "en".to_string());
 
         let file_name = attributes
             .get("files")
@@ -227,6 +405,16 @@ impl OpenSubtitlesClient {
             anyhow!("OpenSubtitles API key not configured")
         })?;
 
+        // Don't spend a request when the last response said the quota is used up
+        if let Some(quota) = subtitle_quota() {
+            if quota.remaining <= 0 && quota.reset_at.is_some_and(|t| t > Utc::now()) {
+                return Err(SubtitleError::QuotaExhausted {
+                    reset_at: quota.reset_at,
+                }
+                .into());
+            }
+        }
+
         debug!(file_id = %file_id, "Downloading subtitle from OpenSubtitles");
 
         let url = format!("{}/download", self.base_url);
@@ -235,31 +423,28 @@ impl OpenSubtitlesClient {
         });
 
         let client = reqwest::Client::new();
-        let response = client
-            .post(&url)
-            .header("Api-Key", api_key)
-            .header("User-Agent", &self.user_agent)
-            .header("Content-Type", "application/json")
+        let response = self
+            .authorize(client.post(&url), api_key)
             .json(&body)
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+        if let (Some(quota), Ok(mut last)) = (parse_quota(&json), OPENSUBTITLES_QUOTA.lock()) {
+            debug!(remaining = quota.remaining, "OpenSubtitles download quota");
+            *last = Some(quota);
+        }
+        if !status.is_success() {
             error!(status = %status, body = %body, "OpenSubtitles download error");
-            return Err(anyhow!("Failed to download subtitle: {}", status));
         }
-
-        let json: serde_json::Value = response.json().await?;
-        let download_link = json
-            .get("link")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow!("No download link in response"))?;
+        let download_link = parse_download_response(status, &json)?;
 
         // Download the actual subtitle file
-        let subtitle_response = reqwest::get(download_link).await?;
-        let subtitle_content = subtitle_response.text().await?;
+        let subtitle_response = reqwest::get(&download_link).await?;
+        let subtitle_bytes = subtitle_response.bytes().await?;
+        let subtitle_content = crate::player::SubtitleManager::detect_and_decode(&subtitle_bytes);
 
         info!("Successfully downloaded subtitle from OpenSubtitles");
         Ok(subtitle_content)
@@ -310,13 +495,15 @@ impl SubDBClient {
         let available_languages = response.text().await?;
         let mut results = Vec::new();
 
+        let wanted: Vec<String> = languages.iter().map(|l| language_key(l)).collect();
         for lang in available_languages.split(',') {
             let lang = lang.trim();
-            if languages.contains(&lang) {
+            let language_code = language_key(lang);
+            if wanted.contains(&language_code) {
                 results.push(SubtitleResult {
                     id: format!("{}_{}", file_hash, lang),
                     language: lang.to_string(),
-                    language_code: lang.to_string(),
+                    language_code,
                     file_name: format!("subtitle_{}.srt", lang),
                     download_url: format!(
                         "{}/?action=download&hash={}&language={}",
@@ -356,7 +543,8 @@ impl SubDBClient {
             return Err(anyhow!("Failed to download subtitle from SubDB"));
         }
 
-        let subtitle_content = response.text().await?;
+        let subtitle_bytes = response.bytes().await?;
+        let subtitle_content = crate::player::SubtitleManager::detect_and_decode(&subtitle_bytes);
         info!("Successfully downloaded subtitle from SubDB");
         Ok(subtitle_content)
     }
@@ -482,6 +670,18 @@ impl SubtitleManager {
         }
     }
 
+    /// Use an OpenSubtitles user token from `login` for OpenSubtitles requests
+    pub fn with_opensubtitles_token(mut self, token: Option<String>) -> Self {
+        self.opensubtitles = self.opensubtitles.with_token(token);
+        self
+    }
+
+    #[cfg(test)]
+    fn with_opensubtitles_base_url(mut self, base_url: &str) -> Self {
+        self.opensubtitles = self.opensubtitles.with_base_url(base_url);
+        self
+    }
+
     /// Auto-fetch subtitles for a video file
     pub async fn auto_fetch(
         &self,
@@ -557,12 +757,466 @@ impl SubtitleManager {
 
         Ok((content, best.clone()))
     }
+
+    /// Search by IMDB ID and download the best subtitle for each language
+    /// that has any match. A failed download skips only its language; fails
+    /// if nothing could be downloaded, with the last download error if any.
+    pub async fn fetch_best_per_language(
+        &self,
+        imdb_id: &str,
+        languages: &[&str],
+    ) -> Result<Vec<(String, SubtitleResult)>> {
+        let results = self.auto_fetch(None, Some(imdb_id), languages).await?;
+
+        let mut downloaded = Vec::new();
+        let mut last_error = None;
+        for language in languages {
+            let key = language_key(language);
+            // Results are sorted by score, so the first match is the best
+            let matching: Vec<SubtitleResult> = results
+                .iter()
+                .filter(|r| r.language_code == key)
+                .take(1)
+                .cloned()
+                .collect();
+            if matching.is_empty() {
+                debug!(imdb_id = %imdb_id, language = %key, "No subtitle match");
+                continue;
+            }
+            match self.download_best(&matching).await {
+                Ok(subtitle) => downloaded.push(subtitle),
+                Err(e) => {
+                    warn!(imdb_id = %imdb_id, language = %key, error = %e, "Subtitle download failed");
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if downloaded.is_empty() {
+            return Err(last_error.unwrap_or_else(|| anyhow!("No subtitles found for {}", imdb_id)));
+        }
+        Ok(downloaded)
+    }
+}
+
+/// Convert a downloaded SRT subtitle to WebVTT for the built-in player,
+/// updating the result's format and file name to match. Other formats are
+/// returned unchanged.
+pub fn srt_download_to_vtt(
+    content: String,
+    mut result: SubtitleResult,
+) -> Result<(String, SubtitleResult)> {
+    if !result.format.eq_ignore_ascii_case("srt") {
+        return Ok((content, result));
+    }
+
+    let vtt = crate::player::SubtitleManager::srt_to_vtt(&content)?;
+    result.format = "vtt".to_string();
+    result.file_name = Path::new(&result.file_name)
+        .with_extension("vtt")
+        .to_string_lossy()
+        .into_owned();
+    Ok((vtt, result))
+}
+
+/// Downloaded subtitle for `content_id` in `language`, served from the cache
+/// when present. Otherwise (or with `force_refresh`) `download` is called and
+/// its result cached for next time, under the language of the subtitle it
+/// returned (a fallback download may not be in `language`).
+pub async fn download_cached<F, Fut>(
+    cache: &Mutex<CacheManager>,
+    content_id: &str,
+    language: &str,
+    force_refresh: bool,
+    download: F,
+) -> Result<(String, SubtitleResult)>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<(String, SubtitleResult)>>,
+{
+    let language = language_key(language);
+    if !force_refresh {
+        let cached = cache
+            .lock()
+            .map_err(|e| anyhow!("Cache lock poisoned: {}", e))?
+            .get_subtitle(content_id, &language)?;
+        if let Some(subtitle) = cached {
+            debug!(content_id = %content_id, language = %language, "Subtitle served from cache");
+            return Ok(subtitle);
+        }
+    }
+
+    let subtitle = download().await?;
+    let downloaded_language = language_key(&subtitle.1.language_code);
+    if downloaded_language != language {
+        debug!(
+            content_id = %content_id,
+            requested = %language,
+            downloaded = %downloaded_language,
+            "Downloaded subtitle is in a fallback language"
+        );
+    }
+    cache
+        .lock()
+        .map_err(|e| anyhow!("Cache lock poisoned: {}", e))?
+        .set_subtitle(content_id, &downloaded_language, &subtitle)?;
+    Ok(subtitle)
+}
+
+/// Subtitles fetched ahead of time for one media item
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrefetchResult {
+    /// Best subtitle per language that was found and downloaded
+    pub subtitles: Vec<SubtitleResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Run `fetch` for every media ID, at most `max_concurrency` at a time and
+/// starting no more often than every `min_interval`. A failure for one ID is
+/// recorded in its result and doesn't stop the others.
+pub async fn prefetch<F, Fut>(
+    media_ids: Vec<String>,
+    max_concurrency: usize,
+    min_interval: Duration,
+    fetch: F,
+) -> HashMap<String, PrefetchResult>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<SubtitleResult>>>,
+{
+    let limiter = RateLimiter::new(min_interval);
+    let fetch = &fetch;
+    let limiter = &limiter;
+
+    stream::iter(media_ids)
+        .map(|media_id| async move {
+            limiter.wait().await;
+            let result = match fetch(media_id.clone()).await {
+                Ok(subtitles) => PrefetchResult {
+                    subtitles,
+                    error: None,
+                },
+                Err(e) => {
+                    warn!(media_id = %media_id, error = %e, "Subtitle prefetch failed");
+                    PrefetchResult {
+                        subtitles: Vec::new(),
+                        error: Some(e.to_string()),
+                    }
+                }
+            };
+            (media_id, result)
+        })
+        .buffer_unordered(max_concurrency.max(1))
+        .collect()
+        .await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn subtitle(language_code: &str) -> SubtitleResult {
+        SubtitleResult {
+            id: "1".to_string(),
+            language: language_code.to_string(),
+            language_code: language_code.to_string(),
+            file_name: "subtitle.srt".to_string(),
+            download_url: String::new(),
+            score: 10.0,
+            provider: SubtitleProvider::OpenSubtitles,
+            format: "srt".to_string(),
+            hearing_impaired: false,
+            download_count: None,
+            rating: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_tolerates_per_item_failures() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let attempts = AtomicUsize::new(0);
+        let ids = ["tt0944947:1:1", "tt0944947:1:2", "tt0944947:1:3"];
+        let results = prefetch(
+            ids.iter().map(|id| id.to_string()).collect(),
+            2,
+            Duration::from_millis(10),
+            |media_id| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if media_id.ends_with(":2") {
+                        Err(anyhow!("No subtitles found for {}", media_id))
+                    } else {
+                        Ok(vec![subtitle("en"), subtitle("es")])
+                    }
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results["tt0944947:1:1"].subtitles.len(), 2);
+        assert_eq!(results["tt0944947:1:3"].subtitles.len(), 2);
+        assert!(results["tt0944947:1:1"].error.is_none());
+        assert!(results["tt0944947:1:2"].subtitles.is_empty());
+        assert_eq!(
+            results["tt0944947:1:2"].error.as_deref(),
+            Some("No subtitles found for tt0944947:1:2")
+        );
+    }
+
+    #[test]
+    fn test_srt_download_converted_to_vtt() {
+        let srt = "1\n00:00:01,500 --> 00:00:04,000\nHello, world\n\n2\n00:00:05,000 --> 00:00:06,250\nBye\n";
+        let (vtt, result) = srt_download_to_vtt(srt.to_string(), subtitle("en")).unwrap();
+
+        assert!(vtt.starts_with("WEBVTT\n\n"));
+        assert_eq!(result.format, "vtt");
+        assert_eq!(result.file_name, "subtitle.vtt");
+        let cues = crate::player::SubtitleManager::parse_vtt(&vtt).unwrap();
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].start, "00:00:01.500");
+        assert_eq!(cues[0].text, "Hello, world");
+        assert_eq!(cues[1].end, "00:00:06.250");
+
+        // Already-VTT downloads pass through untouched
+        let mut vtt_result = subtitle("en");
+        vtt_result.format = "vtt".to_string();
+        let (content, result) = srt_download_to_vtt(vtt.clone(), vtt_result).unwrap();
+        assert_eq!(content, vtt);
+        assert_eq!(result.file_name, "subtitle.srt");
+    }
+
+    #[tokio::test]
+    async fn test_download_cached_serves_repeat_fetch_from_cache() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = Mutex::new(CacheManager::new(None).unwrap());
+        let downloads = AtomicUsize::new(0);
+        let fetch = |force_refresh: bool| {
+            download_cached(&cache, "tt0133093", "English", force_refresh, || async {
+                let n = downloads.fetch_add(1, Ordering::SeqCst);
+                Ok((format!("download {}", n), subtitle("en")))
+            })
+        };
+
+        let first = fetch(false).await.unwrap();
+        let second = fetch(false).await.unwrap();
+        assert_eq!(downloads.load(Ordering::SeqCst), 1);
+        assert_eq!(second.0, first.0);
+        assert_eq!(second.1.language_code, "en");
+
+        // Force refresh goes to the provider and replaces the cached copy
+        let refreshed = fetch(true).await.unwrap();
+        assert_eq!(downloads.load(Ordering::SeqCst), 2);
+        assert_eq!(refreshed.0, "download 1");
+        assert_eq!(fetch(false).await.unwrap().0, "download 1");
+
+        // Other languages and titles are cached separately
+        let cached = cache.lock().unwrap();
+        assert!(cached.get_subtitle("tt0133093", "en").unwrap().is_some());
+        assert!(cached.get_subtitle("tt0133093", "es").unwrap().is_none());
+        assert!(cached.get_subtitle("tt0234215", "en").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_download_cached_keys_fallback_by_downloaded_language() {
+        let cache = Mutex::new(CacheManager::new(None).unwrap());
+
+        // English was asked for, but only the Spanish result downloaded
+        let (_, result) = download_cached(&cache, "tt0133093", "en", false, || async {
+            Ok(("spanish".to_string(), subtitle("es")))
+        })
+        .await
+        .unwrap();
+        assert_eq!(result.language_code, "es");
+
+        let cached = cache.lock().unwrap();
+        assert!(cached.get_subtitle("tt0133093", "en").unwrap().is_none());
+        let (content, _) = cached.get_subtitle("tt0133093", "es").unwrap().unwrap();
+        assert_eq!(content, "spanish");
+    }
+
+    #[test]
+    fn test_download_response_quota() {
+        use reqwest::StatusCode;
+
+        let body = serde_json::json!({
+            "link": "https://dl.opensubtitles.com/file/123.srt",
+            "file_name": "movie.srt",
+            "requests": 3,
+            "remaining": 17,
+            "message": "Your quota will be renewed in 07 hours and 50 minutes",
+            "reset_time": "07 hours and 50 minutes",
+            "reset_time_utc": "2024-04-08T13:03:16.000Z"
+        });
+        let link = parse_download_response(StatusCode::OK, &body).unwrap();
+        assert_eq!(link, "https://dl.opensubtitles.com/file/123.srt");
+        let quota = parse_quota(&body).unwrap();
+        assert_eq!(quota.remaining, 17);
+        assert_eq!(
+            quota.reset_at.unwrap().to_rfc3339(),
+            "2024-04-08T13:03:16+00:00"
+        );
+
+        let exhausted = serde_json::json!({
+            "requests": 21,
+            "remaining": 0,
+            "message": "You have downloaded your allowed 20 subtitles for 24h.",
+            "reset_time": "23 hours and 59 minutes",
+            "reset_time_utc": "2024-04-09T13:03:16.000Z"
+        });
+        let err = parse_download_response(StatusCode::NOT_ACCEPTABLE, &exhausted).unwrap_err();
+        match err.downcast_ref::<SubtitleError>() {
+            Some(SubtitleError::QuotaExhausted { reset_at }) => {
+                assert_eq!(reset_at.unwrap().to_rfc3339(), "2024-04-09T13:03:16+00:00")
+            }
+            None => panic!("expected a quota error, got {}", err),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Subtitle download quota exhausted until 2024-04-09T13:03:16+00:00"
+        );
+        assert_eq!(parse_quota(&exhausted).unwrap().remaining, 0);
+
+        // Other failures stay generic
+        let err =
+            parse_download_response(StatusCode::UNAUTHORIZED, &serde_json::json!({})).unwrap_err();
+        assert!(err.downcast_ref::<SubtitleError>().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_login_token_sent_with_requests() {
+        use wiremock::matchers::{body_json, header, method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/login"))
+            .and(header("Api-Key", "test-key"))
+            .and(body_json(
+                serde_json::json!({ "username": "alice", "password": "secret" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "user": { "allowed_downloads": 100, "level": "Sub leecher" },
+                "base_url": "api.opensubtitles.com",
+                "token": "user-token-123",
+                "status": 200
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/subtitles"))
+            .and(query_param("imdb_id", "0133093"))
+            .and(header("Authorization", "Bearer user-token-123"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{
+                    "id": "42",
+                    "attributes": {
+                        "language": "en",
+                        "download_count": 1200,
+                        "ratings": 8.0,
+                        "hearing_impaired": false,
+                        "files": [{ "file_id": 4242, "file_name": "The.Matrix.srt" }]
+                    }
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/logout"))
+            .and(header("Authorization", "Bearer user-token-123"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let anonymous =
+            OpenSubtitlesClient::new(Some("test-key".to_string())).with_base_url(&server.uri());
+        let token = anonymous.login("alice", "secret").await.unwrap();
+        assert_eq!(token, "user-token-123");
+        // Logging out without a session doesn't call the API
+        anonymous.logout().await.unwrap();
+
+        let client = OpenSubtitlesClient::new(Some("test-key".to_string()))
+            .with_base_url(&server.uri())
+            .with_token(Some(token));
+        let results = client.search_by_imdb("tt0133093", &["en"]).await.unwrap();
+        assert_eq!(results.len(), 1);
+        client.logout().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_language_download_keeps_the_others() {
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/subtitles"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [
+                    {
+                        "id": "1",
+                        "attributes": {
+                            "language": "en",
+                            "download_count": 5000,
+                            "files": [{ "file_id": 1, "file_name": "Movie.en.srt" }]
+                        }
+                    },
+                    {
+                        "id": "2",
+                        "attributes": {
+                            "language": "es",
+                            "download_count": 100,
+                            "files": [{ "file_id": 2, "file_name": "Movie.es.srt" }]
+                        }
+                    }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/download"))
+            .and(body_json(serde_json::json!({ "file_id": 1 })))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/download"))
+            .and(body_json(serde_json::json!({ "file_id": 2 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "link": format!("{}/files/2.srt", server.uri())
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/files/2.srt"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("1\n00:00:01,000 --> 00:00:02,000\nHola\n"),
+            )
+            .mount(&server)
+            .await;
+
+        let manager = SubtitleManager::new(Some("test-key".to_string()))
+            .with_opensubtitles_base_url(&server.uri());
+        let downloaded = manager
+            .fetch_best_per_language("tt0133093", &["en", "es"])
+            .await
+            .unwrap();
+
+        assert_eq!(downloaded.len(), 1);
+        assert_eq!(downloaded[0].1.language_code, "es");
+        assert!(downloaded[0].0.contains("Hola"));
+    }
+
     #[test]
     fn test_score_calculation() {
         // High score subtitle
@@ -0,0 +1,178 @@
+/**
+ * Version Utilities
+ *
+ * Semver-style parsing and comparison for addon manifest versions
+ */
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+
+/// A parsed `major.minor[.patch][-pre][+build]` version.
+///
+/// Addon manifests in the wild are not always strict semver, so a missing
+/// patch number is treated as `0` and a fourth numeric segment is tolerated
+/// (it does not take part in ordering). Build metadata is ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    pub fn parse(version: &str) -> Result<Self> {
+        let version = version.trim();
+        if version.is_empty() {
+            return Err(anyhow!("Version is required"));
+        }
+
+        let without_build = version.split('+').next().unwrap_or(version);
+        let (core, pre) = match without_build.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (without_build, None),
+        };
+
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.len() < 2 || parts.len() > 4 {
+            return Err(anyhow!(
+                "Version must follow semver format (e.g., 1.0.0 or 1.0.0-beta)"
+            ));
+        }
+
+        let mut numbers = [0u32; 3];
+        for (i, part) in parts.iter().enumerate() {
+            let number = part
+                .parse::<u32>()
+                .map_err(|_| anyhow!("Version part {} must be numeric: {}", i + 1, part))?;
+            if i < 3 {
+                numbers[i] = number;
+            }
+        }
+
+        let pre = match pre {
+            Some(pre) => {
+                let identifiers: Vec<String> = pre.split('.').map(|s| s.to_string()).collect();
+                if identifiers.iter().any(|id| id.is_empty()) {
+                    return Err(anyhow!("Pre-release identifiers cannot be empty"));
+                }
+                identifiers
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Version {
+            major: numbers[0],
+            minor: numbers[1],
+            patch: numbers[2],
+            pre,
+        })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| compare_pre_release(&self.pre, &other.pre))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Semver pre-release precedence: a release outranks any pre-release,
+/// numeric identifiers compare numerically and sort below alphanumeric ones.
+fn compare_pre_release(a: &[String], b: &[String]) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {}
+    }
+
+    for (x, y) in a.iter().zip(b.iter()) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    a.len().cmp(&b.len())
+}
+
+/// Compare two version strings.
+/// Malformed versions sort below every valid one and equal to each other.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    match (Version::parse(a), Version::parse(b)) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Greater,
+        (Err(_), Ok(_)) => Ordering::Less,
+        (Err(_), Err(_)) => Ordering::Equal,
+    }
+}
+
+/// Whether `latest` is a strictly newer version than `current`.
+/// Unparseable versions never count as an update.
+pub fn is_newer_version(current: &str, latest: &str) -> bool {
+    match (Version::parse(current), Version::parse(latest)) {
+        (Ok(current), Ok(latest)) => latest > current,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_ordering() {
+        assert_eq!(compare_versions("1.2.0", "1.10.0"), Ordering::Less);
+        assert_eq!(compare_versions("2.0.0", "1.99.99"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.0+build.5", "1.0.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_pre_release_ordering() {
+        assert_eq!(compare_versions("1.0.0-beta", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-alpha", "1.0.0-beta"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-beta.2", "1.0.0-beta.11"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-beta", "1.0.0-beta.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-1", "1.0.0-alpha"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.1-beta", "1.0.0"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_malformed_versions() {
+        assert!(Version::parse("").is_err());
+        assert!(Version::parse("1").is_err());
+        assert!(Version::parse("1.x.0").is_err());
+        assert!(Version::parse("1.0.0.0.0").is_err());
+        assert!(Version::parse("1.0.0-").is_err());
+        assert!(Version::parse("1.0.0.1").is_ok());
+
+        assert_eq!(compare_versions("latest", "0.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("latest", "nightly"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_is_newer_version() {
+        assert!(is_newer_version("1.0.0", "1.0.1"));
+        assert!(is_newer_version("1.2.0", "1.10.0"));
+        assert!(is_newer_version("1.9", "2.0.0"));
+        assert!(is_newer_version("1.0.0-beta", "1.0.0"));
+        assert!(!is_newer_version("1.0.0", "1.0"));
+        assert!(!is_newer_version("2.0.0", "1.9.9"));
+        assert!(!is_newer_version("1.0.0", "1.0.0-beta"));
+        assert!(!is_newer_version("1.0.0", "latest"));
+        assert!(!is_newer_version("", "1.0.0"));
+    }
+}
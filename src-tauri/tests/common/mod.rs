@@ -0,0 +1,288 @@
+/**
+ * Mock Addon Server
+ *
+ * Test harness serving the Stremio addon endpoints (`/manifest.json`,
+ * `/catalog/...`, `/stream/...`, `/subtitles/...`) from a local wiremock
+ * server, with configurable responses, status codes and delays
+ */
+use app_lib::{Addon, AddonManifest, AddonType};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+pub struct MockAddon {
+    server: MockServer,
+    id: String,
+}
+
+impl MockAddon {
+    /// Start a server that already serves a valid manifest for `id`
+    pub async fn start(id: &str) -> Self {
+        let mock = Self {
+            server: MockServer::start().await,
+            id: id.to_string(),
+        };
+        // Low priority so tests can override the manifest with `with_manifest`
+        Mock::given(method("GET"))
+            .and(path("/manifest.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(mock.default_manifest()))
+            .with_priority(u8::MAX)
+            .mount(&mock.server)
+            .await;
+        mock
+    }
+
+    /// Base URL of the addon (without `/manifest.json`)
+    pub fn base_url(&self) -> String {
+        self.server.uri()
+    }
+
+    pub fn default_manifest(&self) -> Value {
+        json!({
+            "id": self.id,
+            "name": format!("Mock {}", self.id),
+            "version": "1.0.0",
+            "description": "Mock addon for tests",
+            "types": ["movie", "series"],
+            "catalogs": [{ "type": "movie", "id": "top", "name": "Top" }],
+            "resources": ["catalog", "stream", "subtitles"]
+        })
+    }
+
+    pub async fn with_manifest(&self, manifest: Value) -> &Self {
+        self.respond(
+            "/manifest.json",
+            ResponseTemplate::new(200).set_body_json(manifest),
+        )
+        .await
+    }
+
+    pub async fn with_catalog(&self, media_type: &str, catalog_id: &str, body: Value) -> &Self {
+        self.respond(
+            &format!("/catalog/{}/{}.json", media_type, catalog_id),
+            ResponseTemplate::new(200).set_body_json(body),
+        )
+        .await
+    }
+
+    pub async fn with_streams(&self, media_type: &str, media_id: &str, body: Value) -> &Self {
+        self.respond(
+            &format!("/stream/{}/{}.json", media_type, media_id),
+            ResponseTemplate::new(200).set_body_json(body),
+        )
+        .await
+    }
+
+    /// Serve streams only after `delay`, to exercise timeouts
+    pub async fn with_slow_streams(
+        &self,
+        media_type: &str,
+        media_id: &str,
+        body: Value,
+        delay: Duration,
+    ) -> &Self {
+        self.respond(
+            &format!("/stream/{}/{}.json", media_type, media_id),
+            ResponseTemplate::new(200)
+                .set_body_json(body)
+                .set_delay(delay),
+        )
+        .await
+    }
+
+    pub async fn with_subtitles(&self, media_type: &str, media_id: &str, body: Value) -> &Self {
+        self.respond(
+            &format!("/subtitles/{}/{}.json", media_type, media_id),
+            ResponseTemplate::new(200).set_body_json(body),
+        )
+        .await
+    }
+
+    pub async fn with_meta(&self, media_type: &str, media_id: &str, body: Value) -> &Self {
+        self.respond(
+            &format!("/meta/{}/{}.json", media_type, media_id),
+            ResponseTemplate::new(200).set_body_json(body),
+        )
+        .await
+    }
+
+    /// Answer `route` with a bare status code
+    pub async fn with_status(&self, route: &str, status: u16) -> &Self {
+        self.respond(route, ResponseTemplate::new(status)).await
+    }
+
+    /// Answer `route` with an arbitrary response
+    pub async fn respond(&self, route: &str, response: ResponseTemplate) -> &Self {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(response)
+            .mount(&self.server)
+            .await;
+        self
+    }
+
+    /// Number of requests the server received for `route`
+    pub async fn request_count(&self, route: &str) -> usize {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|r| r.url.path() == route)
+            .count()
+    }
+
+    /// An installed, enabled addon pointing at this server
+    pub fn addon(&self, priority: i32) -> Addon {
+        addon_at(&self.id, &self.base_url(), priority)
+    }
+}
+
+/// An installed, enabled addon `id` served from `base_url`
+pub fn addon_at(id: &str, base_url: &str, priority: i32) -> Addon {
+    Addon {
+        id: id.to_string(),
+        name: format!("Mock {}", id),
+        version: "1.0.0".to_string(),
+        description: "Mock addon for tests".to_string(),
+        author: "Tests".to_string(),
+        url: format!("{}/manifest.json", base_url),
+        enabled: true,
+        addon_type: AddonType::ContentProvider,
+        manifest: AddonManifest {
+            id: id.to_string(),
+            name: format!("Mock {}", id),
+            version: "1.0.0".to_string(),
+            description: "Mock addon for tests".to_string(),
+            resources: vec![
+                "catalog".to_string(),
+                "stream".to_string(),
+                "subtitles".to_string(),
+            ],
+            types: vec!["movie".to_string(), "series".to_string()],
+            catalogs: vec![],
+        },
+        priority,
+        config: Default::default(),
+    }
+}
+
+/// Requests a [`spawn_counting_stream_server`] is handling, and the most it
+/// ever handled at once
+#[derive(Default)]
+pub struct InFlight {
+    current: AtomicUsize,
+    pub peak: AtomicUsize,
+}
+
+/// A server whose stream endpoint answers after `delay`, recording how many
+/// requests it was serving at the same time
+pub async fn spawn_counting_stream_server(delay: Duration) -> (String, Arc<InFlight>) {
+    use axum::extract::{Path, State};
+    use axum::routing::get;
+
+    async fn streams(
+        State((in_flight, delay)): State<(Arc<InFlight>, Duration)>,
+        Path((_, media_id)): Path<(String, String)>,
+    ) -> axum::Json<Value> {
+        let now = in_flight.current.fetch_add(1, Ordering::SeqCst) + 1;
+        in_flight.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(delay).await;
+        in_flight.current.fetch_sub(1, Ordering::SeqCst);
+
+        let url = format!("https://cdn.example.com/{}", media_id);
+        axum::Json(json!({ "streams": [stream_json(&url, "1080p")] }))
+    }
+
+    let in_flight = Arc::new(InFlight::default());
+    let app = axum::Router::new()
+        .route("/stream/:media_type/:media_id", get(streams))
+        .with_state((in_flight.clone(), delay));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.ok();
+    });
+
+    (format!("http://{}", addr), in_flight)
+}
+
+/// A server whose subtitles endpoint answers with `subtitles` only once
+/// `barrier` is released, so servers sharing a barrier can only all answer
+/// when they are queried at the same time
+pub async fn spawn_barrier_subtitle_server(
+    barrier: Arc<tokio::sync::Barrier>,
+    subtitles: Value,
+) -> String {
+    use axum::extract::State;
+    use axum::routing::get;
+
+    async fn subtitles_handler(
+        State((barrier, subtitles)): State<(Arc<tokio::sync::Barrier>, Value)>,
+    ) -> axum::Json<Value> {
+        barrier.wait().await;
+        axum::Json(subtitles)
+    }
+
+    let app = axum::Router::new()
+        .route("/subtitles/:media_type/:media_id", get(subtitles_handler))
+        .with_state((barrier, subtitles));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.ok();
+    });
+
+    format!("http://{}", addr)
+}
+
+/// A server whose subtitles endpoint streams `subtitles` without a
+/// Content-Length, the way chunked responses arrive
+pub async fn spawn_chunked_subtitle_server(subtitles: Value) -> String {
+    use axum::extract::State;
+    use axum::routing::get;
+
+    async fn subtitles_handler(State(subtitles): State<Value>) -> axum::body::Body {
+        let chunk = Ok::<_, std::io::Error>(subtitles.to_string());
+        axum::body::Body::from_stream(futures::stream::iter([chunk]))
+    }
+
+    let app = axum::Router::new()
+        .route("/subtitles/:media_type/:media_id", get(subtitles_handler))
+        .with_state(subtitles);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.ok();
+    });
+
+    format!("http://{}", addr)
+}
+
+/// A TCP server that accepts connections and closes them immediately,
+/// counting attempts, so clients see transport errors and retry
+pub async fn spawn_dropping_server() -> (String, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let counter = attempts.clone();
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            drop(socket);
+        }
+    });
+
+    (format!("http://{}", addr), attempts)
+}
+
+pub fn stream_json(url: &str, name: &str) -> Value {
+    json!({ "url": url, "name": name, "title": format!("{} stream", name) })
+}
@@ -31,11 +31,14 @@ fn test_database_lifecycle() {
         description: Some("A test movie".to_string()),
         poster_url: Some("https://example.com/poster.jpg".to_string()),
         backdrop_url: None,
+        logo_url: None,
+        thumb_url: None,
         rating: Some(8.5),
         duration: Some(120),
         added_to_library: None,
         watched: false,
         progress: None,
+        progress_percent: None,
     };
 
     // Add to library
@@ -104,11 +107,14 @@ fn test_watchlist_and_favorites() {
         description: None,
         poster_url: None,
         backdrop_url: None,
+        logo_url: None,
+        thumb_url: None,
         rating: None,
         duration: None,
         added_to_library: None,
         watched: false,
         progress: None,
+        progress_percent: None,
     };
 
     db.add_to_library(media_item).expect("Failed to add item");
@@ -186,11 +192,14 @@ fn test_watch_progress_tracking() {
         description: None,
         poster_url: None,
         backdrop_url: None,
+        logo_url: None,
+        thumb_url: None,
         rating: None,
         duration: Some(45),
         added_to_library: None,
         watched: false,
         progress: None,
+        progress_percent: None,
     };
 
     db.add_to_library(media_item).expect("Failed to add item");
@@ -237,11 +246,14 @@ fn test_playlist_management() {
         description: None,
         poster_url: None,
         backdrop_url: None,
+        logo_url: None,
+        thumb_url: None,
         rating: None,
         duration: None,
         added_to_library: None,
         watched: false,
         progress: None,
+        progress_percent: None,
     };
 
     db.add_to_library(media_item).expect("Failed to add item");
@@ -278,3 +290,37 @@ fn test_aggregator_with_empty_addons() {
     assert_eq!(result.items.len(), 0);
     assert_eq!(result.sources.len(), 0);
 }
+
+#[test]
+fn test_addon_update_detection() {
+    let addon = Addon {
+        id: "org.example.addon".to_string(),
+        name: "Example".to_string(),
+        version: "1.2.0".to_string(),
+        description: "Example addon".to_string(),
+        author: "Community".to_string(),
+        url: "https://addon.example.com".to_string(),
+        enabled: false,
+        addon_type: app_lib::AddonType::ContentProvider,
+        manifest: app_lib::AddonManifest {
+            id: "org.example.addon".to_string(),
+            name: "Example".to_string(),
+            version: "1.2.0".to_string(),
+            description: "Example addon".to_string(),
+            resources: vec!["stream".to_string()],
+            types: vec!["movie".to_string()],
+            catalogs: vec![],
+        },
+        priority: 3,
+        config: Default::default(),
+    };
+
+    let update = app_lib::api::addon_update_status(&addon, "1.10.0");
+    assert!(update.has_update);
+    assert_eq!(update.current_version, "1.2.0");
+    assert_eq!(update.latest_version, "1.10.0");
+
+    assert!(!app_lib::api::addon_update_status(&addon, "1.2.0").has_update);
+    assert!(!app_lib::api::addon_update_status(&addon, "1.1.9").has_update);
+    assert!(!app_lib::api::addon_update_status(&addon, "not-a-version").has_update);
+}
@@ -0,0 +1,726 @@
+/**
+ * Addon Networking Tests
+ *
+ * Exercise AddonClient and ContentAggregator end to end against mock addon servers
+ */
+#[allow(dead_code)] // Not every test binary uses the whole harness
+mod common;
+
+use app_lib::{api, AddonClient, AddonError, CacheManager, ContentAggregator, ResponseLimits};
+use common::{
+    addon_at, spawn_barrier_subtitle_server, spawn_chunked_subtitle_server,
+    spawn_counting_stream_server, spawn_dropping_server, stream_json, MockAddon,
+};
+use serde_json::json;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use wiremock::ResponseTemplate;
+
+#[tokio::test]
+async fn test_addon_client_endpoints() {
+    let mock = MockAddon::start("org.mock.endpoints").await;
+    mock.with_catalog(
+        "movie",
+        "top",
+        json!({ "metas": [{ "id": "tt0111161", "type": "movie", "name": "The Shawshank Redemption" }] }),
+    )
+    .await
+    .with_streams(
+        "movie",
+        "tt0111161",
+        json!({ "streams": [stream_json("https://cdn.example.com/movie.mp4", "1080p")] }),
+    )
+    .await
+    .with_subtitles(
+        "movie",
+        "tt0111161",
+        json!({ "subtitles": [{ "id": "1", "url": "https://subs.example.com/en.srt", "lang": "eng" }] }),
+    )
+    .await;
+
+    let client = AddonClient::new(mock.base_url()).unwrap();
+
+    let manifest = client.get_manifest().await.unwrap();
+    assert_eq!(manifest.id, "org.mock.endpoints");
+
+    let catalog = client.get_catalog("movie", "top", None).await.unwrap();
+    assert_eq!(catalog.metas.len(), 1);
+    assert_eq!(catalog.metas[0].id, "tt0111161");
+
+    let streams = client.get_streams("movie", "tt0111161").await.unwrap();
+    assert_eq!(streams.streams[0].url, "https://cdn.example.com/movie.mp4");
+
+    let subtitles = client.get_subtitles("movie", "tt0111161").await.unwrap();
+    assert_eq!(subtitles.subtitles[0].lang, "eng");
+}
+
+#[tokio::test]
+async fn test_aggregator_merges_addons_and_reports_failures() {
+    let first = MockAddon::start("org.mock.first").await;
+    first
+        .with_streams(
+            "movie",
+            "tt0111161",
+            json!({ "streams": [stream_json("https://a.example.com/1.mp4", "720p")] }),
+        )
+        .await;
+    let second = MockAddon::start("org.mock.second").await;
+    second
+        .with_streams(
+            "movie",
+            "tt0111161",
+            json!({ "streams": [stream_json("https://b.example.com/2.m3u8", "1080p")] }),
+        )
+        .await;
+    let broken = MockAddon::start("org.mock.broken").await;
+    broken
+        .with_status("/stream/movie/tt0111161.json", 500)
+        .await;
+
+    let addons = vec![first.addon(1), second.addon(2), broken.addon(3)];
+    let result = ContentAggregator::new()
+        .query_streams(&addons, "movie", "tt0111161")
+        .await;
+
+    let mut urls: Vec<&str> = result.streams.iter().map(|s| s.url.as_str()).collect();
+    urls.sort();
+    assert_eq!(
+        urls,
+        vec![
+            "https://a.example.com/1.mp4",
+            "https://b.example.com/2.m3u8"
+        ]
+    );
+
+    assert_eq!(result.sources.len(), 3);
+    let broken_health = result
+        .sources
+        .iter()
+        .find(|s| s.addon_id == "org.mock.broken")
+        .unwrap();
+    assert!(!broken_health.success);
+    assert!(broken_health.error.is_some());
+}
+
+#[tokio::test]
+async fn test_aggregator_queries_subtitle_addons_in_parallel() {
+    let subtitles = |urls: &[(&str, &str)]| {
+        let subs: Vec<_> = urls
+            .iter()
+            .enumerate()
+            .map(|(i, (url, lang))| json!({ "id": i.to_string(), "url": url, "lang": lang }))
+            .collect();
+        json!({ "subtitles": subs })
+    };
+
+    // Neither server answers until both have been asked, so queried one
+    // after the other the first would time out
+    let barrier = Arc::new(tokio::sync::Barrier::new(2));
+    let first = spawn_barrier_subtitle_server(
+        barrier.clone(),
+        subtitles(&[
+            ("https://subs.example.com/en.srt", "eng"),
+            ("https://subs.example.com/fr.srt", "fre"),
+        ]),
+    )
+    .await;
+    let second = spawn_barrier_subtitle_server(
+        barrier,
+        subtitles(&[
+            ("https://subs.example.com/en.srt", "eng"),
+            ("https://other.example.com/es.vtt", "spa"),
+        ]),
+    )
+    .await;
+    let broken = MockAddon::start("org.mock.subs.broken").await;
+    broken
+        .with_status("/subtitles/movie/tt0111161.json", 500)
+        .await;
+
+    let addons = vec![
+        addon_at("org.mock.subs.first", &first, 1),
+        addon_at("org.mock.subs.second", &second, 2),
+        broken.addon(3),
+    ];
+    let result = ContentAggregator::new()
+        .query_subtitles_detailed(&addons, "movie", "tt0111161")
+        .await;
+
+    let mut urls: Vec<&str> = result.subtitles.iter().map(|s| s.url.as_str()).collect();
+    urls.sort();
+    assert_eq!(
+        urls,
+        vec![
+            "https://other.example.com/es.vtt",
+            "https://subs.example.com/en.srt",
+            "https://subs.example.com/fr.srt"
+        ]
+    );
+
+    assert_eq!(result.sources.len(), 3);
+    let broken_health = result
+        .sources
+        .iter()
+        .find(|s| s.addon_id == "org.mock.subs.broken")
+        .unwrap();
+    assert!(!broken_health.success);
+}
+
+#[tokio::test]
+async fn test_aggregator_sorts_streams_by_quality() {
+    let preferred = MockAddon::start("org.mock.preferred").await;
+    preferred
+        .with_streams(
+            "movie",
+            "tt0111161",
+            json!({ "streams": [
+                stream_json("https://preferred.example.com/480.mp4", "480p"),
+                stream_json("https://preferred.example.com/1080.mp4", "1080p"),
+            ] }),
+        )
+        .await;
+    let fallback = MockAddon::start("org.mock.fallback").await;
+    fallback
+        .with_streams(
+            "movie",
+            "tt0111161",
+            json!({ "streams": [
+                stream_json("https://fallback.example.com/1080.mp4", "1080p"),
+                stream_json("https://fallback.example.com/4k.mkv", "4K"),
+            ] }),
+        )
+        .await;
+    let addons = [preferred.addon(10), fallback.addon(1)];
+
+    let urls = |streams: Vec<app_lib::Stream>| -> Vec<String> {
+        streams.into_iter().map(|s| s.url).collect()
+    };
+
+    // Addon priority order by default
+    let result = ContentAggregator::new()
+        .query_streams(&addons, "movie", "tt0111161")
+        .await;
+    assert_eq!(
+        urls(result.streams)[0],
+        "https://preferred.example.com/480.mp4"
+    );
+
+    // Best quality first, equal quality keeps priority order
+    let result = ContentAggregator::new()
+        .with_quality_sort(true)
+        .query_streams(&addons, "movie", "tt0111161")
+        .await;
+    assert_eq!(
+        urls(result.streams),
+        vec![
+            "https://fallback.example.com/4k.mkv",
+            "https://preferred.example.com/1080.mp4",
+            "https://fallback.example.com/1080.mp4",
+            "https://preferred.example.com/480.mp4",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_aggregator_strict_validation_drops_invalid_entries() {
+    let mock = MockAddon::start("org.mock.sloppy").await;
+    mock.with_catalog(
+        "movie",
+        "top",
+        json!({ "metas": [
+            { "id": "tt0111161", "type": "movie", "name": "The Shawshank Redemption" },
+            { "id": "tt0068646", "type": "movie", "name": "" },
+            { "id": "  ", "type": "movie", "name": "No Id" },
+            { "id": "tt0468569", "type": "movie", "name": "The Dark Knight" },
+        ] }),
+    )
+    .await
+    .with_streams(
+        "movie",
+        "tt0111161",
+        json!({ "streams": [
+            stream_json("https://cdn.example.com/1080.mp4", "1080p"),
+            { "url": "https://cdn.example.com/unlabelled.mp4" },
+            { "url": "https://cdn.example.com/blank.mp4", "name": " ", "title": "" },
+        ] }),
+    )
+    .await;
+    let addons = [mock.addon(1)];
+
+    // Off by default: everything that parses is passed on
+    let lenient = ContentAggregator::new()
+        .query_catalogs(&addons, "movie", "top", &None)
+        .await;
+    assert_eq!(lenient.items.len(), 4);
+    assert_eq!(lenient.sources[0].invalid_count, 0);
+
+    let strict = ContentAggregator::new().with_strict_validation(true);
+    let catalog = strict.query_catalogs(&addons, "movie", "top", &None).await;
+    let ids: Vec<&str> = catalog.items.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, vec!["tt0111161", "tt0468569"]);
+    assert!(catalog.sources[0].success);
+    assert_eq!(catalog.sources[0].item_count, 2);
+    assert_eq!(catalog.sources[0].invalid_count, 2);
+
+    let streams = strict
+        .query_streams_detailed(&addons, "movie", "tt0111161")
+        .await;
+    assert_eq!(streams.streams.len(), 1);
+    assert_eq!(streams.streams[0].url, "https://cdn.example.com/1080.mp4");
+    assert_eq!(streams.sources[0].item_count, 1);
+    assert_eq!(streams.sources[0].invalid_count, 2);
+}
+
+#[tokio::test]
+async fn test_aggregator_artwork_first_sorts_posterless_items_last() {
+    let mock = MockAddon::start("org.mock.stubs").await;
+    mock.with_catalog(
+        "movie",
+        "top",
+        json!({ "metas": [
+            { "id": "tt0000001", "type": "movie", "name": "Stub" },
+            { "id": "tt0000002", "type": "movie", "name": "Poster", "poster": "https://img.example.com/2.jpg" },
+            { "id": "tt0000003", "type": "movie", "name": "Blank Poster", "poster": " " },
+            { "id": "tt0000004", "type": "movie", "name": "Background", "background": "https://img.example.com/4.jpg" },
+        ] }),
+    )
+    .await;
+    let addons = [mock.addon(1)];
+
+    // Off by default: addon order is kept
+    let unsorted = ContentAggregator::new()
+        .query_catalogs(&addons, "movie", "top", &None)
+        .await;
+    let ids: Vec<&str> = unsorted.items.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec!["tt0000001", "tt0000002", "tt0000003", "tt0000004"]
+    );
+
+    let sorted = ContentAggregator::new()
+        .with_artwork_first(true)
+        .query_catalogs(&addons, "movie", "top", &None)
+        .await;
+    let ids: Vec<&str> = sorted.items.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec!["tt0000002", "tt0000004", "tt0000001", "tt0000003"]
+    );
+}
+
+#[tokio::test]
+async fn test_aggregator_tags_catalog_sources_with_request_id() {
+    let working = MockAddon::start("org.mock.working").await;
+    working
+        .with_catalog(
+            "movie",
+            "top",
+            json!({ "metas": [{ "id": "tt0111161", "type": "movie", "name": "The Shawshank Redemption" }] }),
+        )
+        .await;
+    let broken = MockAddon::start("org.mock.broken").await;
+    broken.with_status("/catalog/movie/top.json", 500).await;
+    let addons = [working.addon(2), broken.addon(1)];
+
+    let aggregator = ContentAggregator::new();
+    let first = aggregator
+        .query_catalogs(&addons, "movie", "top", &None)
+        .await;
+    assert!(!first.request_id.is_empty());
+    assert_eq!(first.sources.len(), 2);
+    for source in &first.sources {
+        assert_eq!(
+            source.request_id.as_deref(),
+            Some(first.request_id.as_str())
+        );
+    }
+
+    // Each aggregation gets its own id
+    let second = aggregator
+        .query_catalogs(&addons, "movie", "top", &None)
+        .await;
+    assert_ne!(second.request_id, first.request_id);
+    assert_eq!(
+        second.sources[0].request_id.as_deref(),
+        Some(second.request_id.as_str())
+    );
+}
+
+#[tokio::test]
+async fn test_aggregator_times_out_slow_addon() {
+    let slow = MockAddon::start("org.mock.slow").await;
+    slow.with_slow_streams(
+        "movie",
+        "tt0111161",
+        json!({ "streams": [stream_json("https://slow.example.com/1.mp4", "1080p")] }),
+        Duration::from_secs(2),
+    )
+    .await;
+
+    let result = ContentAggregator::new()
+        .with_timeout(Duration::from_millis(200))
+        .query_streams(&[slow.addon(1)], "movie", "tt0111161")
+        .await;
+
+    assert!(result.streams.is_empty());
+    assert!(!result.sources[0].success);
+    assert_eq!(result.sources[0].error.as_deref(), Some("Timeout"));
+}
+
+#[tokio::test]
+async fn test_aggregator_streams_sources_as_they_arrive() {
+    let fast = MockAddon::start("org.mock.fast").await;
+    fast.with_streams(
+        "movie",
+        "tt0111161",
+        json!({ "streams": [stream_json("https://fast.example.com/1.mp4", "720p")] }),
+    )
+    .await;
+    let slow = MockAddon::start("org.mock.slow").await;
+    slow.with_slow_streams(
+        "movie",
+        "tt0111161",
+        json!({ "streams": [
+            stream_json("https://slow.example.com/1.mp4", "1080p"),
+            stream_json("https://fast.example.com/1.mp4", "720p"),
+        ] }),
+        Duration::from_secs(2),
+    )
+    .await;
+
+    let started = std::time::Instant::now();
+    let (mut rx, mut health) = ContentAggregator::new()
+        .with_timeout(Duration::from_secs(5))
+        // The slow addon has the higher priority but must not hold the fast one back
+        .query_streams_stream(&[fast.addon(1), slow.addon(10)], "movie", "tt0111161");
+
+    let first = rx.recv().await.expect("fast addon stream");
+    assert_eq!(first.addon_id, "org.mock.fast");
+    assert!(started.elapsed() < Duration::from_secs(2));
+    // The fast addon's health is reported without waiting for the slow one
+    let fast_health = health.recv().await.expect("fast addon health");
+    assert_eq!(fast_health.addon_id, "org.mock.fast");
+    assert!(fast_health.success);
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    let second = rx.recv().await.expect("slow addon stream");
+    assert_eq!(second.addon_id, "org.mock.slow");
+    assert_eq!(second.url, "https://slow.example.com/1.mp4");
+
+    // The slow addon's copy of the fast stream is deduplicated, then the channel closes
+    assert!(rx.recv().await.is_none());
+    let slow_health = health.recv().await.expect("slow addon health");
+    assert_eq!(slow_health.addon_id, "org.mock.slow");
+    assert_eq!(slow_health.item_count, 2);
+    assert!(health.recv().await.is_none());
+}
+
+#[tokio::test]
+async fn test_aggregator_skips_recently_failed_addon() {
+    let route = "/stream/movie/tt0111161.json";
+    let broken = MockAddon::start("org.mock.broken").await;
+    broken.with_status(route, 500).await;
+    let addons = [broken.addon(1)];
+    let cache = || Arc::new(Mutex::new(CacheManager::new(None).unwrap()));
+
+    let aggregator = ContentAggregator::with_cache(cache());
+    let first = aggregator
+        .query_streams(&addons, "movie", "tt0111161")
+        .await;
+    let second = aggregator
+        .query_streams(&addons, "movie", "tt0111161")
+        .await;
+
+    // The failure is remembered, so the second query never reaches the addon
+    assert_eq!(broken.request_count(route).await, 1);
+    assert!(!second.sources[0].success);
+    assert_eq!(second.sources[0].error, first.sources[0].error);
+
+    // With negative caching disabled every query goes out
+    let aggregator = ContentAggregator::with_cache(cache()).with_negative_ttl(Duration::ZERO);
+    aggregator
+        .query_streams(&addons, "movie", "tt0111161")
+        .await;
+    aggregator
+        .query_streams(&addons, "movie", "tt0111161")
+        .await;
+    assert_eq!(broken.request_count(route).await, 3);
+}
+
+#[tokio::test]
+async fn test_aggregator_limits_concurrent_addon_queries() {
+    let (base_url, in_flight) = spawn_counting_stream_server(Duration::from_millis(300)).await;
+    let addons: Vec<_> = (0..6)
+        .map(|i| addon_at(&format!("org.mock.busy.{}", i), &base_url, i))
+        .collect();
+
+    let result = ContentAggregator::new()
+        .with_max_concurrency(2)
+        .query_streams(&addons, "movie", "tt0111161")
+        .await;
+
+    assert_eq!(result.sources.len(), 6);
+    assert!(result.sources.iter().all(|s| s.success));
+    assert_eq!(in_flight.peak.load(Ordering::SeqCst), 2);
+
+    // Six 300ms requests two at a time take three rounds (900ms); one at a
+    // time they would take 1800ms. The gap leaves room for a loaded machine.
+    assert!(
+        (900..1800).contains(&result.total_time_ms),
+        "took {}ms",
+        result.total_time_ms
+    );
+}
+
+#[tokio::test]
+async fn test_addon_client_retries_transport_errors_only() {
+    // Connection failures are retried (initial attempt + 3 retries)
+    let (base_url, attempts) = spawn_dropping_server().await;
+    let client = AddonClient::new(base_url).unwrap();
+    assert!(client.get_streams("movie", "tt0111161").await.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 4);
+
+    // HTTP error statuses are returned as-is without retrying
+    let mock = MockAddon::start("org.mock.status").await;
+    mock.with_status("/stream/movie/tt0111161.json", 503).await;
+    let client = AddonClient::new(mock.base_url()).unwrap();
+    let result = client.get_streams("movie", "tt0111161").await;
+    assert!(matches!(result, Err(AddonError::HttpError(_))));
+    assert_eq!(mock.request_count("/stream/movie/tt0111161.json").await, 1);
+}
+
+#[tokio::test]
+async fn test_addon_client_enforces_size_limits() {
+    let mock = MockAddon::start("org.mock.large").await;
+    let mut manifest = mock.default_manifest();
+    manifest["description"] = json!("x".repeat(200 * 1024));
+    mock.with_manifest(manifest).await;
+
+    let client = AddonClient::new(mock.base_url()).unwrap();
+    let result = client.get_manifest().await;
+    assert!(matches!(result, Err(AddonError::ValidationError(_))));
+}
+
+#[tokio::test]
+async fn test_addon_client_configurable_response_limits() {
+    let mock = MockAddon::start("org.mock.limits").await;
+    let metas: Vec<_> = (0..50)
+        .map(|i| json!({ "id": format!("tt{:07}", i), "type": "movie", "name": "Movie" }))
+        .collect();
+    mock.with_catalog("movie", "top", json!({ "metas": metas }))
+        .await
+        .with_streams(
+            "movie",
+            "tt0111161",
+            json!({ "streams": [{
+                "url": "https://cdn.example.com/1080.mp4",
+                "name": "1080p",
+                "description": "x".repeat(2 * 1024 * 1024),
+            }] }),
+        )
+        .await;
+
+    // Defaults keep the whole catalog and accept a 2MB response
+    let client = AddonClient::new(mock.base_url()).unwrap();
+    let catalog = client.get_catalog("movie", "top", None).await.unwrap();
+    assert_eq!(catalog.metas.len(), 50);
+    assert!(client.get_streams("movie", "tt0111161").await.is_ok());
+
+    let limited = AddonClient::new(mock.base_url())
+        .unwrap()
+        .with_limits(ResponseLimits::new(10, 1024 * 1024).unwrap());
+    let catalog = limited.get_catalog("movie", "top", None).await.unwrap();
+    assert_eq!(catalog.metas.len(), 10);
+    assert_eq!(catalog.metas[9].id, "tt0000009");
+    let result = limited.get_streams("movie", "tt0111161").await;
+    assert!(matches!(result, Err(AddonError::ValidationError(_))));
+
+    // Bodies without a Content-Length are checked once read
+    let subtitles = json!({ "subtitles": [{
+        "id": "en",
+        "url": "https://subs.example.com/en.srt",
+        "lang": "eng",
+        "padding": "x".repeat(2 * 1024 * 1024),
+    }] });
+    let base_url = spawn_chunked_subtitle_server(subtitles).await;
+    let client = AddonClient::new(base_url.clone()).unwrap();
+    let subs = client.get_subtitles("movie", "tt0111161").await.unwrap();
+    assert_eq!(subs.subtitles.len(), 1);
+    let limited = AddonClient::new(base_url)
+        .unwrap()
+        .with_limits(ResponseLimits::new(10, 1024 * 1024).unwrap());
+    let result = limited.get_subtitles("movie", "tt0111161").await;
+    assert!(matches!(result, Err(AddonError::ValidationError(_))));
+
+    // Out-of-bounds limits are rejected
+    assert!(ResponseLimits::new(0, 1024 * 1024).is_err());
+    assert!(ResponseLimits::new(100_000, 1024 * 1024).is_err());
+    assert!(ResponseLimits::new(1000, 1024).is_err());
+    assert!(ResponseLimits::new(1000, 1024 * 1024 * 1024).is_err());
+}
+
+#[tokio::test]
+async fn test_addon_client_typed_series_meta() {
+    let mock = MockAddon::start("org.mock.meta").await;
+    mock.with_meta(
+        "series",
+        "tt0903747",
+        json!({
+            "meta": {
+                "id": "tt0903747",
+                "type": "series",
+                "name": "Breaking Bad",
+                "background": "https://images.example.com/bb.jpg",
+                "genres": null,
+                "cast": ["Bryan Cranston", "Aaron Paul"],
+                "runtime": 49,
+                "videos": [
+                    { "id": "tt0903747:1:1", "name": "Pilot", "season": 1, "episode": 1 },
+                    { "id": "tt0903747:1:2", "name": "Cat's in the Bag...", "season": 1, "episode": 2 }
+                ]
+            }
+        }),
+    )
+    .await;
+
+    let client = AddonClient::new(mock.base_url()).unwrap();
+    let meta = client.get_meta("series", "tt0903747").await.unwrap().meta;
+    assert_eq!(meta.name, "Breaking Bad");
+    assert!(meta.genres.is_empty());
+    assert_eq!(meta.cast.len(), 2);
+    assert_eq!(meta.runtime.as_deref(), Some("49"));
+    assert_eq!(meta.videos.len(), 2);
+    assert_eq!(meta.videos[0].title, "Pilot");
+    assert_eq!(meta.videos[1].episode, Some(2));
+
+    let missing = client.get_meta("series", "tt0000000").await;
+    assert!(matches!(missing, Err(AddonError::HttpError(_))));
+}
+
+#[tokio::test]
+async fn test_addon_client_custom_timeout() {
+    let mock = MockAddon::start("org.mock.indexer").await;
+    let manifest = mock.default_manifest();
+    mock.respond(
+        "/manifest.json",
+        ResponseTemplate::new(200)
+            .set_body_json(manifest)
+            .set_delay(Duration::from_secs(8)),
+    )
+    .await;
+
+    let default_client = AddonClient::new(mock.base_url()).unwrap();
+    let patient_client =
+        AddonClient::with_timeout(mock.base_url(), Duration::from_secs(10)).unwrap();
+    let mut adjusted_client = AddonClient::new(mock.base_url()).unwrap();
+    adjusted_client
+        .set_timeout(Duration::from_secs(10))
+        .unwrap();
+
+    let (default_result, patient_result, adjusted_result) = tokio::join!(
+        default_client.get_manifest(),
+        patient_client.get_manifest(),
+        adjusted_client.get_manifest()
+    );
+    assert!(matches!(default_result, Err(AddonError::HttpError(_))));
+    assert_eq!(patient_result.unwrap().id, "org.mock.indexer");
+    assert_eq!(adjusted_result.unwrap().id, "org.mock.indexer");
+}
+
+#[tokio::test]
+async fn test_probe_addon_reports_partial_capabilities() {
+    let mock = MockAddon::start("org.mock.partial").await;
+    mock.with_catalog(
+        "movie",
+        "top",
+        json!({ "metas": [{ "id": "tt0111161", "type": "movie", "name": "The Shawshank Redemption" }] }),
+    )
+    .await
+    .with_streams(
+        "movie",
+        "tt0111161",
+        json!({ "streams": [stream_json("https://cdn.example.com/movie.mp4", "1080p")] }),
+    )
+    .await;
+    // Subtitles are declared in the manifest but never answered
+
+    let report = api::probe_addon(&mock.base_url()).await;
+
+    let manifest = report.manifest.as_ref().expect("manifest summary");
+    assert_eq!(manifest.id, "org.mock.partial");
+    assert_eq!(manifest.resources, vec!["catalog", "stream", "subtitles"]);
+    assert_eq!(manifest.types, vec!["movie", "series"]);
+
+    let check = |resource: &str| {
+        report
+            .checks
+            .iter()
+            .find(|c| c.resource == resource)
+            .unwrap_or_else(|| panic!("no {} check", resource))
+    };
+    assert!(check("manifest").success);
+    assert!(check("catalog").success);
+    assert_eq!(check("catalog").item_count, Some(1));
+    assert!(check("stream").success);
+    assert_eq!(check("stream").item_count, Some(1));
+    assert!(!check("subtitles").success);
+    assert!(check("subtitles").error.is_some());
+    assert!(!report.is_healthy());
+
+    // The install URL policy still applies to test_addon itself
+    let err = api::test_addon(&mock.base_url()).await.unwrap_err();
+    assert!(err.to_string().contains("https"));
+}
+
+#[tokio::test]
+async fn test_probe_addon_without_manifest() {
+    let mock = MockAddon::start("org.mock.missing").await;
+    mock.with_status("/manifest.json", 404).await;
+
+    let report = api::probe_addon(&mock.base_url()).await;
+
+    assert!(report.manifest.is_none());
+    assert_eq!(report.checks.len(), 1);
+    assert_eq!(report.checks[0].resource, "manifest");
+    assert!(!report.checks[0].success);
+}
+
+#[tokio::test]
+async fn test_sandbox_query_returns_raw_response() {
+    let mock = MockAddon::start("org.mock.sandbox").await;
+    mock.with_catalog(
+        "movie",
+        "top",
+        json!({ "metas": [{ "id": "tt0111161", "type": "movie", "name": "" }], "cacheMaxAge": 60 }),
+    )
+    .await
+    .with_streams(
+        "movie",
+        "tt0111161",
+        json!({ "streams": [stream_json("https://cdn.example.com/1080.mp4", "1080p")] }),
+    )
+    .await;
+
+    // Entries a strict aggregator would drop and unknown fields are kept as sent
+    let manifest_url = format!("{}/manifest.json", mock.base_url());
+    let catalog = api::sandbox_query(&manifest_url, "catalog", "movie", "top", None)
+        .await
+        .unwrap();
+    assert_eq!(catalog["metas"][0]["name"], "");
+    assert_eq!(catalog["cacheMaxAge"], 60);
+
+    let streams = api::sandbox_query(&mock.base_url(), "stream", "movie", "tt0111161", None)
+        .await
+        .unwrap();
+    assert_eq!(
+        streams["streams"][0]["url"],
+        "https://cdn.example.com/1080.mp4"
+    );
+
+    assert!(
+        api::sandbox_query(&mock.base_url(), "manifest", "movie", "top", None)
+            .await
+            .is_err()
+    );
+}
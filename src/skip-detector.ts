@@ -125,7 +125,7 @@ export class SkipDetector {
         outro_start: segments.outro?.start ?? null,
         outro_end: segments.outro?.end ?? null,
       };
-      await invoke('save_skip_segments', { mediaId: this.media.id, segments: payload });
+      await invoke('set_skip_segments', { mediaId: this.media.id, segments: payload });
       // Also mirror to localStorage for offline fallback
       localStorage.setItem(`skipSegments:${this.media.id}`, JSON.stringify(segments));
       this.store.setSkipSegments(segments);
@@ -1,6 +1,6 @@
 import { writable } from 'svelte/store';
 import { invoke } from '@tauri-apps/api/core';
-import type { Addon } from '../types/tauri';
+import type { Addon, AddonInstallResult } from '../types/tauri';
 import { Toast } from '../ui-utils';
 
 // This would typically come from a remote API
@@ -10,8 +10,12 @@ const CURATED_ADDON_URLS = [
     'https://94c8cb9f702d-stremio-streaming-catalogs.baby-beamup.club/manifest.json'
 ];
 
+// Addons idle for this long are suggested for cleanup
+const UNUSED_ADDON_DAYS = 30;
+
 interface AddonStoreState {
   installedAddons: Addon[];
+  unusedAddonIds: string[];
   storeAddons: Addon[];
   loading: boolean;
   error: string | null;
@@ -20,6 +24,7 @@ interface AddonStoreState {
 function createAddonStore() {
   const { subscribe, update } = writable<AddonStoreState>({
     installedAddons: [],
+    unusedAddonIds: [],
     storeAddons: [],
     loading: false,
     error: null,
@@ -31,11 +36,22 @@ function createAddonStore() {
       try {
         const addons = await invoke<Addon[]>('get_addons', {});
         update(state => ({ ...state, installedAddons: addons, loading: false }));
+        await methods.loadUnusedAddons();
       } catch (err) {
         update(state => ({ ...state, error: String(err), loading: false }));
       }
     },
 
+    async loadUnusedAddons() {
+      try {
+        const unused = await invoke<Addon[]>('get_unused_addons', { days: UNUSED_ADDON_DAYS });
+        update(state => ({ ...state, unusedAddonIds: unused.map(a => a.id) }));
+      } catch (err) {
+        // Cleanup suggestions are optional; keep the installed list usable
+        console.warn('Failed to load unused addons:', err);
+      }
+    },
+
     async loadStoreAddons() {
         update(state => ({ ...state, loading: true }));
         try {
@@ -50,7 +66,11 @@ function createAddonStore() {
 
     async installAddon(manifestUrl: string) {
       try {
-        await invoke('install_addon', { manifestUrl });
+        const result = await invoke<AddonInstallResult>('install_addon', { manifestUrl });
+        if (result?.conflict) {
+          Toast.warning(`This addon is already installed from ${result.conflict.existing_url}`);
+          return;
+        }
         Toast.success('Addon installed successfully!');
         await methods.loadInstalledAddons(); // Refresh the list
       } catch (err) {
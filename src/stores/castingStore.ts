@@ -5,7 +5,7 @@
  */
 import { writable, derived } from 'svelte/store';
 import { invoke } from '@tauri-apps/api/core';
-import type { CastDevice, CastSession } from '../types/tauri';
+import type { CastDevice, CastDeviceTest, CastSession } from '../types/tauri';
 
 interface CastingState {
   devices: CastDevice[];
@@ -116,6 +116,76 @@ function createCastingStore() {
       console.log('Cast session stopped');
     },
 
+    // Cast library items one after another
+    async castQueue(deviceId: string, mediaIds: string[]): Promise<void> {
+      update(state => ({ ...state, error: null }));
+
+      try {
+        const session = await invoke<CastSession>('cast_queue', { deviceId, mediaIds });
+        update(state => ({ ...state, activeSession: session, showDevicePicker: false }));
+      } catch (error) {
+        console.error('Failed to start cast queue:', error);
+        update(state => ({ ...state, error: `Failed to start casting: ${error}` }));
+        throw error;
+      }
+    },
+
+    castNext(): Promise<void> {
+      return this.controlCasting('cast_next');
+    },
+
+    castPrevious(): Promise<void> {
+      return this.controlCasting('cast_previous');
+    },
+
+    // Send a playback control to the active session
+    async controlCasting(command: string, args: Record<string, unknown> = {}): Promise<void> {
+      let sessionId: string | undefined;
+      update(state => {
+        sessionId = state.activeSession?.session_id;
+        return state;
+      });
+      if (!sessionId) return;
+
+      try {
+        const session = await invoke<CastSession>(command, { sessionId, ...args });
+        update(state => ({ ...state, activeSession: session, error: null }));
+      } catch (error) {
+        console.error(`Failed to run ${command}:`, error);
+        update(state => ({ ...state, error: `Cast control failed: ${error}` }));
+      }
+    },
+
+    pauseCasting(): Promise<void> {
+      return this.controlCasting('pause_casting');
+    },
+
+    resumeCasting(): Promise<void> {
+      return this.controlCasting('resume_casting');
+    },
+
+    seekCasting(positionSecs: number): Promise<void> {
+      return this.controlCasting('seek_casting', { positionSecs });
+    },
+
+    setCastingVolume(level: number): Promise<void> {
+      return this.controlCasting('set_casting_volume', { level });
+    },
+
+    setCastingMute(muted: boolean): Promise<void> {
+      return this.controlCasting('set_casting_mute', { muted });
+    },
+
+    // Play a short sample on a device to check that casting works
+    async testDevice(deviceId: string): Promise<CastDeviceTest> {
+      const result = await invoke<CastDeviceTest>('test_cast_device', { deviceId });
+      update(state => ({
+        ...state,
+        error: result.success ? null : `Device test failed: ${result.error}`,
+      }));
+      return result;
+    },
+
     // Get active sessions
     async getSessions(): Promise<void> {
       try {
@@ -16,6 +16,7 @@ const DEFAULT_SETTINGS: UserPreferences = {
   playback_speed: 1.0,
   volume: 0.8,
   notifications_enabled: true,
+  episode_check_interval_minutes: 60,
   auto_update: true,
   telemetry_enabled: false,
   default_quality: 'auto',
@@ -34,8 +35,18 @@ const DEFAULT_SETTINGS: UserPreferences = {
   preload_next: true,
   torrent_connections: '100',
   cache_size: '1024',
+  prefer_non_p2p: false,
+  max_active_torrents: 3,
+  max_catalog_items: 1000,
+  max_addon_response_mb: 10,
+  scan_concurrency: 4,
+  probe_timeout_secs: 30,
+  prefer_local: true,
   player_engine: 'auto',
   debug_logging: false,
+  strict_addon_validation: false,
+  allow_private_addon_urls: false,
+  catalog_artwork_first: false,
   analytics: false
 };
 
@@ -9,11 +9,14 @@ export interface MediaItem {
   description?: string;
   poster_url?: string;
   backdrop_url?: string;
+  logo_url?: string;
+  thumb_url?: string;
   rating?: number;
   duration?: number;
   added_to_library?: string;
   watched: boolean;
   progress?: number;
+  progress_percent?: number;
 }
 
 export type MediaType = 
@@ -32,10 +35,13 @@ export interface UserPreferences {
   ui_language: string;
   region: string;
   notifications_enabled: boolean;
+  episode_check_interval_minutes?: number;
   auto_update: boolean;
   
   // Integrations / API keys
   tmdb_api_key?: string;
+  /** Set by opensubtitles_login; the token itself stays in the OS keyring */
+  opensubtitles_logged_in?: boolean;
   // Playback
   autoplay: boolean;
   quality: string;
@@ -60,12 +66,32 @@ export interface UserPreferences {
   buffer_size?: string;
   preload_next?: boolean;
   torrent_connections?: string;
-  cache_size?: string;
+  cache_size?: string; // MB of metadata and addon responses to keep cached
+  prefer_non_p2p?: boolean;
+  max_active_torrents?: number;
+  max_catalog_items?: number;
+  max_addon_response_mb?: number;
+  scan_concurrency?: number;
+  probe_timeout_secs?: number;
+  prefer_local?: boolean;
   player_engine?: string;
   debug_logging?: boolean;
+  strict_addon_validation?: boolean;
+  allow_private_addon_urls?: boolean;
+  catalog_artwork_first?: boolean;
   analytics?: boolean;
 }
 
+export interface UserProfile {
+  id: string;
+  username: string;
+  email?: string | null;
+  preferences: UserPreferences;
+  library_items: string[];
+  watchlist: string[];
+  favorites: string[];
+}
+
 export interface CacheStats {
   metadata_total: number;
   metadata_valid: number;
@@ -73,6 +99,15 @@ export interface CacheStats {
   addon_total: number;
   addon_valid: number;
   addon_expired: number;
+  size_bytes: number;
+}
+
+export interface OptimizeReport {
+  health_records_removed: number;
+  vacuumed: boolean;
+  size_before: number;
+  size_after: number;
+  reclaimed_bytes: number;
 }
 
 export interface Addon {
@@ -87,6 +122,40 @@ export interface Addon {
   manifest: AddonManifest;
 }
 
+export interface AddonInstallResult {
+  addon_id: string;
+  installed: boolean;
+  conflict?: AddonConflict;
+}
+
+export interface ManifestSummary {
+  id: string;
+  name: string;
+  version: string;
+  resources: string[];
+  types: string[];
+}
+
+export interface ResourceCheck {
+  resource: string;
+  success: boolean;
+  latency_ms: number;
+  error?: string;
+  item_count?: number;
+}
+
+export interface AddonTestReport {
+  url: string;
+  manifest: ManifestSummary | null;
+  checks: ResourceCheck[];
+}
+
+export interface AddonConflict {
+  addon_id: string;
+  existing_url: string;
+  new_url: string;
+}
+
 export type AddonType =
   | { ContentProvider: null }
   | { MetadataProvider: null }
@@ -110,9 +179,44 @@ export interface Stream {
   description?: string;
 }
 
+export interface Episode {
+  id: string;
+  series_id: string;
+  season: number;
+  episode: number;
+  title: string;
+  overview?: string;
+  thumbnail?: string;
+  released?: string;
+  runtime?: string;
+  watched: boolean;
+  progress: number;
+}
+
 export interface StreamWithSource extends Stream {
   addon_id: string;
   addon_name: string;
+  size_bytes?: number;
+  seeders?: number;
+  not_web_ready?: boolean;
+  subtitles?: Subtitle[];
+}
+
+export type ResolvedVia = 'local_file' | 'addon' | 'debrid' | 'torrent';
+
+export interface PlayableResult {
+  url: string;
+  stream?: StreamWithSource;
+  subtitles: Subtitle[];
+  is_local: boolean;
+  resolved_via: ResolvedVia;
+}
+
+export interface StreamFilter {
+  max_size_bytes?: number;
+  max_resolution?: number;
+  min_seeders?: number;
+  allow_p2p?: boolean;
 }
 
 // Live TV
@@ -125,7 +229,20 @@ export interface LiveTvChannel {
   stream_url: string;
 }
 
+export interface ChannelStatus {
+  channel_id: string;
+  online: boolean;
+  checked_at: number; // unix timestamp
+  error?: string;
+}
+
+export interface ChannelGroup {
+  name: string | null;
+  channel_count: number;
+}
+
 export interface EpgProgram {
+  id?: number;
   channel_id: string;
   start: number; // unix timestamp
   end: number;   // unix timestamp
@@ -136,6 +253,16 @@ export interface EpgProgram {
   episode?: number;
 }
 
+export interface ProgramReminder {
+  program_id: number;
+  channel_id: string;
+  channel_name: string;
+  title: string;
+  start: number; // unix timestamp
+  end: number;   // unix timestamp
+  notified: boolean;
+}
+
 // Local media
 export interface LocalMediaFile {
   id: string;
@@ -153,6 +280,7 @@ export interface LocalMediaFile {
   tmdb_id?: string;
   imdb_id?: string;
   poster_url?: string;
+  plot?: string;
   added_at: string;
   last_modified: string;
 }
@@ -252,7 +380,7 @@ export const EpisodeId = {
 };
 
 // Casting Types
-export type CastProtocol = 'chromecast' | 'dlna' | 'airplay';
+export type CastProtocol = 'chromecast' | 'dlna' | 'airplay' | 'local';
 
 export type DeviceStatus = 
   | 'available' 
@@ -273,8 +401,11 @@ export interface CastDevice {
   model?: string;
   manufacturer?: string;
   status: DeviceStatus;
+  capability?: DeviceCapability;
 }
 
+export type DeviceCapability = 'video' | 'audio_only';
+
 export interface CastSession {
   session_id: string;
   device_id: string;
@@ -284,11 +415,79 @@ export interface CastSession {
   position: number;
   duration: number;
   state: PlaybackState;
+  volume?: number;
+  muted?: boolean;
+  queue?: string[];
+  queue_index?: number;
+}
+
+export interface CastDeviceTest {
+  device_id: string;
+  success: boolean;
+  error?: string;
+}
+
+// Backend events, by event name (listen with @tauri-apps/api/event)
+export interface ScanProgress {
+  path: string;
+  processed: number;
+  total: number;
+}
+
+export interface NewEpisode {
+  series_id: string;
+  series_name: string;
+  episode_id: string;
+  season: number;
+  episode: number;
+  title: string;
+  air_date?: string;
+  poster_url?: string;
+}
+
+export interface AddonHealthChanged {
+  addon_id: string;
+  healthy: boolean;
+  error?: string;
+}
+
+export type RecordingState = 'scheduled' | 'recording' | 'completed' | 'failed' | 'cancelled';
+
+export interface RecordingProgress {
+  program_id: number;
+  channel_id: string;
+  title: string;
+  state: RecordingState;
+  recorded_secs: number;
+  duration_secs: number;
+  file_path: string;
+  error: string | null;
+}
+
+export interface JellyfinImportProgress {
+  processed: number;
+  total: number;
+  imported: number;
+}
+
+export interface AppEvents {
+  'scan-progress': ScanProgress;
+  'new-episodes': NewEpisode[];
+  'addon-health-changed': AddonHealthChanged;
+  'cast-state-changed': CastSession;
+  'program-starting-soon': ProgramReminder;
+  'recording-progress': RecordingProgress;
+  'jellyfin-import-progress': JellyfinImportProgress;
 }
 
 // Subtitle Auto-Fetch Types
 export type SubtitleProvider = 'opensubtitles' | 'subdb';
 
+export interface QuotaInfo {
+  remaining: number;
+  reset_at?: string;
+}
+
 export interface SubtitleResult {
   id: string;
   language: string;
@@ -303,6 +502,11 @@ export interface SubtitleResult {
   rating?: number;
 }
 
+export interface PrefetchResult {
+  subtitles: SubtitleResult[];
+  error?: string;
+}
+
 export interface Catalog {
   catalog_type: string;
   id: string;
@@ -318,6 +522,39 @@ export interface CatalogInfo {
   media_type: string;
 }
 
+export interface LibraryPage {
+  items: MediaItem[];
+  total_count: number;
+}
+
+export interface WatchHistoryEntry {
+  id: number;
+  media_id: string;
+  title: string;
+  poster_url: string | null;
+  watched_at: string;
+  position: number; // seconds
+  duration: number | null; // seconds
+}
+
+export interface ImportedHistory {
+  title: string;
+  year?: number;
+  external_id?: string;
+  media_type?: MediaType;
+  watched_at?: string; // RFC3339 timestamp
+  position?: number; // seconds
+  watched?: boolean;
+}
+
+export interface WatchHistoryImport {
+  matched: number;
+  created: number;
+  duplicates: number; // already in the watch history, not added again
+  unmatched: number; // no library match and no external_id to add the title under
+  unmatched_titles: string[];
+}
+
 export interface Playlist {
   id: string;
   name: string;
@@ -351,6 +588,13 @@ export interface SearchFilters {
   sort_by?: string;
 }
 
+export interface SearchHit {
+  item: MediaItem;
+  // Matched terms are wrapped in <mark></mark>; other text is not escaped
+  title_snippet: string;
+  description_snippet?: string;
+}
+
 export interface AddonHealthSummary {
   addon_id: string;
   addon_name?: string;
@@ -362,6 +606,21 @@ export interface AddonHealthSummary {
   failed_requests: number;
   last_error: string | null;
   health_score: number;
+  invalid_count?: number;
+}
+
+export interface AddonRatingSummary {
+  addon_id: string;
+  rating_avg: number;
+  rating_count: number;
+  weighted_rating: number;
+}
+
+export interface SkipSegments {
+  intro_start?: number;
+  intro_end?: number;
+  outro_start?: number;
+  outro_end?: number;
 }
 
 export interface PerformanceMetrics {
@@ -384,6 +643,12 @@ export interface DiagnosticsInfo {
 }
 
 // Calendar
+export interface TorrentQueueStatus {
+  active: number;
+  queued: number;
+  max_active: number;
+}
+
 export interface CalendarEntry {
   series_id: string;
   series_name: string;
@@ -395,38 +660,76 @@ export interface CalendarEntry {
   poster_url?: string;
   backdrop_url?: string;
   description?: string;
+  air_time_known?: boolean;
 }
 
 // Tauri Command Definitions
 export interface TauriCommands {
   // Library
   get_library_items: { args: {}; return: MediaItem[] };
+  get_library_items_paged: { args: { offset: number; limit: number; sort?: string }; return: LibraryPage };
   add_to_library: { args: { item: MediaItem }; return: void };
+  add_to_library_batch: { args: { items: MediaItem[] }; return: number };
+  jellyfin_import: { args: { serverUrl: string; apiKey: string }; return: number };
+  remove_from_library: { args: { mediaId: string }; return: boolean };
   
   // Search
   search_content: { args: { query: string }; return: MediaItem[] };
+  hydrate_posters: { args: { mediaIds: string[] }; return: MediaItem[] };
+  backfill_durations: { args: {}; return: MediaItem[] };
   search_library_advanced: { args: { filters: SearchFilters }; return: MediaItem[] };
+  search_library_highlighted: { args: { query: string; limit?: number }; return: SearchHit[] };
+  rebuild_search_index: { args: {}; return: number };
   
   // Media & Catalogs
   get_media_details: { args: { contentId: string; mediaType: MediaType }; return: MediaItem };
   get_stream_url: { args: { contentId: string; mediaType?: string }; return: string };
 get_streams: { args: { contentId: string; mediaType?: string }; return: StreamWithSource[] };
-  get_subtitles: { args: { contentId: string; mediaType?: string }; return: Subtitle[] };
+get_streams_filtered: { args: { contentId: string; mediaType?: string; filter: StreamFilter }; return: StreamWithSource[] };
+  resolve_playable: { args: { contentId: string; mediaType?: string; qualityPref?: string }; return: PlayableResult };
+  get_streams_incremental: { args: { contentId: string; mediaType?: string; onStream: import('@tauri-apps/api/core').Channel<StreamWithSource> }; return: number };
+  get_subtitles: { args: { contentId: string; mediaType?: string; streamSubtitles?: Subtitle[]; languages?: string[] }; return: Subtitle[] };
+  auto_fetch_subtitles: { args: { filePath?: string; imdbId?: string; languages: string[]; forceRefresh?: boolean }; return: SubtitleResult[] };
+  download_best_subtitle: { args: { results: SubtitleResult[]; convertToVtt?: boolean; contentId?: string; forceRefresh?: boolean }; return: [string, SubtitleResult] };
+  get_subtitle_quota: { args: {}; return: QuotaInfo | null };
+  opensubtitles_login: { args: { username: string; password: string }; return: void };
+  opensubtitles_logout: { args: {}; return: void };
+  prefetch_subtitles: { args: { mediaIds: string[]; languages: string[] }; return: Record<string, PrefetchResult> };
+  get_prefetched_subtitle: { args: { mediaId: string; language: string }; return: [string, SubtitleResult] | null };
   get_addon_meta: { args: { contentId: string; mediaType?: string }; return: MetaItem };
   list_catalogs: { args: { mediaType: string }; return: CatalogInfo[] };
-  aggregate_catalogs: { args: { mediaType: string; catalogId: string; extra?: { [key: string]: string } }; return: { items: any[]; sources: any[]; total_time_ms: number } };
+  aggregate_catalogs: { args: { mediaType: string; catalogId: string; extra?: { [key: string]: string } }; return: { items: any[]; sources: any[]; total_time_ms: number; request_id: string } };
   
   // Addons
   get_addons: { args: {}; return: Addon[] };
-  install_addon: { args: { addonUrl: string }; return: string };
+  install_addon: { args: { addonUrl: string; replaceExisting?: boolean }; return: AddonInstallResult };
+  test_addon: { args: { addonUrl: string }; return: AddonTestReport };
+  sandbox_query: { args: { addonUrl: string; resource: string; mediaType: string; id: string; extra?: Record<string, string> }; return: unknown };
   enable_addon: { args: { addonId: string }; return: void };
   disable_addon: { args: { addonId: string }; return: void };
+  set_addon_priority: { args: { addonId: string; priority: number }; return: void };
+  reorder_addons: { args: { orderedIds: string[] }; return: Addon[] };
   uninstall_addon: { args: { addonId: string }; return: void };
+  set_addon_config: { args: { addonId: string; key: string; value: string }; return: void };
+  get_addon_config: { args: { addonId: string }; return: Record<string, string> };
+
+  // App settings (JSON-encoded values)
+  get_app_setting: { args: { key: string }; return: string | null };
+  set_app_setting: { args: { key: string; value: string }; return: void };
+  get_all_app_settings: { args: {}; return: Record<string, string> };
+  get_unused_addons: { args: { days?: number }; return: Addon[] };
   
   // Settings
   get_settings: { args: {}; return: UserPreferences };
   save_settings: { args: { settings: UserPreferences }; return: void };
   
+  // Profiles
+  create_profile: { args: { username: string; email?: string }; return: UserProfile };
+  list_profiles: { args: {}; return: UserProfile[] };
+  get_active_profile: { args: {}; return: string };
+  switch_active_profile: { args: { profileId: string }; return: UserProfile };
+  delete_profile: { args: { profileId: string }; return: void };
+
   // Watchlist  
   add_to_watchlist: { args: { mediaId: string }; return: void };
   remove_from_watchlist: { args: { mediaId: string }; return: void };
@@ -438,8 +741,13 @@ get_streams: { args: { contentId: string; mediaType?: string }; return: StreamWi
   get_favorites: { args: {}; return: MediaItem[] };
   
   // Watch Progress
-  update_watch_progress: { args: { mediaId: string; progress: number; watched: boolean }; return: void };
+  update_watch_progress: { args: { mediaId: string; progress: number; watched: boolean; duration?: number }; return: void };
+  get_watch_history: { args: { limit?: number }; return: WatchHistoryEntry[] };
+  import_watch_history: { args: { entries: ImportedHistory[] }; return: WatchHistoryImport };
   get_continue_watching: { args: {}; return: MediaItem[] };
+  get_episodes: { args: { seriesId: string }; return: Episode[] };
+  get_next_unwatched_episode: { args: { seriesId: string }; return: Episode | null };
+  update_episode_progress: { args: { episodeId: string; progress: number; watched: boolean }; return: void };
   
   // Playlists
   create_playlist: { args: { name: string; description?: string }; return: string };
@@ -456,6 +764,7 @@ get_streams: { args: { contentId: string; mediaType?: string }; return: StreamWi
   get_cache_stats: { args: {}; return: CacheStats };
   clear_cache: { args: {}; return: void };
   clear_expired_cache: { args: {}; return: number };
+  optimize_database: { args: { vacuum?: boolean }; return: OptimizeReport };
 
   // Local Media
   scan_local_folder: { args: { path: string }; return: LocalMediaFile[] };
@@ -470,12 +779,34 @@ get_streams: { args: { contentId: string; mediaType?: string }; return: StreamWi
   // Live TV
   live_tv_import_m3u: { args: { url: string }; return: number };
   live_tv_get_channels: { args: {}; return: LiveTvChannel[] };
+  import_m3u_playlist: { args: { content: string }; return: number };
+  get_live_channels: { args: { group?: string }; return: LiveTvChannel[] };
+  delete_live_channel: { args: { channelId: string }; return: boolean };
   live_tv_import_xmltv: { args: { url: string }; return: number };
   live_tv_get_epg: { args: { channel_id: string; since?: number; until?: number }; return: EpgProgram[] };
+  import_xmltv: { args: { content: string }; return: number };
+  get_now_next: { args: { channelId: string }; return: [EpgProgram | null, EpgProgram | null] };
+  add_favorite_channel: { args: { channelId: string }; return: void };
+  remove_favorite_channel: { args: { channelId: string }; return: boolean };
+  get_favorite_channels: { args: {}; return: LiveTvChannel[] };
+  get_channel_groups: { args: {}; return: ChannelGroup[] };
+  probe_channels: { args: {}; return: ChannelStatus[] };
+  get_channel_statuses: { args: {}; return: ChannelStatus[] };
+  set_program_reminder: { args: { channelId: string; programId: number }; return: void };
+  remove_program_reminder: { args: { programId: number }; return: boolean };
+  get_upcoming_reminders: { args: {}; return: ProgramReminder[] };
+  record_program: { args: { channelId: string; programId: number }; return: boolean };
+  cancel_recording: { args: { programId: number }; return: boolean };
   
   // Health & Diagnostics
   get_addon_health_summaries: { args: {}; return: AddonHealthSummary[] };
   get_addon_health: { args: { addonId: string }; return: AddonHealthSummary | null };
+  rate_addon: { args: { addonId: string; rating: number }; return: AddonRatingSummary };
+  get_addon_rating: { args: { addonId: string }; return: AddonRatingSummary };
+  get_addon_ratings: { args: {}; return: AddonRatingSummary[] };
+  set_skip_segments: { args: { mediaId: string; segments: SkipSegments }; return: void };
+  get_skip_segments: { args: { mediaId: string }; return: SkipSegments | null };
+  export_addon_health_csv: { args: { since?: number }; return: string };
   get_performance_metrics: { args: {}; return: PerformanceMetrics };
   export_diagnostics: { args: {}; return: DiagnosticsInfo };
   export_diagnostics_file: { args: {}; return: string };
@@ -483,9 +814,12 @@ get_streams: { args: { contentId: string; mediaType?: string }; return: StreamWi
 
   // Calendar
   get_calendar: { args: { daysAhead?: number }; return: CalendarEntry[] };
+  export_calendar_ics: { args: { daysAhead?: number }; return: string };
 
   // Torrent streaming
   start_torrent_stream: { args: { magnetOrUrl: string; fileIndex?: number }; return: string };
+  get_torrent_queue_status: { args: {}; return: TorrentQueueStatus };
+  serve_local_file: { args: { path: string }; return: string };
 }
 
 // Global Tauri API